@@ -29,6 +29,8 @@ fn main() {
             "EGL_KHR_image",
             #[cfg(feature = "EGL_KHR_platform_wayland")]
             "EGL_KHR_platform_wayland",
+            #[cfg(feature = "EGL_KHR_swap_buffers_with_damage")]
+            "EGL_KHR_swap_buffers_with_damage",
             #[cfg(feature = "EGL_MESA_image_dma_buf_export")]
             "EGL_MESA_image_dma_buf_export",
         ],
@@ -17,6 +17,8 @@ struct GlContextEgl {
     egl_window_surfaces: [Option<(*mut c_void, egl::wrap::WindowSurface)>; 16],
     egl_context: egl::wrap::Context,
     egl_connection: egl::wrap::Connection,
+    /// whether EGL_KHR_swap_buffers_with_damage is supported.
+    swap_buffers_with_damage: bool,
 }
 
 #[cfg(unix)]
@@ -97,10 +99,24 @@ impl GlContextEgl {
         // TODO: figure out an okay way to include vsync toggle.
         // unsafe { egl_connection.api.SwapInterval(*egl_connection.display, 0) };
 
+        let swap_buffers_with_damage = {
+            let extensions = unsafe {
+                egl_connection
+                    .api
+                    .QueryString(*egl_connection.display, egl::EXTENSIONS as _)
+            };
+            !extensions.is_null()
+                && unsafe { std::ffi::CStr::from_ptr(extensions) }
+                    .to_bytes()
+                    .split(|&b| b == b' ')
+                    .any(|extension| extension == b"EGL_KHR_swap_buffers_with_damage")
+        };
+
         Ok(Self {
             egl_window_surfaces: array::from_fn(|_| None),
             egl_context,
             egl_connection,
+            swap_buffers_with_damage,
         })
     }
 
@@ -229,9 +245,16 @@ impl GlContext {
         }
     }
 
+    /// `damage` rects are x, y, width, height in buffer pixels with the origin at the top left
+    /// corner. empty `damage` means that everything is damaged.
+    ///
+    /// NOTE: on wayland damage ends up in wl_surface.damage_buffer (if egl supports
+    ///   EGL_KHR_swap_buffers_with_damage); that allows the compositor to only repaint what
+    ///   changed.
     pub fn swap_window_buffers(
         &self,
         raw_window_handle: rwh::RawWindowHandle,
+        damage: &[[i32; 4]],
     ) -> anyhow::Result<()> {
         match (&self.kind, raw_window_handle) {
             #[cfg(unix)]
@@ -247,10 +270,29 @@ impl GlContext {
                 ) else {
                     return Err(anyhow!("unknown surface"));
                 };
-                let ok = unsafe {
-                    ctx.egl_connection
-                        .api
-                        .SwapBuffers(*ctx.egl_connection.display, egl_window_surface.surface)
+                let ok = if ctx.swap_buffers_with_damage && !damage.is_empty() {
+                    // NOTE: egl wants rects with the origin at the bottom left corner.
+                    let height = match egl_window_surface.wsi {
+                        egl::wrap::Wsi::Wayland(ref wayland) => wayland.size().1 as i32,
+                    };
+                    let mut rects = Vec::with_capacity(damage.len() * 4);
+                    for &[x, y, width, rect_height] in damage.iter() {
+                        rects.extend_from_slice(&[x, height - y - rect_height, width, rect_height]);
+                    }
+                    unsafe {
+                        ctx.egl_connection.api.SwapBuffersWithDamageKHR(
+                            *ctx.egl_connection.display,
+                            egl_window_surface.surface,
+                            rects.as_mut_ptr(),
+                            damage.len() as egl::EGLint,
+                        )
+                    }
+                } else {
+                    unsafe {
+                        ctx.egl_connection
+                            .api
+                            .SwapBuffers(*ctx.egl_connection.display, egl_window_surface.surface)
+                    }
                 };
                 if ok == egl::FALSE {
                     Err(ctx.egl_connection.unwrap_err().into())
@@ -260,7 +302,11 @@ impl GlContext {
             }
 
             #[cfg(target_family = "wasm")]
-            (GlContextKind::Web, rwh::RawWindowHandle::Web(_)) => Ok(()),
+            (GlContextKind::Web, rwh::RawWindowHandle::Web(_)) => {
+                // NOTE: browsers don't take damage hints.
+                _ = damage;
+                Ok(())
+            }
 
             _ => Err(anyhow!("unsupported window: {raw_window_handle:?}")),
         }
@@ -1,7 +1,7 @@
 use std::collections::{HashMap, hash_map};
 use std::ffi::c_void;
 use std::fmt::{self, Write as _};
-use std::hash::Hasher as _;
+use std::hash::{Hash as _, Hasher as _};
 use std::mem::offset_of;
 use std::ptr::null;
 
@@ -60,22 +60,123 @@ in vec4 v_color;
 
 uniform sampler2D u_sampler;
 
-#if defined(SDF_RECT)
-uniform vec2 u_sdf_rect_center;
-uniform vec2 u_sdf_rect_size;
-uniform float u_sdf_rect_corner_radius;
-uniform float u_sdf_rect_stroke_width;
-uniform vec4 u_sdf_rect_stroke_color;
-uniform int u_sdf_rect_stroke_alignment; // -1 inside, 0 center, 1 outside
+#if defined(TRANSFORM)
+// @LogicalPixels -> local space of sdf params and gradient.
+uniform mat4 u_inverse_transform;
+// approximate scale of the forward transform.
+uniform float u_transform_scale;
+#endif
 
-// https://iquilezles.org/articles/distfunctions2d/
-float sd_rounded_box(vec2 p, vec2 b, vec4 r) {
-    r.xy = (p.x > 0.0) ? r.xy : r.zw;
-    r.x  = (p.y > 0.0) ? r.x  : r.y;
-    vec2 q = abs(p) - b + r.x;
-    return min(max(q.x, q.y), 0.0) + length(max(q, 0.0)) - r.x;
+const float PI = 3.14159265359;
+
+#if defined(CUSTOM_SHADER)
+// NOTE: defined by the snippet that is appended to this source (see
+//   GlRenderer::fragment_shader_desc). `color` is vertex color (multiplied by texture),
+//   `local_pos` is in @LogicalPixels in local space of the shape.
+vec4 custom_shader(vec4 color, vec2 local_pos);
+#endif
+
+#if defined(GRADIENT)
+uniform int u_gradient_kind; // 0 linear, 1 radial, 2 conic
+// linear: start, end; radial: center, radius; conic: center, start angle.
+uniform vec4 u_gradient_geometry;
+uniform int u_gradient_spread; // 0 pad, 1 repeat, 2 reflect
+uniform int u_gradient_interpolation; // 0 srgb, 1 linear
+uniform int u_gradient_stop_count;
+uniform vec4 u_gradient_offsets_lo; // offsets of stops 0..4
+uniform vec4 u_gradient_offsets_hi; // offsets of stops 4..8
+uniform vec4 u_gradient_color_0;
+uniform vec4 u_gradient_color_1;
+uniform vec4 u_gradient_color_2;
+uniform vec4 u_gradient_color_3;
+uniform vec4 u_gradient_color_4;
+uniform vec4 u_gradient_color_5;
+uniform vec4 u_gradient_color_6;
+uniform vec4 u_gradient_color_7;
+
+// https://en.wikipedia.org/wiki/SRGB#Transformation
+vec3 srgb_to_linear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+vec3 linear_to_srgb(vec3 c) {
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+// returns position of p along the gradient; 0 at the start, 1 at the end.
+float gradient_position(vec2 p) {
+    if (u_gradient_kind == 0) {
+        vec2 a = u_gradient_geometry.xy;
+        vec2 ab = u_gradient_geometry.zw - a;
+        return dot(p - a, ab) / max(dot(ab, ab), 1e-6);
+    } else if (u_gradient_kind == 1) {
+        return length(p - u_gradient_geometry.xy) / max(u_gradient_geometry.z, 1e-6);
+    } else {
+        vec2 d = p - u_gradient_geometry.xy;
+        return fract((atan(d.y, d.x) - u_gradient_geometry.z) / (2.0 * PI));
+    }
 }
 
+float gradient_spread(float t) {
+    if (u_gradient_spread == 1) {
+        return fract(t);
+    } else if (u_gradient_spread == 2) {
+        return 1.0 - abs(mod(t, 2.0) - 1.0);
+    }
+    return clamp(t, 0.0, 1.0);
+}
+
+// NOTE: colors are interpolated premultiplied, otherwise transparent stops bleed their color into
+//   neighbours.
+vec4 gradient_premultiply(vec4 c) {
+    if (u_gradient_interpolation == 1) {
+        c.rgb = srgb_to_linear(c.rgb);
+    }
+    return vec4(c.rgb * c.a, c.a);
+}
+
+vec4 gradient_color(vec2 p) {
+    if (u_gradient_stop_count <= 0) {
+        return vec4(0.0);
+    }
+
+    float offsets[8] = float[8](
+        u_gradient_offsets_lo.x, u_gradient_offsets_lo.y,
+        u_gradient_offsets_lo.z, u_gradient_offsets_lo.w,
+        u_gradient_offsets_hi.x, u_gradient_offsets_hi.y,
+        u_gradient_offsets_hi.z, u_gradient_offsets_hi.w
+    );
+    vec4 colors[8] = vec4[8](
+        u_gradient_color_0, u_gradient_color_1, u_gradient_color_2, u_gradient_color_3,
+        u_gradient_color_4, u_gradient_color_5, u_gradient_color_6, u_gradient_color_7
+    );
+
+    float t = gradient_spread(gradient_position(p));
+    vec4 c = gradient_premultiply(colors[0]);
+    for (int i = 1; i < 8; i++) {
+        if (i >= u_gradient_stop_count || t < offsets[i - 1]) {
+            break;
+        }
+        float f = clamp((t - offsets[i - 1]) / max(offsets[i] - offsets[i - 1], 1e-6), 0.0, 1.0);
+        c = mix(gradient_premultiply(colors[i - 1]), gradient_premultiply(colors[i]), f);
+    }
+
+    if (c.a > 0.0) {
+        c.rgb /= c.a;
+    }
+    if (u_gradient_interpolation == 1) {
+        c.rgb = linear_to_srgb(c.rgb);
+    }
+    return c;
+}
+#endif
+
+#if defined(SDF_RECT) || defined(SDF_ELLIPSE) || defined(SDF_ARC)
+// TODO: maybe better aa?
+//   but don't use fwidth, it sucks.
+//   also see https://mini.gmshaders.com/p/antialiasing
+const float SDF_AA = 0.5;
+
 // https://en.wikipedia.org/wiki/Alpha_compositing
 // https://www.w3.org/TR/compositing-1/#whatiscompositing
 vec4 composite_rgba(vec4 bg, vec4 fg) {
@@ -95,14 +196,63 @@ vec4 composite_rgba_with_coverage(vec4 bg, float bg_cov, vec4 fg, float fg_cov)
     return composite_rgba(bg, fg);
 }
 
-// returns (inner, outer) stroke parts where outer + inner = width.
-//   (16.0, -1.0) -> fully inner  ( 0.0, 16.0)
-//   (16.0,  0.0) -> centered     ( 8.0,  8.0)
-//   (16.0, +1.0) -> fully outer  (16.0,  0.0)
-vec2 split_stroke(float width, int alignment) {
-    float inner = width * 0.5 * (1.0 - float(alignment));
-    float outer = width * 0.5 * (1.0 + float(alignment));
-    return vec2(inner, outer);
+// fills and strokes a shape given signed distance to its edge.
+vec4 sdf_fill_and_stroke(
+    vec4 fill_color,
+    float dist,
+    float stroke_width,
+    int stroke_alignment,
+    vec4 stroke_color
+) {
+    float stroke_inner = stroke_width * 0.5 * (1.0 - float(stroke_alignment));
+    float stroke_outer = stroke_width * 0.5 * (1.0 + float(stroke_alignment));
+
+    float aa = SDF_AA;
+    float fill_cov = 1.0 - smoothstep(-aa, aa, dist);
+    float stroke_cov_inner = 1.0 - smoothstep(-aa, aa, dist + stroke_inner);
+    float stroke_cov_outer = 1.0 - smoothstep(-aa, aa, dist - stroke_outer);
+    float stroke_cov = max(stroke_cov_outer - stroke_cov_inner, 0.0);
+
+    return composite_rgba_with_coverage(fill_color, fill_cov, stroke_color, stroke_cov);
+}
+#endif
+
+#if defined(SDF_RECT)
+uniform vec2 u_sdf_rect_center;
+uniform vec2 u_sdf_rect_size;
+uniform vec4 u_sdf_rect_corner_radii; // top left, top right, bottom right, bottom left
+uniform vec4 u_sdf_rect_stroke_widths; // top, right, bottom, left
+uniform vec4 u_sdf_rect_stroke_color_top;
+uniform vec4 u_sdf_rect_stroke_color_right;
+uniform vec4 u_sdf_rect_stroke_color_bottom;
+uniform vec4 u_sdf_rect_stroke_color_left;
+uniform int u_sdf_rect_stroke_alignment; // -1 inside, 0 center, 1 outside
+
+// https://iquilezles.org/articles/distfunctions2d/
+//
+// NOTE: unlike the original, r is (top left, top right, bottom right, bottom left) and y points
+//   down (same as gl_FragCoord in here).
+float sd_rounded_box(vec2 p, vec2 b, vec4 r) {
+    r.xy = (p.x > 0.0) ? r.yz : r.xw;
+    r.x  = (p.y > 0.0) ? r.y  : r.x;
+    vec2 q = abs(p) - b + r.x;
+    return min(max(q.x, q.y), 0.0) + length(max(q, 0.0)) - r.x;
+}
+
+float sd_rounded_box_min_max(vec2 p, vec2 b_min, vec2 b_max, vec4 r) {
+    return sd_rounded_box(p - (b_min + b_max) * 0.5, (b_max - b_min) * 0.5, r);
+}
+
+// returns inner stroke parts of each side. inner + outer = width.
+//   (16.0, -1.0) -> fully inner  16.0
+//   (16.0,  0.0) -> centered      8.0
+//   (16.0, +1.0) -> fully outer   0.0
+vec4 stroke_inner_part(vec4 widths, int alignment) {
+    return widths * 0.5 * (1.0 - float(alignment));
+}
+
+vec4 stroke_outer_part(vec4 widths, int alignment) {
+    return widths * 0.5 * (1.0 + float(alignment));
 }
 
 vec4 sdf_rect(
@@ -110,40 +260,225 @@ vec4 sdf_rect(
     vec4 frag_color,
     vec2 rect_center,
     vec2 rect_size,
-    float corner_radius,
-    float stroke_width,
+    vec4 corner_radii,
+    vec4 stroke_widths,
     int stroke_alignment,
-    vec4 stroke_color
+    vec4 stroke_color_top,
+    vec4 stroke_color_right,
+    vec4 stroke_color_bottom,
+    vec4 stroke_color_left
 ) {
-    vec2 stroke_split = split_stroke(stroke_width, stroke_alignment);
-    float stroke_inner = stroke_split.x;
-    float stroke_outer = stroke_split.y;
+    // NOTE: components are top, right, bottom, left.
+    vec4 stroke_inner = stroke_inner_part(stroke_widths, stroke_alignment);
+    vec4 stroke_outer = stroke_outer_part(stroke_widths, stroke_alignment);
 
     vec2 p = frag_pos - rect_center;
-    vec2 b = rect_size * 0.5 + stroke_outer;
-    // TODO: maybe need to select specific corner's radius; this wont work with radii.
-    float r_zero_mask = float(int(corner_radius > 0.0));
-    float r = (corner_radius + stroke_outer) * r_zero_mask;
-
-    float stroke_dist_outer = sd_rounded_box(p, b, vec4(r));
-    // stroke_dist_outer(-32.5), stroke_outer(0.5) -> -32.0
-    float fill_dist = stroke_dist_outer + stroke_outer;
-    // fill_dist(-32.0), stroke_inner(0.5) -> -31.5
-    float stroke_dist_inner = fill_dist + stroke_inner;
-
-    // TODO: maybe better aa?
-    //   but don't use fwidth, it sucks.
-    //   also see https://mini.gmshaders.com/p/antialiasing
-    float aa = 0.5;
+    vec2 rect_max = rect_size * 0.5;
+    vec2 rect_min = -rect_max;
+
+    vec2 outer_min = rect_min - stroke_outer.wx;
+    vec2 outer_max = rect_max + stroke_outer.yz;
+    vec2 inner_min = rect_min + stroke_inner.wx;
+    vec2 inner_max = max(rect_max - stroke_inner.yz, inner_min);
+
+    // NOTE: each corner grows (or shrinks) by the wider of the two sides that meet at it:
+    //   top left - top and left, top right - top and right, and so on.
+    //   sharp corners stay sharp.
+    vec4 r_zero_mask = vec4(greaterThan(corner_radii, vec4(0.0)));
+    vec4 r_outer = (corner_radii + max(stroke_outer.xxzz, stroke_outer.wyyw)) * r_zero_mask;
+    vec4 r_inner = max(corner_radii - max(stroke_inner.xxzz, stroke_inner.wyyw), vec4(0.0));
+
+    float fill_dist = sd_rounded_box(p, rect_max, corner_radii);
+    float stroke_dist_outer = sd_rounded_box_min_max(p, outer_min, outer_max, r_outer);
+    float stroke_dist_inner = sd_rounded_box_min_max(p, inner_min, inner_max, r_inner);
+
+    // NOTE: pick the color of the side the fragment is deepest into (relative to that side's
+    //   width). sides meet at the diagonal between inner and outer corners, same as in css.
+    //   sides with zero width never win.
+    vec4 side_depth = vec4(
+        inner_min.y - p.y,
+        p.x - inner_max.x,
+        p.y - inner_max.y,
+        inner_min.x - p.x
+    ) / max(stroke_widths, vec4(1e-6));
+    side_depth = mix(vec4(-1e9), side_depth, vec4(greaterThan(stroke_widths, vec4(0.0))));
+    vec4 stroke_color = stroke_color_top;
+    float depth = side_depth.x;
+    if (side_depth.y > depth) { depth = side_depth.y; stroke_color = stroke_color_right; }
+    if (side_depth.z > depth) { depth = side_depth.z; stroke_color = stroke_color_bottom; }
+    if (side_depth.w > depth) { depth = side_depth.w; stroke_color = stroke_color_left; }
+
+    float aa = SDF_AA;
     float fill_cov = 1.0 - smoothstep(-aa, aa, fill_dist);
     float stroke_cov_inner = 1.0 - smoothstep(-aa, aa, stroke_dist_inner);
     float stroke_cov_outer = 1.0 - smoothstep(-aa, aa, stroke_dist_outer);
-    float stroke_cov = stroke_cov_outer - stroke_cov_inner;
+    float stroke_cov = max(stroke_cov_outer - stroke_cov_inner, 0.0);
 
     return composite_rgba_with_coverage(frag_color, fill_cov, stroke_color, stroke_cov);
 }
 #endif
 
+
+#if defined(SDF_ELLIPSE)
+uniform vec2 u_sdf_ellipse_center;
+uniform vec2 u_sdf_ellipse_radii;
+uniform float u_sdf_ellipse_stroke_width;
+uniform vec4 u_sdf_ellipse_stroke_color;
+uniform int u_sdf_ellipse_stroke_alignment; // -1 inside, 0 center, 1 outside
+
+// https://iquilezles.org/articles/ellipsedist/
+//
+// NOTE: this is an approximation (exact for circles), but it is good near the edge, which is
+//   what matters for aa and strokes.
+float sd_ellipse(vec2 p, vec2 r) {
+    float k0 = length(p / r);
+    float k1 = length(p / (r * r));
+    if (k1 < 1e-6) {
+        return -min(r.x, r.y);
+    }
+    return k0 * (k0 - 1.0) / k1;
+}
+
+vec4 sdf_ellipse(
+    vec2 frag_pos,
+    vec4 frag_color,
+    vec2 center,
+    vec2 radii,
+    float stroke_width,
+    int stroke_alignment,
+    vec4 stroke_color
+) {
+    float dist = sd_ellipse(frag_pos - center, radii);
+    return sdf_fill_and_stroke(frag_color, dist, stroke_width, stroke_alignment, stroke_color);
+}
+#endif
+
+#if defined(SDF_ARC)
+uniform vec2 u_sdf_arc_center;
+uniform float u_sdf_arc_radius;
+uniform vec2 u_sdf_arc_angles; // start, end
+uniform float u_sdf_arc_thickness;
+uniform int u_sdf_arc_cap; // 0 butt, 1 round
+uniform float u_sdf_arc_stroke_width;
+uniform vec4 u_sdf_arc_stroke_color;
+uniform int u_sdf_arc_stroke_alignment; // -1 inside, 0 center, 1 outside
+
+// https://iquilezles.org/articles/distfunctions2d/
+//
+// arc is symmetric around +y, sc is sin/cos of the half aperture, ra is radius and rb is half of
+// the thickness.
+float sd_arc_round(vec2 p, vec2 sc, float ra, float rb) {
+    p.x = abs(p.x);
+    return ((sc.y * p.x > sc.x * p.y) ? length(p - sc * ra) : abs(length(p) - ra)) - rb;
+}
+
+// same as sd_arc_round, but ends are cut off flat along the radius.
+float sd_arc_butt(vec2 p, vec2 sc, float ra, float rb) {
+    p.x = abs(p.x);
+    float ring_dist = abs(length(p) - ra) - rb;
+
+    // distance to the end segment
+    vec2 a = sc * (ra - rb);
+    vec2 ba = sc * (2.0 * rb);
+    vec2 pa = p - a;
+    float h = clamp(dot(pa, ba) / max(dot(ba, ba), 1e-6), 0.0, 1.0);
+    float end_dist = length(pa - ba * h);
+
+    return (sc.y * p.x > sc.x * p.y) ? end_dist : max(ring_dist, -end_dist);
+}
+
+vec4 sdf_arc(
+    vec2 frag_pos,
+    vec4 frag_color,
+    vec2 center,
+    float radius,
+    vec2 angles,
+    float thickness,
+    int cap,
+    float stroke_width,
+    int stroke_alignment,
+    vec4 stroke_color
+) {
+    // NOTE: rotate so that the middle of the arc points to +y.
+    float mid = (angles.x + angles.y) * 0.5;
+    float half_aperture = min(abs(angles.y - angles.x) * 0.5, PI);
+    vec2 d = frag_pos - center;
+    float sm = sin(mid);
+    float cm = cos(mid);
+    vec2 p = vec2(d.x * sm - d.y * cm, d.x * cm + d.y * sm);
+    vec2 sc = vec2(sin(half_aperture), cos(half_aperture));
+
+    float dist;
+    if (half_aperture >= PI) {
+        // full circle has no ends.
+        dist = abs(length(p) - radius) - thickness * 0.5;
+    } else if (cap == 1) {
+        dist = sd_arc_round(p, sc, radius, thickness * 0.5);
+    } else {
+        dist = sd_arc_butt(p, sc, radius, thickness * 0.5);
+    }
+    return sdf_fill_and_stroke(frag_color, dist, stroke_width, stroke_alignment, stroke_color);
+}
+#endif
+
+#if defined(SDF_SHADOW)
+uniform vec2 u_sdf_shadow_center;
+uniform vec2 u_sdf_shadow_size;
+uniform vec4 u_sdf_shadow_corner_radii; // top left, top right, bottom right, bottom left
+uniform float u_sdf_shadow_sigma;
+
+// https://madebyevan.com/shaders/fast-rounded-rectangle-shadows/
+//
+// NOTE: the original supports a single corner radius; in here radius of the quadrant the fragment
+//   is in is used.
+
+// abramowitz and stegun approximation of the error function.
+vec2 erf_approx(vec2 x) {
+    vec2 s = sign(x);
+    vec2 a = abs(x);
+    x = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    x *= x;
+    return s - s / (x * x);
+}
+
+float gaussian(float x, float sigma) {
+    return exp(-(x * x) / (2.0 * sigma * sigma)) / (sqrt(2.0 * PI) * sigma);
+}
+
+// integral of the blurred shape along x at y.
+float rounded_box_shadow_x(float x, float y, float sigma, float corner, vec2 half_size) {
+    float delta = min(half_size.y - corner - abs(y), 0.0);
+    float curved = half_size.x - corner + sqrt(max(0.0, corner * corner - delta * delta));
+    vec2 integral = 0.5 + 0.5 * erf_approx((x + vec2(-curved, curved)) * (sqrt(0.5) / sigma));
+    return integral.y - integral.x;
+}
+
+// returns coverage of the blurred rounded box.
+float sdf_shadow(vec2 frag_pos, vec2 center, vec2 size, vec4 corner_radii, float sigma) {
+    vec2 p = frag_pos - center;
+    vec2 half_size = size * 0.5;
+
+    vec4 r = corner_radii;
+    r.xy = (p.x > 0.0) ? r.yz : r.xw;
+    float corner = min((p.y > 0.0) ? r.y : r.x, min(half_size.x, half_size.y));
+
+    // NOTE: y is integrated numerically; gaussian is negligible past 3 sigma.
+    float low = p.y - half_size.y;
+    float high = p.y + half_size.y;
+    float start = clamp(-3.0 * sigma, low, high);
+    float end = clamp(3.0 * sigma, low, high);
+    float step = (end - start) / 4.0;
+    float y = start + step * 0.5;
+    float value = 0.0;
+    for (int i = 0; i < 4; i++) {
+        value += rounded_box_shadow_x(p.x, p.y - y, sigma, corner, half_size)
+            * gaussian(y, sigma) * step;
+        y += step;
+    }
+    return value;
+}
+#endif
+
 out vec4 FragColor;
 
 void main() {
@@ -155,16 +490,70 @@ void main() {
     FragColor *= texture(u_sampler, v_tex_coord);
 #endif
 
+    // NOTE: sdf params and gradient are in local space of the shape.
+    //   sdfs are evaluated in local space scaled such that distances are in physical pixels
+    //   (approximately, if the transform has non-uniform scale or skew), that is what aa expects.
+#if defined(TRANSFORM)
+    vec2 local_pos = (u_inverse_transform * vec4(gl_FragCoord.xy / u_scale, 0.0, 1.0)).xy;
+    float local_scale = u_scale * u_transform_scale;
+#else
+    vec2 local_pos = gl_FragCoord.xy / u_scale;
+    float local_scale = u_scale;
+#endif
+    vec2 frag_pos = local_pos * local_scale;
+
+#if defined(GRADIENT)
+    FragColor *= gradient_color(local_pos);
+#endif
+
+#if defined(CUSTOM_SHADER)
+    FragColor = custom_shader(FragColor, local_pos);
+#endif
+
 #if defined(SDF_RECT)
     FragColor = sdf_rect(
-        gl_FragCoord.xy,
+        frag_pos,
         FragColor,
-        u_sdf_rect_center * u_scale,
-        u_sdf_rect_size * u_scale,
-        u_sdf_rect_corner_radius * u_scale,
-        u_sdf_rect_stroke_width * u_scale,
+        u_sdf_rect_center * local_scale,
+        u_sdf_rect_size * local_scale,
+        u_sdf_rect_corner_radii * local_scale,
+        u_sdf_rect_stroke_widths * local_scale,
         u_sdf_rect_stroke_alignment,
-        u_sdf_rect_stroke_color
+        u_sdf_rect_stroke_color_top,
+        u_sdf_rect_stroke_color_right,
+        u_sdf_rect_stroke_color_bottom,
+        u_sdf_rect_stroke_color_left
+    );
+#elif defined(SDF_ELLIPSE)
+    FragColor = sdf_ellipse(
+        frag_pos,
+        FragColor,
+        u_sdf_ellipse_center * local_scale,
+        u_sdf_ellipse_radii * local_scale,
+        u_sdf_ellipse_stroke_width * local_scale,
+        u_sdf_ellipse_stroke_alignment,
+        u_sdf_ellipse_stroke_color
+    );
+#elif defined(SDF_ARC)
+    FragColor = sdf_arc(
+        frag_pos,
+        FragColor,
+        u_sdf_arc_center * local_scale,
+        u_sdf_arc_radius * local_scale,
+        u_sdf_arc_angles,
+        u_sdf_arc_thickness * local_scale,
+        u_sdf_arc_cap,
+        u_sdf_arc_stroke_width * local_scale,
+        u_sdf_arc_stroke_alignment,
+        u_sdf_arc_stroke_color
+    );
+#elif defined(SDF_SHADOW)
+    FragColor.a *= sdf_shadow(
+        frag_pos,
+        u_sdf_shadow_center * local_scale,
+        u_sdf_shadow_size * local_scale,
+        u_sdf_shadow_corner_radii * local_scale,
+        u_sdf_shadow_sigma * local_scale
     );
 #endif
 }
@@ -186,20 +575,312 @@ const SHADER_SOURCE: sx::ShaderSource = sx::ShaderSource {
     },
 };
 
-fn hash_shader_desc(shader_desc: &sx::ShaderDesc) -> u64 {
-    // NOTE: source doesn't change. uniforms don't affect anything.
-    //   what we care about is defines.
-    let mut hasher = FxHasher::default();
-    for define in shader_desc
-        .vertex_stage
-        .defines
-        .0
-        .iter()
-        .chain(shader_desc.fragment_stage.defines.0.iter())
-    {
-        hasher.write(define.as_bytes())
+fn affine_to_mat4(transform: sx::Affine2) -> [[f32; 4]; 4] {
+    let sx::Affine2 {
+        x_axis,
+        y_axis,
+        translation,
+    } = transform;
+    [
+        [x_axis.x, x_axis.y, 0.0, 0.0],
+        [y_axis.x, y_axis.y, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [translation.x, translation.y, 0.0, 1.0],
+    ]
+}
+
+unsafe fn set_gradient_uniform(
+    gl_api: &gl::wrap::Api,
+    location: gl::wrap::UniformLocation,
+    name: &str,
+    gradient: &sx::Gradient,
+) {
+    let stops = gradient.stops();
+    let offset = |i: usize| stops.get(i).map_or(1.0, |stop| stop.offset);
+    let color = |i: usize| {
+        stops
+            .get(i)
+            .map_or([0.0; 4], |stop| stop.color.to_f32_array())
+    };
+    unsafe {
+        match name {
+            "u_gradient_kind" => {
+                let kind = match gradient.kind {
+                    sx::GradientKind::Linear { .. } => 0,
+                    sx::GradientKind::Radial { .. } => 1,
+                    sx::GradientKind::Conic { .. } => 2,
+                };
+                gl_api.uniform_1i(location, kind);
+            }
+            "u_gradient_geometry" => {
+                let g = match gradient.kind {
+                    sx::GradientKind::Linear { start, end } => [start.x, start.y, end.x, end.y],
+                    sx::GradientKind::Radial { center, radius } => {
+                        [center.x, center.y, radius, 0.0]
+                    }
+                    sx::GradientKind::Conic {
+                        center,
+                        start_angle,
+                    } => [center.x, center.y, start_angle, 0.0],
+                };
+                gl_api.uniform_4f(location, g[0], g[1], g[2], g[3]);
+            }
+            "u_gradient_spread" => gl_api.uniform_1i(location, gradient.spread as i32),
+            "u_gradient_interpolation" => {
+                gl_api.uniform_1i(location, gradient.interpolation as i32)
+            }
+            "u_gradient_stop_count" => gl_api.uniform_1i(location, stops.len() as i32),
+            "u_gradient_offsets_lo" => {
+                gl_api.uniform_4f(location, offset(0), offset(1), offset(2), offset(3))
+            }
+            "u_gradient_offsets_hi" => {
+                gl_api.uniform_4f(location, offset(4), offset(5), offset(6), offset(7))
+            }
+            other => {
+                let Some(i) = other
+                    .strip_prefix("u_gradient_color_")
+                    .and_then(|i| i.parse::<usize>().ok())
+                else {
+                    log::warn!("uniform {other} was left unset");
+                    return;
+                };
+                let c = color(i);
+                gl_api.uniform_4f(location, c[0], c[1], c[2], c[3]);
+            }
+        }
+    }
+}
+
+fn base_shader_desc() -> sx::ShaderDesc {
+    sx::ShaderDesc {
+        vertex_stage: sx::ShaderStageDesc {
+            source: SHADER_SOURCE.clone(),
+            snippet: None,
+            defines: {
+                let mut defines = sx::ShaderDefines::default();
+                defines.extend_from_iter(
+                    [sx::ShaderDefine::from_str("SHADER_STAGE_VERTEX")].into_iter(),
+                );
+                defines
+            },
+        },
+        fragment_stage: sx::ShaderStageDesc {
+            source: SHADER_SOURCE.clone(),
+            snippet: None,
+            defines: {
+                let mut defines = sx::ShaderDefines::default();
+                defines.extend_from_iter(
+                    [sx::ShaderDefine::from_str("SHADER_STAGE_FRAGMENT")].into_iter(),
+                );
+                defines
+            },
+        },
+        uniforms: {
+            let mut uniforms = sx::ShaderUniformDescs::default();
+            uniforms.extend_from_iter(
+                [
+                    (
+                        sx::ShaderUniformName::from_str("u_projection"),
+                        sx::ShaderUniformType::Mat4,
+                    ),
+                    (
+                        sx::ShaderUniformName::from_str("u_scale"),
+                        sx::ShaderUniformType::Float,
+                    ),
+                    (
+                        sx::ShaderUniformName::from_str("u_sampler"),
+                        sx::ShaderUniformType::Sampler2D,
+                    ),
+                ]
+                .into_iter(),
+            );
+            uniforms
+        },
+    }
+}
+
+type SdfShaderVariant = (
+    &'static str,
+    &'static [(&'static str, sx::ShaderUniformType)],
+);
+
+/// returns define and uniforms that shader needs for `sdf_params`.
+fn sdf_shader_variant(sdf_params: &sx::SdfParams) -> SdfShaderVariant {
+    use sx::ShaderUniformType as Type;
+    match sdf_params {
+        sx::SdfParams::Rect(..) => (
+            "SDF_RECT",
+            &[
+                ("u_sdf_rect_center", Type::Vec2),
+                ("u_sdf_rect_size", Type::Vec2),
+                ("u_sdf_rect_corner_radii", Type::Vec4),
+                ("u_sdf_rect_stroke_widths", Type::Vec4),
+                ("u_sdf_rect_stroke_color_top", Type::Vec4),
+                ("u_sdf_rect_stroke_color_right", Type::Vec4),
+                ("u_sdf_rect_stroke_color_bottom", Type::Vec4),
+                ("u_sdf_rect_stroke_color_left", Type::Vec4),
+                ("u_sdf_rect_stroke_alignment", Type::Int),
+            ],
+        ),
+        sx::SdfParams::Ellipse(..) => (
+            "SDF_ELLIPSE",
+            &[
+                ("u_sdf_ellipse_center", Type::Vec2),
+                ("u_sdf_ellipse_radii", Type::Vec2),
+                ("u_sdf_ellipse_stroke_width", Type::Float),
+                ("u_sdf_ellipse_stroke_color", Type::Vec4),
+                ("u_sdf_ellipse_stroke_alignment", Type::Int),
+            ],
+        ),
+        sx::SdfParams::Arc(..) => (
+            "SDF_ARC",
+            &[
+                ("u_sdf_arc_center", Type::Vec2),
+                ("u_sdf_arc_radius", Type::Float),
+                ("u_sdf_arc_angles", Type::Vec2),
+                ("u_sdf_arc_thickness", Type::Float),
+                ("u_sdf_arc_cap", Type::Int),
+                ("u_sdf_arc_stroke_width", Type::Float),
+                ("u_sdf_arc_stroke_color", Type::Vec4),
+                ("u_sdf_arc_stroke_alignment", Type::Int),
+            ],
+        ),
+        sx::SdfParams::Shadow(..) => (
+            "SDF_SHADOW",
+            &[
+                ("u_sdf_shadow_center", Type::Vec2),
+                ("u_sdf_shadow_size", Type::Vec2),
+                ("u_sdf_shadow_corner_radii", Type::Vec4),
+                ("u_sdf_shadow_sigma", Type::Float),
+            ],
+        ),
+    }
+}
+
+/// identifies a shader program. unlike [`sx::ShaderDesc`] this is cheap to build for every draw
+/// command; desc is built only when the program needs to be created.
+struct ShaderKey {
+    /// NOTE: custom shaders are looked up by handle, their descs are not hashed. programs are
+    ///   evicted when handle is deleted (see [`GlRenderer::handle_deleted_shaders`]).
+    custom: Option<sx::ShaderHandle>,
+    texture_format: sx::TextureFormat,
+    sdf: Option<SdfShaderVariant>,
+    gradient: bool,
+    transform: bool,
+}
+
+impl ShaderKey {
+    fn hash_key(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        self.custom.hash(&mut hasher);
+        hasher.write_u8(self.texture_format as u8);
+        match self.sdf {
+            Some((define, _)) => {
+                hasher.write_u8(1);
+                hasher.write(define.as_bytes());
+            }
+            None => hasher.write_u8(0),
+        }
+        hasher.write_u8(self.gradient as u8);
+        hasher.write_u8(self.transform as u8);
+        hasher.finish()
     }
-    hasher.finish()
+
+    fn desc(&self, shader_service: &sx::ShaderService) -> sx::ShaderDesc {
+        let mut shader_desc = match self.custom {
+            Some(handle) => shader_service.get(handle).clone(),
+            None => base_shader_desc(),
+        };
+
+        shader_desc
+            .fragment_stage
+            .defines
+            .insert(sx::ShaderDefine::from_str(match self.texture_format {
+                sx::TextureFormat::Rgba8Unorm => "TEXTURE_FORMAT_RGBA8",
+                sx::TextureFormat::R8Unorm => "TEXTURE_FORMAT_R8",
+            }));
+
+        if let Some((define, uniforms)) = self.sdf {
+            shader_desc
+                .fragment_stage
+                .defines
+                .extend_from_iter([sx::ShaderDefine::from_str(define)].into_iter());
+            shader_desc.uniforms.extend_from_iter(
+                uniforms
+                    .iter()
+                    .map(|&(name, ty)| (sx::ShaderUniformName::from_str(name), ty)),
+            );
+        }
+
+        if self.gradient {
+            use sx::ShaderUniformType as Type;
+            shader_desc
+                .fragment_stage
+                .defines
+                .extend_from_iter([sx::ShaderDefine::from_str("GRADIENT")].into_iter());
+            shader_desc.uniforms.extend_from_iter(
+                [
+                    ("u_gradient_kind", Type::Int),
+                    ("u_gradient_geometry", Type::Vec4),
+                    ("u_gradient_spread", Type::Int),
+                    ("u_gradient_interpolation", Type::Int),
+                    ("u_gradient_stop_count", Type::Int),
+                    ("u_gradient_offsets_lo", Type::Vec4),
+                    ("u_gradient_offsets_hi", Type::Vec4),
+                    ("u_gradient_color_0", Type::Vec4),
+                    ("u_gradient_color_1", Type::Vec4),
+                    ("u_gradient_color_2", Type::Vec4),
+                    ("u_gradient_color_3", Type::Vec4),
+                    ("u_gradient_color_4", Type::Vec4),
+                    ("u_gradient_color_5", Type::Vec4),
+                    ("u_gradient_color_6", Type::Vec4),
+                    ("u_gradient_color_7", Type::Vec4),
+                ]
+                .into_iter()
+                .map(|(name, ty)| (sx::ShaderUniformName::from_str(name), ty)),
+            );
+        }
+
+        if self.transform {
+            use sx::ShaderUniformType as Type;
+            shader_desc
+                .fragment_stage
+                .defines
+                .extend_from_iter([sx::ShaderDefine::from_str("TRANSFORM")].into_iter());
+            shader_desc.uniforms.extend_from_iter(
+                [
+                    ("u_inverse_transform", Type::Mat4),
+                    ("u_transform_scale", Type::Float),
+                ]
+                .into_iter()
+                .map(|(name, ty)| (sx::ShaderUniformName::from_str(name), ty)),
+            );
+        }
+
+        shader_desc
+    }
+}
+
+fn get_or_create_shader<'a>(
+    shaders: &'a mut HashMap<u64, Shader, NoBuildHasher<u64>>,
+    shader_key: &ShaderKey,
+    shader_service: &sx::ShaderService,
+    gl_api: &gl::wrap::Api,
+    temp: &TempAllocator<'_>,
+) -> anyhow::Result<&'a Shader> {
+    Ok(match shaders.entry(shader_key.hash_key()) {
+        hash_map::Entry::Occupied(x) => x.into_mut(),
+        hash_map::Entry::Vacant(x) => {
+            let shader_desc = shader_key.desc(shader_service);
+            let mut shader = Shader::new(&shader_desc, gl_api, temp).with_context(|| {
+                // TODO: can i use temp alloc here? it needs to be send+sync for some
+                // fucking reason.
+                std::format!("could not create shader\n{shader_desc:?}")
+            })?;
+            shader.custom = shader_key.custom;
+            x.insert(shader)
+        }
+    })
 }
 
 fn prefix_stage_source<A: Allocator>(
@@ -212,6 +893,7 @@ fn prefix_stage_source<A: Allocator>(
                 kind: sx::ShaderSourceKind::Static(code),
                 desc: sx::ShaderSourceDesc::Glsl { version, profile },
             },
+        snippet,
         defines,
     } = stage_desc;
 
@@ -242,6 +924,16 @@ fn prefix_stage_source<A: Allocator>(
     }
 
     ret.push_str(code);
+
+    if let Some(sx::ShaderSource {
+        kind: sx::ShaderSourceKind::Static(snippet_code),
+        ..
+    }) = snippet
+    {
+        ret.push_str("\n");
+        ret.push_str(snippet_code);
+    }
+
     Ok(ret)
 }
 
@@ -304,6 +996,8 @@ unsafe fn create_program(
 struct Shader {
     gl_handle: gl::wrap::Program,
     uniform_locations: ShaderUniformLocations,
+    /// custom shader this program was built from.
+    custom: Option<sx::ShaderHandle>,
 }
 
 impl Shader {
@@ -339,6 +1033,7 @@ impl Shader {
         Ok(Shader {
             gl_handle: program,
             uniform_locations,
+            custom: None,
         })
     }
 }
@@ -403,7 +1098,7 @@ struct Framebuffer {
     width: u32,
     height: u32,
     color_renderbuffer: gl::wrap::Renderbuffer,
-    depth_renderbuffer: gl::wrap::Renderbuffer,
+    depth_stencil_renderbuffer: gl::wrap::Renderbuffer,
     framebuffer: gl::wrap::Framebuffer,
 }
 
@@ -428,13 +1123,13 @@ fn create_framebuffer(
         );
         gl_api.bind_renderbuffer(gl::RENDERBUFFER, None);
 
-        let depth_renderbuffer = gl_api
+        let depth_stencil_renderbuffer = gl_api
             .create_renderbuffer()
             .context("could not create renderbuffer")?;
-        gl_api.bind_renderbuffer(gl::RENDERBUFFER, Some(depth_renderbuffer));
+        gl_api.bind_renderbuffer(gl::RENDERBUFFER, Some(depth_stencil_renderbuffer));
         gl_api.renderbuffer_storage(
             gl::RENDERBUFFER,
-            gl::DEPTH_COMPONENT,
+            gl::DEPTH24_STENCIL8,
             width as gl::GLint,
             height as gl::GLint,
         );
@@ -452,9 +1147,9 @@ fn create_framebuffer(
         );
         gl_api.framebuffer_renderbuffer(
             gl::FRAMEBUFFER,
-            gl::DEPTH_ATTACHMENT,
+            gl::DEPTH_STENCIL_ATTACHMENT,
             gl::RENDERBUFFER,
-            Some(depth_renderbuffer),
+            Some(depth_stencil_renderbuffer),
         );
         let framebuffer_status = gl_api.check_framebuffer_status(gl::FRAMEBUFFER);
         if framebuffer_status != gl::FRAMEBUFFER_COMPLETE {
@@ -466,7 +1161,7 @@ fn create_framebuffer(
             width,
             height,
             color_renderbuffer,
-            depth_renderbuffer,
+            depth_stencil_renderbuffer,
             framebuffer,
         })
     }
@@ -475,7 +1170,7 @@ fn create_framebuffer(
 fn delete_framebuffer(framebuffer: Framebuffer, gl_api: &gl::wrap::Api) {
     unsafe {
         gl_api.delete_renderbuffer(framebuffer.color_renderbuffer);
-        gl_api.delete_renderbuffer(framebuffer.depth_renderbuffer);
+        gl_api.delete_renderbuffer(framebuffer.depth_stencil_renderbuffer);
         gl_api.delete_framebuffer(framebuffer.framebuffer);
     }
 }
@@ -705,15 +1400,64 @@ impl GlRenderer {
         Ok(())
     }
 
+    /// deletes programs that were built from deleted custom shaders. must be called before
+    /// [`GlRenderer::render`] because handles get reused.
+    pub fn handle_deleted_shaders(
+        &mut self,
+        deleted: impl Iterator<Item = sx::ShaderHandle>,
+        gl_api: &gl::wrap::Api,
+    ) {
+        for handle in deleted {
+            self.shaders.retain(|_, shader| {
+                if shader.custom != Some(handle) {
+                    return true;
+                }
+                unsafe { gl_api.delete_program(shader.gl_handle) };
+                false
+            });
+        }
+    }
+
+    /// makes a desc of the renderer's own shader with `snippet` plugged into the fragment stage.
+    /// register it with [`sx::ShaderService`] and draw with [`sx::Fill::Shader`].
+    ///
+    /// snippet must define `vec4 custom_shader(vec4 color, vec2 local_pos)` and declare uniforms
+    /// it uses; `uniforms` must list them.
+    pub fn fragment_shader_desc(
+        snippet: &'static str,
+        uniforms: &[(&str, sx::ShaderUniformType)],
+    ) -> sx::ShaderDesc {
+        let mut shader_desc = base_shader_desc();
+        shader_desc.fragment_stage.snippet = Some(sx::ShaderSource {
+            kind: sx::ShaderSourceKind::Static(snippet),
+            desc: SHADER_SOURCE.desc,
+        });
+        shader_desc
+            .fragment_stage
+            .defines
+            .extend_from_iter([sx::ShaderDefine::from_str("CUSTOM_SHADER")].into_iter());
+        shader_desc.uniforms.extend_from_iter(
+            uniforms
+                .iter()
+                .map(|&(name, ty)| (sx::ShaderUniformName::from_str(name), ty)),
+        );
+        shader_desc
+    }
+
     pub fn render<'a>(
         &mut self,
         logical_size: sx::Vec2,
         scale_factor: f32,
         draw_data: &sx::DrawData,
+        shader_service: &sx::ShaderService,
         gl_api: &gl::wrap::Api,
         temp: &TempAllocator<'_>,
     ) -> anyhow::Result<()> {
-        let physical_size = logical_size * scale_factor;
+        // NOTE: must match window's logical_to_physical_size.
+        let physical_size = sx::Vec2::new(
+            (logical_size.x * scale_factor).ceil(),
+            (logical_size.y * scale_factor).ceil(),
+        );
         // NOTE: this is opengl-specific matrix. y is up.
         //   glBlitFramebuffer flips whole thing.
         //   this way is easier because there's no need to micromanage each uniform value, etc.
@@ -749,7 +1493,8 @@ impl GlRenderer {
             gl_api.bind_framebuffer(gl::FRAMEBUFFER, Some(framebuffer.framebuffer));
 
             gl_api.clear_color(0.0, 0.0, 0.0, 1.0);
-            gl_api.clear(gl::COLOR_BUFFER_BIT);
+            gl_api.clear_stencil(0);
+            gl_api.clear(gl::COLOR_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
 
             gl_api.viewport(
                 0,
@@ -776,6 +1521,7 @@ impl GlRenderer {
             vertices,
             indices,
             commands,
+            clip_masks,
             ..
         } = draw_data;
         unsafe {
@@ -794,13 +1540,71 @@ impl GlRenderer {
             );
         }
 
+        // NOTE: masks that are currently written into the stencil buffer, outermost first.
+        //   value of the stencil is the number of masks (of this chain) that cover the pixel.
+        let mut stencil_chain: Vec<u32> = Vec::new();
+        let mut command_chain: Vec<u32> = Vec::new();
+
         for sx::DrawCommand {
             index_range,
-            texture,
             scissor,
+            clip_mask,
+            texture,
             sdf_params,
+            gradient,
+            shader,
+            shader_uniforms,
+            transform,
         } in commands
         {
+            command_chain.clear();
+            let mut next = *clip_mask;
+            while let Some(id) = next {
+                command_chain.push(id);
+                next = clip_masks[id as usize].parent;
+            }
+            command_chain.reverse();
+
+            if command_chain.is_empty() {
+                unsafe { gl_api.disable(gl::STENCIL_TEST) };
+            } else {
+                // NOTE: if command's chain is a prefix of what's in the stencil (clip was popped)
+                //   nothing needs to be written, stencil test below handles it.
+                if !stencil_chain.starts_with(&command_chain) {
+                    // NOTE: if what's in the stencil is a prefix of command's chain (clip was
+                    //   pushed) only new masks need to be written.
+                    if !command_chain.starts_with(&stencil_chain) {
+                        unsafe { gl_api.clear(gl::STENCIL_BUFFER_BIT) };
+                        stencil_chain.clear();
+                    }
+                    unsafe {
+                        gl_api.enable(gl::STENCIL_TEST);
+                        gl_api.color_mask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+                        gl_api.stencil_op(gl::KEEP, gl::KEEP, gl::INCR);
+                    }
+                    for (depth, &id) in command_chain.iter().enumerate().skip(stencil_chain.len()) {
+                        unsafe { gl_api.stencil_func(gl::EQUAL, depth as gl::GLint, 0xff) };
+                        self.draw_clip_mask(
+                            &clip_masks[id as usize],
+                            &projection_matrix,
+                            scale_factor,
+                            shader_service,
+                            gl_api,
+                            temp,
+                        )?;
+                    }
+                    unsafe {
+                        gl_api.color_mask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+                        gl_api.stencil_op(gl::KEEP, gl::KEEP, gl::KEEP);
+                    }
+                    stencil_chain.clone_from(&command_chain);
+                }
+                unsafe {
+                    gl_api.enable(gl::STENCIL_TEST);
+                    gl_api.stencil_func(gl::LEQUAL, command_chain.len() as gl::GLint, 0xff);
+                }
+            }
+
             let _maybe_scissor_guard = scissor.map(|logical_rect| {
                 // NOTE: scissor needs to be aware of y flip.
                 //   projection matrix does not flip y, it's opengl-specific.
@@ -820,117 +1624,27 @@ impl GlRenderer {
                 DropGuard::new(|| unsafe { gl_api.disable(gl::SCISSOR_TEST) })
             });
 
-            let mut shader_desc = sx::ShaderDesc {
-                vertex_stage: sx::ShaderStageDesc {
-                    source: SHADER_SOURCE.clone(),
-                    defines: {
-                        let mut defines = sx::ShaderDefines::default();
-                        defines.extend_from_iter(
-                            [sx::ShaderDefine::from_str("SHADER_STAGE_VERTEX")].into_iter(),
-                        );
-                        defines
-                    },
-                },
-                fragment_stage: sx::ShaderStageDesc {
-                    source: SHADER_SOURCE.clone(),
-                    defines: {
-                        let mut defines = sx::ShaderDefines::default();
-                        defines.extend_from_iter(
-                            [sx::ShaderDefine::from_str("SHADER_STAGE_FRAGMENT")].into_iter(),
-                        );
-                        defines
-                    },
-                },
-                uniforms: {
-                    let mut uniforms = sx::ShaderUniformDescs::default();
-                    uniforms.extend_from_iter(
-                        [
-                            (
-                                sx::ShaderUniformName::from_str("u_projection"),
-                                sx::ShaderUniformType::Mat4,
-                            ),
-                            (
-                                sx::ShaderUniformName::from_str("u_scale"),
-                                sx::ShaderUniformType::Float,
-                            ),
-                            (
-                                sx::ShaderUniformName::from_str("u_sampler"),
-                                sx::ShaderUniformType::Sampler2D,
-                            ),
-                        ]
-                        .into_iter(),
-                    );
-                    uniforms
-                },
-            };
-
             let texture = if let Some(handle) = texture {
                 self.textures.get(&handle).expect("invalid handle")
             } else {
                 &self.default_white_texture
             };
-            shader_desc
-                .fragment_stage
-                .defines
-                .insert(sx::ShaderDefine::from_str(match texture.format {
-                    sx::TextureFormat::Rgba8Unorm => "TEXTURE_FORMAT_RGBA8",
-                    sx::TextureFormat::R8Unorm => "TEXTURE_FORMAT_R8",
-                }));
-
-            if let Some(sdf_params) = sdf_params.as_ref() {
-                use sx::ShaderUniformType as Type;
-                match sdf_params {
-                    sx::SdfParams::Rect(..) => {
-                        shader_desc
-                            .fragment_stage
-                            .defines
-                            .extend_from_iter([sx::ShaderDefine::from_str("SDF_RECT")].into_iter());
-
-                        shader_desc.uniforms.extend_from_iter(
-                            [
-                                (
-                                    sx::ShaderUniformName::from_str("u_sdf_rect_center"),
-                                    Type::Vec2,
-                                ),
-                                (
-                                    sx::ShaderUniformName::from_str("u_sdf_rect_size"),
-                                    Type::Vec2,
-                                ),
-                                (
-                                    sx::ShaderUniformName::from_str("u_sdf_rect_corner_radius"),
-                                    Type::Float,
-                                ),
-                                (
-                                    sx::ShaderUniformName::from_str("u_sdf_rect_stroke_width"),
-                                    Type::Float,
-                                ),
-                                (
-                                    sx::ShaderUniformName::from_str("u_sdf_rect_stroke_color"),
-                                    Type::Vec4,
-                                ),
-                                (
-                                    sx::ShaderUniformName::from_str("u_sdf_rect_stroke_alignment"),
-                                    Type::Int,
-                                ),
-                            ]
-                            .into_iter(),
-                        );
-                    }
-                }
-            }
 
-            let shader_key = hash_shader_desc(&shader_desc);
-            let shader = match self.shaders.entry(shader_key) {
-                hash_map::Entry::Occupied(x) => x.into_mut(),
-                hash_map::Entry::Vacant(x) => {
-                    let shader = Shader::new(&shader_desc, gl_api, temp).with_context(|| {
-                        // TODO: can i use temp alloc here? it needs to be send+sync for some
-                        // fucking reason.
-                        std::format!("could not create shader\n{shader_desc:?}")
-                    })?;
-                    x.insert(shader)
-                }
+            // NOTE: vertices are already transformed, transform matters only for things that are
+            //   evaluated in the fragment shader.
+            let transform = (*transform != sx::Affine2::IDENTITY
+                && (sdf_params.is_some() || gradient.is_some() || shader.is_some()))
+            .then_some(*transform);
+
+            let shader_key = ShaderKey {
+                custom: *shader,
+                texture_format: texture.format,
+                sdf: sdf_params.as_ref().map(sdf_shader_variant),
+                gradient: gradient.is_some(),
+                transform: transform.is_some(),
             };
+            let shader =
+                get_or_create_shader(&mut self.shaders, &shader_key, shader_service, gl_api, temp)?;
 
             unsafe {
                 gl_api.use_program(Some(shader.gl_handle));
@@ -940,9 +1654,11 @@ impl GlRenderer {
                 //
                 // NOTE: this is somewhat awkward,
                 //   but still i prefer this loop over individual lookups for each loc.
+                use sx::SdfParams::{Arc, Ellipse, Rect, Shadow};
+                let mut next_texture_unit = 1;
                 for (name, location) in shader.uniform_locations.0.iter() {
-                    match name.as_str() {
-                        "u_projection" => {
+                    match (name.as_str(), sdf_params.as_ref()) {
+                        ("u_projection", _) => {
                             gl_api.uniform_matrix_4fv(
                                 *location,
                                 1,
@@ -950,55 +1666,159 @@ impl GlRenderer {
                                 projection_matrix.as_ptr().cast(),
                             );
                         }
-                        "u_scale" => {
+                        ("u_scale", _) => {
                             gl_api.uniform_1f(*location, scale_factor);
                         }
-                        "u_sampler" => {
+                        ("u_sampler", _) => {
                             gl_api.active_texture(gl::TEXTURE0);
                             gl_api.bind_texture(gl::TEXTURE_2D, Some(texture.gl_handle));
                             gl_api.uniform_1i(*location, 0);
                         }
 
-                        "u_sdf_rect_center" => {
-                            let Some(sx::SdfParams::Rect(p)) = sdf_params.as_ref() else {
-                                unreachable!();
-                            };
+                        ("u_inverse_transform", _) => {
+                            let transform = transform.expect("transform uniform without transform");
+                            let m = affine_to_mat4(transform.inverse());
+                            gl_api.uniform_matrix_4fv(*location, 1, gl::FALSE, m.as_ptr().cast());
+                        }
+                        ("u_transform_scale", _) => {
+                            let transform = transform.expect("transform uniform without transform");
+                            gl_api.uniform_1f(*location, transform.scale_factor());
+                        }
+
+                        ("u_sdf_rect_center", Some(Rect(p))) => {
                             gl_api.uniform_2f(*location, p.center[0], p.center[1]);
                         }
-                        "u_sdf_rect_size" => {
-                            let Some(sx::SdfParams::Rect(p)) = sdf_params.as_ref() else {
-                                unreachable!();
-                            };
+                        ("u_sdf_rect_size", Some(Rect(p))) => {
                             gl_api.uniform_2f(*location, p.size[0], p.size[1]);
                         }
-                        "u_sdf_rect_corner_radius" => {
-                            let Some(sx::SdfParams::Rect(p)) = sdf_params.as_ref() else {
-                                unreachable!();
-                            };
-                            gl_api.uniform_1f(*location, p.corner_radius);
+                        ("u_sdf_rect_corner_radii", Some(Rect(p))) => {
+                            let r = p.corner_radii;
+                            gl_api.uniform_4f(*location, r[0], r[1], r[2], r[3]);
                         }
-                        "u_sdf_rect_stroke_width" => {
-                            let Some(sx::SdfParams::Rect(p)) = sdf_params.as_ref() else {
-                                unreachable!();
-                            };
+                        ("u_sdf_rect_stroke_widths", Some(Rect(p))) => {
+                            let w = p.stroke_widths;
+                            gl_api.uniform_4f(*location, w[0], w[1], w[2], w[3]);
+                        }
+                        ("u_sdf_rect_stroke_color_top", Some(Rect(p))) => {
+                            let c = p.stroke_colors[0];
+                            gl_api.uniform_4f(*location, c[0], c[1], c[2], c[3]);
+                        }
+                        ("u_sdf_rect_stroke_color_right", Some(Rect(p))) => {
+                            let c = p.stroke_colors[1];
+                            gl_api.uniform_4f(*location, c[0], c[1], c[2], c[3]);
+                        }
+                        ("u_sdf_rect_stroke_color_bottom", Some(Rect(p))) => {
+                            let c = p.stroke_colors[2];
+                            gl_api.uniform_4f(*location, c[0], c[1], c[2], c[3]);
+                        }
+                        ("u_sdf_rect_stroke_color_left", Some(Rect(p))) => {
+                            let c = p.stroke_colors[3];
+                            gl_api.uniform_4f(*location, c[0], c[1], c[2], c[3]);
+                        }
+                        ("u_sdf_rect_stroke_alignment", Some(Rect(p))) => {
+                            gl_api.uniform_1i(*location, p.stroke_alignment);
+                        }
+
+                        ("u_sdf_ellipse_center", Some(Ellipse(p))) => {
+                            gl_api.uniform_2f(*location, p.center[0], p.center[1]);
+                        }
+                        ("u_sdf_ellipse_radii", Some(Ellipse(p))) => {
+                            gl_api.uniform_2f(*location, p.radii[0], p.radii[1]);
+                        }
+                        ("u_sdf_ellipse_stroke_width", Some(Ellipse(p))) => {
                             gl_api.uniform_1f(*location, p.stroke_width);
                         }
-                        "u_sdf_rect_stroke_color" => {
-                            let Some(sx::SdfParams::Rect(p)) = sdf_params.as_ref() else {
-                                unreachable!();
-                            };
+                        ("u_sdf_ellipse_stroke_color", Some(Ellipse(p))) => {
                             let c = p.stroke_color;
                             gl_api.uniform_4f(*location, c[0], c[1], c[2], c[3]);
                         }
-                        "u_sdf_rect_stroke_alignment" => {
-                            let Some(sx::SdfParams::Rect(p)) = sdf_params.as_ref() else {
-                                unreachable!();
-                            };
+                        ("u_sdf_ellipse_stroke_alignment", Some(Ellipse(p))) => {
+                            gl_api.uniform_1i(*location, p.stroke_alignment);
+                        }
+
+                        ("u_sdf_arc_center", Some(Arc(p))) => {
+                            gl_api.uniform_2f(*location, p.center[0], p.center[1]);
+                        }
+                        ("u_sdf_arc_radius", Some(Arc(p))) => {
+                            gl_api.uniform_1f(*location, p.radius);
+                        }
+                        ("u_sdf_arc_angles", Some(Arc(p))) => {
+                            gl_api.uniform_2f(*location, p.angles[0], p.angles[1]);
+                        }
+                        ("u_sdf_arc_thickness", Some(Arc(p))) => {
+                            gl_api.uniform_1f(*location, p.thickness);
+                        }
+                        ("u_sdf_arc_cap", Some(Arc(p))) => {
+                            gl_api.uniform_1i(*location, p.cap);
+                        }
+                        ("u_sdf_arc_stroke_width", Some(Arc(p))) => {
+                            gl_api.uniform_1f(*location, p.stroke_width);
+                        }
+                        ("u_sdf_arc_stroke_color", Some(Arc(p))) => {
+                            let c = p.stroke_color;
+                            gl_api.uniform_4f(*location, c[0], c[1], c[2], c[3]);
+                        }
+                        ("u_sdf_arc_stroke_alignment", Some(Arc(p))) => {
                             gl_api.uniform_1i(*location, p.stroke_alignment);
                         }
 
-                        other => {
-                            log::warn!("uniform {other} was left unset");
+                        ("u_sdf_shadow_center", Some(Shadow(p))) => {
+                            gl_api.uniform_2f(*location, p.center[0], p.center[1]);
+                        }
+                        ("u_sdf_shadow_size", Some(Shadow(p))) => {
+                            gl_api.uniform_2f(*location, p.size[0], p.size[1]);
+                        }
+                        ("u_sdf_shadow_corner_radii", Some(Shadow(p))) => {
+                            let r = p.corner_radii;
+                            gl_api.uniform_4f(*location, r[0], r[1], r[2], r[3]);
+                        }
+                        ("u_sdf_shadow_sigma", Some(Shadow(p))) => {
+                            gl_api.uniform_1f(*location, p.sigma);
+                        }
+
+                        (name, _) if name.starts_with("u_gradient_") => {
+                            let gradient = gradient
+                                .as_ref()
+                                .expect("gradient uniform without gradient");
+                            set_gradient_uniform(gl_api, *location, name, gradient);
+                        }
+
+                        (other, _) => {
+                            let Some((_, value)) = shader_uniforms
+                                .0
+                                .iter()
+                                .find(|(name, _)| name.as_str() == other)
+                            else {
+                                log::warn!("uniform {other} was left unset");
+                                continue;
+                            };
+                            match value {
+                                sx::ShaderUniformValue::Int(v) => gl_api.uniform_1i(*location, *v),
+                                sx::ShaderUniformValue::Float(v) => {
+                                    gl_api.uniform_1f(*location, *v)
+                                }
+                                sx::ShaderUniformValue::Vec2(v) => {
+                                    gl_api.uniform_2f(*location, v[0], v[1])
+                                }
+                                sx::ShaderUniformValue::Vec4(v) => {
+                                    gl_api.uniform_4f(*location, v[0], v[1], v[2], v[3])
+                                }
+                                sx::ShaderUniformValue::Mat4(m) => gl_api.uniform_matrix_4fv(
+                                    *location,
+                                    1,
+                                    gl::FALSE,
+                                    m.as_ptr().cast(),
+                                ),
+                                sx::ShaderUniformValue::Texture2D(handle) => {
+                                    // NOTE: unit 0 is taken by u_sampler.
+                                    let texture =
+                                        self.textures.get(handle).expect("invalid handle");
+                                    gl_api.active_texture(gl::TEXTURE0 + next_texture_unit);
+                                    gl_api.bind_texture(gl::TEXTURE_2D, Some(texture.gl_handle));
+                                    gl_api.uniform_1i(*location, next_texture_unit as gl::GLint);
+                                    next_texture_unit += 1;
+                                }
+                            }
                         }
                     }
                 }
@@ -1012,6 +1832,70 @@ impl GlRenderer {
             }
         }
 
+        unsafe { gl_api.disable(gl::STENCIL_TEST) };
+
+        Ok(())
+    }
+
+    fn draw_clip_mask(
+        &mut self,
+        clip_mask: &sx::ClipMask,
+        projection_matrix: &[[f32; 4]; 4],
+        scale_factor: f32,
+        shader_service: &sx::ShaderService,
+        gl_api: &gl::wrap::Api,
+        temp: &TempAllocator<'_>,
+    ) -> anyhow::Result<()> {
+        // NOTE: colors are masked out, only coverage matters.
+        let shader_key = ShaderKey {
+            custom: None,
+            texture_format: sx::TextureFormat::Rgba8Unorm,
+            sdf: None,
+            gradient: false,
+            transform: false,
+        };
+        let shader =
+            get_or_create_shader(&mut self.shaders, &shader_key, shader_service, gl_api, temp)?;
+
+        unsafe {
+            gl_api.use_program(Some(shader.gl_handle));
+
+            for (name, location) in shader.uniform_locations.0.iter() {
+                match name.as_str() {
+                    "u_projection" => {
+                        gl_api.uniform_matrix_4fv(
+                            *location,
+                            1,
+                            gl::FALSE,
+                            projection_matrix.as_ptr().cast(),
+                        );
+                    }
+                    "u_scale" => {
+                        gl_api.uniform_1f(*location, scale_factor);
+                    }
+                    "u_sampler" => {
+                        gl_api.active_texture(gl::TEXTURE0);
+                        gl_api.bind_texture(
+                            gl::TEXTURE_2D,
+                            Some(self.default_white_texture.gl_handle),
+                        );
+                        gl_api.uniform_1i(*location, 0);
+                    }
+                    other => {
+                        log::warn!("uniform {other} was left unset");
+                    }
+                }
+            }
+
+            let index_range = &clip_mask.index_range;
+            gl_api.draw_elements(
+                gl::TRIANGLES,
+                index_range.len() as gl::GLsizei,
+                gl::UNSIGNED_INT,
+                (index_range.start * size_of::<u32>() as u32) as *const c_void,
+            );
+        }
+
         Ok(())
     }
 
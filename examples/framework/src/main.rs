@@ -1,5 +1,6 @@
 use std::iter;
 use std::mem::MaybeUninit;
+use std::time::Duration;
 
 use anyhow::{Context as _, anyhow};
 use example_framework::{GlContext, GlRenderer};
@@ -9,6 +10,16 @@ use window::{Event, Window, WindowAttrs, WindowEvent};
 
 const DEFAULT_FONT_DATA: &[u8] = include_bytes!("../fixtures/JetBrainsMono-Regular.ttf");
 
+const CHECKER_SHADER: &str = "
+uniform float u_checker_size;
+uniform vec4 u_checker_color;
+
+vec4 custom_shader(vec4 color, vec2 local_pos) {
+    vec2 cell = floor(local_pos / u_checker_size);
+    return mix(color, u_checker_color, mod(cell.x + cell.y, 2.0));
+}
+";
+
 struct Logger;
 
 impl log::Log for Logger {
@@ -90,8 +101,15 @@ struct Context {
     texture_service: sx::TextureService,
     font_service: sx::FontService,
     default_font_handle: sx::FontHandle,
+    shader_service: sx::ShaderService,
+    checker_shader_handle: sx::ShaderHandle,
     draw_buffer: sx::DrawBuffer,
     gl_renderer: GlRenderer,
+    /// none until the first frame is presented.
+    presented_physical_size: Option<sx::U32Vec2>,
+    /// set when last frame had nothing new to present. there's no point in drawing again until
+    /// something happens.
+    idle: bool,
 }
 
 impl Context {
@@ -133,6 +151,15 @@ impl Context {
             .register_font_slice(DEFAULT_FONT_DATA)
             .context("default font is invalid")?;
 
+        let mut shader_service = sx::ShaderService::default();
+        let checker_shader_handle = shader_service.create(GlRenderer::fragment_shader_desc(
+            CHECKER_SHADER,
+            &[
+                ("u_checker_size", sx::ShaderUniformType::Float),
+                ("u_checker_color", sx::ShaderUniformType::Vec4),
+            ],
+        ));
+
         let gl_renderer =
             GlRenderer::new(&gl_context.api).context("could not create gl renderer")?;
 
@@ -145,13 +172,21 @@ impl Context {
             texture_service: sx::TextureService::default(),
             font_service,
             default_font_handle,
+            shader_service,
+            checker_shader_handle,
             draw_buffer: sx::DrawBuffer::default(),
             gl_renderer,
+            presented_physical_size: None,
+            idle: false,
         })
     }
 
     fn iterate(&mut self, temp: &TempAllocator<'_>) -> anyhow::Result<()> {
-        self.window.pump_events()?;
+        self.window.pump_events(if self.idle {
+            None
+        } else {
+            Some(Duration::ZERO)
+        })?;
         let input_events =
             iter::from_fn(|| self.window.pop_event()).filter_map(|event| match event {
                 Event::Window(window_event) => {
@@ -173,21 +208,24 @@ impl Context {
 
         let logical_size = sx::U32Vec2::from(self.window.logical_size()).as_vec2();
         let scale_factor = self.window.scale_factor() as f32;
-        let physical_size = logical_size * scale_factor;
+        let physical_size = sx::U32Vec2::from(self.window.physical_size());
 
         let raw_window_handle = self
             .window
             .window_handle()
             .context("window handle is unavailable")?
             .as_raw();
-        self.gl_context.make_window_current(
-            raw_window_handle,
-            physical_size.x as u32,
-            physical_size.y as u32,
-        )?;
+        self.gl_context
+            .make_window_current(raw_window_handle, physical_size.x, physical_size.y)?;
 
         {
             let center_rect = sx::Rect::from_center_size(logical_size * 0.5, 64.0);
+            self.draw_buffer.push_shadow(
+                sx::ShadowShape::new(center_rect)
+                    .with_offset(sx::Vec2::new(0.0, 4.0))
+                    .with_blur_radius(16.0)
+                    .with_color(sx::Rgba8::BLACK.with_af(0.5)),
+            );
             self.draw_buffer.push_rect(
                 sx::RectShape::new(center_rect).with_fill(Some(sx::Fill::Color(sx::Rgba8::MAROON))),
             );
@@ -198,12 +236,163 @@ impl Context {
             self.draw_buffer.push_rect(
                 sx::RectShape::new(other_rect)
                     .with_fill(Some(sx::Fill::Color(sx::Rgba8::RED)))
-                    .with_corner_radius(Some(12.0))
+                    .with_corner_radius(Some(sx::CornerRadii::all(12.0)))
                     .with_stroke(Some(
                         sx::Stroke::new(8.0, sx::Rgba8::WHITE.with_af(0.5))
                             .with_alignment(sx::StrokeAlignment::Center),
                     )),
             );
+
+            // tab: only top corners are rounded, and the bottom side is highlighted.
+            let tab_rect = sx::Rect::new(sx::Vec2::splat(16.0), sx::Vec2::new(136.0, 48.0));
+            self.draw_buffer.push_rect(
+                sx::RectShape::new(tab_rect)
+                    .with_fill(Some(sx::Fill::Color(sx::Rgba8::MAROON)))
+                    .with_corner_radius(Some(sx::CornerRadii::top(8.0)))
+                    .with_rect_stroke(Some(
+                        sx::RectStroke::default()
+                            .with_top(1.0, sx::Rgba8::WHITE.with_af(0.5))
+                            .with_bottom(3.0, sx::Rgba8::RED)
+                            .with_alignment(sx::StrokeAlignment::Inside),
+                    )),
+            );
+
+            // radio button and a spinner.
+            let radio_center = sx::Vec2::new(168.0, 32.0);
+            self.draw_buffer.push_circle(
+                sx::CircleShape::new(radio_center, 8.0)
+                    .with_stroke(Some(sx::Stroke::new(2.0, sx::Rgba8::WHITE))),
+            );
+            self.draw_buffer.push_circle(
+                sx::CircleShape::new(radio_center, 4.0)
+                    .with_fill(Some(sx::Fill::Color(sx::Rgba8::WHITE))),
+            );
+            self.draw_buffer.push_arc(
+                sx::ArcShape::new(
+                    sx::Vec2::new(208.0, 32.0),
+                    10.0,
+                    0.0,
+                    std::f32::consts::PI * 1.5,
+                    4.0,
+                )
+                .with_cap(sx::ArcCap::Round)
+                .with_fill(Some(sx::Fill::Color(sx::Rgba8::RED))),
+            );
+
+            // check mark.
+            let check_mark = sx::Path::new()
+                .move_to(sx::Vec2::new(232.0, 32.0))
+                .line_to(sx::Vec2::new(238.0, 38.0))
+                .line_to(sx::Vec2::new(250.0, 24.0));
+            self.draw_buffer.push_path(
+                sx::PathShape::new(&check_mark)
+                    .with_stroke(Some(sx::Stroke::new(3.0, sx::Rgba8::LIME)))
+                    .with_join(sx::LineJoin::Round)
+                    .with_cap(sx::LineCap::Round),
+            );
+
+            // gradient button and a color wheel.
+            let button_rect = sx::Rect::new(sx::Vec2::new(16.0, 64.0), sx::Vec2::new(136.0, 96.0));
+            self.draw_buffer.push_rect(
+                sx::RectShape::new(button_rect)
+                    .with_fill(Some(sx::Fill::Gradient(
+                        sx::Gradient::linear(button_rect.top_left(), button_rect.bottom_left())
+                            .with_stop(0.0, sx::Rgba8::RED)
+                            .with_stop(1.0, sx::Rgba8::MAROON),
+                    )))
+                    .with_corner_radius(Some(sx::CornerRadii::all(6.0))),
+            );
+            let wheel_center = sx::Vec2::new(168.0, 80.0);
+            self.draw_buffer.push_circle(
+                sx::CircleShape::new(wheel_center, 16.0).with_fill(Some(sx::Fill::Gradient(
+                    sx::Gradient::conic(wheel_center, 0.0)
+                        .with_stop(0.0, sx::Rgba8::RED)
+                        .with_stop(1.0 / 6.0, sx::Rgba8::YELLOW)
+                        .with_stop(2.0 / 6.0, sx::Rgba8::LIME)
+                        .with_stop(3.0 / 6.0, sx::Rgba8::AQUA)
+                        .with_stop(4.0 / 6.0, sx::Rgba8::BLUE)
+                        .with_stop(5.0 / 6.0, sx::Rgba8::FUCHSIA)
+                        .with_stop(1.0, sx::Rgba8::RED)
+                        .with_interpolation(sx::GradientInterpolation::Linear),
+                ))),
+            );
+
+            // rotated badge.
+            self.draw_buffer.push_transform(
+                sx::Affine2::from_translation(sx::Vec2::new(232.0, 80.0))
+                    * sx::Affine2::from_angle(-std::f32::consts::FRAC_PI_8),
+            );
+            self.draw_buffer.push_rect(
+                sx::RectShape::new(sx::Rect::new(
+                    sx::Vec2::new(-24.0, -10.0),
+                    sx::Vec2::new(24.0, 10.0),
+                ))
+                .with_fill(Some(sx::Fill::Color(sx::Rgba8::ORANGE)))
+                .with_corner_radius(Some(sx::CornerRadii::all(4.0))),
+            );
+            self.draw_buffer.pop_transform();
+
+            // rounded panel that clips its (scrolled) content.
+            let panel_rect = sx::Rect::new(sx::Vec2::new(16.0, 112.0), sx::Vec2::new(136.0, 176.0));
+            self.draw_buffer
+                .push_clip_rounded_rect(panel_rect, sx::CornerRadii::all(12.0));
+            for i in 0..6 {
+                let row_min = panel_rect.min + sx::Vec2::new(0.0, i as f32 * 16.0 - 8.0);
+                self.draw_buffer.push_rect(
+                    sx::RectShape::new(sx::Rect::new(
+                        row_min,
+                        sx::Vec2::new(panel_rect.max.x, row_min.y + 16.0),
+                    ))
+                    .with_fill(Some(sx::Fill::Color(if i % 2 == 0 {
+                        sx::Rgba8::TEAL
+                    } else {
+                        sx::Rgba8::NAVY
+                    }))),
+                );
+            }
+
+            // tooltip is recorded while the panel's clip is active, but it goes into its own
+            // layer, so it's neither clipped by the panel nor covered by anything drawn later.
+            self.draw_buffer.set_layer(sx::Layer::TOOLTIP);
+            let tooltip_rect =
+                sx::Rect::new(sx::Vec2::new(96.0, 160.0), sx::Vec2::new(176.0, 184.0));
+            self.draw_buffer.push_shadow(
+                sx::ShadowShape::new(tooltip_rect)
+                    .with_corner_radius(Some(sx::CornerRadii::all(4.0)))
+                    .with_offset(sx::Vec2::new(0.0, 2.0))
+                    .with_blur_radius(8.0)
+                    .with_color(sx::Rgba8::BLACK.with_af(0.5)),
+            );
+            self.draw_buffer.push_rect(
+                sx::RectShape::new(tooltip_rect)
+                    .with_fill(Some(sx::Fill::Color(sx::Rgba8::SILVER)))
+                    .with_corner_radius(Some(sx::CornerRadii::all(4.0))),
+            );
+            self.draw_buffer.set_layer(sx::Layer::CONTENT);
+
+            self.draw_buffer.pop_clip();
+
+            // checkerboard made with a custom shader; rounded corners still come from the sdf.
+            let mut checker_uniforms = sx::ShaderUniforms::default();
+            checker_uniforms.insert(
+                sx::ShaderUniformName::from_str("u_checker_size"),
+                sx::ShaderUniformValue::Float(8.0),
+            );
+            checker_uniforms.insert(
+                sx::ShaderUniformName::from_str("u_checker_color"),
+                sx::ShaderUniformValue::Vec4(sx::Rgba8::GRAY.to_f32_array()),
+            );
+            self.draw_buffer.push_rect(
+                sx::RectShape::new(sx::Rect::new(
+                    sx::Vec2::new(192.0, 112.0),
+                    sx::Vec2::new(256.0, 176.0),
+                ))
+                .with_fill(Some(sx::Fill::Shader {
+                    shader: self.checker_shader_handle,
+                    uniforms: checker_uniforms,
+                }))
+                .with_corner_radius(Some(sx::CornerRadii::all(8.0))),
+            );
         }
 
         {
@@ -229,22 +418,55 @@ impl Context {
             );
         }
 
-        let texture_commands = self.texture_service.drain_comands();
+        let mut texture_commands = self.texture_service.drain_comands().peekable();
+        let textures_changed = texture_commands.peek().is_some();
         self.gl_renderer
             .handle_texture_commands(texture_commands, &self.gl_context.api)?;
+        self.gl_renderer
+            .handle_deleted_shaders(self.shader_service.drain_deleted(), &self.gl_context.api);
 
         self.draw_buffer.flush();
+
+        // NOTE: new surface size or new texture contents invalidate everything; otherwise only
+        //   regions where draw commands differ from the previous frame need to be repainted.
+        let full_damage = textures_changed || self.presented_physical_size != Some(physical_size);
+        if !full_damage && !self.draw_buffer.changed() {
+            self.draw_buffer.clear();
+            self.idle = true;
+            return Ok(());
+        }
+        self.idle = false;
+        let mut damage = [[0; 4]; sx::MAX_DAMAGE_RECTS];
+        let mut damage_len = 0;
+        if !full_damage {
+            for rect in self.draw_buffer.damage().iter() {
+                // NOTE: round outwards so that anti-aliased edges are included.
+                let min_x = (rect.min.x * scale_factor).floor().max(0.0) as i32;
+                let min_y = (rect.min.y * scale_factor).floor().max(0.0) as i32;
+                let max_x = ((rect.max.x * scale_factor).ceil() as i32).min(physical_size.x as i32);
+                let max_y = ((rect.max.y * scale_factor).ceil() as i32).min(physical_size.y as i32);
+                if min_x < max_x && min_y < max_y {
+                    damage[damage_len] = [min_x, min_y, max_x - min_x, max_y - min_y];
+                    damage_len += 1;
+                }
+            }
+        }
+
         self.gl_renderer.render(
             logical_size,
             scale_factor,
             self.draw_buffer.draw_data(),
+            &self.shader_service,
             &self.gl_context.api,
             temp,
         )?;
         self.draw_buffer.clear();
 
         self.gl_renderer.render_to_screen(&self.gl_context.api)?;
-        self.gl_context.swap_window_buffers(raw_window_handle)?;
+        self.window.pre_present_notify();
+        self.gl_context
+            .swap_window_buffers(raw_window_handle, &damage[..damage_len])?;
+        self.presented_physical_size = Some(physical_size);
 
         Ok(())
     }
@@ -38,6 +38,8 @@ pub trait Adapter {
     unsafe fn check_framebuffer_status(&self, target: gl::GLenum) -> gl::GLenum;
     unsafe fn clear(&self, mask: gl::GLbitfield);
     unsafe fn clear_color(&self, red: gl::GLfloat, green: gl::GLfloat, blue: gl::GLfloat, alpha: gl::GLfloat);
+    unsafe fn clear_stencil(&self, s: gl::GLint);
+    unsafe fn color_mask(&self, red: gl::GLboolean, green: gl::GLboolean, blue: gl::GLboolean, alpha: gl::GLboolean);
     unsafe fn compile_shader(&self, shader: Self::Shader);
     unsafe fn create_buffer(&self) -> Option<Self::Buffer>;
     unsafe fn create_framebuffer(&self) -> Option<Self::Framebuffer>;
@@ -83,6 +85,8 @@ pub trait Adapter {
     unsafe fn renderbuffer_storage(&self, target: gl::GLenum, internalformat: gl::GLenum, width: gl::GLsizei, height: gl::GLsizei);
     unsafe fn scissor(&self, x: gl::GLint, y: gl::GLint, width: gl::GLsizei, height: gl::GLsizei);
     unsafe fn shader_source(&self, shader: Self::Shader, source: &str);
+    unsafe fn stencil_func(&self, func: gl::GLenum, r#ref: gl::GLint, mask: gl::GLuint);
+    unsafe fn stencil_op(&self, fail: gl::GLenum, zfail: gl::GLenum, zpass: gl::GLenum);
     unsafe fn tex_image_2d(&self, target: gl::GLenum, level: gl::GLint, internalformat: gl::GLint, width: gl::GLsizei, height: gl::GLsizei, border: gl::GLint, format: gl::GLenum, r#type: gl::GLenum, pixels: *const c_void);
     unsafe fn tex_parameteri(&self, target: gl::GLenum, pname: gl::GLenum, param: gl::GLint);
     unsafe fn tex_parameteriv(&self, target: gl::GLenum, pname: gl::GLenum, params: *const gl::GLint);
@@ -253,6 +257,22 @@ mod gl46 {
             unsafe { self.api.ClearColor(red, green, blue, alpha) };
         }
 
+        #[inline]
+        unsafe fn clear_stencil(&self, s: gl::GLint) {
+            unsafe { self.api.ClearStencil(s) };
+        }
+
+        #[inline]
+        unsafe fn color_mask(
+            &self,
+            red: gl::GLboolean,
+            green: gl::GLboolean,
+            blue: gl::GLboolean,
+            alpha: gl::GLboolean,
+        ) {
+            unsafe { self.api.ColorMask(red, green, blue, alpha) };
+        }
+
         #[inline]
         unsafe fn compile_shader(&self, shader: Self::Shader) {
             unsafe { self.api.CompileShader(shader) };
@@ -570,6 +590,16 @@ mod gl46 {
             };
         }
 
+        #[inline]
+        unsafe fn stencil_func(&self, func: gl::GLenum, r#ref: gl::GLint, mask: gl::GLuint) {
+            unsafe { self.api.StencilFunc(func, r#ref, mask) };
+        }
+
+        #[inline]
+        unsafe fn stencil_op(&self, fail: gl::GLenum, zfail: gl::GLenum, zpass: gl::GLenum) {
+            unsafe { self.api.StencilOp(fail, zfail, zpass) };
+        }
+
         #[inline]
         unsafe fn tex_image_2d(
             &self,
@@ -760,6 +790,8 @@ mod webgl2 {
         bufferData: js::Value,
         clear: js::Value,
         clearColor: js::Value,
+        clearStencil: js::Value,
+        colorMask: js::Value,
         compileShader: js::Value,
         createBuffer: js::Value,
         createProgram: js::Value,
@@ -781,6 +813,8 @@ mod webgl2 {
         linkProgram: js::Value,
         pixelStorei: js::Value,
         shaderSource: js::Value,
+        stencilFunc: js::Value,
+        stencilOp: js::Value,
         texImage2D: js::Value,
         texParameteri: js::Value,
         texSubImage2D: js::Value,
@@ -819,6 +853,8 @@ mod webgl2 {
                 bufferData: context.get("bufferData"),
                 clear: context.get("clear"),
                 clearColor: context.get("clearColor"),
+                clearStencil: context.get("clearStencil"),
+                colorMask: context.get("colorMask"),
                 compileShader: context.get("compileShader"),
                 createBuffer: context.get("createBuffer"),
                 createProgram: context.get("createProgram"),
@@ -840,6 +876,8 @@ mod webgl2 {
                 linkProgram: context.get("linkProgram"),
                 pixelStorei: context.get("pixelStorei"),
                 shaderSource: context.get("shaderSource"),
+                stencilFunc: context.get("stencilFunc"),
+                stencilOp: context.get("stencilOp"),
                 texImage2D: context.get("texImage2D"),
                 texParameteri: context.get("texParameteri"),
                 texSubImage2D: context.get("texSubImage2D"),
@@ -1003,6 +1041,31 @@ mod webgl2 {
                 .unwrap();
         }
 
+        #[inline]
+        unsafe fn clear_stencil(&self, s: gl::GLint) {
+            self.clearStencil
+                .call(&[js::Value::from_f64(s as f64)])
+                .unwrap();
+        }
+
+        #[inline]
+        unsafe fn color_mask(
+            &self,
+            red: gl::GLboolean,
+            green: gl::GLboolean,
+            blue: gl::GLboolean,
+            alpha: gl::GLboolean,
+        ) {
+            self.colorMask
+                .call(&[
+                    js::Value::from_bool(red != gl::FALSE),
+                    js::Value::from_bool(green != gl::FALSE),
+                    js::Value::from_bool(blue != gl::FALSE),
+                    js::Value::from_bool(alpha != gl::FALSE),
+                ])
+                .unwrap();
+        }
+
         #[inline]
         unsafe fn compile_shader(&self, shader: Self::Shader) {
             let shader = self.get_value(shader);
@@ -1260,6 +1323,28 @@ mod webgl2 {
                 .unwrap();
         }
 
+        #[inline]
+        unsafe fn stencil_func(&self, func: gl::GLenum, r#ref: gl::GLint, mask: gl::GLuint) {
+            self.stencilFunc
+                .call(&[
+                    js::Value::from_f64(func as f64),
+                    js::Value::from_f64(r#ref as f64),
+                    js::Value::from_f64(mask as f64),
+                ])
+                .unwrap();
+        }
+
+        #[inline]
+        unsafe fn stencil_op(&self, fail: gl::GLenum, zfail: gl::GLenum, zpass: gl::GLenum) {
+            self.stencilOp
+                .call(&[
+                    js::Value::from_f64(fail as f64),
+                    js::Value::from_f64(zfail as f64),
+                    js::Value::from_f64(zpass as f64),
+                ])
+                .unwrap();
+        }
+
         #[inline]
         unsafe fn tex_image_2d(
             &self,
@@ -0,0 +1,162 @@
+// NOTE: criterion is not a dependency; this is a tiny harness that reports the same way
+// (per-iteration time of several functions within a group). run with `cargo bench -p input`.
+//
+// compares StateTracker (sorted array) against the hashmap-based tracker that it replaced.
+
+use std::{
+    collections::HashMap,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use input::{Scancode, StateTracker};
+use mars::nohash::NoBuildHasher;
+
+const WARMUP: Duration = Duration::from_millis(200);
+const MEASUREMENT: Duration = Duration::from_secs(1);
+
+// hashmap tracker
+// ----
+
+const JUST_PRESSED: u8 = 1 << 0;
+const JUST_RELEASED: u8 = 1 << 1;
+const DOWN: u8 = 1 << 2;
+
+#[derive(Default)]
+struct HashMapStateTracker {
+    map: HashMap<Scancode, u8, NoBuildHasher<Scancode>>,
+}
+
+impl HashMapStateTracker {
+    fn clear_transient_flags(&mut self) {
+        self.map.iter_mut().for_each(|(_, state)| *state &= DOWN);
+    }
+
+    fn press(&mut self, button: Scancode) {
+        let state = self.map.entry(button).or_insert(0);
+        *state |= JUST_PRESSED | DOWN;
+    }
+
+    fn release(&mut self, button: Scancode) {
+        let state = self.map.entry(button).or_insert(0);
+        *state &= !DOWN;
+        *state |= JUST_RELEASED;
+    }
+
+    fn just_pressed(&self, button: Scancode) -> bool {
+        self.map
+            .get(&button)
+            .is_some_and(|state| state & JUST_PRESSED != 0)
+    }
+
+    fn down(&self, button: Scancode) -> bool {
+        self.map.get(&button).is_some_and(|state| state & DOWN != 0)
+    }
+}
+
+// harness
+// ----
+
+fn bench(group: &str, name: &str, mut f: impl FnMut()) {
+    let start = Instant::now();
+    let mut iters_per_sample: u64 = 1;
+    while start.elapsed() < WARMUP {
+        for _ in 0..iters_per_sample {
+            f();
+        }
+        iters_per_sample *= 2;
+    }
+
+    let mut total_iters: u64 = 0;
+    let mut best = Duration::MAX;
+    let start = Instant::now();
+    while start.elapsed() < MEASUREMENT {
+        let sample_start = Instant::now();
+        for _ in 0..iters_per_sample {
+            f();
+        }
+        best = best.min(sample_start.elapsed() / iters_per_sample as u32);
+        total_iters += iters_per_sample;
+    }
+    let mean = start.elapsed().as_nanos() as f64 / total_iters as f64;
+
+    println!(
+        "{group}/{name:<11} mean: {mean:>9.2} ns/iter  best: {:>9.2} ns/iter",
+        best.as_nanos() as f64
+    );
+}
+
+// NOTE: a typical frame: a few keys are held (wasd + shift) and the game polls a lot of keys that
+// are mostly not down.
+const HELD: [Scancode; 5] = [
+    Scancode::W,
+    Scancode::A,
+    Scancode::S,
+    Scancode::D,
+    Scancode::ShiftLeft,
+];
+
+const POLLED: [Scancode; 16] = [
+    Scancode::W,
+    Scancode::A,
+    Scancode::S,
+    Scancode::D,
+    Scancode::Q,
+    Scancode::E,
+    Scancode::R,
+    Scancode::F,
+    Scancode::Space,
+    Scancode::ShiftLeft,
+    Scancode::CtrlLeft,
+    Scancode::Esc,
+    Scancode::Tab,
+    Scancode::Num1,
+    Scancode::Num2,
+    Scancode::Num3,
+];
+
+fn main() {
+    // queries
+
+    let mut hashmap = HashMapStateTracker::default();
+    let mut sortedarray = StateTracker::default();
+    for scancode in HELD {
+        hashmap.press(scancode);
+        sortedarray.press(scancode, false);
+    }
+    bench("query", "hashmap", || {
+        for scancode in POLLED {
+            black_box(hashmap.down(black_box(scancode)));
+            black_box(hashmap.just_pressed(black_box(scancode)));
+        }
+    });
+    bench("query", "sortedarray", || {
+        for scancode in POLLED {
+            black_box(sortedarray.down(black_box(scancode)));
+            black_box(sortedarray.just_pressed(black_box(scancode)));
+        }
+    });
+
+    // frame: clear, press, release
+
+    let mut hashmap = HashMapStateTracker::default();
+    let mut sortedarray = StateTracker::default();
+    bench("frame", "hashmap", || {
+        hashmap.clear_transient_flags();
+        for scancode in HELD {
+            hashmap.press(black_box(scancode));
+        }
+        for scancode in HELD {
+            hashmap.release(black_box(scancode));
+        }
+    });
+    bench("frame", "sortedarray", || {
+        sortedarray.clear_transient_flags();
+        for scancode in HELD {
+            sortedarray.press(black_box(scancode), false);
+        }
+        for scancode in HELD {
+            sortedarray.release(black_box(scancode));
+        }
+    });
+}
@@ -0,0 +1,926 @@
+use std::fmt::Write as _;
+
+use anyhow::{Context as _, anyhow};
+
+use crate::{
+    Button, Event, GamepadAxis, GamepadButton, GamepadId, GamepadState, Keycode, PointerEventKind,
+    RawKey, Scancode, State, StateFlags,
+};
+
+// NOTE: action maps sit on top of State. the idea is that app describes what it wants ("jump",
+// "zoom_in", "move") and user (or app's defaults) decides which keys/buttons/sticks drive that.
+//
+// TODO: chords (ctrl+z). currently modifiers can only be checked separately.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    ZoomIn,
+    ZoomOut,
+    RotateCw,
+    RotateCcw,
+}
+
+const GESTURE_NAMES: [(Gesture, &str); 4] = [
+    (Gesture::ZoomIn, "zoom_in"),
+    (Gesture::ZoomOut, "zoom_out"),
+    (Gesture::RotateCw, "rotate_cw"),
+    (Gesture::RotateCcw, "rotate_ccw"),
+];
+
+const BUTTON_NAMES: [(Button, &str); 3] = [
+    (Button::Primary, "primary"),
+    (Button::Secondary, "secondary"),
+    (Button::Tertiary, "tertiary"),
+];
+
+const GAMEPAD_BUTTON_NAMES: [(GamepadButton, &str); GamepadButton::COUNT] = [
+    (GamepadButton::South, "south"),
+    (GamepadButton::East, "east"),
+    (GamepadButton::West, "west"),
+    (GamepadButton::North, "north"),
+    (GamepadButton::LeftBumper, "left_bumper"),
+    (GamepadButton::RightBumper, "right_bumper"),
+    (GamepadButton::LeftTrigger, "left_trigger"),
+    (GamepadButton::RightTrigger, "right_trigger"),
+    (GamepadButton::Select, "select"),
+    (GamepadButton::Start, "start"),
+    (GamepadButton::Mode, "mode"),
+    (GamepadButton::LeftStick, "left_stick"),
+    (GamepadButton::RightStick, "right_stick"),
+    (GamepadButton::DPadUp, "dpad_up"),
+    (GamepadButton::DPadDown, "dpad_down"),
+    (GamepadButton::DPadLeft, "dpad_left"),
+    (GamepadButton::DPadRight, "dpad_right"),
+];
+
+const GAMEPAD_AXIS_NAMES: [(GamepadAxis, &str); GamepadAxis::COUNT] = [
+    (GamepadAxis::LeftStickX, "left_stick_x"),
+    (GamepadAxis::LeftStickY, "left_stick_y"),
+    (GamepadAxis::RightStickX, "right_stick_x"),
+    (GamepadAxis::RightStickY, "right_stick_y"),
+    (GamepadAxis::LeftTrigger, "left_trigger"),
+    (GamepadAxis::RightTrigger, "right_trigger"),
+];
+
+fn find_value<T: Copy>(names: &[(T, &str)], name: &str) -> Option<T> {
+    names.iter().find(|(_, n)| *n == name).map(|(v, _)| *v)
+}
+
+fn find_name<T: PartialEq>(names: &[(T, &'static str)], value: T) -> &'static str {
+    names
+        .iter()
+        .find(|(v, _)| *v == value)
+        .map(|(_, n)| *n)
+        .expect("all values must be named")
+}
+
+/// something that can be down or up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Scancode(Scancode),
+    Keycode(Keycode),
+    Button(Button),
+    GamepadButton(GamepadButton),
+    /// gestures don't have up/down states; gesture binding is considered to be just pressed (and
+    /// down) during frames in which gesture progresses in its direction.
+    Gesture(Gesture),
+}
+
+/// something that produces a value in one dimension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AxisBinding {
+    /// -1 when negative is down, 1 when positive is down, 0 when both or neither.
+    Buttons {
+        negative: Binding,
+        positive: Binding,
+    },
+    /// NOTE: scroll and gesture values are deltas accumulated over a frame; they are not bound to
+    /// [-1, 1].
+    ScrollX,
+    ScrollY,
+    GamepadAxis(GamepadAxis),
+    Zoom,
+    Rotate,
+}
+
+/// NOTE: positive y points down (same as in gamepad sticks and in screen space), thus for wasd
+/// you want w to be negative and s positive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Axis2dBinding {
+    pub x: AxisBinding,
+    pub y: AxisBinding,
+}
+
+#[derive(Debug)]
+struct Action {
+    name: String,
+    bindings: Vec<Binding>,
+    flags: StateFlags,
+}
+
+#[derive(Debug)]
+struct Axis {
+    name: String,
+    bindings: Vec<AxisBinding>,
+    value: f32,
+}
+
+#[derive(Debug)]
+struct Axis2d {
+    name: String,
+    bindings: Vec<Axis2dBinding>,
+    value: (f32, f32),
+}
+
+/// per-frame values that aren't tracked by State.
+#[derive(Debug, Default)]
+struct FrameDeltas {
+    zoom: f64,
+    rotate: f64,
+}
+
+impl FrameDeltas {
+    fn from_events(events: &[Event]) -> Self {
+        let mut this = Self::default();
+        for event in events {
+            let Event::Pointer(ev) = event else {
+                continue;
+            };
+            match ev.kind {
+                PointerEventKind::Zoom { scale_delta, .. } => this.zoom += scale_delta,
+                PointerEventKind::Rotate { rotation_delta, .. } => this.rotate += rotation_delta,
+                _ => {}
+            }
+        }
+        this
+    }
+}
+
+/// maps named actions and axes to bindings.
+///
+/// bindings can be set up in code or loaded from text config (see [`ActionMap::load_config`]).
+/// [`ActionMap::update`] must be called each frame after [`State::handle_events`].
+#[derive(Debug, Default)]
+pub struct ActionMap {
+    actions: Vec<Action>,
+    axes: Vec<Axis>,
+    axes2d: Vec<Axis2d>,
+    gamepad_id: Option<GamepadId>,
+}
+
+impl ActionMap {
+    pub fn from_config(config: &str) -> anyhow::Result<Self> {
+        let mut this = Self::default();
+        this.load_config(config)?;
+        Ok(this)
+    }
+
+    /// restricts gamepad bindings to a particular gamepad (for split screen). by default (`None`)
+    /// all gamepads are considered.
+    pub fn set_gamepad(&mut self, gamepad_id: Option<GamepadId>) {
+        self.gamepad_id = gamepad_id;
+    }
+
+    // bindings
+
+    pub fn bind_action(&mut self, name: &str, binding: Binding) {
+        let index = match self.actions.iter().position(|action| action.name == name) {
+            Some(index) => index,
+            None => {
+                self.actions.push(Action {
+                    name: name.to_string(),
+                    bindings: Vec::new(),
+                    flags: StateFlags::default(),
+                });
+                self.actions.len() - 1
+            }
+        };
+        self.actions[index].bindings.push(binding);
+    }
+
+    pub fn bind_axis(&mut self, name: &str, binding: AxisBinding) {
+        let index = match self.axes.iter().position(|axis| axis.name == name) {
+            Some(index) => index,
+            None => {
+                self.axes.push(Axis {
+                    name: name.to_string(),
+                    bindings: Vec::new(),
+                    value: 0.0,
+                });
+                self.axes.len() - 1
+            }
+        };
+        self.axes[index].bindings.push(binding);
+    }
+
+    pub fn bind_axis2d(&mut self, name: &str, binding: Axis2dBinding) {
+        let index = match self.axes2d.iter().position(|axis| axis.name == name) {
+            Some(index) => index,
+            None => {
+                self.axes2d.push(Axis2d {
+                    name: name.to_string(),
+                    bindings: Vec::new(),
+                    value: (0.0, 0.0),
+                });
+                self.axes2d.len() - 1
+            }
+        };
+        self.axes2d[index].bindings.push(binding);
+    }
+
+    /// removes bindings of actions and axes with given name. the name stays known (it'll be
+    /// written out by [`ActionMap::to_config`] with no bindings).
+    pub fn unbind(&mut self, name: &str) {
+        self.actions
+            .iter_mut()
+            .filter(|action| action.name == name)
+            .for_each(|action| action.bindings.clear());
+        self.axes
+            .iter_mut()
+            .filter(|axis| axis.name == name)
+            .for_each(|axis| axis.bindings.clear());
+        self.axes2d
+            .iter_mut()
+            .filter(|axis| axis.name == name)
+            .for_each(|axis| axis.bindings.clear());
+    }
+
+    pub fn action_bindings(&self, name: &str) -> &[Binding] {
+        self.find_action(name)
+            .map_or(&[], |action| action.bindings.as_slice())
+    }
+
+    pub fn axis_bindings(&self, name: &str) -> &[AxisBinding] {
+        self.find_axis(name)
+            .map_or(&[], |axis| axis.bindings.as_slice())
+    }
+
+    pub fn axis2d_bindings(&self, name: &str) -> &[Axis2dBinding] {
+        self.find_axis2d(name)
+            .map_or(&[], |axis| axis.bindings.as_slice())
+    }
+
+    // config
+
+    /// config is line based:
+    ///
+    /// ```text
+    /// # comment
+    /// action jump = scancode:Space, gamepad:south
+    /// action zoom_in = keycode:+, gesture:zoom_in
+    /// axis zoom = keycode:-/keycode:+, scroll:y, gesture:zoom
+    /// axis2d move = scancode:A/scancode:D scancode:W/scancode:S
+    /// axis2d move = gamepad_axis:left_stick_x gamepad_axis:left_stick_y
+    /// ```
+    ///
+    /// bindings are `scancode:<Debug name>`, `keycode:<single char>`, `button:<name>`,
+    /// `gamepad:<name>` and `gesture:<name>`. axes additionally accept `<binding>/<binding>`
+    /// (negative/positive), `scroll:x`, `scroll:y`, `gamepad_axis:<name>`, `gesture:zoom` and
+    /// `gesture:rotate`. 2d axes are pairs of axis bindings separated by whitespace.
+    ///
+    /// names that are mentioned in config get their bindings replaced; the rest keep theirs.
+    /// this allows to load defaults first and user's overrides on top.
+    pub fn load_config(&mut self, config: &str) -> anyhow::Result<()> {
+        // NOTE: parse everything first so that a broken config doesn't leave map half-updated.
+        let mut actions: Vec<(&str, Vec<Binding>)> = Vec::new();
+        let mut axes: Vec<(&str, Vec<AxisBinding>)> = Vec::new();
+        let mut axes2d: Vec<(&str, Vec<Axis2dBinding>)> = Vec::new();
+
+        for (line_index, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            parse_line(line, &mut actions, &mut axes, &mut axes2d)
+                .with_context(|| format!("line {}: {line:?}", line_index + 1))?;
+        }
+
+        for (name, _) in actions.iter() {
+            self.unbind_action(name);
+        }
+        for (name, bindings) in actions {
+            bindings
+                .into_iter()
+                .for_each(|binding| self.bind_action(name, binding));
+        }
+        for (name, _) in axes.iter() {
+            self.unbind_axis(name);
+        }
+        for (name, bindings) in axes {
+            bindings
+                .into_iter()
+                .for_each(|binding| self.bind_axis(name, binding));
+        }
+        for (name, _) in axes2d.iter() {
+            self.unbind_axis2d(name);
+        }
+        for (name, bindings) in axes2d {
+            bindings
+                .into_iter()
+                .for_each(|binding| self.bind_axis2d(name, binding));
+        }
+
+        Ok(())
+    }
+
+    /// writes out all bindings in the format that [`ActionMap::load_config`] understands.
+    ///
+    /// NOTE: bindings to unidentified keys (without raw code) and to whitespace keycodes are
+    /// skipped.
+    pub fn to_config(&self) -> String {
+        let mut config = String::new();
+        for action in self.actions.iter() {
+            let bindings: Vec<String> = action.bindings.iter().filter_map(format_binding).collect();
+            _ = writeln!(config, "action {} = {}", action.name, bindings.join(", "));
+        }
+        for axis in self.axes.iter() {
+            let bindings: Vec<String> = axis
+                .bindings
+                .iter()
+                .filter_map(format_axis_binding)
+                .collect();
+            _ = writeln!(config, "axis {} = {}", axis.name, bindings.join(", "));
+        }
+        for axis in self.axes2d.iter() {
+            let bindings: Vec<String> = axis
+                .bindings
+                .iter()
+                .filter_map(|binding| {
+                    Some(format!(
+                        "{} {}",
+                        format_axis_binding(&binding.x)?,
+                        format_axis_binding(&binding.y)?
+                    ))
+                })
+                .collect();
+            _ = writeln!(config, "axis2d {} = {}", axis.name, bindings.join(", "));
+        }
+        config
+    }
+
+    // update
+
+    pub fn update(&mut self, state: &State) {
+        let deltas = FrameDeltas::from_events(&state.events);
+        let gamepad_id = self.gamepad_id;
+        let gamepads = || {
+            state
+                .gamepads
+                .iter()
+                .filter(move |gamepad| gamepad_id.is_none_or(|id| id == gamepad.id))
+        };
+
+        for action in self.actions.iter_mut() {
+            let was_down = action.flags.contains(StateFlags::DOWN);
+            let mut flags = StateFlags::NONE;
+            let mut any_just_released = false;
+            for binding in action.bindings.iter() {
+                let binding_flags = binding_flags(binding, state, &deltas, gamepads);
+                flags |= binding_flags & !StateFlags::JUST_RELEASED;
+                any_just_released |= binding_flags.contains(StateFlags::JUST_RELEASED);
+            }
+            // NOTE: pressing another binding of an action that is already held is not a press of
+            // the action.
+            if was_down {
+                flags &= !StateFlags::JUST_PRESSED;
+            }
+            if !flags.contains(StateFlags::DOWN) && (was_down || any_just_released) {
+                flags |= StateFlags::JUST_RELEASED;
+            }
+            action.flags = flags;
+        }
+
+        for axis in self.axes.iter_mut() {
+            axis.value = axis
+                .bindings
+                .iter()
+                .map(|binding| axis_value(binding, state, &deltas, gamepads))
+                .fold(0.0, max_magnitude);
+        }
+
+        for axis in self.axes2d.iter_mut() {
+            axis.value = axis
+                .bindings
+                .iter()
+                .map(|binding| {
+                    (
+                        axis_value(&binding.x, state, &deltas, gamepads),
+                        axis_value(&binding.y, state, &deltas, gamepads),
+                    )
+                })
+                .fold((0.0, 0.0), |acc, value| {
+                    let len_sq = |(x, y): (f32, f32)| x * x + y * y;
+                    if len_sq(value) > len_sq(acc) {
+                        value
+                    } else {
+                        acc
+                    }
+                });
+        }
+    }
+
+    // queries
+
+    pub fn action_down(&self, name: &str) -> bool {
+        self.action_flags(name).contains(StateFlags::DOWN)
+    }
+
+    pub fn action_just_pressed(&self, name: &str) -> bool {
+        self.action_flags(name).contains(StateFlags::JUST_PRESSED)
+    }
+
+    pub fn action_just_released(&self, name: &str) -> bool {
+        self.action_flags(name).contains(StateFlags::JUST_RELEASED)
+    }
+
+    /// NOTE: only keyboard bindings repeat.
+    pub fn action_repeated(&self, name: &str) -> bool {
+        self.action_flags(name).contains(StateFlags::REPEAT)
+    }
+
+    pub fn axis(&self, name: &str) -> f32 {
+        self.find_axis(name).map_or(0.0, |axis| axis.value)
+    }
+
+    pub fn axis2d(&self, name: &str) -> (f32, f32) {
+        self.find_axis2d(name).map_or((0.0, 0.0), |axis| axis.value)
+    }
+
+    // private
+
+    fn find_action(&self, name: &str) -> Option<&Action> {
+        self.actions.iter().find(|action| action.name == name)
+    }
+
+    fn find_axis(&self, name: &str) -> Option<&Axis> {
+        self.axes.iter().find(|axis| axis.name == name)
+    }
+
+    fn find_axis2d(&self, name: &str) -> Option<&Axis2d> {
+        self.axes2d.iter().find(|axis| axis.name == name)
+    }
+
+    fn action_flags(&self, name: &str) -> StateFlags {
+        self.find_action(name)
+            .map_or(StateFlags::NONE, |action| action.flags)
+    }
+
+    fn unbind_action(&mut self, name: &str) {
+        if let Some(action) = self.actions.iter_mut().find(|action| action.name == name) {
+            action.bindings.clear();
+        }
+    }
+
+    fn unbind_axis(&mut self, name: &str) {
+        if let Some(axis) = self.axes.iter_mut().find(|axis| axis.name == name) {
+            axis.bindings.clear();
+        }
+    }
+
+    fn unbind_axis2d(&mut self, name: &str) {
+        if let Some(axis) = self.axes2d.iter_mut().find(|axis| axis.name == name) {
+            axis.bindings.clear();
+        }
+    }
+}
+
+// evaluation
+// ----
+
+fn max_magnitude(acc: f32, value: f32) -> f32 {
+    if value.abs() > acc.abs() { value } else { acc }
+}
+
+fn binding_flags<'a, I>(
+    binding: &Binding,
+    state: &State,
+    deltas: &FrameDeltas,
+    gamepads: impl Fn() -> I,
+) -> StateFlags
+where
+    I: Iterator<Item = &'a GamepadState>,
+{
+    match *binding {
+        Binding::Scancode(scancode) => state.keyboard.scancodes.flags(scancode),
+        Binding::Keycode(keycode) => state.keyboard.keycodes.flags(keycode),
+        Binding::Button(button) => state.pointer.buttons.flags(button),
+        Binding::GamepadButton(button) => gamepads()
+            .map(|gamepad| gamepad.buttons.flags(button))
+            .fold(StateFlags::NONE, |acc, flags| acc | flags),
+        Binding::Gesture(gesture) => {
+            let active = match gesture {
+                Gesture::ZoomIn => deltas.zoom > 0.0,
+                Gesture::ZoomOut => deltas.zoom < 0.0,
+                Gesture::RotateCw => deltas.rotate > 0.0,
+                Gesture::RotateCcw => deltas.rotate < 0.0,
+            };
+            if active {
+                StateFlags::DOWN | StateFlags::JUST_PRESSED
+            } else {
+                StateFlags::NONE
+            }
+        }
+    }
+}
+
+fn axis_value<'a, I>(
+    binding: &AxisBinding,
+    state: &State,
+    deltas: &FrameDeltas,
+    gamepads: impl Fn() -> I,
+) -> f32
+where
+    I: Iterator<Item = &'a GamepadState>,
+{
+    match binding {
+        AxisBinding::Buttons { negative, positive } => {
+            let down = |binding| {
+                binding_flags(binding, state, deltas, &gamepads).contains(StateFlags::DOWN)
+            };
+            down(positive) as i32 as f32 - down(negative) as i32 as f32
+        }
+        AxisBinding::ScrollX => state.pointer.scroll_delta.map_or(0.0, |(x, _)| x as f32),
+        AxisBinding::ScrollY => state.pointer.scroll_delta.map_or(0.0, |(_, y)| y as f32),
+        // NOTE: with multiple gamepads the one that is pushed the furthest wins.
+        AxisBinding::GamepadAxis(axis) => gamepads()
+            .map(|gamepad| gamepad.axis(*axis))
+            .fold(0.0, max_magnitude),
+        AxisBinding::Zoom => deltas.zoom as f32,
+        AxisBinding::Rotate => deltas.rotate as f32,
+    }
+}
+
+// config
+// ----
+
+/// returns `None` for bindings that can't be expressed in config.
+fn format_binding(binding: &Binding) -> Option<String> {
+    let formatted = match binding {
+        Binding::Scancode(Scancode::Unidentified(RawKey::Unix(code))) => {
+            format!("scancode:unix_{code}")
+        }
+        Binding::Keycode(Keycode::Unidentified(RawKey::Unix(code))) => {
+            format!("keycode:unix_{code}")
+        }
+        // NOTE: unidentified keys without raw code can't be told apart; there's no point in
+        // binding them.
+        Binding::Scancode(Scancode::Unidentified(RawKey::Unidentified))
+        | Binding::Keycode(Keycode::Unidentified(RawKey::Unidentified)) => return None,
+        Binding::Scancode(scancode) => format!("scancode:{scancode:?}"),
+        Binding::Keycode(Keycode::Char(ch)) if ch.is_whitespace() => return None,
+        Binding::Keycode(Keycode::Char(ch)) => format!("keycode:{ch}"),
+        Binding::Button(button) => format!("button:{}", find_name(&BUTTON_NAMES, *button)),
+        Binding::GamepadButton(button) => {
+            format!("gamepad:{}", find_name(&GAMEPAD_BUTTON_NAMES, *button))
+        }
+        Binding::Gesture(gesture) => format!("gesture:{}", find_name(&GESTURE_NAMES, *gesture)),
+    };
+    Some(formatted)
+}
+
+fn format_axis_binding(binding: &AxisBinding) -> Option<String> {
+    let formatted = match binding {
+        AxisBinding::Buttons { negative, positive } => {
+            format!(
+                "{}/{}",
+                format_binding(negative)?,
+                format_binding(positive)?
+            )
+        }
+        AxisBinding::ScrollX => "scroll:x".to_string(),
+        AxisBinding::ScrollY => "scroll:y".to_string(),
+        AxisBinding::GamepadAxis(axis) => {
+            format!("gamepad_axis:{}", find_name(&GAMEPAD_AXIS_NAMES, *axis))
+        }
+        AxisBinding::Zoom => "gesture:zoom".to_string(),
+        AxisBinding::Rotate => "gesture:rotate".to_string(),
+    };
+    Some(formatted)
+}
+
+fn parse_binding(kind: &str, value: &str) -> anyhow::Result<Binding> {
+    let parse_unix = |value: &str| {
+        value
+            .strip_prefix("unix_")
+            .map(|code| {
+                code.parse::<u32>()
+                    .map_err(|err| anyhow!("invalid unix code {code:?}: {err}"))
+            })
+            .transpose()
+    };
+    let binding = match kind {
+        "scancode" => match parse_unix(value)? {
+            Some(code) => Binding::Scancode(Scancode::Unidentified(RawKey::Unix(code))),
+            None => Binding::Scancode(
+                Scancode::from_name(value).ok_or_else(|| anyhow!("unknown scancode {value:?}"))?,
+            ),
+        },
+        "keycode" => match parse_unix(value)? {
+            Some(code) => Binding::Keycode(Keycode::Unidentified(RawKey::Unix(code))),
+            None => Binding::Keycode(Keycode::Char(
+                value.chars().next().expect("keycode value is one char"),
+            )),
+        },
+        "button" => Binding::Button(
+            find_value(&BUTTON_NAMES, value).ok_or_else(|| anyhow!("unknown button {value:?}"))?,
+        ),
+        "gamepad" => Binding::GamepadButton(
+            find_value(&GAMEPAD_BUTTON_NAMES, value)
+                .ok_or_else(|| anyhow!("unknown gamepad button {value:?}"))?,
+        ),
+        "gesture" => Binding::Gesture(
+            find_value(&GESTURE_NAMES, value)
+                .ok_or_else(|| anyhow!("unknown gesture {value:?}"))?,
+        ),
+        _ => return Err(anyhow!("unknown binding kind {kind:?}")),
+    };
+    Ok(binding)
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        self.skip_whitespace();
+        match self.rest.strip_prefix(ch) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest.is_empty()
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let end = self.rest.find(|ch| !f(ch)).unwrap_or(self.rest.len());
+        let (taken, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        taken
+    }
+
+    fn ident(&mut self) -> anyhow::Result<&'a str> {
+        self.skip_whitespace();
+        let ident = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+        if ident.is_empty() {
+            return Err(anyhow!("expected identifier at {:?}", self.rest));
+        }
+        Ok(ident)
+    }
+
+    /// returns kind and value of `kind:value`.
+    fn kind_value(&mut self) -> anyhow::Result<(&'a str, &'a str)> {
+        let kind = self.ident()?;
+        if !self.rest.starts_with(':') {
+            return Err(anyhow!("expected ':' after {kind:?}"));
+        }
+        self.rest = &self.rest[1..];
+        let value = if kind == "keycode" && !self.rest.starts_with("unix_") {
+            // NOTE: keycode value is exactly one char, which allows to bind keys like `,` or `/`
+            // without escaping.
+            let ch = self
+                .rest
+                .chars()
+                .next()
+                .filter(|ch| !ch.is_whitespace())
+                .ok_or_else(|| anyhow!("expected char after 'keycode:'"))?;
+            let (value, rest) = self.rest.split_at(ch.len_utf8());
+            self.rest = rest;
+            value
+        } else {
+            self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        };
+        Ok((kind, value))
+    }
+
+    fn binding(&mut self) -> anyhow::Result<Binding> {
+        let (kind, value) = self.kind_value()?;
+        parse_binding(kind, value)
+    }
+
+    fn axis_binding(&mut self) -> anyhow::Result<AxisBinding> {
+        let (kind, value) = self.kind_value()?;
+        let axis_binding = match (kind, value) {
+            ("scroll", "x") => AxisBinding::ScrollX,
+            ("scroll", "y") => AxisBinding::ScrollY,
+            ("gesture", "zoom") => AxisBinding::Zoom,
+            ("gesture", "rotate") => AxisBinding::Rotate,
+            ("gamepad_axis", value) => AxisBinding::GamepadAxis(
+                find_value(&GAMEPAD_AXIS_NAMES, value)
+                    .ok_or_else(|| anyhow!("unknown gamepad axis {value:?}"))?,
+            ),
+            (kind, value) => {
+                let negative = parse_binding(kind, value)?;
+                if !self.eat('/') {
+                    return Err(anyhow!("expected '/' after {kind}:{value}"));
+                }
+                let positive = self.binding()?;
+                AxisBinding::Buttons { negative, positive }
+            }
+        };
+        Ok(axis_binding)
+    }
+
+    fn list<T>(&mut self, f: impl Fn(&mut Self) -> anyhow::Result<T>) -> anyhow::Result<Vec<T>> {
+        let mut items = Vec::new();
+        if self.is_empty() {
+            return Ok(items);
+        }
+        loop {
+            items.push(f(self)?);
+            if self.is_empty() {
+                return Ok(items);
+            }
+            if !self.eat(',') {
+                return Err(anyhow!("expected ',' at {:?}", self.rest));
+            }
+        }
+    }
+}
+
+fn parse_line<'a>(
+    line: &'a str,
+    actions: &mut Vec<(&'a str, Vec<Binding>)>,
+    axes: &mut Vec<(&'a str, Vec<AxisBinding>)>,
+    axes2d: &mut Vec<(&'a str, Vec<Axis2dBinding>)>,
+) -> anyhow::Result<()> {
+    let (head, tail) = line.split_once('=').context("expected '='")?;
+    let mut head = head.split_whitespace();
+    let (Some(kind), Some(name), None) = (head.next(), head.next(), head.next()) else {
+        return Err(anyhow!("expected `<kind> <name> =`"));
+    };
+    let mut parser = Parser { rest: tail };
+
+    // NOTE: multiple lines with the same name accumulate bindings.
+    fn entry<'a, 'b, T>(entries: &'b mut Vec<(&'a str, Vec<T>)>, name: &'a str) -> &'b mut Vec<T> {
+        let index = match entries.iter().position(|(n, _)| *n == name) {
+            Some(index) => index,
+            None => {
+                entries.push((name, Vec::new()));
+                entries.len() - 1
+            }
+        };
+        &mut entries[index].1
+    }
+
+    match kind {
+        "action" => {
+            let bindings = parser.list(Parser::binding)?;
+            entry(actions, name).extend(bindings);
+        }
+        "axis" => {
+            let bindings = parser.list(Parser::axis_binding)?;
+            entry(axes, name).extend(bindings);
+        }
+        "axis2d" => {
+            let bindings = parser.list(|parser| {
+                Ok(Axis2dBinding {
+                    x: parser.axis_binding()?,
+                    y: parser.axis_binding()?,
+                })
+            })?;
+            entry(axes2d, name).extend(bindings);
+        }
+        _ => return Err(anyhow!("unknown kind {kind:?}")),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_action_map() {
+    use crate::{KeyState, KeyboardEvent, KeyboardEventKind, SurfaceId};
+
+    let key_event = |state, scancode, ch| {
+        Event::Keyboard(KeyboardEvent {
+            surface_id: SurfaceId(0),
+            kind: KeyboardEventKind::Key {
+                state,
+                scancode,
+                keycode: Keycode::Char(ch),
+                repeat: false,
+            },
+        })
+    };
+
+    let defaults = "
+        # defaults
+        action jump = scancode:Space, gamepad:south
+        action zoom_in = keycode:+, gesture:zoom_in
+        axis zoom = keycode:-/keycode:+, scroll:y
+        axis2d move = scancode:A/scancode:D scancode:W/scancode:S
+    ";
+    let mut action_map = ActionMap::from_config(defaults).unwrap();
+    // NOTE: user rebinds jump, the rest stays.
+    action_map.load_config("action jump = scancode:W").unwrap();
+    assert_eq!(
+        action_map.action_bindings("jump"),
+        &[Binding::Scancode(Scancode::W)]
+    );
+    assert_eq!(action_map.axis_bindings("zoom").len(), 2);
+    assert!(
+        action_map
+            .load_config("action jump = scancode:Nope")
+            .is_err()
+    );
+    assert!(action_map.load_config("axis zoom = keycode:-").is_err());
+    assert!(
+        action_map
+            .load_config("action jump = keycode:unix_")
+            .is_err()
+    );
+    assert!(
+        action_map
+            .load_config("action jump = keycode:unix_x")
+            .is_err()
+    );
+    assert_eq!(
+        action_map.action_bindings("jump"),
+        &[Binding::Scancode(Scancode::W)]
+    );
+
+    // NOTE: config survives a round trip.
+    let config = action_map.to_config();
+    let reloaded = ActionMap::from_config(&config).unwrap();
+    assert_eq!(reloaded.to_config(), config);
+    // NOTE: Reserved is formatted like any other scancode and must parse back.
+    let reserved = ActionMap::from_config("action jump = scancode:Reserved").unwrap();
+    assert_eq!(
+        ActionMap::from_config(&reserved.to_config())
+            .unwrap()
+            .action_bindings("jump"),
+        &[Binding::Scancode(Scancode::Reserved)]
+    );
+
+    let mut state = State::default();
+    state.handle_events(
+        [
+            key_event(KeyState::Pressed, Scancode::W, 'w'),
+            key_event(KeyState::Pressed, Scancode::D, 'd'),
+            key_event(KeyState::Pressed, Scancode::Equal, '+'),
+        ]
+        .into_iter(),
+    );
+    action_map.update(&state);
+    assert!(action_map.action_just_pressed("jump"));
+    assert!(action_map.action_down("zoom_in"));
+    assert_eq!(action_map.axis("zoom"), 1.0);
+    assert_eq!(action_map.axis2d("move"), (1.0, -1.0));
+    assert!(!action_map.action_just_pressed("unknown"));
+
+    state.handle_events([key_event(KeyState::Released, Scancode::W, 'w')].into_iter());
+    action_map.update(&state);
+    assert!(!action_map.action_down("jump"));
+    assert!(action_map.action_just_released("jump"));
+    assert!(!action_map.action_just_pressed("zoom_in"));
+    assert!(action_map.action_down("zoom_in"));
+    assert_eq!(action_map.axis2d("move"), (1.0, 0.0));
+}
+
+#[test]
+fn test_action_map_held_by_two_bindings() {
+    use crate::{KeyState, KeyboardEvent, KeyboardEventKind, SurfaceId};
+
+    let key_event = |state, scancode| {
+        Event::Keyboard(KeyboardEvent {
+            surface_id: SurfaceId(0),
+            kind: KeyboardEventKind::Key {
+                state,
+                scancode,
+                keycode: Keycode::Unidentified(RawKey::Unix(0)),
+                repeat: false,
+            },
+        })
+    };
+
+    let mut action_map =
+        ActionMap::from_config("action jump = scancode:Space, scancode:W").unwrap();
+    let mut state = State::default();
+
+    state.handle_events([key_event(KeyState::Pressed, Scancode::Space)].into_iter());
+    action_map.update(&state);
+    assert!(action_map.action_just_pressed("jump"));
+
+    state.handle_events([key_event(KeyState::Pressed, Scancode::W)].into_iter());
+    action_map.update(&state);
+    assert!(action_map.action_down("jump"));
+    assert!(!action_map.action_just_pressed("jump"));
+
+    state.handle_events([key_event(KeyState::Released, Scancode::Space)].into_iter());
+    action_map.update(&state);
+    assert!(action_map.action_down("jump"));
+    assert!(!action_map.action_just_released("jump"));
+
+    state.handle_events([key_event(KeyState::Released, Scancode::W)].into_iter());
+    action_map.update(&state);
+    assert!(action_map.action_just_released("jump"));
+}
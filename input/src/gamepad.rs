@@ -0,0 +1,314 @@
+use std::hash::{Hash, Hasher};
+
+use crate::{ButtonState, StateTracker};
+
+// NOTE: gamepad ids are not reused. a gamepad that gets reconnected gets a new id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GamepadId(pub u32);
+
+/// buttons of a "standard" gamepad layout. face buttons are named by their position (south is A
+/// on xbox pads and cross on playstation pads).
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    /// NOTE: analog triggers are reported both as axis and as button (see
+    /// [`GamepadConfig::trigger_threshold`]).
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl Hash for GamepadButton {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u8(*self as u8);
+    }
+}
+
+impl GamepadButton {
+    pub const COUNT: usize = 17;
+
+    pub fn all() -> [Self; Self::COUNT] {
+        use GamepadButton::*;
+        [
+            South,
+            East,
+            West,
+            North,
+            LeftBumper,
+            RightBumper,
+            LeftTrigger,
+            RightTrigger,
+            Select,
+            Start,
+            Mode,
+            LeftStick,
+            RightStick,
+            DPadUp,
+            DPadDown,
+            DPadLeft,
+            DPadRight,
+        ]
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    pub const COUNT: usize = 6;
+
+    pub fn all() -> [Self; Self::COUNT] {
+        use GamepadAxis::*;
+        [
+            LeftStickX,
+            LeftStickY,
+            RightStickX,
+            RightStickY,
+            LeftTrigger,
+            RightTrigger,
+        ]
+    }
+}
+
+/// values with magnitude below `inner` become 0, above `outer` become 1; whatever is in between
+/// is rescaled to cover the whole range (so that there's no jump at the inner edge).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deadzone {
+    pub inner: f32,
+    pub outer: f32,
+}
+
+impl Deadzone {
+    pub fn apply(&self, value: f32) -> f32 {
+        let magnitude = value.abs();
+        if magnitude <= self.inner {
+            return 0.0;
+        }
+        // NOTE: there's nothing to rescale into, this is a step.
+        if self.outer <= self.inner {
+            return 1.0f32.copysign(value);
+        }
+        let scaled = ((magnitude - self.inner) / (self.outer - self.inner)).min(1.0);
+        scaled.copysign(value)
+    }
+
+    /// NOTE: sticks need radial deadzone. applying axial deadzone to each axis separately makes
+    /// it impossible to do small movements along diagonals (values snap to the axes).
+    pub fn apply_radial(&self, x: f32, y: f32) -> (f32, f32) {
+        let magnitude = (x * x + y * y).sqrt();
+        if magnitude <= self.inner {
+            return (0.0, 0.0);
+        }
+        let scaled = self.apply(magnitude);
+        (x / magnitude * scaled, y / magnitude * scaled)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadConfig {
+    pub stick_deadzone: Deadzone,
+    pub trigger_deadzone: Deadzone,
+    /// trigger value (after deadzone) at which trigger buttons are considered pressed.
+    pub trigger_threshold: f32,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            stick_deadzone: Deadzone {
+                inner: 0.15,
+                outer: 0.95,
+            },
+            trigger_deadzone: Deadzone {
+                inner: 0.05,
+                outer: 1.0,
+            },
+            trigger_threshold: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GamepadEventKind {
+    Connected {
+        name: String,
+    },
+    /// NOTE: buttons that were held are released and axes are zeroed (with corresponding events)
+    /// before this.
+    Disconnected,
+    Button {
+        state: ButtonState,
+        button: GamepadButton,
+    },
+    /// sticks are in [-1, 1] (positive y is down), triggers are in [0, 1]. deadzones are already
+    /// applied.
+    Axis {
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct GamepadEvent {
+    pub gamepad_id: GamepadId,
+    pub kind: GamepadEventKind,
+}
+
+#[derive(Debug)]
+pub struct GamepadState {
+    pub id: GamepadId,
+    pub name: String,
+    pub buttons: StateTracker<GamepadButton>,
+    axes: [f32; GamepadAxis::COUNT],
+}
+
+impl GamepadState {
+    fn new(id: GamepadId, name: String) -> Self {
+        Self {
+            id,
+            name,
+            buttons: StateTracker::default(),
+            axes: [0.0; GamepadAxis::COUNT],
+        }
+    }
+
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+
+    pub fn left_stick(&self) -> (f32, f32) {
+        (
+            self.axis(GamepadAxis::LeftStickX),
+            self.axis(GamepadAxis::LeftStickY),
+        )
+    }
+
+    pub fn right_stick(&self) -> (f32, f32) {
+        (
+            self.axis(GamepadAxis::RightStickX),
+            self.axis(GamepadAxis::RightStickY),
+        )
+    }
+
+    #[inline]
+    pub fn clear_transient_flags(&mut self) {
+        self.buttons.clear_transient_flags();
+    }
+
+    #[inline]
+    fn handle_event(&mut self, kind: GamepadEventKind) {
+        match kind {
+            GamepadEventKind::Button {
+                state: ButtonState::Pressed,
+                button,
+            } => self.buttons.press(button, false),
+            GamepadEventKind::Button {
+                state: ButtonState::Released,
+                button,
+            } => self.buttons.release(button),
+            GamepadEventKind::Axis { axis, value } => self.axes[axis as usize] = value,
+            GamepadEventKind::Connected { .. } | GamepadEventKind::Disconnected => {}
+        }
+    }
+}
+
+// NOTE: there's rarely more than a handful of gamepads connected; vec is fine.
+#[derive(Debug, Default)]
+pub struct GamepadsState {
+    gamepads: Vec<GamepadState>,
+}
+
+impl GamepadsState {
+    pub fn get(&self, id: GamepadId) -> Option<&GamepadState> {
+        self.gamepads.iter().find(|gamepad| gamepad.id == id)
+    }
+
+    /// iterates in order of connection.
+    pub fn iter(&self) -> impl Iterator<Item = &GamepadState> {
+        self.gamepads.iter()
+    }
+
+    /// gamepad that was connected first. convenient for single player games.
+    pub fn first(&self) -> Option<&GamepadState> {
+        self.gamepads.first()
+    }
+
+    #[inline]
+    pub fn clear_transient_flags(&mut self) {
+        self.gamepads
+            .iter_mut()
+            .for_each(GamepadState::clear_transient_flags);
+    }
+
+    #[inline]
+    pub fn handle_event(&mut self, ev: GamepadEvent) {
+        match ev.kind {
+            GamepadEventKind::Connected { name } => {
+                self.gamepads.push(GamepadState::new(ev.gamepad_id, name));
+            }
+            GamepadEventKind::Disconnected => {
+                self.gamepads.retain(|gamepad| gamepad.id != ev.gamepad_id);
+            }
+            kind => {
+                if let Some(gamepad) = self
+                    .gamepads
+                    .iter_mut()
+                    .find(|gamepad| gamepad.id == ev.gamepad_id)
+                {
+                    gamepad.handle_event(kind);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_deadzone() {
+    let deadzone = Deadzone {
+        inner: 0.2,
+        outer: 0.8,
+    };
+    assert_eq!(deadzone.apply(0.1), 0.0);
+    assert_eq!(deadzone.apply(-0.2), 0.0);
+    assert!((deadzone.apply(0.5) - 0.5).abs() < 1e-6);
+    assert!((deadzone.apply(-0.5) + 0.5).abs() < 1e-6);
+    assert_eq!(deadzone.apply(0.9), 1.0);
+    assert_eq!(deadzone.apply(-1.0), -1.0);
+
+    // NOTE: each axis alone is within axial deadzone, but together they are not.
+    assert_eq!(deadzone.apply_radial(0.1, 0.1), (0.0, 0.0));
+    let (x, y) = deadzone.apply_radial(0.19, 0.19);
+    assert!(x > 0.0 && (x - y).abs() < 1e-6);
+    let (x, y) = deadzone.apply_radial(1.0, 0.0);
+    assert_eq!((x, y), (1.0, 0.0));
+
+    let deadzone = Deadzone {
+        inner: 0.5,
+        outer: 0.5,
+    };
+    assert_eq!(deadzone.apply(0.5), 0.0);
+    assert_eq!(deadzone.apply(0.51), 1.0);
+    assert_eq!(deadzone.apply(-0.51), -1.0);
+}
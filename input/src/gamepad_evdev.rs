@@ -0,0 +1,896 @@
+use std::ffi::{CStr, CString, c_int, c_ulong, c_void};
+use std::mem::MaybeUninit;
+
+use anyhow::anyhow;
+
+use crate::{
+    ButtonState, Event, GamepadAxis, GamepadButton, GamepadConfig, GamepadEvent, GamepadEventKind,
+    GamepadId,
+};
+
+// NOTE: see linux/input-event-codes.h
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+const EV_MAX: usize = 0x1f;
+
+const SYN_REPORT: u16 = 0;
+const SYN_DROPPED: u16 = 3;
+
+const BTN_GAMEPAD: u16 = 0x130;
+const BTN_SOUTH: u16 = 0x130;
+const BTN_EAST: u16 = 0x131;
+const BTN_NORTH: u16 = 0x133;
+const BTN_WEST: u16 = 0x134;
+const BTN_TL: u16 = 0x136;
+const BTN_TR: u16 = 0x137;
+const BTN_TL2: u16 = 0x138;
+const BTN_TR2: u16 = 0x139;
+const BTN_SELECT: u16 = 0x13a;
+const BTN_START: u16 = 0x13b;
+const BTN_MODE: u16 = 0x13c;
+const BTN_THUMBL: u16 = 0x13d;
+const BTN_THUMBR: u16 = 0x13e;
+const BTN_DPAD_UP: u16 = 0x220;
+const BTN_DPAD_DOWN: u16 = 0x221;
+const BTN_DPAD_LEFT: u16 = 0x222;
+const BTN_DPAD_RIGHT: u16 = 0x223;
+const KEY_MAX: usize = 0x2ff;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_RZ: u16 = 0x05;
+const ABS_GAS: u16 = 0x09;
+const ABS_BRAKE: u16 = 0x0a;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+const ABS_MAX: usize = 0x3f;
+
+const KEY_CODES: [u16; 19] = [
+    BTN_SOUTH,
+    BTN_EAST,
+    BTN_NORTH,
+    BTN_WEST,
+    BTN_TL,
+    BTN_TR,
+    BTN_TL2,
+    BTN_TR2,
+    BTN_SELECT,
+    BTN_START,
+    BTN_MODE,
+    BTN_THUMBL,
+    BTN_THUMBR,
+    BTN_DPAD_UP,
+    BTN_DPAD_DOWN,
+    BTN_DPAD_LEFT,
+    BTN_DPAD_RIGHT,
+    // NOTE: BTN_C and BTN_Z exist on some (mostly old) pads, but there's nothing in the standard
+    // layout to map them to.
+    0x132,
+    0x135,
+];
+
+const ABS_CODES: [u16; 10] = [
+    ABS_X, ABS_Y, ABS_Z, ABS_RX, ABS_RY, ABS_RZ, ABS_GAS, ABS_BRAKE, ABS_HAT0X, ABS_HAT0Y,
+];
+
+fn try_map_key_code(code: u16) -> Option<GamepadButton> {
+    match code {
+        BTN_SOUTH => Some(GamepadButton::South),
+        BTN_EAST => Some(GamepadButton::East),
+        BTN_NORTH => Some(GamepadButton::North),
+        BTN_WEST => Some(GamepadButton::West),
+        BTN_TL => Some(GamepadButton::LeftBumper),
+        BTN_TR => Some(GamepadButton::RightBumper),
+        BTN_TL2 => Some(GamepadButton::LeftTrigger),
+        BTN_TR2 => Some(GamepadButton::RightTrigger),
+        BTN_SELECT => Some(GamepadButton::Select),
+        BTN_START => Some(GamepadButton::Start),
+        BTN_MODE => Some(GamepadButton::Mode),
+        BTN_THUMBL => Some(GamepadButton::LeftStick),
+        BTN_THUMBR => Some(GamepadButton::RightStick),
+        BTN_DPAD_UP => Some(GamepadButton::DPadUp),
+        BTN_DPAD_DOWN => Some(GamepadButton::DPadDown),
+        BTN_DPAD_LEFT => Some(GamepadButton::DPadLeft),
+        BTN_DPAD_RIGHT => Some(GamepadButton::DPadRight),
+        _ => None,
+    }
+}
+
+fn try_map_abs_code(code: u16) -> Option<GamepadAxis> {
+    match code {
+        ABS_X => Some(GamepadAxis::LeftStickX),
+        ABS_Y => Some(GamepadAxis::LeftStickY),
+        ABS_RX => Some(GamepadAxis::RightStickX),
+        ABS_RY => Some(GamepadAxis::RightStickY),
+        // NOTE: xpad reports triggers as z/rz, some other drivers as brake/gas.
+        ABS_Z | ABS_BRAKE => Some(GamepadAxis::LeftTrigger),
+        ABS_RZ | ABS_GAS => Some(GamepadAxis::RightTrigger),
+        _ => None,
+    }
+}
+
+// ioctls
+// ----
+
+// NOTE: see asm-generic/ioctl.h. powerpc, mips and sparc have 13 size bits instead of 14 (and
+// thus 3 direction bits); read direction is 2 everywhere.
+const IOC_READ: c_ulong = 2;
+#[cfg(not(any(
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "mips32r6",
+    target_arch = "mips64r6",
+    target_arch = "sparc",
+    target_arch = "sparc64",
+)))]
+const IOC_SIZEBITS: c_ulong = 14;
+#[cfg(any(
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "mips32r6",
+    target_arch = "mips64r6",
+    target_arch = "sparc",
+    target_arch = "sparc64",
+))]
+const IOC_SIZEBITS: c_ulong = 13;
+const IOC_SIZESHIFT: c_ulong = 16;
+const IOC_DIRSHIFT: c_ulong = IOC_SIZESHIFT + IOC_SIZEBITS;
+
+const fn ioc_read(nr: usize, size: usize) -> c_ulong {
+    (IOC_READ << IOC_DIRSHIFT)
+        | ((size as c_ulong) << IOC_SIZESHIFT)
+        | ((b'E' as c_ulong) << 8)
+        | nr as c_ulong
+}
+
+const fn eviocgname(len: usize) -> c_ulong {
+    ioc_read(0x06, len)
+}
+
+const fn eviocgkey(len: usize) -> c_ulong {
+    ioc_read(0x18, len)
+}
+
+const fn eviocgbit(ev: usize, len: usize) -> c_ulong {
+    ioc_read(0x20 + ev, len)
+}
+
+const fn eviocgabs(abs: usize) -> c_ulong {
+    ioc_read(0x40 + abs, size_of::<libc::input_absinfo>())
+}
+
+fn test_bit(bits: &[u8], bit: usize) -> bool {
+    bits.get(bit / 8)
+        .is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+}
+
+fn errno() -> c_int {
+    unsafe { *libc::__errno_location() }
+}
+
+unsafe fn ioctl_read_bytes(fd: c_int, request: c_ulong, buf: &mut [u8]) -> anyhow::Result<usize> {
+    let ret = unsafe { libc::ioctl(fd, request as _, buf.as_mut_ptr()) };
+    if ret == -1 {
+        let errno = errno();
+        Err(anyhow!("ioctl 0x{request:x} failed: 0x{errno:x}"))
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+unsafe fn ioctl_read_absinfo(fd: c_int, abs: u16) -> anyhow::Result<libc::input_absinfo> {
+    let mut absinfo = MaybeUninit::<libc::input_absinfo>::uninit();
+    let ret = unsafe { libc::ioctl(fd, eviocgabs(abs as usize) as _, absinfo.as_mut_ptr()) };
+    if ret == -1 {
+        let errno = errno();
+        Err(anyhow!("could not get absinfo 0x{abs:x}: 0x{errno:x}"))
+    } else {
+        Ok(unsafe { absinfo.assume_init() })
+    }
+}
+
+// mapper
+// ----
+
+#[derive(Debug, Clone, Copy)]
+struct AbsRange {
+    minimum: i32,
+    maximum: i32,
+}
+
+impl AbsRange {
+    /// maps into [0, 1].
+    fn normalize(&self, value: i32) -> f32 {
+        if self.maximum <= self.minimum {
+            return 0.0;
+        }
+        let value = value.clamp(self.minimum, self.maximum);
+        (value - self.minimum) as f32 / (self.maximum - self.minimum) as f32
+    }
+}
+
+/// turns raw evdev events into gamepad events. it knows nothing about file descriptors, which
+/// makes it testable with recorded event dumps.
+#[derive(Debug)]
+struct Mapper {
+    abs_ranges: [Option<AbsRange>; ABS_MAX + 1],
+    /// bit per [`GamepadAxis`] that has an abs range. must be kept in sync with `abs_ranges`
+    /// (see [`Self::set_abs_range`]).
+    present_axes: u8,
+    /// normalized, deadzones not applied yet.
+    raw_axes: [f32; GamepadAxis::COUNT],
+    /// what was emitted last.
+    axes: [f32; GamepadAxis::COUNT],
+    /// bit per [`GamepadButton`].
+    buttons: u32,
+    /// set on SYN_DROPPED. events are ignored until next SYN_REPORT (after which device must be
+    /// re-synced).
+    dropped: bool,
+}
+
+impl Mapper {
+    fn new(abs_ranges: [Option<AbsRange>; ABS_MAX + 1]) -> Self {
+        let mut present_axes = 0;
+        for (code, range) in abs_ranges.iter().enumerate() {
+            if let (Some(_), Some(axis)) = (range, try_map_abs_code(code as u16)) {
+                present_axes |= 1 << axis as u8;
+            }
+        }
+        Self {
+            abs_ranges,
+            present_axes,
+            raw_axes: [0.0; GamepadAxis::COUNT],
+            axes: [0.0; GamepadAxis::COUNT],
+            buttons: 0,
+            dropped: false,
+        }
+    }
+
+    fn set_button(
+        &mut self,
+        button: GamepadButton,
+        down: bool,
+        emit: &mut impl FnMut(GamepadEventKind),
+    ) {
+        let bit = 1 << button as u32;
+        if (self.buttons & bit != 0) == down {
+            return;
+        }
+        self.buttons ^= bit;
+        emit(GamepadEventKind::Button {
+            state: if down {
+                ButtonState::Pressed
+            } else {
+                ButtonState::Released
+            },
+            button,
+        });
+    }
+
+    fn set_axis(&mut self, axis: GamepadAxis, value: f32, emit: &mut impl FnMut(GamepadEventKind)) {
+        if self.axes[axis as usize] == value {
+            return;
+        }
+        self.axes[axis as usize] = value;
+        emit(GamepadEventKind::Axis { axis, value });
+    }
+
+    /// returns true if device needs to be re-synced.
+    fn handle_event(
+        &mut self,
+        config: &GamepadConfig,
+        ty: u16,
+        code: u16,
+        value: i32,
+        emit: &mut impl FnMut(GamepadEventKind),
+    ) -> bool {
+        if self.dropped {
+            if ty == EV_SYN && code == SYN_REPORT {
+                self.dropped = false;
+                return true;
+            }
+            return false;
+        }
+
+        match (ty, code) {
+            (EV_SYN, SYN_REPORT) => self.flush(config, emit),
+            (EV_SYN, SYN_DROPPED) => self.dropped = true,
+            // NOTE: value 2 is autorepeat.
+            (EV_KEY, code) if value != 2 => {
+                // NOTE: analog triggers drive trigger buttons; digital ones (BTN_TL2/BTN_TR2) on
+                // pads that have analog triggers too would fight with them.
+                let Some(button) = try_map_key_code(code) else {
+                    return false;
+                };
+                let analog_trigger = match button {
+                    GamepadButton::LeftTrigger => self.has_axis(GamepadAxis::LeftTrigger),
+                    GamepadButton::RightTrigger => self.has_axis(GamepadAxis::RightTrigger),
+                    _ => false,
+                };
+                if !analog_trigger {
+                    self.set_button(button, value != 0, emit);
+                }
+            }
+            (EV_ABS, ABS_HAT0X) => {
+                self.set_button(GamepadButton::DPadLeft, value < 0, emit);
+                self.set_button(GamepadButton::DPadRight, value > 0, emit);
+            }
+            (EV_ABS, ABS_HAT0Y) => {
+                self.set_button(GamepadButton::DPadUp, value < 0, emit);
+                self.set_button(GamepadButton::DPadDown, value > 0, emit);
+            }
+            (EV_ABS, code) => {
+                let (Some(axis), Some(Some(range))) =
+                    (try_map_abs_code(code), self.abs_ranges.get(code as usize))
+                else {
+                    return false;
+                };
+                let normalized = range.normalize(value);
+                self.raw_axes[axis as usize] = match axis {
+                    GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => normalized,
+                    _ => normalized * 2.0 - 1.0,
+                };
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn set_abs_range(&mut self, code: u16, range: AbsRange) {
+        self.abs_ranges[code as usize] = Some(range);
+        if let Some(axis) = try_map_abs_code(code) {
+            self.present_axes |= 1 << axis as u8;
+        }
+    }
+
+    fn has_axis(&self, axis: GamepadAxis) -> bool {
+        self.present_axes & (1 << axis as u8) != 0
+    }
+
+    /// applies deadzones and emits axes that changed.
+    fn flush(&mut self, config: &GamepadConfig, emit: &mut impl FnMut(GamepadEventKind)) {
+        use GamepadAxis::*;
+
+        for (x_axis, y_axis) in [(LeftStickX, LeftStickY), (RightStickX, RightStickY)] {
+            let (x, y) = config.stick_deadzone.apply_radial(
+                self.raw_axes[x_axis as usize],
+                self.raw_axes[y_axis as usize],
+            );
+            self.set_axis(x_axis, x, emit);
+            self.set_axis(y_axis, y, emit);
+        }
+
+        for (axis, button) in [
+            (LeftTrigger, GamepadButton::LeftTrigger),
+            (RightTrigger, GamepadButton::RightTrigger),
+        ] {
+            if !self.has_axis(axis) {
+                continue;
+            }
+            let value = config.trigger_deadzone.apply(self.raw_axes[axis as usize]);
+            self.set_axis(axis, value, emit);
+            self.set_button(button, value >= config.trigger_threshold, emit);
+        }
+    }
+
+    fn reset(&mut self, emit: &mut impl FnMut(GamepadEventKind)) {
+        for button in GamepadButton::all() {
+            self.set_button(button, false, emit);
+        }
+        for axis in GamepadAxis::all() {
+            self.set_axis(axis, 0.0, emit);
+        }
+        self.raw_axes = [0.0; GamepadAxis::COUNT];
+    }
+}
+
+// device
+// ----
+
+#[derive(Debug)]
+struct Device {
+    id: GamepadId,
+    path: CString,
+    fd: c_int,
+    mapper: Mapper,
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+impl Device {
+    /// returns `Ok(None)` if device is not a gamepad.
+    unsafe fn open(id: GamepadId, path: CString) -> anyhow::Result<Option<(Self, String)>> {
+        let fd = unsafe {
+            libc::open(
+                path.as_ptr(),
+                libc::O_RDONLY | libc::O_NONBLOCK | libc::O_CLOEXEC,
+            )
+        };
+        if fd == -1 {
+            let errno = errno();
+            return Err(anyhow!("could not open {path:?}: 0x{errno:x}"));
+        }
+        // NOTE: construct right away so that fd gets closed on early returns.
+        let mut device = Self {
+            id,
+            path,
+            fd,
+            mapper: Mapper::new([None; ABS_MAX + 1]),
+        };
+
+        let mut ev_bits = [0u8; EV_MAX / 8 + 1];
+        let mut key_bits = [0u8; KEY_MAX / 8 + 1];
+        let mut abs_bits = [0u8; ABS_MAX / 8 + 1];
+        unsafe {
+            ioctl_read_bytes(fd, eviocgbit(0, ev_bits.len()), &mut ev_bits)?;
+            ioctl_read_bytes(
+                fd,
+                eviocgbit(EV_KEY as usize, key_bits.len()),
+                &mut key_bits,
+            )?;
+            ioctl_read_bytes(
+                fd,
+                eviocgbit(EV_ABS as usize, abs_bits.len()),
+                &mut abs_bits,
+            )?;
+        }
+        // NOTE: this is what sdl and the kernel's joydev consider a gamepad (as opposed to
+        // joysticks, wheels, etc.).
+        if !test_bit(&ev_bits, EV_KEY as usize)
+            || !test_bit(&ev_bits, EV_ABS as usize)
+            || !test_bit(&key_bits, BTN_GAMEPAD as usize)
+        {
+            return Ok(None);
+        }
+
+        for abs in ABS_CODES {
+            if !test_bit(&abs_bits, abs as usize) {
+                continue;
+            }
+            let absinfo = unsafe { ioctl_read_absinfo(fd, abs) }?;
+            device.mapper.set_abs_range(
+                abs,
+                AbsRange {
+                    minimum: absinfo.minimum,
+                    maximum: absinfo.maximum,
+                },
+            );
+        }
+
+        let mut name = [0u8; 256];
+        let name = match unsafe { ioctl_read_bytes(fd, eviocgname(name.len()), &mut name) } {
+            Ok(_) => CStr::from_bytes_until_nul(&name)
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            Err(err) => {
+                log::warn!("could not get name of {:?}: {err:?}", device.path);
+                String::new()
+            }
+        };
+
+        Ok(Some((device, name)))
+    }
+
+    /// feeds current state of keys and axes into mapper. this is needed initially and after
+    /// SYN_DROPPED.
+    unsafe fn sync(
+        &mut self,
+        config: &GamepadConfig,
+        emit: &mut impl FnMut(GamepadEventKind),
+    ) -> anyhow::Result<()> {
+        let mut key_bits = [0u8; KEY_MAX / 8 + 1];
+        unsafe { ioctl_read_bytes(self.fd, eviocgkey(key_bits.len()), &mut key_bits) }?;
+        for code in KEY_CODES {
+            let value = test_bit(&key_bits, code as usize) as i32;
+            self.mapper.handle_event(config, EV_KEY, code, value, emit);
+        }
+        for abs in ABS_CODES {
+            if self.mapper.abs_ranges[abs as usize].is_none() {
+                continue;
+            }
+            let absinfo = unsafe { ioctl_read_absinfo(self.fd, abs) }?;
+            self.mapper
+                .handle_event(config, EV_ABS, abs, absinfo.value, emit);
+        }
+        self.mapper
+            .handle_event(config, EV_SYN, SYN_REPORT, 0, emit);
+        Ok(())
+    }
+
+    /// returns false if device is gone.
+    unsafe fn read_events(
+        &mut self,
+        config: &GamepadConfig,
+        emit: &mut impl FnMut(GamepadEventKind),
+    ) -> bool {
+        let mut buf = [MaybeUninit::<libc::input_event>::uninit(); 64];
+        loop {
+            let ret =
+                unsafe { libc::read(self.fd, buf.as_mut_ptr() as *mut c_void, size_of_val(&buf)) };
+            if ret == -1 {
+                return match errno() {
+                    libc::EAGAIN | libc::EINTR => true,
+                    errno => {
+                        // NOTE: ENODEV is what you get when device is unplugged.
+                        if errno != libc::ENODEV {
+                            log::warn!("could not read {:?}: 0x{errno:x}", self.path);
+                        }
+                        false
+                    }
+                };
+            }
+
+            let n = ret as usize / size_of::<libc::input_event>();
+            for event in &buf[..n] {
+                let event = unsafe { event.assume_init_ref() };
+                let needs_sync =
+                    self.mapper
+                        .handle_event(config, event.type_, event.code, event.value, emit);
+                if needs_sync && let Err(err) = unsafe { self.sync(config, emit) } {
+                    log::warn!("could not sync {:?}: {err:?}", self.path);
+                }
+            }
+            if n < buf.len() {
+                return true;
+            }
+        }
+    }
+
+    /// releases everything that is held and reports disconnection.
+    fn disconnect(&mut self, emit: &mut impl FnMut(GamepadEventKind)) {
+        self.mapper.reset(emit);
+        emit(GamepadEventKind::Disconnected);
+    }
+}
+
+// gamepads
+// ----
+
+const INPUT_DIR: &CStr = c"/dev/input";
+
+/// discovers gamepads (initially and on hotplug) and reads their events.
+///
+/// NOTE: nothing here blocks. call [`EvdevGamepads::poll_events`] once per frame, or wait for
+/// [`EvdevGamepads::fds`] to become readable if you have an event loop that can do that.
+#[derive(Debug)]
+pub struct EvdevGamepads {
+    config: GamepadConfig,
+    inotify_fd: c_int,
+    devices: Vec<Device>,
+    next_id: u32,
+    /// events that were produced outside of poll_events (connections of initially present
+    /// devices).
+    pending: Vec<GamepadEvent>,
+}
+
+impl Drop for EvdevGamepads {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.inotify_fd) };
+    }
+}
+
+impl EvdevGamepads {
+    pub fn new(config: GamepadConfig) -> anyhow::Result<Self> {
+        let inotify_fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if inotify_fd == -1 {
+            let errno = errno();
+            return Err(anyhow!("could not init inotify: 0x{errno:x}"));
+        }
+        let mut this = Self {
+            config,
+            inotify_fd,
+            devices: Vec::new(),
+            next_id: 0,
+            pending: Vec::new(),
+        };
+
+        // NOTE: IN_ATTRIB is needed because udev adjusts permissions after the node is created;
+        // at IN_CREATE time open would most likely fail with EACCES.
+        let wd = unsafe {
+            libc::inotify_add_watch(
+                inotify_fd,
+                INPUT_DIR.as_ptr(),
+                libc::IN_CREATE | libc::IN_ATTRIB | libc::IN_DELETE,
+            )
+        };
+        if wd == -1 {
+            let errno = errno();
+            return Err(anyhow!("could not watch {INPUT_DIR:?}: 0x{errno:x}"));
+        }
+
+        let entries = std::fs::read_dir(INPUT_DIR.to_str()?)?;
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.starts_with("event"))
+            .collect();
+        // NOTE: sort to make ids deterministic-ish (in order of event node numbers).
+        names.sort_by_key(|name| name["event".len()..].parse::<u32>().unwrap_or(u32::MAX));
+        let mut pending = Vec::new();
+        for name in names {
+            this.try_add_device(name.as_bytes(), &mut |event| pending.push(event));
+        }
+        this.pending = pending;
+
+        Ok(this)
+    }
+
+    pub fn config(&self) -> &GamepadConfig {
+        &self.config
+    }
+
+    /// NOTE: new deadzones apply from the next axis change on.
+    pub fn set_config(&mut self, config: GamepadConfig) {
+        self.config = config;
+    }
+
+    /// fds that become readable when there's something for [`EvdevGamepads::poll_events`] to do.
+    pub fn fds(&self) -> impl Iterator<Item = c_int> {
+        std::iter::once(self.inotify_fd).chain(self.devices.iter().map(|device| device.fd))
+    }
+
+    pub fn poll_events(&mut self, events: &mut impl Extend<Event>) {
+        let mut emit = |event: GamepadEvent| events.extend([Event::Gamepad(event)]);
+
+        self.pending.drain(..).for_each(&mut emit);
+
+        self.read_inotify_events(&mut emit);
+
+        let config = self.config;
+        self.devices.retain_mut(|device| {
+            let id = device.id;
+            let mut emit_kind = |kind| {
+                emit(GamepadEvent {
+                    gamepad_id: id,
+                    kind,
+                })
+            };
+            if unsafe { device.read_events(&config, &mut emit_kind) } {
+                return true;
+            }
+            device.disconnect(&mut emit_kind);
+            false
+        });
+    }
+
+    fn read_inotify_events(&mut self, emit: &mut impl FnMut(GamepadEvent)) {
+        // NOTE: inotify_event must be aligned.
+        #[repr(C, align(8))]
+        struct Buf([u8; 4096]);
+
+        let mut buf = Buf([0; 4096]);
+        loop {
+            let ret = unsafe {
+                libc::read(
+                    self.inotify_fd,
+                    buf.0.as_mut_ptr() as *mut c_void,
+                    buf.0.len(),
+                )
+            };
+            if ret == -1 {
+                let errno = errno();
+                if errno != libc::EAGAIN && errno != libc::EINTR {
+                    log::warn!("could not read inotify events: 0x{errno:x}");
+                }
+                return;
+            }
+
+            let len = ret as usize;
+            let mut offset = 0;
+            while offset + size_of::<libc::inotify_event>() <= len {
+                let event = unsafe {
+                    (buf.0.as_ptr().add(offset) as *const libc::inotify_event).read_unaligned()
+                };
+                let name_start = offset + size_of::<libc::inotify_event>();
+                let name_end = name_start + event.len as usize;
+                offset = name_end;
+
+                // NOTE: name is nul-padded.
+                let name = &buf.0[name_start..name_end.min(len)];
+                let name = name.split(|b| *b == 0).next().unwrap_or_default();
+                if !name.starts_with(b"event") {
+                    continue;
+                }
+
+                if event.mask & libc::IN_DELETE != 0 {
+                    // NOTE: node may be re-created before the next poll (within the same batch
+                    // even); the device must be gone by then so that the new node gets opened
+                    // instead of keeping the dead fd.
+                    self.remove_device(name, emit);
+                    continue;
+                }
+                if event.mask & (libc::IN_CREATE | libc::IN_ATTRIB) != 0 {
+                    self.try_add_device(name, emit);
+                }
+            }
+        }
+    }
+
+    fn try_add_device(&mut self, name: &[u8], emit: &mut impl FnMut(GamepadEvent)) {
+        let Some(path) = make_device_path(name) else {
+            return;
+        };
+        if self.devices.iter().any(|device| device.path == path) {
+            return;
+        }
+
+        let id = GamepadId(self.next_id);
+        let (mut device, name) = match unsafe { Device::open(id, path) } {
+            Ok(Some(device)) => device,
+            Ok(None) => return,
+            Err(err) => {
+                // NOTE: this is expected for devices that user has no access to (keyboards
+                // and such are often root-only).
+                log::debug!("{err:?}");
+                return;
+            }
+        };
+        self.next_id += 1;
+
+        let mut emit_kind = |kind| {
+            emit(GamepadEvent {
+                gamepad_id: id,
+                kind,
+            })
+        };
+        emit_kind(GamepadEventKind::Connected { name });
+        if let Err(err) = unsafe { device.sync(&self.config, &mut emit_kind) } {
+            log::warn!("could not sync {:?}: {err:?}", device.path);
+        }
+        self.devices.push(device);
+    }
+
+    fn remove_device(&mut self, name: &[u8], emit: &mut impl FnMut(GamepadEvent)) {
+        let Some(path) = make_device_path(name) else {
+            return;
+        };
+        let Some(idx) = self.devices.iter().position(|device| device.path == path) else {
+            return;
+        };
+        let mut device = self.devices.remove(idx);
+        let id = device.id;
+        device.disconnect(&mut |kind| {
+            emit(GamepadEvent {
+                gamepad_id: id,
+                kind,
+            })
+        });
+    }
+}
+
+fn make_device_path(name: &[u8]) -> Option<CString> {
+    let mut path = INPUT_DIR.to_bytes().to_vec();
+    path.push(b'/');
+    path.extend_from_slice(name);
+    CString::new(path).ok()
+}
+
+#[test]
+fn test_evdev_mapper() {
+    // NOTE: ranges are what xpad reports for xbox 360 pad.
+    let mut abs_ranges = [None; ABS_MAX + 1];
+    for abs in [ABS_X, ABS_Y, ABS_RX, ABS_RY] {
+        abs_ranges[abs as usize] = Some(AbsRange {
+            minimum: -32768,
+            maximum: 32767,
+        });
+    }
+    for abs in [ABS_Z, ABS_RZ] {
+        abs_ranges[abs as usize] = Some(AbsRange {
+            minimum: 0,
+            maximum: 255,
+        });
+    }
+    for abs in [ABS_HAT0X, ABS_HAT0Y] {
+        abs_ranges[abs as usize] = Some(AbsRange {
+            minimum: -1,
+            maximum: 1,
+        });
+    }
+    let mut mapper = Mapper::new(abs_ranges);
+    let config = GamepadConfig::default();
+    assert!(mapper.has_axis(GamepadAxis::LeftStickX));
+    assert!(mapper.has_axis(GamepadAxis::RightTrigger));
+    let mut no_triggers = Mapper::new([None; ABS_MAX + 1]);
+    assert!(!no_triggers.has_axis(GamepadAxis::LeftTrigger));
+    no_triggers.set_abs_range(
+        ABS_BRAKE,
+        AbsRange {
+            minimum: 0,
+            maximum: 1023,
+        },
+    );
+    assert!(no_triggers.has_axis(GamepadAxis::LeftTrigger));
+    assert!(!no_triggers.has_axis(GamepadAxis::RightTrigger));
+
+    // NOTE: this is a recording of: a press, slight left stick wobble (within deadzone), left
+    // stick pushed right, dpad left, right trigger fully pressed, a release.
+    #[rustfmt::skip]
+    let dump: &[(u16, u16, i32)] = &[
+        (EV_KEY, BTN_SOUTH, 1), (EV_SYN, SYN_REPORT, 0),
+        (EV_ABS, ABS_X, 1200), (EV_ABS, ABS_Y, -900), (EV_SYN, SYN_REPORT, 0),
+        (EV_ABS, ABS_X, 32767), (EV_SYN, SYN_REPORT, 0),
+        (EV_ABS, ABS_HAT0X, -1), (EV_SYN, SYN_REPORT, 0),
+        (EV_ABS, ABS_RZ, 255), (EV_SYN, SYN_REPORT, 0),
+        (EV_KEY, BTN_SOUTH, 0), (EV_SYN, SYN_REPORT, 0),
+    ];
+    let mut events = Vec::new();
+    for &(ty, code, value) in dump {
+        mapper.handle_event(&config, ty, code, value, &mut |kind| events.push(kind));
+    }
+
+    let mut events = events.into_iter();
+    assert!(matches!(
+        events.next(),
+        Some(GamepadEventKind::Button {
+            state: ButtonState::Pressed,
+            button: GamepadButton::South
+        })
+    ));
+    let Some(GamepadEventKind::Axis {
+        axis: GamepadAxis::LeftStickX,
+        value,
+    }) = events.next()
+    else {
+        panic!();
+    };
+    assert!(value > 0.99);
+    // NOTE: y is -900 (within deadzone on its own), but with x fully pushed radial deadzone
+    // lets it through as a tiny negative value.
+    assert!(matches!(
+        events.next(),
+        Some(GamepadEventKind::Axis {
+            axis: GamepadAxis::LeftStickY,
+            value,
+        }) if value < 0.0 && value > -0.05
+    ));
+    assert!(matches!(
+        events.next(),
+        Some(GamepadEventKind::Button {
+            state: ButtonState::Pressed,
+            button: GamepadButton::DPadLeft
+        })
+    ));
+    assert!(matches!(
+        events.next(),
+        Some(GamepadEventKind::Axis {
+            axis: GamepadAxis::RightTrigger,
+            value,
+        }) if value == 1.0
+    ));
+    assert!(matches!(
+        events.next(),
+        Some(GamepadEventKind::Button {
+            state: ButtonState::Pressed,
+            button: GamepadButton::RightTrigger
+        })
+    ));
+    assert!(matches!(
+        events.next(),
+        Some(GamepadEventKind::Button {
+            state: ButtonState::Released,
+            button: GamepadButton::South
+        })
+    ));
+    assert!(events.next().is_none());
+
+    // NOTE: events after SYN_DROPPED are ignored until SYN_REPORT, which requests a re-sync.
+    let mut events = Vec::new();
+    let mut emit = |kind| events.push(kind);
+    assert!(!mapper.handle_event(&config, EV_SYN, SYN_DROPPED, 0, &mut emit));
+    assert!(!mapper.handle_event(&config, EV_KEY, BTN_EAST, 1, &mut emit));
+    assert!(mapper.handle_event(&config, EV_SYN, SYN_REPORT, 0, &mut emit));
+    assert!(events.is_empty());
+}
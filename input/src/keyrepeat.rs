@@ -0,0 +1,348 @@
+use std::time::Duration;
+
+use crate::{Event, KeyState, KeyboardEvent, KeyboardEventKind, Keycode, Scancode, SurfaceId};
+
+/// source of monotonic time. the epoch does not matter, only differences between values do.
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+/// NOTE: std's Instant is not available on wasm32-unknown-unknown (it panics). on web implement
+/// [`Clock`] with `performance.now()`.
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug, Clone, Copy)]
+pub struct StdClock {
+    start: std::time::Instant,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Clock for StdClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// shorter intervals are clamped to this.
+pub const MIN_KEY_REPEAT_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyRepeatInfo {
+    /// time since key down until repeating starts.
+    pub delay: Duration,
+    /// time between repeats. clamped to [`MIN_KEY_REPEAT_INTERVAL`].
+    pub interval: Duration,
+}
+
+impl Default for KeyRepeatInfo {
+    // NOTE: these are sway's defaults (and are close to what most other environments use).
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(600),
+            interval: Duration::from_millis(40),
+        }
+    }
+}
+
+impl KeyRepeatInfo {
+    /// rate is in repeats per second (that's how wayland and x11 describe it). a rate of zero
+    /// means that repeating is disabled.
+    pub fn from_rate(rate: u32, delay: Duration) -> Option<Self> {
+        if rate == 0 {
+            return None;
+        }
+        Some(Self {
+            delay,
+            interval: Duration::from_nanos(1_000_000_000 / rate as u64)
+                .max(MIN_KEY_REPEAT_INTERVAL),
+        })
+    }
+}
+
+/// NOTE: toggles and modifiers don't repeat. this matches what xkb does with most keymaps.
+pub fn scancode_repeats(scancode: Scancode) -> bool {
+    !matches!(
+        scancode,
+        Scancode::CtrlLeft
+            | Scancode::CtrlRight
+            | Scancode::ShiftLeft
+            | Scancode::ShiftRight
+            | Scancode::AltLeft
+            | Scancode::AltRight
+            | Scancode::CapsLock
+            | Scancode::NumLock
+            | Scancode::ScrollLock
+    )
+}
+
+/// generates key repeats independently of the window backend.
+///
+/// repeats that come from the backend (`repeat: true` presses) are dropped, repeats are produced
+/// according to [`KeyRepeatInfo`] instead. that gives the same behaviour on all backends and
+/// allows to disable repeating (for game controls for example) by setting repeat info to `None`.
+///
+/// NOTE: only the most recently pressed key repeats.
+#[derive(Debug)]
+pub struct KeyRepeater<C: Clock> {
+    clock: C,
+    repeat_info: Option<KeyRepeatInfo>,
+    key: Option<(SurfaceId, Scancode, Keycode)>,
+    next_repeat_at: Duration,
+}
+
+impl<C: Clock> KeyRepeater<C> {
+    pub fn new(clock: C, repeat_info: Option<KeyRepeatInfo>) -> Self {
+        Self {
+            clock,
+            repeat_info,
+            key: None,
+            next_repeat_at: Duration::ZERO,
+        }
+    }
+
+    pub fn repeat_info(&self) -> Option<KeyRepeatInfo> {
+        self.repeat_info
+    }
+
+    /// stops current repeat (if any). new info is applied on the next key press.
+    pub fn set_repeat_info(&mut self, repeat_info: Option<KeyRepeatInfo>) {
+        self.repeat_info = repeat_info;
+        self.key = None;
+    }
+
+    /// stops current repeat (if any).
+    ///
+    /// NOTE: this happens automatically on [`KeyboardEventKind::Leave`].
+    pub fn cancel(&mut self) {
+        self.key = None;
+    }
+
+    /// time (of the clock) at which the next repeat is due. may be used to limit how long
+    /// the event loop may block.
+    pub fn next_repeat_at(&self) -> Option<Duration> {
+        self.key.map(|_| self.next_repeat_at)
+    }
+
+    /// yields repeats that are due, followed by the given events (minus backend repeats).
+    pub fn process<'a>(
+        &'a mut self,
+        events: impl Iterator<Item = Event> + 'a,
+    ) -> impl Iterator<Item = Event> + 'a {
+        let mut repeats: Vec<Event> = Vec::new();
+        self.poll(|event| repeats.push(Event::Keyboard(event)));
+        repeats
+            .into_iter()
+            .chain(events.filter_map(|event| match event {
+                Event::Keyboard(event) => self.handle_event(event).map(Event::Keyboard),
+                other => Some(other),
+            }))
+    }
+
+    /// emits a repeat if it is due.
+    ///
+    /// NOTE: repeats that were missed (if the app stalled for example) are skipped instead of
+    /// being delivered in a burst.
+    pub fn poll(&mut self, mut emit: impl FnMut(KeyboardEvent)) {
+        let (Some((surface_id, scancode, keycode)), Some(repeat_info)) =
+            (self.key, self.repeat_info)
+        else {
+            return;
+        };
+
+        let now = self.clock.now();
+        if self.next_repeat_at > now {
+            return;
+        }
+        emit(KeyboardEvent {
+            surface_id,
+            kind: KeyboardEventKind::Key {
+                state: KeyState::Pressed,
+                scancode,
+                keycode,
+                repeat: true,
+            },
+        });
+
+        let interval = repeat_info.interval.max(MIN_KEY_REPEAT_INTERVAL).as_nanos();
+        let missed = (now - self.next_repeat_at).as_nanos() / interval;
+        self.next_repeat_at += Duration::from_nanos(((missed + 1) * interval) as u64);
+    }
+
+    /// returns `None` if the event must be dropped.
+    pub fn handle_event(&mut self, event: KeyboardEvent) -> Option<KeyboardEvent> {
+        match event.kind {
+            KeyboardEventKind::Key {
+                state: KeyState::Pressed,
+                repeat: true,
+                ..
+            } => return None,
+            KeyboardEventKind::Key {
+                state: KeyState::Pressed,
+                scancode,
+                keycode,
+                repeat: false,
+            } => {
+                if let Some(repeat_info) = self.repeat_info
+                    && scancode_repeats(scancode)
+                {
+                    self.key = Some((event.surface_id, scancode, keycode));
+                    self.next_repeat_at = self.clock.now() + repeat_info.delay;
+                }
+            }
+            KeyboardEventKind::Key {
+                state: KeyState::Released,
+                scancode,
+                ..
+            } => {
+                if self
+                    .key
+                    .is_some_and(|(_, repeating, _)| repeating == scancode)
+                {
+                    self.key = None;
+                }
+            }
+            // NOTE: release may never arrive after focus is lost.
+            KeyboardEventKind::Leave => self.cancel(),
+            KeyboardEventKind::Enter => {}
+        }
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+struct FakeClock(std::rc::Rc<std::cell::Cell<Duration>>);
+
+#[cfg(test)]
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+#[cfg(test)]
+fn key_event(state: KeyState, scancode: Scancode, repeat: bool) -> Event {
+    Event::Keyboard(KeyboardEvent {
+        surface_id: SurfaceId(0),
+        kind: KeyboardEventKind::Key {
+            state,
+            scancode,
+            keycode: Keycode::Char('a'),
+            repeat,
+        },
+    })
+}
+
+#[cfg(test)]
+fn count_repeats(events: impl Iterator<Item = Event>) -> usize {
+    events
+        .filter(|event| {
+            matches!(
+                event,
+                Event::Keyboard(KeyboardEvent {
+                    kind: KeyboardEventKind::Key { repeat: true, .. },
+                    ..
+                })
+            )
+        })
+        .count()
+}
+
+#[test]
+fn test_key_repeater() {
+    use std::cell::Cell;
+    use std::iter;
+    use std::rc::Rc;
+
+    let now = Rc::new(Cell::new(Duration::ZERO));
+    let repeat_info = KeyRepeatInfo {
+        delay: Duration::from_millis(500),
+        interval: Duration::from_millis(100),
+    };
+    let mut key_repeater = KeyRepeater::new(FakeClock(Rc::clone(&now)), Some(repeat_info));
+
+    // NOTE: backend repeats are dropped.
+    let events = [
+        key_event(KeyState::Pressed, Scancode::A, false),
+        key_event(KeyState::Pressed, Scancode::A, true),
+    ];
+    assert_eq!(key_repeater.process(events.into_iter()).count(), 1);
+
+    now.set(Duration::from_millis(499));
+    assert_eq!(count_repeats(key_repeater.process(iter::empty())), 0);
+    now.set(Duration::from_millis(500));
+    assert_eq!(count_repeats(key_repeater.process(iter::empty())), 1);
+    // NOTE: missed repeats are skipped, the schedule is kept.
+    now.set(Duration::from_millis(750));
+    assert_eq!(count_repeats(key_repeater.process(iter::empty())), 1);
+    assert_eq!(
+        key_repeater.next_repeat_at(),
+        Some(Duration::from_millis(800))
+    );
+
+    // NOTE: repeats that became due before release are still delivered.
+    now.set(Duration::from_millis(800));
+    let events = [key_event(KeyState::Released, Scancode::A, false)];
+    assert_eq!(count_repeats(key_repeater.process(events.into_iter())), 1);
+    now.set(Duration::from_millis(2000));
+    assert_eq!(count_repeats(key_repeater.process(iter::empty())), 0);
+    assert_eq!(key_repeater.next_repeat_at(), None);
+
+    // NOTE: modifiers don't repeat.
+    let events = [key_event(KeyState::Pressed, Scancode::ShiftLeft, false)];
+    assert_eq!(key_repeater.process(events.into_iter()).count(), 1);
+    now.set(Duration::from_millis(5000));
+    assert_eq!(count_repeats(key_repeater.process(iter::empty())), 0);
+}
+
+#[test]
+fn test_key_repeater_disabled() {
+    use std::cell::Cell;
+    use std::iter;
+    use std::rc::Rc;
+
+    let now = Rc::new(Cell::new(Duration::ZERO));
+    let mut key_repeater = KeyRepeater::new(FakeClock(Rc::clone(&now)), None);
+
+    let events = [
+        key_event(KeyState::Pressed, Scancode::A, false),
+        key_event(KeyState::Pressed, Scancode::A, true),
+        key_event(KeyState::Pressed, Scancode::A, true),
+    ];
+    assert_eq!(key_repeater.process(events.into_iter()).count(), 1);
+    now.set(Duration::from_secs(10));
+    assert_eq!(count_repeats(key_repeater.process(iter::empty())), 0);
+}
+
+#[test]
+fn test_key_repeater_zero_interval() {
+    use std::cell::Cell;
+    use std::iter;
+    use std::rc::Rc;
+
+    let repeat_info = KeyRepeatInfo::from_rate(u32::MAX, Duration::ZERO).unwrap();
+    assert_eq!(repeat_info.interval, MIN_KEY_REPEAT_INTERVAL);
+
+    let now = Rc::new(Cell::new(Duration::ZERO));
+    let repeat_info = KeyRepeatInfo {
+        delay: Duration::ZERO,
+        interval: Duration::ZERO,
+    };
+    let mut key_repeater = KeyRepeater::new(FakeClock(Rc::clone(&now)), Some(repeat_info));
+    let events = [key_event(KeyState::Pressed, Scancode::A, false)];
+    assert_eq!(count_repeats(key_repeater.process(events.into_iter())), 0);
+
+    now.set(Duration::from_secs(60));
+    assert_eq!(count_repeats(key_repeater.process(iter::empty())), 1);
+    assert_eq!(
+        key_repeater.next_repeat_at(),
+        Some(Duration::from_secs(60) + MIN_KEY_REPEAT_INTERVAL)
+    );
+}
@@ -1,9 +1,23 @@
 use std::{
-    collections::HashMap,
     hash::{Hash, Hasher},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not},
 };
 
-use mars::nohash::{NoBuildHasher, NoHash};
+use mars::{alloc, nohash::NoHash, sortedarray::SpillableSortedArrayMap};
+
+mod action;
+mod gamepad;
+#[cfg(target_os = "linux")]
+mod gamepad_evdev;
+mod keyrepeat;
+mod surface;
+
+pub use action::*;
+pub use gamepad::*;
+#[cfg(target_os = "linux")]
+pub use gamepad_evdev::*;
+pub use keyrepeat::*;
+pub use surface::*;
 
 // TODO: events must carry device id in addition to surface id.
 //   (on device id) maybe you want to let people play split screen with with different controllers
@@ -12,20 +26,60 @@ use mars::nohash::{NoBuildHasher, NoHash};
 // TODO: some kind of surface state
 //   must be able to do something like .just_resized(), .just_rescaled() or
 //   .scale_factor_just_changed() .. stuff like that.
-//
-// TODO: don't use hashmaps here.
-//   SortedArrayMap does perform better on small number of ints (see benchmark in mars repo).
-//   and is supposedly significantly more compact (although that's theoretic; i did not compare
-//   real numbners).
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SurfaceId(pub u64);
 
+// dpi
+// ----
+
+// NOTE: logical pixels are the surface-local coordinates; that is what wayland operates in and what
+// is independent of scale factor. physical pixels are the pixels of the buffer that is being
+// rendered into (physical = logical * scale_factor).
+
+/// position in logical pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LogicalPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl LogicalPosition {
+    #[inline]
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    #[inline]
+    pub fn to_physical(self, scale_factor: f64) -> PhysicalPosition {
+        PhysicalPosition::new(self.x * scale_factor, self.y * scale_factor)
+    }
+}
+
+/// position in physical pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PhysicalPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl PhysicalPosition {
+    #[inline]
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    #[inline]
+    pub fn to_logical(self, scale_factor: f64) -> LogicalPosition {
+        LogicalPosition::new(self.x / scale_factor, self.y / scale_factor)
+    }
+}
+
 // pointer
 // ----
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Button {
     /// equivalent to left mouse button
     Primary,
@@ -41,8 +95,6 @@ impl Hash for Button {
     }
 }
 
-impl NoHash for Button {}
-
 impl Button {
     /// NOTE: this is useful for calling InputState's all_just_pressed/all_just_released method for
     /// example.
@@ -91,11 +143,19 @@ pub enum PointerEventKind {
     Enter {
         // NOTE: winit (v 0.30.12) does not provide enter position. but it seems like future
         // versions will.
-        position: Option<(f64, f64)>,
+        position: Option<LogicalPosition>,
     },
     Leave,
     Move {
-        position: (f64, f64),
+        position: LogicalPosition,
+    },
+    /// raw pointer motion that is not bound by surface edges (or screen edges). it continues to
+    /// arrive when pointer is locked.
+    RelativeMotion {
+        /// accelerated delta (the same delta that moves the cursor).
+        delta: (f64, f64),
+        /// delta as it was reported by the device. useful for camera controls.
+        unaccelerated_delta: (f64, f64),
     },
     Button {
         state: ButtonState,
@@ -110,6 +170,7 @@ pub enum PointerEventKind {
         // TODO: consider being more descriptive with what delta this is (like gesture events).
         delta: (f64, f64),
     },
+    // NOTE: gesture deltas (as well as position deltas) are in logical pixels.
     // TODO: winit does not support gestures (only on ios?). extract gesture handling from wayland
     // backend and use it in winit backend if winint backend is using wayland under the hood.
     Pan {
@@ -168,7 +229,7 @@ pub enum CursorShape {
 // ----
 
 #[repr(u32)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RawKey {
     /// linux [1], and pretty sure would work just fine on freebsd and its forks [2], but not mac.
     ///
@@ -187,7 +248,7 @@ pub enum RawKey {
 ///
 /// https://github.com/torvalds/linux/blob/231825b2e1ff6ba799c5eaf396d3ab2354e37c6b/include/uapi/linux/input-event-codes.h#L76
 #[repr(u32)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[rustfmt::skip]
 pub enum Scancode {
     Reserved,               // KEY_RESERVED          0
@@ -343,13 +404,94 @@ impl Hash for Scancode {
 
 impl NoHash for Scancode {}
 
+impl Scancode {
+    /// inverse of Debug formatting (for identified scancodes). useful for parsing configs.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Reserved" => Some(Self::Reserved),
+            "Esc" => Some(Self::Esc),
+            "Num1" => Some(Self::Num1),
+            "Num2" => Some(Self::Num2),
+            "Num3" => Some(Self::Num3),
+            "Num4" => Some(Self::Num4),
+            "Num5" => Some(Self::Num5),
+            "Num6" => Some(Self::Num6),
+            "Num7" => Some(Self::Num7),
+            "Num8" => Some(Self::Num8),
+            "Num9" => Some(Self::Num9),
+            "Num0" => Some(Self::Num0),
+            "Minus" => Some(Self::Minus),
+            "Equal" => Some(Self::Equal),
+            "Backspace" => Some(Self::Backspace),
+            "Tab" => Some(Self::Tab),
+            "Q" => Some(Self::Q),
+            "W" => Some(Self::W),
+            "E" => Some(Self::E),
+            "R" => Some(Self::R),
+            "T" => Some(Self::T),
+            "Y" => Some(Self::Y),
+            "U" => Some(Self::U),
+            "I" => Some(Self::I),
+            "O" => Some(Self::O),
+            "P" => Some(Self::P),
+            "BraceLeft" => Some(Self::BraceLeft),
+            "BraceRight" => Some(Self::BraceRight),
+            "Enter" => Some(Self::Enter),
+            "CtrlLeft" => Some(Self::CtrlLeft),
+            "A" => Some(Self::A),
+            "S" => Some(Self::S),
+            "D" => Some(Self::D),
+            "F" => Some(Self::F),
+            "G" => Some(Self::G),
+            "H" => Some(Self::H),
+            "J" => Some(Self::J),
+            "K" => Some(Self::K),
+            "L" => Some(Self::L),
+            "Semicolon" => Some(Self::Semicolon),
+            "Apostrophe" => Some(Self::Apostrophe),
+            "Grave" => Some(Self::Grave),
+            "ShiftLeft" => Some(Self::ShiftLeft),
+            "Backslash" => Some(Self::Backslash),
+            "Z" => Some(Self::Z),
+            "X" => Some(Self::X),
+            "C" => Some(Self::C),
+            "V" => Some(Self::V),
+            "B" => Some(Self::B),
+            "N" => Some(Self::N),
+            "M" => Some(Self::M),
+            "Comma" => Some(Self::Comma),
+            "Dot" => Some(Self::Dot),
+            "Slash" => Some(Self::Slash),
+            "ShiftRight" => Some(Self::ShiftRight),
+            "AltLeft" => Some(Self::AltLeft),
+            "Space" => Some(Self::Space),
+            "CapsLock" => Some(Self::CapsLock),
+            "NumLock" => Some(Self::NumLock),
+            "ScrollLock" => Some(Self::ScrollLock),
+            "CtrlRight" => Some(Self::CtrlRight),
+            "AltRight" => Some(Self::AltRight),
+            "Home" => Some(Self::Home),
+            "ArrowUp" => Some(Self::ArrowUp),
+            "PageUp" => Some(Self::PageUp),
+            "ArrowLeft" => Some(Self::ArrowLeft),
+            "ArrowRight" => Some(Self::ArrowRight),
+            "End" => Some(Self::End),
+            "ArrowDown" => Some(Self::ArrowDown),
+            "PageDown" => Some(Self::PageDown),
+            "Insert" => Some(Self::Insert),
+            "Delete" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+
 /// Keycode is a code assigned by the operating system or software that represents the symbol or
 /// character mapped to the key pressed, taking into account the current keyboard layout. For
 /// example, pressing the same physical key might generate a different keycode on an AZERTY
 /// keyboard compared to a QWERTY keyboard because the symbol mapped to that key differs
 ///   - llm
 #[repr(u32)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Keycode {
     Char(char),
     Unidentified(RawKey),
@@ -379,8 +521,6 @@ impl Hash for Keycode {
     }
 }
 
-impl NoHash for Keycode {}
-
 // TODO: consider converting KeyboardEventKind::Key's repeat bool into KeyState::Repeated variant
 // (this will match WL_KEYBOARD_KEY_STATE_* enum).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -393,6 +533,10 @@ pub enum KeyState {
 // enum ButtonState { Pressed, Released }
 #[derive(Debug, Clone)]
 pub enum KeyboardEventKind {
+    /// surface received keyboard focus.
+    Enter,
+    /// surface lost keyboard focus.
+    Leave,
     Key {
         state: KeyState,
         scancode: Scancode,
@@ -411,74 +555,156 @@ pub struct KeyboardEvent {
 // states
 // ----
 
-// TODO: might want to implement bitwise op traits for StateFlags.
-//
+macro_rules! impl_flags_ops {
+    ($ty:ident) => {
+        impl $ty {
+            /// true if all bits of `other` are set.
+            #[inline]
+            pub fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// true if any bit of `other` is set.
+            #[inline]
+            pub fn intersects(self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            #[inline]
+            pub fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+        }
+
+        impl BitOr for $ty {
+            type Output = Self;
+
+            #[inline]
+            fn bitor(self, rhs: Self) -> Self {
+                Self(self.0 | rhs.0)
+            }
+        }
+
+        impl BitOrAssign for $ty {
+            #[inline]
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.0 |= rhs.0;
+            }
+        }
+
+        impl BitAnd for $ty {
+            type Output = Self;
+
+            #[inline]
+            fn bitand(self, rhs: Self) -> Self {
+                Self(self.0 & rhs.0)
+            }
+        }
+
+        impl BitAndAssign for $ty {
+            #[inline]
+            fn bitand_assign(&mut self, rhs: Self) {
+                self.0 &= rhs.0;
+            }
+        }
+
+        impl Not for $ty {
+            type Output = Self;
+
+            #[inline]
+            fn not(self) -> Self {
+                Self(!self.0)
+            }
+        }
+    };
+}
+
 // NOTE: button may have multiple states at the same time.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct StateFlags(u8);
 
 impl StateFlags {
-    pub const NONE: u8 = 0;
-    pub const JUST_PRESSED: u8 = 1 << 0;
-    pub const JUST_RELEASED: u8 = 1 << 1;
-    pub const DOWN: u8 = 1 << 2;
-    pub const REPEAT: u8 = 1 << 3;
+    pub const NONE: Self = Self(0);
+    pub const JUST_PRESSED: Self = Self(1 << 0);
+    pub const JUST_RELEASED: Self = Self(1 << 1);
+    pub const DOWN: Self = Self(1 << 2);
+    pub const REPEAT: Self = Self(1 << 3);
 }
 
+impl_flags_ops!(StateFlags);
+
+/// number of buttons that a tracker can hold without spilling onto the heap.
+pub const STATE_TRACKER_INLINE_CAP: usize = 8;
+
 // NOTE: this was originally inspired by bevy's ButtonInput thing.
+//
+// NOTE: sorted array performs better than hashmap on small number of ints (see benchmark in mars
+// repo and benches/statetracker.rs) and is more compact.
+//
+// tracker only holds buttons that are down or that changed state during the frame (the rest is
+// dropped in clear_transient_flags), so there's rarely more than a handful of them.
 #[derive(Debug)]
 pub struct StateTracker<B>
 where
-    B: Copy + Eq + NoHash,
+    B: Copy + Ord,
 {
-    map: HashMap<B, StateFlags, NoBuildHasher<B>>,
+    map: SpillableSortedArrayMap<B, StateFlags, STATE_TRACKER_INLINE_CAP, alloc::Global>,
 }
 
 // @BlindDerive
 impl<B> Default for StateTracker<B>
 where
-    B: Copy + Eq + NoHash,
+    B: Copy + Ord,
 {
     fn default() -> Self {
         Self {
-            map: HashMap::default(),
+            map: SpillableSortedArrayMap::default(),
         }
     }
 }
 
 impl<B> StateTracker<B>
 where
-    B: Copy + Eq + NoHash,
+    B: Copy + Ord,
 {
     pub fn clear_transient_flags(&mut self) {
-        self.map.values_mut().for_each(|state| {
-            state.0 &= !StateFlags::JUST_PRESSED;
-            state.0 &= !StateFlags::JUST_RELEASED;
-            state.0 &= !StateFlags::REPEAT;
+        self.map.retain(|_, state| {
+            *state &= StateFlags::DOWN;
+            !state.is_empty()
         });
     }
 
     // ----
 
     pub fn press(&mut self, button: B, repeat: bool) {
-        let state = self.map.entry(button).or_insert(StateFlags(0));
-        state.0 = StateFlags::JUST_PRESSED | StateFlags::DOWN;
+        let mut state = StateFlags::JUST_PRESSED | StateFlags::DOWN;
         if repeat {
-            state.0 |= StateFlags::REPEAT
+            state |= StateFlags::REPEAT;
         }
+        self.map.insert(button, state);
     }
 
     pub fn release(&mut self, button: B) {
-        let state = self.map.entry(button).or_insert(StateFlags(0));
-        state.0 = StateFlags::JUST_RELEASED;
+        self.map.insert(button, StateFlags::JUST_RELEASED);
+    }
+
+    fn has(&self, button: B, flags: StateFlags) -> bool {
+        self.map
+            .get(&button)
+            .is_some_and(|state| state.contains(flags))
+    }
+
+    fn iter_with(&self, flags: StateFlags) -> impl Iterator<Item = B> {
+        self.map
+            .0
+            .iter()
+            .filter_map(move |(button, state)| state.contains(flags).then_some(*button))
     }
 
     // just pressed
 
     pub fn just_pressed(&self, button: B) -> bool {
-        self.map
-            .get(&button)
-            .is_some_and(|state| state.0 & StateFlags::JUST_PRESSED != 0)
+        self.has(button, StateFlags::JUST_PRESSED)
     }
 
     pub fn any_just_pressed(&self, buttons: impl IntoIterator<Item = B>) -> bool {
@@ -490,17 +716,13 @@ where
     }
 
     pub fn iter_just_pressed(&self) -> impl Iterator<Item = B> {
-        self.map.iter().filter_map(|(button, state)| {
-            (state.0 & StateFlags::JUST_PRESSED != 0).then_some(*button)
-        })
+        self.iter_with(StateFlags::JUST_PRESSED)
     }
 
     // just released
 
     pub fn just_released(&self, button: B) -> bool {
-        self.map
-            .get(&button)
-            .is_some_and(|state| state.0 & StateFlags::JUST_RELEASED != 0)
+        self.has(button, StateFlags::JUST_RELEASED)
     }
 
     pub fn any_just_released(&self, buttons: impl IntoIterator<Item = B>) -> bool {
@@ -512,17 +734,13 @@ where
     }
 
     pub fn iter_just_released(&self) -> impl Iterator<Item = B> {
-        self.map.iter().filter_map(|(button, state)| {
-            (state.0 & StateFlags::JUST_RELEASED != 0).then_some(*button)
-        })
+        self.iter_with(StateFlags::JUST_RELEASED)
     }
 
     // down
 
     pub fn down(&self, button: B) -> bool {
-        self.map
-            .get(&button)
-            .is_some_and(|state| state.0 & StateFlags::DOWN != 0)
+        self.has(button, StateFlags::DOWN)
     }
 
     pub fn any_down(&self, buttons: impl IntoIterator<Item = B>) -> bool {
@@ -534,17 +752,13 @@ where
     }
 
     pub fn iter_down(&self) -> impl Iterator<Item = B> {
-        self.map
-            .iter()
-            .filter_map(|(button, state)| (state.0 & StateFlags::DOWN != 0).then_some(*button))
+        self.iter_with(StateFlags::DOWN)
     }
 
     // repeat
 
     pub fn repeated(&self, button: B) -> bool {
-        self.map
-            .get(&button)
-            .is_some_and(|state| state.0 & StateFlags::REPEAT != 0)
+        self.has(button, StateFlags::REPEAT)
     }
 
     pub fn any_repeated(&self, buttons: impl IntoIterator<Item = B>) -> bool {
@@ -556,27 +770,37 @@ where
     }
 
     pub fn iter_repeated(&self) -> impl Iterator<Item = B> {
-        self.map
-            .iter()
-            .filter_map(|(button, state)| (state.0 & StateFlags::REPEAT != 0).then_some(*button))
+        self.iter_with(StateFlags::REPEAT)
+    }
+
+    /// raw flags of a button. [`StateFlags::NONE`] for buttons that are up and didn't change
+    /// state during the frame.
+    pub fn flags(&self, button: B) -> StateFlags {
+        self.map.get(&button).copied().unwrap_or_default()
     }
 }
 
 #[derive(Debug, Default)]
 pub struct PointerState {
-    pub position: Option<(f64, f64)>,
+    pub position: Option<LogicalPosition>,
     // NOTE: prev_position is needed to compute position_delta.
     //   a single iteration (of an event loop) may accumulate multiple move events thus to compute
     //   correct deltas we need to diff against prev frame and not against prev value.
-    prev_position: Option<(f64, f64)>,
+    prev_position: Option<LogicalPosition>,
+    /// in logical pixels.
     pub position_delta: Option<(f64, f64)>,
 
+    // NOTE: relative deltas are accumulators that are being reset each iteration (same as
+    // scroll_delta).
+    pub relative_delta: Option<(f64, f64)>,
+    pub unaccelerated_relative_delta: Option<(f64, f64)>,
+
     // NOTE: scroll_delta is a accumulator that is being reset each iteration.
     //   accumulator because multiple scroll events may be received per iteration(/frame).
     pub scroll_delta: Option<(f64, f64)>,
 
     pub buttons: StateTracker<Button>,
-    pub press_origins: HashMap<Button, (f64, f64), NoBuildHasher<Button>>,
+    pub press_origins: SpillableSortedArrayMap<Button, LogicalPosition, 3, alloc::Global>,
 }
 
 impl PointerState {
@@ -586,6 +810,9 @@ impl PointerState {
         self.prev_position = self.position;
         self.position_delta = None;
 
+        self.relative_delta = None;
+        self.unaccelerated_relative_delta = None;
+
         self.scroll_delta = None;
     }
 
@@ -606,7 +833,7 @@ impl PointerState {
             | Move { position } => {
                 self.position = Some(position);
                 if let Some(prev) = self.prev_position {
-                    let delta = (position.0 - prev.0, position.1 - prev.1);
+                    let delta = (position.x - prev.x, position.y - prev.y);
                     if delta != (0.0, 0.0) {
                         self.position_delta = Some(delta);
                     }
@@ -621,6 +848,17 @@ impl PointerState {
                 acc.0 += delta.0;
                 acc.1 += delta.1;
             }
+            RelativeMotion {
+                delta,
+                unaccelerated_delta,
+            } => {
+                let acc = self.relative_delta.get_or_insert((0.0, 0.0));
+                acc.0 += delta.0;
+                acc.1 += delta.1;
+                let acc = self.unaccelerated_relative_delta.get_or_insert((0.0, 0.0));
+                acc.0 += unaccelerated_delta.0;
+                acc.1 += unaccelerated_delta.1;
+            }
             Button {
                 state: ButtonState::Pressed,
                 button,
@@ -643,30 +881,30 @@ impl PointerState {
     }
 }
 
-// TODO: might want to implement bitwise op traits for ModifierFlags.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct ModifierFlags(u16);
 
 impl ModifierFlags {
-    pub const CTRL_LEFT: u16 = 1 << 0;
-    pub const CTRL_RIGHT: u16 = 1 << 1;
-    pub const SHIFT_LEFT: u16 = 1 << 2;
-    pub const SHIFT_RIGHT: u16 = 1 << 3;
-    pub const ALT_LEFT: u16 = 1 << 4;
-    pub const ALT_RIGHT: u16 = 1 << 5;
-
-    pub const CTRL: u16 = Self::CTRL_LEFT | Self::CTRL_RIGHT;
-    pub const SHIFT: u16 = Self::SHIFT_LEFT | Self::SHIFT_RIGHT;
-    pub const ALT: u16 = Self::ALT_LEFT | Self::ALT_RIGHT;
+    pub const NONE: Self = Self(0);
+    pub const CTRL_LEFT: Self = Self(1 << 0);
+    pub const CTRL_RIGHT: Self = Self(1 << 1);
+    pub const SHIFT_LEFT: Self = Self(1 << 2);
+    pub const SHIFT_RIGHT: Self = Self(1 << 3);
+    pub const ALT_LEFT: Self = Self(1 << 4);
+    pub const ALT_RIGHT: Self = Self(1 << 5);
+
+    pub const CTRL: Self = Self(Self::CTRL_LEFT.0 | Self::CTRL_RIGHT.0);
+    pub const SHIFT: Self = Self(Self::SHIFT_LEFT.0 | Self::SHIFT_RIGHT.0);
+    pub const ALT: Self = Self(Self::ALT_LEFT.0 | Self::ALT_RIGHT.0);
 
     pub fn try_from_scancode(scancode: Scancode) -> Option<Self> {
         match scancode {
-            Scancode::CtrlLeft => Some(Self(Self::CTRL_LEFT)),
-            Scancode::CtrlRight => Some(Self(Self::CTRL_RIGHT)),
-            Scancode::ShiftLeft => Some(Self(Self::SHIFT_LEFT)),
-            Scancode::ShiftRight => Some(Self(Self::SHIFT_RIGHT)),
-            Scancode::AltLeft => Some(Self(Self::ALT_LEFT)),
-            Scancode::AltRight => Some(Self(Self::ALT_RIGHT)),
+            Scancode::CtrlLeft => Some(Self::CTRL_LEFT),
+            Scancode::CtrlRight => Some(Self::CTRL_RIGHT),
+            Scancode::ShiftLeft => Some(Self::SHIFT_LEFT),
+            Scancode::ShiftRight => Some(Self::SHIFT_RIGHT),
+            Scancode::AltLeft => Some(Self::ALT_LEFT),
+            Scancode::AltRight => Some(Self::ALT_RIGHT),
             _ => None,
         }
     }
@@ -674,18 +912,20 @@ impl ModifierFlags {
     // TODO: would i ever want to look for just either left or right mod but not both?
 
     pub fn ctrl(&self) -> bool {
-        self.0 & Self::CTRL != 0
+        self.intersects(Self::CTRL)
     }
 
     pub fn shift(&self) -> bool {
-        self.0 & Self::SHIFT != 0
+        self.intersects(Self::SHIFT)
     }
 
     pub fn alt(&self) -> bool {
-        self.0 & Self::ALT != 0
+        self.intersects(Self::ALT)
     }
 }
 
+impl_flags_ops!(ModifierFlags);
+
 #[derive(Debug, Default)]
 pub struct KeyboardState {
     pub scancodes: StateTracker<Scancode>,
@@ -712,8 +952,8 @@ impl KeyboardState {
             } => {
                 self.scancodes.press(scancode, repeat);
                 self.keycodes.press(keycode, repeat);
-                if let Some(ModifierFlags(flags)) = ModifierFlags::try_from_scancode(scancode) {
-                    self.modifiers.0 |= flags;
+                if let Some(flags) = ModifierFlags::try_from_scancode(scancode) {
+                    self.modifiers |= flags;
                 }
             }
             Key {
@@ -724,10 +964,23 @@ impl KeyboardState {
             } => {
                 self.scancodes.release(scancode);
                 self.keycodes.release(keycode);
-                if let Some(ModifierFlags(flags)) = ModifierFlags::try_from_scancode(scancode) {
-                    self.modifiers.0 &= !flags;
+                if let Some(flags) = ModifierFlags::try_from_scancode(scancode) {
+                    self.modifiers &= !flags;
                 }
             }
+            Enter => {}
+            // NOTE: release events for keys that are held while focus leaves never arrive.
+            Leave => {
+                let scancodes: Vec<Scancode> = self.scancodes.iter_down().collect();
+                scancodes
+                    .into_iter()
+                    .for_each(|scancode| self.scancodes.release(scancode));
+                let keycodes: Vec<Keycode> = self.keycodes.iter_down().collect();
+                keycodes
+                    .into_iter()
+                    .for_each(|keycode| self.keycodes.release(keycode));
+                self.modifiers = ModifierFlags::NONE;
+            }
         }
     }
 }
@@ -737,12 +990,14 @@ impl KeyboardState {
 pub enum Event {
     Pointer(PointerEvent),
     Keyboard(KeyboardEvent),
+    Gamepad(GamepadEvent),
 }
 
 #[derive(Debug, Default)]
 pub struct State {
     pub pointer: PointerState,
     pub keyboard: KeyboardState,
+    pub gamepads: GamepadsState,
     /// event accumulator.
     ///
     /// NOTE: do not rely on `PointerState`/`KeyboardState` while iterating over `events` because
@@ -757,14 +1012,46 @@ impl State {
         self.pointer.reset_deltas();
         self.pointer.clear_transient_flags();
         self.keyboard.clear_transient_flags();
+        self.gamepads.clear_transient_flags();
         self.events.clear();
 
         for event in events {
             match event.clone() {
                 Event::Pointer(ev) => self.pointer.handle_event(ev),
                 Event::Keyboard(ev) => self.keyboard.handle_event(ev),
+                Event::Gamepad(ev) => self.gamepads.handle_event(ev),
             }
             self.events.push(event);
         }
     }
 }
+
+#[test]
+fn test_state_tracker() {
+    let mut tracker = StateTracker::<Scancode>::default();
+    tracker.press(Scancode::A, false);
+    tracker.press(Scancode::B, true);
+    assert!(tracker.just_pressed(Scancode::A) && tracker.down(Scancode::A));
+    assert!(!tracker.repeated(Scancode::A) && tracker.repeated(Scancode::B));
+    assert_eq!(
+        tracker.flags(Scancode::B),
+        StateFlags::JUST_PRESSED | StateFlags::DOWN | StateFlags::REPEAT
+    );
+
+    tracker.clear_transient_flags();
+    tracker.release(Scancode::A);
+    assert!(tracker.just_released(Scancode::A) && !tracker.down(Scancode::A));
+    assert!(!tracker.just_pressed(Scancode::B) && tracker.down(Scancode::B));
+    assert_eq!(tracker.iter_down().collect::<Vec<_>>(), [Scancode::B]);
+
+    // NOTE: buttons that are up are dropped.
+    tracker.clear_transient_flags();
+    assert_eq!(tracker.flags(Scancode::A), StateFlags::NONE);
+    assert_eq!(tracker.map.len(), 1);
+
+    let mut modifiers = ModifierFlags::CTRL_LEFT | ModifierFlags::SHIFT_RIGHT;
+    assert!(modifiers.ctrl() && modifiers.shift() && !modifiers.alt());
+    assert!(!modifiers.contains(ModifierFlags::CTRL));
+    modifiers &= !ModifierFlags::CTRL_LEFT;
+    assert_eq!(modifiers, ModifierFlags::SHIFT_RIGHT);
+}
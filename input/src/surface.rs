@@ -0,0 +1,300 @@
+use crate::{
+    ButtonState, Event, KeyboardEventKind, KeyboardState, PointerEvent, PointerEventKind,
+    PointerState, SurfaceId,
+};
+
+// NOTE: State is global: it mixes up pointer positions and pressed buttons of all surfaces (popups,
+// multiple windows). SurfaceInputState routes events to the surface they belong to and keeps
+// state for each one separately.
+
+#[derive(Debug, Default)]
+pub struct SurfaceState {
+    /// pointer is over the surface.
+    pub hovered: bool,
+    /// surface has keyboard focus.
+    pub focused: bool,
+    pub pointer: PointerState,
+    pub keyboard: KeyboardState,
+}
+
+impl SurfaceState {
+    /// surface holds implicit pointer grab (a button was pressed over it and is still down).
+    pub fn grabbed(&self) -> bool {
+        self.pointer.buttons.iter_down().next().is_some()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SurfaceInputState {
+    // NOTE: there's rarely more than a few surfaces; vec is fine.
+    surfaces: Vec<(SurfaceId, SurfaceState)>,
+    hovered: Option<SurfaceId>,
+    focused: Option<SurfaceId>,
+    grabbed: Option<SurfaceId>,
+    /// routed events of the current frame.
+    ///
+    /// NOTE: see [`crate::State::events`]; the same caveat applies.
+    pub events: Vec<(SurfaceId, Event)>,
+}
+
+impl SurfaceInputState {
+    pub fn surface(&self, surface_id: SurfaceId) -> Option<&SurfaceState> {
+        self.surfaces
+            .iter()
+            .find(|(id, _)| *id == surface_id)
+            .map(|(_, surface)| surface)
+    }
+
+    /// must be called when surface gets destroyed.
+    pub fn remove_surface(&mut self, surface_id: SurfaceId) {
+        self.surfaces.retain(|(id, _)| *id != surface_id);
+        for it in [&mut self.hovered, &mut self.focused, &mut self.grabbed] {
+            if *it == Some(surface_id) {
+                *it = None;
+            }
+        }
+    }
+
+    /// surface under the pointer.
+    pub fn hovered_surface(&self) -> Option<SurfaceId> {
+        self.hovered
+    }
+
+    /// surface that has keyboard focus.
+    pub fn focused_surface(&self) -> Option<SurfaceId> {
+        self.focused
+    }
+
+    /// surface that holds implicit pointer grab.
+    pub fn grabbed_surface(&self) -> Option<SurfaceId> {
+        self.grabbed
+    }
+
+    /// returns surface that event must be delivered to. gamepad events don't belong to any
+    /// surface.
+    ///
+    /// - enter, leave and move go to the surface they came from. they are what defines hover.
+    /// - button presses go to the surface under the pointer and start an implicit grab; while the
+    ///   grab holds (until all buttons are released) other button, scroll and gesture events go
+    ///   to the grabbing surface regardless of where the pointer is.
+    /// - key events go to the surface that has keyboard focus.
+    pub fn route(&self, event: &Event) -> Option<SurfaceId> {
+        match event {
+            Event::Pointer(PointerEvent { surface_id, kind }) => match kind {
+                PointerEventKind::Enter { .. }
+                | PointerEventKind::Leave
+                | PointerEventKind::Move { .. } => Some(*surface_id),
+                _ => Some(self.grabbed.or(self.hovered).unwrap_or(*surface_id)),
+            },
+            Event::Keyboard(ev) => match ev.kind {
+                KeyboardEventKind::Enter | KeyboardEventKind::Leave => Some(ev.surface_id),
+                KeyboardEventKind::Key { .. } => Some(self.focused.unwrap_or(ev.surface_id)),
+            },
+            Event::Gamepad(_) => None,
+        }
+    }
+
+    pub fn handle_events(&mut self, events: impl Iterator<Item = Event>) {
+        for (_, surface) in self.surfaces.iter_mut() {
+            surface.pointer.reset_deltas();
+            surface.pointer.clear_transient_flags();
+            surface.keyboard.clear_transient_flags();
+        }
+        self.events.clear();
+
+        for event in events {
+            let Some(surface_id) = self.route(&event) else {
+                continue;
+            };
+            self.handle_routed_event(surface_id, event.clone());
+            self.events.push((surface_id, event));
+        }
+    }
+
+    fn surface_mut(&mut self, surface_id: SurfaceId) -> &mut SurfaceState {
+        let index = match self.surfaces.iter().position(|(id, _)| *id == surface_id) {
+            Some(index) => index,
+            None => {
+                self.surfaces.push((surface_id, SurfaceState::default()));
+                self.surfaces.len() - 1
+            }
+        };
+        &mut self.surfaces[index].1
+    }
+
+    fn handle_routed_event(&mut self, surface_id: SurfaceId, event: Event) {
+        match event {
+            Event::Pointer(ev) => {
+                match ev.kind {
+                    PointerEventKind::Enter { .. } => {
+                        if let Some(prev) = self.hovered.replace(surface_id)
+                            && prev != surface_id
+                        {
+                            self.surface_mut(prev).hovered = false;
+                        }
+                        self.surface_mut(surface_id).hovered = true;
+                    }
+                    PointerEventKind::Leave => {
+                        if self.hovered == Some(surface_id) {
+                            self.hovered = None;
+                        }
+                        self.surface_mut(surface_id).hovered = false;
+                    }
+                    _ => {}
+                }
+
+                let surface = self.surface_mut(surface_id);
+                // NOTE: button press may be routed to a grabbing surface that pointer has left
+                // already; pointer state expects a position for presses.
+                if let PointerEventKind::Button {
+                    state: ButtonState::Pressed,
+                    ..
+                } = ev.kind
+                    && surface.pointer.position.is_none()
+                {
+                    return;
+                }
+                surface.pointer.handle_event(ev);
+                let grabbed = surface.grabbed();
+
+                if grabbed {
+                    self.grabbed = Some(surface_id);
+                } else if self.grabbed == Some(surface_id) {
+                    self.grabbed = None;
+                }
+            }
+            Event::Keyboard(ev) => {
+                match ev.kind {
+                    KeyboardEventKind::Enter => {
+                        if let Some(prev) = self.focused.replace(surface_id)
+                            && prev != surface_id
+                        {
+                            self.surface_mut(prev).focused = false;
+                        }
+                        self.surface_mut(surface_id).focused = true;
+                    }
+                    KeyboardEventKind::Leave => {
+                        if self.focused == Some(surface_id) {
+                            self.focused = None;
+                        }
+                        self.surface_mut(surface_id).focused = false;
+                    }
+                    KeyboardEventKind::Key { .. } => {}
+                }
+                self.surface_mut(surface_id).keyboard.handle_event(ev);
+            }
+            Event::Gamepad(_) => {}
+        }
+    }
+}
+
+#[test]
+fn test_surface_input_state() {
+    use crate::{Button, KeyState, KeyboardEvent, Keycode, LogicalPosition, Scancode};
+
+    let pointer = |surface_id, kind| {
+        Event::Pointer(PointerEvent {
+            surface_id: SurfaceId(surface_id),
+            kind,
+        })
+    };
+    let button = |surface_id, state| {
+        pointer(
+            surface_id,
+            PointerEventKind::Button {
+                state,
+                button: Button::Primary,
+            },
+        )
+    };
+    let window = SurfaceId(1);
+    let popup = SurfaceId(2);
+
+    let mut state = SurfaceInputState::default();
+    state.handle_events(
+        [
+            pointer(
+                1,
+                PointerEventKind::Enter {
+                    position: Some(LogicalPosition::new(10.0, 10.0)),
+                },
+            ),
+            button(1, ButtonState::Pressed),
+            // NOTE: drag out of window into popup.
+            pointer(1, PointerEventKind::Leave),
+            pointer(
+                2,
+                PointerEventKind::Enter {
+                    position: Some(LogicalPosition::new(1.0, 1.0)),
+                },
+            ),
+        ]
+        .into_iter(),
+    );
+    assert_eq!(state.hovered_surface(), Some(popup));
+    assert_eq!(state.grabbed_surface(), Some(window));
+    assert!(state.surface(popup).unwrap().hovered);
+    assert!(!state.surface(window).unwrap().hovered);
+    assert!(
+        state
+            .surface(window)
+            .unwrap()
+            .pointer
+            .buttons
+            .down(Button::Primary)
+    );
+
+    // NOTE: release reported on popup goes to the window that holds the grab.
+    let event = button(2, ButtonState::Released);
+    assert_eq!(state.route(&event), Some(window));
+    state.handle_events([event].into_iter());
+    assert_eq!(state.grabbed_surface(), None);
+    assert!(
+        state
+            .surface(window)
+            .unwrap()
+            .pointer
+            .buttons
+            .just_released(Button::Primary)
+    );
+    assert!(
+        !state
+            .surface(popup)
+            .unwrap()
+            .pointer
+            .buttons
+            .just_released(Button::Primary)
+    );
+
+    // NOTE: without grab, presses go to the hovered surface.
+    state.handle_events([button(2, ButtonState::Pressed)].into_iter());
+    assert_eq!(state.grabbed_surface(), Some(popup));
+
+    state.handle_events(
+        [
+            Event::Keyboard(KeyboardEvent {
+                surface_id: window,
+                kind: KeyboardEventKind::Enter,
+            }),
+            Event::Keyboard(KeyboardEvent {
+                surface_id: popup,
+                kind: KeyboardEventKind::Key {
+                    state: KeyState::Pressed,
+                    scancode: Scancode::A,
+                    keycode: Keycode::Char('a'),
+                    repeat: false,
+                },
+            }),
+        ]
+        .into_iter(),
+    );
+    assert_eq!(state.focused_surface(), Some(window));
+    assert!(
+        state
+            .surface(window)
+            .unwrap()
+            .keyboard
+            .scancodes
+            .just_pressed(Scancode::A)
+    );
+}
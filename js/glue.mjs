@@ -128,14 +128,14 @@ export class Glue {
             this.refCounts[idx] = 1;
             return idx;
         };
-        const storeJsValueIntoRsValuePtr = (jsValue, rsValuePtr) => {
-            assertEq(typeof rsValuePtr, "number");
-            const mem = getMemoryView(DataView);
+        // NOTE: scratch is used to get bits of f64 as bigint.
+        const scratch = new DataView(new ArrayBuffer(8));
+        const rsValueFromJsValue = (jsValue) => {
             switch (jsValue) {
-                case undefined: return mem.setBigUint64(rsValuePtr, UNDEFINED, true);
-                case null: return mem.setBigUint64(rsValuePtr, NULL, true);
-                case true: return mem.setBigUint64(rsValuePtr, TRUE, true);
-                case false: return mem.setBigUint64(rsValuePtr, FALSE, true);
+                case undefined: return UNDEFINED;
+                case null: return NULL;
+                case true: return TRUE;
+                case false: return FALSE;
                 case globalThis: return assert(false);
                 case this: return assert(false);
             }
@@ -144,11 +144,10 @@ export class Glue {
                 // statement above as NaN !== NaN.
                 // also note that isNan and Number.isNaN behave differently.
                 if (Number.isNaN(jsValue)) {
-                    mem.setBigUint64(rsValuePtr, NAN, true);
-                    return;
+                    return NAN;
                 }
-                mem.setFloat64(rsValuePtr, jsValue, true);
-                return;
+                scratch.setFloat64(0, jsValue, true);
+                return scratch.getBigUint64(0, true);
             }
             let ty = TY_DONT_CARE;
             switch (typeof jsValue) {
@@ -166,8 +165,12 @@ export class Glue {
                     break;
             }
             const idx = allocValue(jsValue);
-            const rsValue = rsValueFromTyIdx(ty, idx);
-            mem.setBigUint64(rsValuePtr, rsValue, true);
+            return rsValueFromTyIdx(ty, idx);
+        };
+        const storeJsValueIntoRsValuePtr = (jsValue, rsValuePtr) => {
+            assertEq(typeof rsValuePtr, "number");
+            const mem = getMemoryView(DataView);
+            mem.setBigUint64(rsValuePtr, rsValueFromJsValue(jsValue), true);
         };
         const resolveJsValueFromRsValue = (rsValue) => {
             assertEq(typeof rsValue, "bigint");
@@ -220,12 +223,21 @@ export class Glue {
                     const callByPtr = this.instance.exports.
                         __indirect_function_table.get(callByPtrIdx);
                     // TODO: is wrapper really needed? can this be avoided?
-                    const callByPtrWrapped = (...args) => {
-                        // TODO: args
+                    const callByPtrWrapped = () => {
                         callByPtr(ptr);
                     };
                     storeJsValueIntoRsValuePtr(callByPtrWrapped, outPtr);
                 },
+                closure_with_arg_new: (callByPtrIdx, ptr, outPtr) => {
+                    const callByPtr = this.instance.exports.
+                        __indirect_function_table.get(callByPtrIdx);
+                    // NOTE: rust side takes ownership of the arg (it'll decrement
+                    // ref count when done with it).
+                    const callByPtrWrapped = (arg) => {
+                        callByPtr(ptr, rsValueFromJsValue(arg));
+                    };
+                    storeJsValueIntoRsValuePtr(callByPtrWrapped, outPtr);
+                },
 
                 increment_ref_count: (ref) => {
                     const idx = rsValueIdx(ref);
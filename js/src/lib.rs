@@ -38,6 +38,13 @@ mod sys {
 
         pub fn string_new(ptr: *const u8, len: u32, out: *mut Value);
         pub fn closure_new(call_by_ptr: extern "C" fn(ptr: *mut ()), ptr: *mut (), out: *mut Value);
+        // NOTE: arg is the first argument that js passes to the closure (event for example). it
+        // is owned by the callee.
+        pub fn closure_with_arg_new(
+            call_by_ptr: extern "C" fn(ptr: *mut (), arg: Value),
+            ptr: *mut (),
+            out: *mut Value,
+        );
 
         // TODO: can ref counting be done on rust side to avoid roundtrips when cloning refs?
         pub fn increment_ref_count(r#ref: Value);
@@ -346,3 +353,28 @@ impl Closure<dyn FnMut()> {
         Self { _f: f, value }
     }
 }
+
+impl Closure<dyn FnMut(Value)> {
+    /// the closure receives the first argument that it was called with (the rest are ignored).
+    pub fn new_with_arg<F>(mut f: Box<F>) -> Self
+    where
+        F: FnMut(Value) + 'static,
+    {
+        #[inline(never)]
+        extern "C" fn call_by_ptr<F>(ptr: *mut (), arg: u64)
+        where
+            F: FnMut(Value) + 'static,
+        {
+            debug_assert!(!ptr.is_null());
+            let f: &mut F = unsafe { &mut *(ptr as *mut F) };
+            f(Value(arg));
+        }
+
+        let mut value = UNDEFINED;
+        unsafe {
+            sys::closure_with_arg_new(call_by_ptr::<F>, &raw mut *f as *mut (), &mut value.0)
+        };
+
+        Self { _f: f, value }
+    }
+}
@@ -0,0 +1,272 @@
+// stand-in compositor for tests. handles just enough of wayland requests to advertise one output,
+// wl_shm and wlr-screencopy, and to fill screencopy frames with known pixels. wire protocol itself
+// is spoken by wayland::fake_compositor.
+
+use std::collections::HashMap;
+use std::os::fd::{AsRawFd as _, OwnedFd};
+use std::ptr::null_mut;
+use std::sync::Arc;
+use std::thread;
+
+use wayland::fake_compositor::{Arg, Args, FakeCompositorHandler, FakeConnection};
+
+const WL_OUTPUT_GLOBAL_NAME: u32 = 7;
+const WL_SHM_GLOBAL_NAME: u32 = 8;
+const ZWLR_SCREENCOPY_MANAGER_V1_GLOBAL_NAME: u32 = 9;
+
+#[derive(Debug, Clone)]
+pub struct FakeOutput {
+    pub name: &'static str,
+    pub size: (u32, u32),
+    pub format: u32,
+    pub stride: u32,
+    pub y_invert: bool,
+    /// contents of the shm buffer as compositor writes it (including stride padding).
+    pub pixels: Vec<u8>,
+}
+
+impl FakeOutput {
+    /// xrgb8888 with padded stride. pixel at (x, y) is (x, y, x + y).
+    pub fn new_test_pattern(name: &'static str, size: (u32, u32), y_invert: bool) -> Self {
+        let stride = size.0 * 4 + 8;
+        let mut pixels = vec![0xaa; (stride * size.1) as usize];
+        for y in 0..size.1 {
+            // NOTE: y-inverted contents are stored bottom row first.
+            let row = if y_invert { size.1 - 1 - y } else { y };
+            for x in 0..size.0 {
+                let offset = (row * stride + x * 4) as usize;
+                let px = (x << 16) | (y << 8) | (x + y);
+                pixels[offset..offset + 4].copy_from_slice(&px.to_le_bytes());
+            }
+        }
+        Self {
+            name,
+            size,
+            format: wayland::WL_SHM_FORMAT_XRGB8888,
+            stride,
+            y_invert,
+            pixels,
+        }
+    }
+
+    pub fn expected_rgba(&self) -> Vec<u8> {
+        let mut rgba = Vec::new();
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                rgba.extend_from_slice(&[x as u8, y as u8, (x + y) as u8, 0xff]);
+            }
+        }
+        rgba
+    }
+}
+
+/// returns client's end of the connection.
+pub fn spawn(output: FakeOutput) -> (OwnedFd, thread::JoinHandle<()>) {
+    wayland::fake_compositor::spawn_fake_compositor(FakeCompositor {
+        objects: HashMap::from([(1, Object::Display)]),
+        output,
+    })
+}
+
+enum Object {
+    Display,
+    Registry,
+    Output,
+    Shm,
+    ShmPool {
+        fd: Arc<OwnedFd>,
+    },
+    Buffer {
+        fd: Arc<OwnedFd>,
+        offset: u32,
+        size: (u32, u32),
+        stride: u32,
+        format: u32,
+    },
+    ScreencopyManager,
+    ScreencopyFrame,
+}
+
+struct FakeCompositor {
+    objects: HashMap<u32, Object>,
+    output: FakeOutput,
+}
+
+impl FakeCompositor {
+    fn delete_id(&mut self, conn: &mut FakeConnection, id: u32) {
+        self.objects.remove(&id);
+        conn.delete_id(id);
+    }
+}
+
+impl FakeCompositorHandler for FakeCompositor {
+    fn handle_request(&mut self, conn: &mut FakeConnection, id: u32, opcode: u16, mut args: Args) {
+        let object = self.objects.get(&id).expect("unknown object");
+        match (object, opcode) {
+            // wl_display.sync
+            (Object::Display, 0) => conn.sync(args.uint()),
+            // wl_display.get_registry
+            (Object::Display, 1) => {
+                let registry = args.uint();
+                self.objects.insert(registry, Object::Registry);
+                for (name, interface, version) in [
+                    (WL_OUTPUT_GLOBAL_NAME, "wl_output", 4),
+                    (WL_SHM_GLOBAL_NAME, "wl_shm", 1),
+                    (
+                        ZWLR_SCREENCOPY_MANAGER_V1_GLOBAL_NAME,
+                        "zwlr_screencopy_manager_v1",
+                        3,
+                    ),
+                ] {
+                    // wl_registry.global
+                    conn.send(
+                        registry,
+                        0,
+                        &[Arg::Uint(name), Arg::Str(interface), Arg::Uint(version)],
+                    );
+                }
+            }
+            // wl_registry.bind
+            (Object::Registry, 0) => {
+                let name = args.uint();
+                let _interface = args.string();
+                let version = args.uint();
+                let new_id = args.uint();
+                match name {
+                    WL_OUTPUT_GLOBAL_NAME => {
+                        self.objects.insert(new_id, Object::Output);
+                        if version >= 4 {
+                            // wl_output.name
+                            conn.send(new_id, 4, &[Arg::Str(self.output.name)]);
+                        }
+                        // wl_output.done
+                        conn.send(new_id, 2, &[]);
+                    }
+                    WL_SHM_GLOBAL_NAME => {
+                        self.objects.insert(new_id, Object::Shm);
+                        for format in [
+                            wayland::WL_SHM_FORMAT_ARGB8888,
+                            wayland::WL_SHM_FORMAT_XRGB8888,
+                        ] {
+                            // wl_shm.format
+                            conn.send(new_id, 0, &[Arg::Uint(format)]);
+                        }
+                    }
+                    ZWLR_SCREENCOPY_MANAGER_V1_GLOBAL_NAME => {
+                        self.objects.insert(new_id, Object::ScreencopyManager);
+                    }
+                    _ => panic!("unknown global: {name}"),
+                }
+            }
+            // wl_output.release
+            (Object::Output, 0) => self.delete_id(conn, id),
+            // wl_shm.create_pool
+            (Object::Shm, 0) => {
+                let new_id = args.uint();
+                let fd = conn.take_fd();
+                let _size = args.int();
+                self.objects
+                    .insert(new_id, Object::ShmPool { fd: Arc::new(fd) });
+            }
+            // wl_shm_pool.create_buffer
+            (Object::ShmPool { fd }, 0) => {
+                let fd = Arc::clone(fd);
+                let new_id = args.uint();
+                let offset = args.int() as u32;
+                let size = (args.int() as u32, args.int() as u32);
+                let stride = args.int() as u32;
+                let format = args.uint();
+                self.objects.insert(
+                    new_id,
+                    Object::Buffer {
+                        fd,
+                        offset,
+                        size,
+                        stride,
+                        format,
+                    },
+                );
+            }
+            // wl_shm_pool.destroy
+            (Object::ShmPool { .. }, 1) => self.delete_id(conn, id),
+            // wl_buffer.destroy
+            (Object::Buffer { .. }, 0) => self.delete_id(conn, id),
+            // zwlr_screencopy_manager_v1.capture_output
+            (Object::ScreencopyManager, 0) => {
+                let frame = args.uint();
+                let _overlay_cursor = args.int();
+                let _output = args.uint();
+                self.objects.insert(frame, Object::ScreencopyFrame);
+                // zwlr_screencopy_frame_v1.buffer
+                conn.send(
+                    frame,
+                    0,
+                    &[
+                        Arg::Uint(self.output.format),
+                        Arg::Uint(self.output.size.0),
+                        Arg::Uint(self.output.size.1),
+                        Arg::Uint(self.output.stride),
+                    ],
+                );
+                // zwlr_screencopy_frame_v1.buffer_done
+                conn.send(frame, 6, &[]);
+            }
+            // zwlr_screencopy_manager_v1.destroy
+            (Object::ScreencopyManager, 2) => self.delete_id(conn, id),
+            // zwlr_screencopy_frame_v1.copy
+            (Object::ScreencopyFrame, 0) => {
+                let buffer = args.uint();
+                let Some(Object::Buffer {
+                    fd,
+                    offset,
+                    size,
+                    stride,
+                    format,
+                }) = self.objects.get(&buffer)
+                else {
+                    panic!("invalid buffer");
+                };
+                if (*size, *stride, *format)
+                    == (self.output.size, self.output.stride, self.output.format)
+                {
+                    write_to_fd(fd, *offset as usize, &self.output.pixels);
+                } else {
+                    // zwlr_screencopy_frame_v1.failed
+                    conn.send(id, 3, &[]);
+                    return;
+                }
+                let flags = if self.output.y_invert {
+                    wayland::ZWLR_SCREENCOPY_FRAME_V1_FLAGS_Y_INVERT
+                } else {
+                    0
+                };
+                // zwlr_screencopy_frame_v1.flags
+                conn.send(id, 1, &[Arg::Uint(flags)]);
+                // zwlr_screencopy_frame_v1.ready
+                conn.send(id, 2, &[Arg::Uint(0), Arg::Uint(0), Arg::Uint(0)]);
+            }
+            // zwlr_screencopy_frame_v1.destroy
+            (Object::ScreencopyFrame, 1) => self.delete_id(conn, id),
+            _ => panic!("unexpected request: object {id}, opcode {opcode}"),
+        }
+    }
+}
+
+fn write_to_fd(fd: &OwnedFd, offset: usize, data: &[u8]) {
+    let len = offset + data.len();
+    let ptr = unsafe {
+        libc::mmap(
+            null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd.as_raw_fd(),
+            0,
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    unsafe {
+        std::ptr::copy_nonoverlapping(data.as_ptr(), (ptr as *mut u8).add(offset), data.len());
+        libc::munmap(ptr, len);
+    }
+}
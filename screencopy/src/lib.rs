@@ -0,0 +1,733 @@
+//! output capture via wlr-screencopy.
+//!
+//! opens its own wayland connection (independent of the window's one); capture blocks until the
+//! compositor delivers the frame.
+//!
+//! TODO: linux-dmabuf buffers are not supported. compositors that implement frame version <= 2
+//! always offer wl_shm; newer ones (wlroots-based at least) offer it too.
+
+#[cfg(test)]
+mod fake_compositor;
+
+use std::ffi::{CStr, c_char, c_void};
+use std::mem;
+use std::os::fd::{IntoRawFd as _, OwnedFd};
+use std::ptr::{NonNull, null_mut};
+use std::slice;
+
+use anyhow::{Context as _, anyhow};
+use mars::dropguard::DropGuard;
+
+unsafe extern "C" fn noop_listener() {}
+const NOOP_LISTENER: unsafe extern "C" fn() = noop_listener;
+macro_rules! noop_listener {
+    () => {
+        unsafe {
+            #[expect(clippy::missing_transmute_annotations)]
+            mem::transmute(NOOP_LISTENER)
+        }
+    };
+}
+
+fn string_from_c_char(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+// shm buffer
+// ----
+
+struct ShmBuffer {
+    wl_buffer: *mut wayland::wl_buffer,
+    data: NonNull<u8>,
+    len: usize,
+}
+
+impl ShmBuffer {
+    fn new(
+        libwayland_client: &wayland::ClientApi,
+        wl_shm: *mut wayland::wl_shm,
+        info: ShmBufferInfo,
+    ) -> anyhow::Result<Self> {
+        assert!(!wl_shm.is_null());
+        if info.size.0 == 0 || info.size.1 == 0 || info.stride < info.size.0 * 4 {
+            return Err(anyhow!("invalid shm buffer info: {info:?}"));
+        }
+
+        let len = info.stride as usize * info.size.1 as usize;
+
+        let fd = unsafe { libc::memfd_create(c"shin-screencopy".as_ptr(), libc::MFD_CLOEXEC) };
+        if fd == -1 {
+            let errno = unsafe { *libc::__errno_location() };
+            return Err(anyhow!("could not create memfd: 0x{errno:x}"));
+        }
+        // NOTE: pool (and thus buffer) holds its own reference to the fd; it is fine to close it
+        // right after creating the pool.
+        let _fd_guard = DropGuard::new(|| {
+            unsafe { libc::close(fd) };
+        });
+
+        if unsafe { libc::ftruncate(fd, len as libc::off_t) } == -1 {
+            let errno = unsafe { *libc::__errno_location() };
+            return Err(anyhow!("could not truncate memfd: 0x{errno:x}"));
+        }
+
+        let data = unsafe {
+            libc::mmap(
+                null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        };
+        if data == libc::MAP_FAILED {
+            let errno = unsafe { *libc::__errno_location() };
+            return Err(anyhow!("could not mmap memfd: 0x{errno:x}"));
+        }
+        let data = NonNull::new(data as *mut u8).expect("mmap succeeded");
+
+        let wl_shm_pool =
+            unsafe { wayland::wl_shm_create_pool(libwayland_client, wl_shm, fd, len as i32) };
+        if wl_shm_pool.is_null() {
+            unsafe { libc::munmap(data.as_ptr() as *mut c_void, len) };
+            return Err(anyhow!("could not create shm pool"));
+        }
+        let wl_buffer = unsafe {
+            wayland::wl_shm_pool_create_buffer(
+                libwayland_client,
+                wl_shm_pool,
+                0,
+                info.size.0 as i32,
+                info.size.1 as i32,
+                info.stride as i32,
+                info.format,
+            )
+        };
+        // NOTE: buffer keeps the pool alive.
+        unsafe { wayland::wl_shm_pool_destroy(libwayland_client, wl_shm_pool) };
+        if wl_buffer.is_null() {
+            unsafe { libc::munmap(data.as_ptr() as *mut c_void, len) };
+            return Err(anyhow!("could not create shm buffer"));
+        }
+
+        Ok(Self {
+            wl_buffer,
+            data,
+            len,
+        })
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.data.as_ptr(), self.len) }
+    }
+
+    fn destroy(self, libwayland_client: &wayland::ClientApi) {
+        unsafe {
+            wayland::wl_buffer_destroy(libwayland_client, self.wl_buffer);
+            libc::munmap(self.data.as_ptr() as *mut c_void, self.len);
+        }
+    }
+}
+
+// pixel conversion
+// ----
+
+fn unpack_8888(px: u32, [r, g, b]: [u32; 3], has_alpha: bool) -> [u8; 4] {
+    let a = if has_alpha { (px >> 24) as u8 } else { 0xff };
+    [(px >> r) as u8, (px >> g) as u8, (px >> b) as u8, a]
+}
+
+fn unpack_2101010(px: u32, [r, g, b]: [u32; 3], has_alpha: bool) -> [u8; 4] {
+    // NOTE: 2 bit alpha maps onto 0, 85, 170, 255.
+    let a = if has_alpha {
+        (px >> 30) as u8 * 85
+    } else {
+        0xff
+    };
+    let c = |shift: u32| ((px >> shift & 0x3ff) >> 2) as u8;
+    [c(r), c(g), c(b), a]
+}
+
+/// converts contents of a wl_shm buffer into tightly packed rgba (top row first).
+///
+/// NOTE: alpha is passed through as is (compositors don't really put anything meaningful in
+/// there; x formats get opaque alpha).
+fn convert_to_rgba(info: ShmBufferInfo, y_invert: bool, src: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let unpack: fn(u32) -> [u8; 4] = match info.format {
+        wayland::WL_SHM_FORMAT_ARGB8888 => |px| unpack_8888(px, [16, 8, 0], true),
+        wayland::WL_SHM_FORMAT_XRGB8888 => |px| unpack_8888(px, [16, 8, 0], false),
+        wayland::WL_SHM_FORMAT_ABGR8888 => |px| unpack_8888(px, [0, 8, 16], true),
+        wayland::WL_SHM_FORMAT_XBGR8888 => |px| unpack_8888(px, [0, 8, 16], false),
+        wayland::WL_SHM_FORMAT_ARGB2101010 => |px| unpack_2101010(px, [20, 10, 0], true),
+        wayland::WL_SHM_FORMAT_XRGB2101010 => |px| unpack_2101010(px, [20, 10, 0], false),
+        wayland::WL_SHM_FORMAT_ABGR2101010 => |px| unpack_2101010(px, [0, 10, 20], true),
+        wayland::WL_SHM_FORMAT_XBGR2101010 => |px| unpack_2101010(px, [0, 10, 20], false),
+        format => return Err(anyhow!("unsupported shm format: 0x{format:x}")),
+    };
+
+    let (width, height) = (info.size.0 as usize, info.size.1 as usize);
+    let stride = info.stride as usize;
+    assert!(src.len() >= stride * height && stride >= width * 4);
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let y = if y_invert { height - 1 - y } else { y };
+        let row = &src[y * stride..y * stride + width * 4];
+        for px in row.chunks_exact(4) {
+            // NOTE: wl_shm formats are little-endian.
+            let px = u32::from_le_bytes(px.try_into().unwrap());
+            rgba.extend_from_slice(&unpack(px));
+        }
+    }
+    Ok(rgba)
+}
+
+// frame
+// ----
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ShmBufferInfo {
+    format: u32,
+    size: (u32, u32),
+    stride: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+struct FrameState {
+    shm_buffer_info: Option<ShmBufferInfo>,
+    buffer_done: bool,
+    y_invert: bool,
+    status: FrameStatus,
+}
+
+unsafe extern "C" fn handle_zwlr_screencopy_frame_v1_buffer(
+    data: *mut c_void,
+    _zwlr_screencopy_frame_v1: *mut wayland::zwlr_screencopy_frame_v1,
+    format: u32,
+    width: u32,
+    height: u32,
+    stride: u32,
+) {
+    let state = unsafe { &mut *(data as *mut FrameState) };
+    state.shm_buffer_info = Some(ShmBufferInfo {
+        format,
+        size: (width, height),
+        stride,
+    });
+}
+
+unsafe extern "C" fn handle_zwlr_screencopy_frame_v1_flags(
+    data: *mut c_void,
+    _zwlr_screencopy_frame_v1: *mut wayland::zwlr_screencopy_frame_v1,
+    flags: u32,
+) {
+    let state = unsafe { &mut *(data as *mut FrameState) };
+    state.y_invert = flags & wayland::ZWLR_SCREENCOPY_FRAME_V1_FLAGS_Y_INVERT != 0;
+}
+
+unsafe extern "C" fn handle_zwlr_screencopy_frame_v1_ready(
+    data: *mut c_void,
+    _zwlr_screencopy_frame_v1: *mut wayland::zwlr_screencopy_frame_v1,
+    _tv_sec_hi: u32,
+    _tv_sec_lo: u32,
+    _tv_nsec: u32,
+) {
+    let state = unsafe { &mut *(data as *mut FrameState) };
+    state.status = FrameStatus::Ready;
+}
+
+unsafe extern "C" fn handle_zwlr_screencopy_frame_v1_failed(
+    data: *mut c_void,
+    _zwlr_screencopy_frame_v1: *mut wayland::zwlr_screencopy_frame_v1,
+) {
+    let state = unsafe { &mut *(data as *mut FrameState) };
+    state.status = FrameStatus::Failed;
+}
+
+unsafe extern "C" fn handle_zwlr_screencopy_frame_v1_buffer_done(
+    data: *mut c_void,
+    _zwlr_screencopy_frame_v1: *mut wayland::zwlr_screencopy_frame_v1,
+) {
+    let state = unsafe { &mut *(data as *mut FrameState) };
+    state.buffer_done = true;
+}
+
+const ZWLR_SCREENCOPY_FRAME_V1_LISTENER: wayland::zwlr_screencopy_frame_v1_listener =
+    wayland::zwlr_screencopy_frame_v1_listener {
+        buffer: handle_zwlr_screencopy_frame_v1_buffer,
+        flags: handle_zwlr_screencopy_frame_v1_flags,
+        ready: handle_zwlr_screencopy_frame_v1_ready,
+        failed: handle_zwlr_screencopy_frame_v1_failed,
+        damage: noop_listener!(),
+        linux_dmabuf: noop_listener!(),
+        buffer_done: handle_zwlr_screencopy_frame_v1_buffer_done,
+    };
+
+// output
+// ----
+
+#[derive(Debug, Clone)]
+pub struct OutputInfo {
+    /// registry name of the wl_output global.
+    ///
+    /// NOTE: registry names are assigned by the compositor and are the same for all clients, thus
+    /// window's `OutputId` (which is the registry name too) can be used to capture the output that
+    /// the window is on.
+    pub registry_name: u32,
+    /// connector name (e.g. `DP-1`). requires wl_output version 4.
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+struct Output {
+    wl_output: *mut wayland::wl_output,
+    wl_output_version: u32,
+    info: OutputInfo,
+}
+
+unsafe extern "C" fn handle_wl_output_name(
+    data: *mut c_void,
+    wl_output: *mut wayland::wl_output,
+    name: *const c_char,
+) {
+    let this = unsafe { &mut *(data as *mut Screencopy) };
+    let Some(output) = this.find_output_mut(wl_output) else {
+        return;
+    };
+    output.info.name = string_from_c_char(name);
+}
+
+unsafe extern "C" fn handle_wl_output_description(
+    data: *mut c_void,
+    wl_output: *mut wayland::wl_output,
+    description: *const c_char,
+) {
+    let this = unsafe { &mut *(data as *mut Screencopy) };
+    let Some(output) = this.find_output_mut(wl_output) else {
+        return;
+    };
+    output.info.description = string_from_c_char(description);
+}
+
+const WL_OUTPUT_LISTENER: wayland::wl_output_listener = wayland::wl_output_listener {
+    geometry: noop_listener!(),
+    mode: noop_listener!(),
+    done: noop_listener!(),
+    scale: noop_listener!(),
+    name: handle_wl_output_name,
+    description: handle_wl_output_description,
+};
+
+fn destroy_output(libwayland_client: &wayland::ClientApi, output: Output) {
+    unsafe {
+        if output.wl_output_version >= 3 {
+            wayland::wl_output_release(libwayland_client, output.wl_output);
+        } else {
+            (libwayland_client.wl_proxy_destroy)(output.wl_output as *mut wayland::wl_proxy);
+        }
+    }
+}
+
+// registry
+// ----
+
+unsafe extern "C" fn handle_wl_registry_global(
+    data: *mut c_void,
+    wl_registry: *mut wayland::wl_registry,
+    name: u32,
+    interface: *const c_char,
+    version: u32,
+) {
+    unsafe {
+        let this = &mut *(data as *mut Screencopy);
+
+        let interface = CStr::from_ptr(interface)
+            .to_str()
+            .expect("invalid interface string");
+
+        match interface {
+            "wl_output" => {
+                let wl_output_version = 4.min(version);
+                let wl_output: *mut wayland::wl_output = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::wl_output_interface,
+                    wl_output_version,
+                ) as _;
+                if wl_output.is_null() {
+                    log::warn!("could not bind wl output");
+                    return;
+                }
+                (this.libwayland_client.wl_proxy_add_listener)(
+                    wl_output as *mut wayland::wl_proxy,
+                    &WL_OUTPUT_LISTENER as *const wayland::wl_output_listener as _,
+                    data,
+                );
+                this.outputs.push(Output {
+                    wl_output,
+                    wl_output_version,
+                    info: OutputInfo {
+                        registry_name: name,
+                        name: None,
+                        description: None,
+                    },
+                });
+            }
+            "wl_shm" => {
+                this.wl_shm = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::wl_shm_interface,
+                    1.min(version),
+                ) as _;
+            }
+            "zwlr_screencopy_manager_v1" => {
+                this.zwlr_screencopy_manager_v1 = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::zwlr_screencopy_manager_v1_interface,
+                    3.min(version),
+                ) as _;
+            }
+            _ => {}
+        }
+    }
+}
+
+unsafe extern "C" fn handle_wl_registry_global_remove(
+    data: *mut c_void,
+    _wl_registry: *mut wayland::wl_registry,
+    name: u32,
+) {
+    let this = unsafe { &mut *(data as *mut Screencopy) };
+    if let Some(index) = this
+        .outputs
+        .iter()
+        .position(|output| output.info.registry_name == name)
+    {
+        let output = this.outputs.remove(index);
+        destroy_output(&this.libwayland_client, output);
+    }
+}
+
+const WL_REGISTRY_LISTENER: wayland::wl_registry_listener = wayland::wl_registry_listener {
+    global: handle_wl_registry_global,
+    global_remove: handle_wl_registry_global_remove,
+};
+
+// screencopy
+// ----
+
+/// captured output contents.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    /// in physical pixels.
+    pub size: (u32, u32),
+    /// wl_shm format (`wayland::WL_SHM_FORMAT_*`) of the buffer that the compositor copied the
+    /// output into.
+    pub format: u32,
+    /// stride (in bytes) of the buffer that the compositor copied the output into.
+    pub stride: u32,
+    /// tightly packed, 8 bits per channel, top row first (y-invert is already applied).
+    pub rgba: Vec<u8>,
+}
+
+pub struct Screencopy {
+    libwayland_client: wayland::ClientApi,
+    wl_display: NonNull<wayland::wl_display>,
+    wl_registry: *mut wayland::wl_registry,
+    wl_shm: *mut wayland::wl_shm,
+    zwlr_screencopy_manager_v1: *mut wayland::zwlr_screencopy_manager_v1,
+    outputs: Vec<Output>,
+}
+
+impl Screencopy {
+    /// connects to the compositor specified by `WAYLAND_DISPLAY`.
+    pub fn connect() -> anyhow::Result<Box<Self>> {
+        let libwayland_client = wayland::ClientApi::load()?;
+        let wl_display =
+            NonNull::new(unsafe { (libwayland_client.wl_display_connect)(null_mut()) })
+                .context("could not connect to wayland display")?;
+        Self::new_boxed(libwayland_client, wl_display)
+    }
+
+    /// connects over an already established connection (fd of a socket); the fd is closed when
+    /// [`Screencopy`] is dropped.
+    pub fn connect_to_fd(fd: OwnedFd) -> anyhow::Result<Box<Self>> {
+        let libwayland_client = wayland::ClientApi::load()?;
+        let fd = fd.into_raw_fd();
+        let Some(wl_display) =
+            NonNull::new(unsafe { (libwayland_client.wl_display_connect_to_fd)(fd) })
+        else {
+            unsafe { libc::close(fd) };
+            return Err(anyhow!("could not connect to wayland display"));
+        };
+        Self::new_boxed(libwayland_client, wl_display)
+    }
+
+    fn new_boxed(
+        libwayland_client: wayland::ClientApi,
+        wl_display: NonNull<wayland::wl_display>,
+    ) -> anyhow::Result<Box<Self>> {
+        let mut this = Box::new(Self {
+            libwayland_client,
+            wl_display,
+            wl_registry: null_mut(),
+            wl_shm: null_mut(),
+            zwlr_screencopy_manager_v1: null_mut(),
+            outputs: Vec::new(),
+        });
+
+        this.wl_registry = unsafe {
+            wayland::wl_display_get_registry(&this.libwayland_client, this.wl_display.as_ptr())
+        };
+        if this.wl_registry.is_null() {
+            return Err(anyhow!("could not get registry"));
+        }
+        unsafe {
+            (this.libwayland_client.wl_proxy_add_listener)(
+                this.wl_registry as *mut wayland::wl_proxy,
+                &WL_REGISTRY_LISTENER as *const wayland::wl_registry_listener as _,
+                this.as_mut() as *mut Self as *mut c_void,
+            );
+        }
+        // NOTE: first roundtrip delivers globals, second one delivers initial output events.
+        this.roundtrip()?;
+        this.roundtrip()?;
+
+        if this.wl_shm.is_null() {
+            return Err(anyhow!("compositor does not support wl_shm"));
+        }
+        if this.zwlr_screencopy_manager_v1.is_null() {
+            return Err(anyhow!("compositor does not support wlr-screencopy"));
+        }
+
+        Ok(this)
+    }
+
+    fn roundtrip(&self) -> anyhow::Result<()> {
+        if unsafe { (self.libwayland_client.wl_display_roundtrip)(self.wl_display.as_ptr()) } == -1
+        {
+            return Err(anyhow!("could not roundtrip wayland display"));
+        }
+        Ok(())
+    }
+
+    fn dispatch(&self) -> anyhow::Result<()> {
+        if unsafe { (self.libwayland_client.wl_display_dispatch)(self.wl_display.as_ptr()) } == -1 {
+            return Err(anyhow!("could not dispatch wayland display"));
+        }
+        Ok(())
+    }
+
+    fn find_output_mut(&mut self, wl_output: *mut wayland::wl_output) -> Option<&mut Output> {
+        self.outputs
+            .iter_mut()
+            .find(|output| output.wl_output == wl_output)
+    }
+
+    /// returns all outputs that are currently known.
+    ///
+    /// NOTE: the list is not updated in the background; it is only updated when events get
+    /// dispatched (during [`Screencopy::capture_output`]).
+    pub fn outputs(&self) -> impl Iterator<Item = &OutputInfo> {
+        self.outputs.iter().map(|output| &output.info)
+    }
+
+    /// blocks until the frame is captured (normally it takes up to one output refresh).
+    pub fn capture_output(
+        &mut self,
+        registry_name: u32,
+        overlay_cursor: bool,
+    ) -> anyhow::Result<Capture> {
+        let wl_output = self
+            .outputs
+            .iter()
+            .find(|output| output.info.registry_name == registry_name)
+            .map(|output| output.wl_output)
+            .with_context(|| format!("unknown output: {registry_name}"))?;
+
+        let frame = unsafe {
+            wayland::zwlr_screencopy_manager_v1_capture_output(
+                &self.libwayland_client,
+                self.zwlr_screencopy_manager_v1,
+                overlay_cursor as i32,
+                wl_output,
+            )
+        };
+        if frame.is_null() {
+            return Err(anyhow!("could not capture output"));
+        }
+        let mut state = FrameState {
+            shm_buffer_info: None,
+            buffer_done: false,
+            y_invert: false,
+            status: FrameStatus::Pending,
+        };
+        unsafe {
+            (self.libwayland_client.wl_proxy_add_listener)(
+                frame as *mut wayland::wl_proxy,
+                &ZWLR_SCREENCOPY_FRAME_V1_LISTENER
+                    as *const wayland::zwlr_screencopy_frame_v1_listener as _,
+                &mut state as *mut FrameState as *mut c_void,
+            );
+        }
+        let _frame_guard = DropGuard::new(|| unsafe {
+            wayland::zwlr_screencopy_frame_v1_destroy(&self.libwayland_client, frame);
+        });
+        let frame_version = unsafe {
+            (self.libwayland_client.wl_proxy_get_version)(frame as *mut wayland::wl_proxy)
+        };
+
+        // NOTE: buffer_done exists since version 3. older versions send a single (shm) buffer
+        // event.
+        while state.status == FrameStatus::Pending
+            && !state.buffer_done
+            && !(frame_version < 3 && state.shm_buffer_info.is_some())
+        {
+            self.dispatch()?;
+        }
+        if state.status == FrameStatus::Failed {
+            return Err(anyhow!("compositor failed to capture output"));
+        }
+        let info = state
+            .shm_buffer_info
+            .context("compositor does not offer shm buffers")?;
+
+        let shm_buffer = ShmBuffer::new(&self.libwayland_client, self.wl_shm, info)?;
+        unsafe {
+            wayland::zwlr_screencopy_frame_v1_copy(
+                &self.libwayland_client,
+                frame,
+                shm_buffer.wl_buffer,
+            )
+        };
+        let result = loop {
+            if let Err(err) = self.dispatch() {
+                break Err(err);
+            }
+            match state.status {
+                FrameStatus::Pending => continue,
+                FrameStatus::Ready => {
+                    break convert_to_rgba(info, state.y_invert, shm_buffer.as_slice());
+                }
+                FrameStatus::Failed => break Err(anyhow!("compositor failed to copy frame")),
+            }
+        };
+        shm_buffer.destroy(&self.libwayland_client);
+
+        Ok(Capture {
+            size: info.size,
+            format: info.format,
+            stride: info.stride,
+            rgba: result?,
+        })
+    }
+}
+
+impl Drop for Screencopy {
+    fn drop(&mut self) {
+        unsafe {
+            for output in self.outputs.drain(..) {
+                destroy_output(&self.libwayland_client, output);
+            }
+            if !self.zwlr_screencopy_manager_v1.is_null() {
+                wayland::zwlr_screencopy_manager_v1_destroy(
+                    &self.libwayland_client,
+                    self.zwlr_screencopy_manager_v1,
+                );
+            }
+            if !self.wl_shm.is_null() {
+                (self.libwayland_client.wl_proxy_destroy)(self.wl_shm as *mut wayland::wl_proxy);
+            }
+            if !self.wl_registry.is_null() {
+                (self.libwayland_client.wl_proxy_destroy)(
+                    self.wl_registry as *mut wayland::wl_proxy,
+                );
+            }
+            (self.libwayland_client.wl_display_disconnect)(self.wl_display.as_ptr());
+        }
+    }
+}
+
+#[test]
+fn test_convert_to_rgba() {
+    let info = ShmBufferInfo {
+        format: wayland::WL_SHM_FORMAT_XRGB8888,
+        size: (1, 2),
+        stride: 8,
+    };
+    // NOTE: bytes are in bgrx order; the rest of each row is stride padding.
+    let src = [
+        0x03, 0x02, 0x01, 0x00, 0xaa, 0xaa, 0xaa, 0xaa, //
+        0x06, 0x05, 0x04, 0x00, 0xaa, 0xaa, 0xaa, 0xaa,
+    ];
+    assert_eq!(
+        convert_to_rgba(info, false, &src).unwrap(),
+        [1, 2, 3, 255, 4, 5, 6, 255]
+    );
+    assert_eq!(
+        convert_to_rgba(info, true, &src).unwrap(),
+        [4, 5, 6, 255, 1, 2, 3, 255]
+    );
+
+    let info = ShmBufferInfo {
+        format: wayland::WL_SHM_FORMAT_XBGR2101010,
+        size: (1, 1),
+        stride: 4,
+    };
+    let px: u32 = 0x3ff | (0x200 << 10);
+    assert_eq!(
+        convert_to_rgba(info, false, &px.to_le_bytes()).unwrap(),
+        [255, 128, 0, 255]
+    );
+}
+
+#[test]
+fn test_capture_output() {
+    // NOTE: libwayland-client is loaded at runtime; skip if it's not installed.
+    if wayland::ClientApi::load().is_err() {
+        return;
+    }
+
+    let output = fake_compositor::FakeOutput::new_test_pattern("FAKE-1", (3, 2), true);
+    let (fd, compositor) = fake_compositor::spawn(output.clone());
+    let mut screencopy = Screencopy::connect_to_fd(fd).unwrap();
+
+    let outputs: Vec<OutputInfo> = screencopy.outputs().cloned().collect();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].name.as_deref(), Some("FAKE-1"));
+    assert!(
+        screencopy
+            .capture_output(outputs[0].registry_name + 1, false)
+            .is_err()
+    );
+
+    let capture = screencopy
+        .capture_output(outputs[0].registry_name, false)
+        .unwrap();
+    assert_eq!(capture.size, (3, 2));
+    assert_eq!(capture.format, output.format);
+    assert_eq!(capture.stride, output.stride);
+    assert_eq!(capture.rgba, output.expected_rgba());
+
+    drop(screencopy);
+    compositor.join().expect("fake compositor panicked");
+}
@@ -0,0 +1,368 @@
+use std::hash::{Hash as _, Hasher as _};
+use std::mem;
+
+use mars::fxhash::FxHasher;
+
+use crate::{
+    Affine2, DrawCommand, DrawData, Gradient, GradientKind, Rect, Rgba8, SdfParams,
+    ShaderUniformValue, Vec2,
+};
+
+/// if more rects than this are damaged - they are merged into one.
+pub const MAX_DAMAGE_RECTS: usize = 16;
+
+#[derive(Debug, Clone, Copy)]
+struct CommandDigest {
+    hash: u64,
+    /// in logical pixels. none if nothing of the command can be visible (scissored out).
+    bounds: Option<Rect>,
+}
+
+/// hashes draw commands and compares them with the ones of the previous frame to figure out
+/// whether anything changed and where.
+///
+/// NOTE: commands are compared as a set, not as a sequence. a command that is drawn in a
+///   different order, but otherwise is the same, is not considered damaged.
+///
+/// NOTE: texture contents are not hashed, only handles are. if you re-upload a texture - you are
+///   responsible for redrawing places where it's used.
+#[derive(Debug, Default)]
+pub struct DamageTracker {
+    frame_hash: Option<u64>,
+    prev_frame_hash: Option<u64>,
+    // NOTE: both are sorted by hash (after update).
+    digests: Vec<CommandDigest>,
+    prev_digests: Vec<CommandDigest>,
+    damage: Vec<Rect>,
+}
+
+impl DamageTracker {
+    /// computes hash of `draw_data` and regions that changed since the previous frame.
+    pub fn update(&mut self, draw_data: &DrawData) {
+        self.digests.clear();
+        self.digests.extend(
+            draw_data
+                .commands
+                .iter()
+                .map(|command| digest_command(draw_data, command)),
+        );
+
+        // NOTE: frame hash respects order of commands.
+        let mut hasher = FxHasher::default();
+        hasher.write_usize(self.digests.len());
+        for digest in self.digests.iter() {
+            hasher.write_u64(digest.hash);
+        }
+        self.frame_hash = Some(hasher.finish());
+
+        self.digests.sort_unstable_by_key(|digest| digest.hash);
+        self.damage.clear();
+        let (mut i, mut j) = (0, 0);
+        while i < self.digests.len() || j < self.prev_digests.len() {
+            let next = self.digests.get(i);
+            let prev = self.prev_digests.get(j);
+            let damaged = match (next, prev) {
+                (Some(next), Some(prev)) if next.hash == prev.hash => {
+                    i += 1;
+                    j += 1;
+                    continue;
+                }
+                (Some(next), Some(prev)) if next.hash < prev.hash => {
+                    i += 1;
+                    next
+                }
+                (Some(next), None) => {
+                    i += 1;
+                    next
+                }
+                (_, Some(prev)) => {
+                    j += 1;
+                    prev
+                }
+                (None, None) => unreachable!(),
+            };
+            if let Some(bounds) = damaged.bounds {
+                self.damage.push(bounds);
+            }
+        }
+
+        if self.damage.len() > MAX_DAMAGE_RECTS {
+            let union = self
+                .damage
+                .iter()
+                .copied()
+                .reduce(Rect::union)
+                .expect("damage is not empty");
+            self.damage.clear();
+            self.damage.push(union);
+        }
+    }
+
+    /// makes the current frame the previous one.
+    pub fn finish_frame(&mut self) {
+        self.prev_frame_hash = self.frame_hash.take();
+        mem::swap(&mut self.digests, &mut self.prev_digests);
+        self.digests.clear();
+        self.damage.clear();
+    }
+
+    /// stable hash of everything that was drawn in the current frame.
+    pub fn frame_hash(&self) -> u64 {
+        self.frame_hash.unwrap_or_default()
+    }
+
+    /// whether the current frame differs from the previous one. first frame is always changed.
+    pub fn changed(&self) -> bool {
+        self.prev_frame_hash.is_none() || self.frame_hash != self.prev_frame_hash
+    }
+
+    /// regions (in logical pixels) where the current frame differs from the previous one.
+    ///
+    /// NOTE: on the first frame this covers only what was drawn; you most likely want to damage
+    ///   the whole surface then (and when the surface is resized).
+    pub fn damage(&self) -> &[Rect] {
+        &self.damage
+    }
+}
+
+fn digest_command(draw_data: &DrawData, command: &DrawCommand) -> CommandDigest {
+    let mut hasher = FxHasher::default();
+
+    // NOTE: vertices are hashed in order they are referenced in; this makes the hash independent
+    //   from where the command is located in vertex and index buffers.
+    let mut bounds = Rect::new(Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY));
+    for &index in draw_data.indices
+        [command.index_range.start as usize..command.index_range.end as usize]
+        .iter()
+    {
+        let vertex = &draw_data.vertices[index as usize];
+        write_vec2(&mut hasher, vertex.pos);
+        write_color(&mut hasher, vertex.color);
+        write_vec2(&mut hasher, vertex.tex_coord);
+        bounds = Rect::new(bounds.min.min(vertex.pos), bounds.max.max(vertex.pos));
+    }
+    let mut bounds = bounds.is_normalized().then_some(bounds);
+
+    hasher.write_u8(command.scissor.is_some() as u8);
+    if let Some(scissor) = command.scissor {
+        write_f32s(
+            &mut hasher,
+            &[scissor.min.x, scissor.min.y, scissor.max.x, scissor.max.y],
+        );
+        bounds = bounds
+            .map(|bounds| bounds.clamp(scissor))
+            .filter(Rect::is_normalized);
+    }
+
+    // NOTE: clip masks are identified by their geometry, indices of masks are not stable.
+    let mut clip_mask = command.clip_mask;
+    while let Some(index) = clip_mask {
+        let clip_mask_ref = &draw_data.clip_masks[index as usize];
+        hasher.write_u8(1);
+        for &index in draw_data.indices
+            [clip_mask_ref.index_range.start as usize..clip_mask_ref.index_range.end as usize]
+            .iter()
+        {
+            write_vec2(&mut hasher, draw_data.vertices[index as usize].pos);
+        }
+        clip_mask = clip_mask_ref.parent;
+    }
+    hasher.write_u8(0);
+
+    command.texture.hash(&mut hasher);
+
+    hasher.write_u8(command.sdf_params.is_some() as u8);
+    if let Some(ref sdf_params) = command.sdf_params {
+        write_sdf_params(&mut hasher, sdf_params);
+    }
+
+    hasher.write_u8(command.gradient.is_some() as u8);
+    if let Some(ref gradient) = command.gradient {
+        write_gradient(&mut hasher, gradient);
+    }
+
+    command.shader.hash(&mut hasher);
+    for (name, value) in command.shader_uniforms.0.iter() {
+        hasher.write(name.as_bytes());
+        write_shader_uniform_value(&mut hasher, value);
+    }
+
+    write_affine2(&mut hasher, &command.transform);
+
+    CommandDigest {
+        hash: hasher.finish(),
+        bounds,
+    }
+}
+
+fn write_f32s(hasher: &mut FxHasher, values: &[f32]) {
+    for value in values.iter() {
+        hasher.write_u32(value.to_bits());
+    }
+}
+
+fn write_vec2(hasher: &mut FxHasher, value: Vec2) {
+    write_f32s(hasher, &[value.x, value.y]);
+}
+
+fn write_color(hasher: &mut FxHasher, value: Rgba8) {
+    hasher.write_u32(u32::from_le_bytes([value.r, value.g, value.b, value.a]));
+}
+
+fn write_affine2(hasher: &mut FxHasher, value: &Affine2) {
+    write_vec2(hasher, value.x_axis);
+    write_vec2(hasher, value.y_axis);
+    write_vec2(hasher, value.translation);
+}
+
+fn write_sdf_params(hasher: &mut FxHasher, sdf_params: &SdfParams) {
+    match sdf_params {
+        SdfParams::Rect(params) => {
+            hasher.write_u8(0);
+            write_f32s(hasher, &params.center);
+            write_f32s(hasher, &params.size);
+            write_f32s(hasher, &params.corner_radii);
+            write_f32s(hasher, &params.stroke_widths);
+            write_f32s(hasher, params.stroke_colors.as_flattened());
+            hasher.write_i32(params.stroke_alignment);
+        }
+        SdfParams::Ellipse(params) => {
+            hasher.write_u8(1);
+            write_f32s(hasher, &params.center);
+            write_f32s(hasher, &params.radii);
+            write_f32s(hasher, &[params.stroke_width]);
+            write_f32s(hasher, &params.stroke_color);
+            hasher.write_i32(params.stroke_alignment);
+        }
+        SdfParams::Arc(params) => {
+            hasher.write_u8(2);
+            write_f32s(hasher, &params.center);
+            write_f32s(hasher, &[params.radius]);
+            write_f32s(hasher, &params.angles);
+            write_f32s(hasher, &[params.thickness]);
+            hasher.write_i32(params.cap);
+            write_f32s(hasher, &[params.stroke_width]);
+            write_f32s(hasher, &params.stroke_color);
+            hasher.write_i32(params.stroke_alignment);
+        }
+        SdfParams::Shadow(params) => {
+            hasher.write_u8(3);
+            write_f32s(hasher, &params.center);
+            write_f32s(hasher, &params.size);
+            write_f32s(hasher, &params.corner_radii);
+            write_f32s(hasher, &[params.sigma]);
+        }
+    }
+}
+
+fn write_gradient(hasher: &mut FxHasher, gradient: &Gradient) {
+    match gradient.kind {
+        GradientKind::Linear { start, end } => {
+            hasher.write_u8(0);
+            write_vec2(hasher, start);
+            write_vec2(hasher, end);
+        }
+        GradientKind::Radial { center, radius } => {
+            hasher.write_u8(1);
+            write_vec2(hasher, center);
+            write_f32s(hasher, &[radius]);
+        }
+        GradientKind::Conic {
+            center,
+            start_angle,
+        } => {
+            hasher.write_u8(2);
+            write_vec2(hasher, center);
+            write_f32s(hasher, &[start_angle]);
+        }
+    }
+    hasher.write_i32(gradient.spread as i32);
+    hasher.write_i32(gradient.interpolation as i32);
+    hasher.write_usize(gradient.stops().len());
+    for stop in gradient.stops().iter() {
+        write_f32s(hasher, &[stop.offset]);
+        write_color(hasher, stop.color);
+    }
+}
+
+fn write_shader_uniform_value(hasher: &mut FxHasher, value: &ShaderUniformValue) {
+    match value {
+        ShaderUniformValue::Int(value) => {
+            hasher.write_u8(0);
+            hasher.write_i32(*value);
+        }
+        ShaderUniformValue::Float(value) => {
+            hasher.write_u8(1);
+            write_f32s(hasher, &[*value]);
+        }
+        ShaderUniformValue::Vec2(value) => {
+            hasher.write_u8(2);
+            write_f32s(hasher, value);
+        }
+        ShaderUniformValue::Vec4(value) => {
+            hasher.write_u8(3);
+            write_f32s(hasher, value);
+        }
+        ShaderUniformValue::Mat4(value) => {
+            hasher.write_u8(4);
+            write_f32s(hasher, value.as_flattened());
+        }
+        ShaderUniformValue::Texture2D(handle) => {
+            hasher.write_u8(5);
+            handle.hash(hasher);
+        }
+    }
+}
+
+#[test]
+fn test_damage_tracker() {
+    use crate::{CornerRadii, DrawBuffer, Fill, RectShape};
+
+    let plain_rect = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+    let rounded_rect = Rect::new(Vec2::new(20.0, 0.0), Vec2::new(30.0, 10.0));
+    let draw = |draw_buffer: &mut DrawBuffer, offset: Vec2| {
+        draw_buffer.push_rect(RectShape::new(plain_rect).with_fill(Some(Fill::Color(Rgba8::RED))));
+        draw_buffer.push_rect(
+            RectShape::new(rounded_rect.translate(offset))
+                .with_fill(Some(Fill::Color(Rgba8::RED)))
+                .with_corner_radius(Some(CornerRadii::all(2.0))),
+        );
+        draw_buffer.flush();
+    };
+
+    let mut draw_buffer = DrawBuffer::default();
+    draw(&mut draw_buffer, Vec2::splat(0.0));
+    assert!(draw_buffer.changed());
+    assert_eq!(draw_buffer.damage().len(), 2);
+    let frame_hash = draw_buffer.frame_hash();
+    draw_buffer.clear();
+
+    draw(&mut draw_buffer, Vec2::splat(0.0));
+    assert!(!draw_buffer.changed());
+    assert!(draw_buffer.damage().is_empty());
+    assert_eq!(draw_buffer.frame_hash(), frame_hash);
+    draw_buffer.clear();
+
+    // NOTE: only the moved rect is damaged; where it was and where it is now.
+    let offset = Vec2::new(0.0, 40.0);
+    draw(&mut draw_buffer, offset);
+    assert!(draw_buffer.changed());
+    assert_ne!(draw_buffer.frame_hash(), frame_hash);
+    let damage = draw_buffer.damage();
+    assert_eq!(damage.len(), 2);
+    let damage_union = damage[0].union(damage[1]);
+    assert!(damage_union.contains_rect(&rounded_rect));
+    assert!(damage_union.contains_rect(&rounded_rect.translate(offset)));
+    assert!(!damage.iter().any(|rect| rect.intersects(&plain_rect)));
+    draw_buffer.clear();
+
+    // NOTE: scissored out commands don't produce damage.
+    draw_buffer.push_clip_rect(Rect::new(Vec2::new(100.0, 100.0), Vec2::new(200.0, 200.0)));
+    draw(&mut draw_buffer, Vec2::splat(0.0));
+    draw_buffer.pop_clip();
+    draw_buffer.flush();
+    assert!(draw_buffer.changed());
+    // NOTE: both rects of the previous frame are gone.
+    assert_eq!(draw_buffer.damage().len(), 2);
+    assert!(!draw_buffer.damage().iter().any(|rect| rect.min.x >= 100.0));
+}
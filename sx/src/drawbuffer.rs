@@ -1,6 +1,13 @@
 use std::ops::Range;
 
-use crate::{Rect, Rgba8, TextureHandle, Vec2};
+use crate::{
+    Affine2, DamageTracker, FillOptions, FillRule, FlattenedPath, FlattenedSubPath, LineCap,
+    LineJoin, Path, Rect, Rgba8, ShaderHandle, ShaderUniforms, StrokeOptions, TessellationOutput,
+    Tessellator, TextureHandle, Vec2,
+};
+
+// NOTE: max distance between curves and line segments that approximate them, in logical pixels.
+const PATH_TOLERANCE: f32 = 0.1;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TextureFill {
@@ -29,10 +36,131 @@ impl TextureFill {
     }
 }
 
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[repr(i32)]
+pub enum GradientSpread {
+    /// colors of the first/last stops extend to infinity.
+    #[default]
+    Pad = 0,
+    Repeat = 1,
+    Reflect = 2,
+}
+
+/// color space in which colors between stops are interpolated.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[repr(i32)]
+pub enum GradientInterpolation {
+    // NOTE: srgb is the default because that is what css and most of the design tools do.
+    #[default]
+    Srgb = 0,
+    Linear = 1,
+}
+
+/// geometry of the gradient in logical pixels (same space as shapes).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientKind {
+    Linear {
+        start: Vec2,
+        end: Vec2,
+    },
+    Radial {
+        center: Vec2,
+        radius: f32,
+    },
+    /// `start_angle` is in radians, 0 points right (+x); goes clockwise.
+    Conic {
+        center: Vec2,
+        start_angle: f32,
+    },
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: Rgba8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub spread: GradientSpread,
+    pub interpolation: GradientInterpolation,
+    stops: [GradientStop; MAX_GRADIENT_STOPS],
+    stop_count: usize,
+}
+
+impl Gradient {
+    pub fn new(kind: GradientKind) -> Self {
+        Self {
+            kind,
+            spread: GradientSpread::default(),
+            interpolation: GradientInterpolation::default(),
+            stops: [GradientStop::default(); MAX_GRADIENT_STOPS],
+            stop_count: 0,
+        }
+    }
+
+    pub fn linear(start: Vec2, end: Vec2) -> Self {
+        Self::new(GradientKind::Linear { start, end })
+    }
+
+    pub fn radial(center: Vec2, radius: f32) -> Self {
+        Self::new(GradientKind::Radial { center, radius })
+    }
+
+    pub fn conic(center: Vec2, start_angle: f32) -> Self {
+        Self::new(GradientKind::Conic {
+            center,
+            start_angle,
+        })
+    }
+
+    /// offset is within 0..=1 range along the gradient.
+    ///
+    /// NOTE: stops must be added in order; same as in css, offset that is smaller than offset of
+    /// the previous stop is bumped up to it.
+    pub fn with_stop(mut self, offset: f32, color: Rgba8) -> Self {
+        assert!(
+            self.stop_count < MAX_GRADIENT_STOPS,
+            "gradient can not have more than {MAX_GRADIENT_STOPS} stops"
+        );
+        let offset = match self.stops().last() {
+            Some(prev) => offset.max(prev.offset),
+            None => offset,
+        };
+        self.stops[self.stop_count] = GradientStop { offset, color };
+        self.stop_count += 1;
+        self
+    }
+
+    pub fn with_spread(mut self, spread: GradientSpread) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn with_interpolation(mut self, interpolation: GradientInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops[..self.stop_count]
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Fill {
     Color(Rgba8),
     Texture(TextureFill),
+    Gradient(Gradient),
+    /// shader is created by [`crate::ShaderService`]. `uniforms` must match what the shader
+    /// declares; uniforms of the renderer itself are set by the renderer.
+    Shader {
+        shader: ShaderHandle,
+        uniforms: ShaderUniforms,
+    },
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
@@ -46,6 +174,17 @@ pub enum StrokeAlignment {
     Center = 0,
 }
 
+impl StrokeAlignment {
+    /// part of the stroke width that lies outside of the shape.
+    pub fn outer_fraction(self) -> f32 {
+        match self {
+            Self::Inside => 0.0,
+            Self::Outside => 1.0,
+            Self::Center => 0.5,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Stroke {
     pub width: f32,
@@ -69,16 +208,131 @@ impl Stroke {
     }
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StrokeSide {
+    pub width: f32,
+    pub color: Rgba8,
+}
+
+/// stroke that may differ per side. sides that have zero width are not stroked.
+///
+/// NOTE: alignment is shared by all sides.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RectStroke {
+    pub top: StrokeSide,
+    pub right: StrokeSide,
+    pub bottom: StrokeSide,
+    pub left: StrokeSide,
+    pub alignment: StrokeAlignment,
+}
+
+impl From<Stroke> for RectStroke {
+    fn from(stroke: Stroke) -> Self {
+        let side = StrokeSide {
+            width: stroke.width,
+            color: stroke.color,
+        };
+        Self {
+            top: side,
+            right: side,
+            bottom: side,
+            left: side,
+            alignment: stroke.alignment,
+        }
+    }
+}
+
+impl RectStroke {
+    pub fn with_top(mut self, width: f32, color: Rgba8) -> Self {
+        self.top = StrokeSide { width, color };
+        self
+    }
+
+    pub fn with_right(mut self, width: f32, color: Rgba8) -> Self {
+        self.right = StrokeSide { width, color };
+        self
+    }
+
+    pub fn with_bottom(mut self, width: f32, color: Rgba8) -> Self {
+        self.bottom = StrokeSide { width, color };
+        self
+    }
+
+    pub fn with_left(mut self, width: f32, color: Rgba8) -> Self {
+        self.left = StrokeSide { width, color };
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: StrokeAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// top, right, bottom, left.
+    pub fn sides(&self) -> [StrokeSide; 4] {
+        [self.top, self.right, self.bottom, self.left]
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl From<f32> for CornerRadii {
+    fn from(radius: f32) -> Self {
+        Self::all(radius)
+    }
+}
+
+impl CornerRadii {
+    pub const fn all(radius: f32) -> Self {
+        Self {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+
+    pub const fn top(radius: f32) -> Self {
+        Self {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: 0.0,
+            bottom_left: 0.0,
+        }
+    }
+
+    pub const fn bottom(radius: f32) -> Self {
+        Self {
+            top_left: 0.0,
+            top_right: 0.0,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+
+    /// top left, top right, bottom right, bottom left (clockwise, same as css border-radius).
+    pub const fn to_array(self) -> [f32; 4] {
+        [
+            self.top_left,
+            self.top_right,
+            self.bottom_right,
+            self.bottom_left,
+        ]
+    }
+}
+
 #[derive(Debug)]
 pub struct RectShape {
     pub rect: Rect,
     pub fill: Option<Fill>,
-    // TODO: i want to be able to specify rect stroke per-side.
-    //   top and left, or maybe only bottom, etc.
-    //   would that still be called "stroke"?
-    pub stroke: Option<Stroke>,
-    // TODO: rect shape must support 4 distinct corner radii.
-    pub corner_radius: Option<f32>,
+    pub stroke: Option<RectStroke>,
+    pub corner_radius: Option<CornerRadii>,
 }
 
 impl RectShape {
@@ -96,17 +350,280 @@ impl RectShape {
         self
     }
 
+    /// uniform stroke. see [`RectShape::with_rect_stroke`] for per-side one.
     pub fn with_stroke(mut self, stroke: Option<Stroke>) -> Self {
+        self.stroke = stroke.map(RectStroke::from);
+        self
+    }
+
+    pub fn with_rect_stroke(mut self, stroke: Option<RectStroke>) -> Self {
         self.stroke = stroke;
         self
     }
 
-    pub fn with_corner_radius(mut self, corner_radius: Option<f32>) -> Self {
+    pub fn with_corner_radius(mut self, corner_radius: Option<CornerRadii>) -> Self {
         self.corner_radius = corner_radius;
         self
     }
 }
 
+#[derive(Debug)]
+pub struct EllipseShape {
+    pub center: Vec2,
+    pub radii: Vec2,
+    pub fill: Option<Fill>,
+    pub stroke: Option<Stroke>,
+}
+
+impl EllipseShape {
+    pub fn new(center: Vec2, radii: Vec2) -> Self {
+        Self {
+            center,
+            radii,
+            fill: None,
+            stroke: None,
+        }
+    }
+
+    pub fn with_fill(mut self, fill: Option<Fill>) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: Option<Stroke>) -> Self {
+        self.stroke = stroke;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct CircleShape {
+    pub center: Vec2,
+    pub radius: f32,
+    pub fill: Option<Fill>,
+    pub stroke: Option<Stroke>,
+}
+
+impl From<CircleShape> for EllipseShape {
+    fn from(circle_shape: CircleShape) -> Self {
+        Self {
+            center: circle_shape.center,
+            radii: Vec2::splat(circle_shape.radius),
+            fill: circle_shape.fill,
+            stroke: circle_shape.stroke,
+        }
+    }
+}
+
+impl CircleShape {
+    pub fn new(center: Vec2, radius: f32) -> Self {
+        Self {
+            center,
+            radius,
+            fill: None,
+            stroke: None,
+        }
+    }
+
+    pub fn with_fill(mut self, fill: Option<Fill>) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: Option<Stroke>) -> Self {
+        self.stroke = stroke;
+        self
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[repr(i32)]
+pub enum ArcCap {
+    #[default]
+    Butt = 0,
+    Round = 1,
+}
+
+/// a band of `thickness` centered on a circle of `radius`, going clockwise from `start_angle` to
+/// `end_angle`.
+///
+/// angles are in radians. 0 points right (+x); because y points down positive angles go
+/// clockwise.
+#[derive(Debug)]
+pub struct ArcShape {
+    pub center: Vec2,
+    pub radius: f32,
+    pub start_angle: f32,
+    pub end_angle: f32,
+    pub thickness: f32,
+    pub cap: ArcCap,
+    pub fill: Option<Fill>,
+    pub stroke: Option<Stroke>,
+}
+
+impl ArcShape {
+    pub fn new(
+        center: Vec2,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        thickness: f32,
+    ) -> Self {
+        Self {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            thickness,
+            cap: ArcCap::default(),
+            fill: None,
+            stroke: None,
+        }
+    }
+
+    pub fn with_cap(mut self, cap: ArcCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_fill(mut self, fill: Option<Fill>) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: Option<Stroke>) -> Self {
+        self.stroke = stroke;
+        self
+    }
+}
+
+/// blurred shadow of a (rounded) rect, same as css box-shadow.
+#[derive(Debug)]
+pub struct ShadowShape {
+    /// rect that casts the shadow.
+    pub rect: Rect,
+    pub corner_radius: Option<CornerRadii>,
+    pub offset: Vec2,
+    /// same as in css; standard deviation of the gaussian is half of it.
+    pub blur_radius: f32,
+    /// grows (or shrinks, if negative) the shadow before blurring.
+    pub spread: f32,
+    pub color: Rgba8,
+}
+
+impl ShadowShape {
+    pub fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            corner_radius: None,
+            offset: Vec2::ZERO,
+            blur_radius: 0.0,
+            spread: 0.0,
+            color: Rgba8::BLACK,
+        }
+    }
+
+    pub fn with_corner_radius(mut self, corner_radius: Option<CornerRadii>) -> Self {
+        self.corner_radius = corner_radius;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_blur_radius(mut self, blur_radius: f32) -> Self {
+        self.blur_radius = blur_radius;
+        self
+    }
+
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn with_color(mut self, color: Rgba8) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct PathShape<'a> {
+    pub path: &'a Path,
+    pub fill: Option<Fill>,
+    pub fill_rule: FillRule,
+    pub stroke: Option<Stroke>,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f32,
+    /// alternating lengths of dashes and gaps. empty means solid line.
+    pub dashes: &'a [f32],
+    pub dash_offset: f32,
+    /// width of the anti-aliasing fringe; 0 disables anti-aliasing.
+    pub feather: f32,
+}
+
+impl<'a> PathShape<'a> {
+    pub fn new(path: &'a Path) -> Self {
+        let stroke_options = StrokeOptions::default();
+        Self {
+            path,
+            fill: None,
+            fill_rule: FillRule::default(),
+            stroke: None,
+            join: stroke_options.join,
+            cap: stroke_options.cap,
+            miter_limit: stroke_options.miter_limit,
+            dashes: &[],
+            dash_offset: 0.0,
+            feather: stroke_options.feather,
+        }
+    }
+
+    pub fn with_fill(mut self, fill: Option<Fill>) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: Option<Stroke>) -> Self {
+        self.stroke = stroke;
+        self
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    pub fn with_dashes(mut self, dashes: &'a [f32], dash_offset: f32) -> Self {
+        self.dashes = dashes;
+        self.dash_offset = dash_offset;
+        self
+    }
+
+    pub fn with_feather(mut self, feather: f32) -> Self {
+        self.feather = feather;
+        self
+    }
+}
+
 #[derive(Debug)]
 pub struct LineShape {
     pub points: [Vec2; 2],
@@ -128,19 +645,52 @@ impl LineShape {
 pub struct RectSdfParams {
     pub center: [f32; 2],
     pub size: [f32; 2],
-    pub corner_radius: f32,
+    pub corner_radii: [f32; 4], // top left, top right, bottom right, bottom left
+    pub stroke_widths: [f32; 4], // top, right, bottom, left
+    pub stroke_colors: [[f32; 4]; 4], // top, right, bottom, left
+    pub stroke_alignment: i32,  // -1 inside, 0 center, 1 outside
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EllipseSdfParams {
+    pub center: [f32; 2],
+    pub radii: [f32; 2],
     pub stroke_width: f32,
     pub stroke_color: [f32; 4],
     pub stroke_alignment: i32, // -1 inside, 0 center, 1 outside
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcSdfParams {
+    pub center: [f32; 2],
+    pub radius: f32,
+    pub angles: [f32; 2], // start, end
+    pub thickness: f32,
+    pub cap: i32, // 0 butt, 1 round
+    pub stroke_width: f32,
+    pub stroke_color: [f32; 4],
+    pub stroke_alignment: i32, // -1 inside, 0 center, 1 outside
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowSdfParams {
+    pub center: [f32; 2],
+    pub size: [f32; 2],
+    pub corner_radii: [f32; 4],
+    /// standard deviation of the gaussian.
+    pub sigma: f32,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SdfParams {
     Rect(RectSdfParams),
+    Ellipse(EllipseSdfParams),
+    Arc(ArcSdfParams),
+    Shadow(ShadowSdfParams),
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Vertex {
     /// screen pixel coordinates.
     /// 0, 0 is the top left corner of the screen.
@@ -152,12 +702,33 @@ pub struct Vertex {
     pub tex_coord: Vec2,
 }
 
-#[derive(Debug)]
+/// triangles (within [`DrawData::indices`]) that form a stencil clip mask.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipMask {
+    pub index_range: Range<u32>,
+    /// mask this one is nested in. fragments must be covered by every mask of the chain.
+    pub parent: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
 pub struct DrawCommand {
     pub index_range: Range<u32>,
+    /// in logical pixels.
     pub scissor: Option<Rect>,
+    /// index into [`DrawData::clip_masks`].
+    pub clip_mask: Option<u32>,
     pub texture: Option<TextureHandle>,
     pub sdf_params: Option<SdfParams>,
+    /// NOTE: gradient color is multiplied with vertex color.
+    pub gradient: Option<Gradient>,
+    /// NOTE: shader's color is multiplied with vertex color.
+    pub shader: Option<ShaderHandle>,
+    /// values for uniforms of `shader`; empty if there's no shader.
+    pub shader_uniforms: ShaderUniforms,
+    /// maps local space of sdf params and gradient to logical pixels.
+    ///
+    /// NOTE: vertices are already transformed.
+    pub transform: Affine2,
 }
 
 #[derive(Debug, Default)]
@@ -165,10 +736,16 @@ pub struct DrawData {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
     pub commands: Vec<DrawCommand>,
+    pub clip_masks: Vec<ClipMask>,
     pending_indices: u32,
     current_scissor_rect: Option<Rect>,
+    current_clip_mask: Option<u32>,
     current_texture: Option<TextureHandle>,
     current_sdf_params: Option<SdfParams>,
+    current_gradient: Option<Gradient>,
+    current_shader: Option<ShaderHandle>,
+    current_shader_uniforms: ShaderUniforms,
+    current_transform: Affine2,
 }
 
 impl DrawData {
@@ -176,10 +753,45 @@ impl DrawData {
         self.vertices.clear();
         self.indices.clear();
         self.commands.clear();
+        self.clip_masks.clear();
         assert_eq!(self.pending_indices, 0);
         self.current_scissor_rect = None;
+        self.current_clip_mask = None;
         self.current_texture = None;
         self.current_sdf_params = None;
+        self.current_gradient = None;
+        self.current_shader = None;
+        self.current_shader_uniforms = ShaderUniforms::default();
+        self.current_transform = Affine2::IDENTITY;
+    }
+
+    /// appends everything drawn in `other` after what's drawn in `self`.
+    fn append(&mut self, other: &DrawData) {
+        assert_eq!(self.pending_indices, 0);
+        assert_eq!(other.pending_indices, 0);
+
+        let vertex_base = self.vertices.len() as u32;
+        let index_base = self.indices.len() as u32;
+        let clip_mask_base = self.clip_masks.len() as u32;
+        let shift_range = |range: &Range<u32>| range.start + index_base..range.end + index_base;
+
+        self.vertices.extend_from_slice(&other.vertices);
+        self.indices
+            .extend(other.indices.iter().map(|index| index + vertex_base));
+        self.clip_masks
+            .extend(other.clip_masks.iter().map(|clip_mask| ClipMask {
+                index_range: shift_range(&clip_mask.index_range),
+                parent: clip_mask.parent.map(|parent| parent + clip_mask_base),
+            }));
+        self.commands.extend(other.commands.iter().map(|command| {
+            DrawCommand {
+                index_range: shift_range(&command.index_range),
+                clip_mask: command
+                    .clip_mask
+                    .map(|clip_mask| clip_mask + clip_mask_base),
+                ..command.clone()
+            }
+        }));
     }
 
     fn flush(&mut self) {
@@ -192,24 +804,48 @@ impl DrawData {
         self.commands.push(DrawCommand {
             index_range: start_index..end_index,
             scissor: self.current_scissor_rect,
+            clip_mask: self.current_clip_mask,
             texture: self.current_texture,
             sdf_params: self.current_sdf_params.clone(),
+            gradient: self.current_gradient.clone(),
+            shader: self.current_shader,
+            shader_uniforms: self.current_shader_uniforms.clone(),
+            transform: self.current_transform,
         });
 
         self.pending_indices = 0;
     }
 
     fn set_scissor_rect(&mut self, rect: Option<Rect>) {
-        let prev = self.current_scissor_rect;
-        let next = match (prev, rect) {
-            (Some(prev), Some(next)) => Some(next.clamp(prev)),
-            (_, next) => next,
-        };
-        if prev == next {
+        if self.current_scissor_rect == rect {
+            return;
+        }
+        self.flush();
+        self.current_scissor_rect = rect;
+    }
+
+    fn set_clip_mask(&mut self, clip_mask: Option<u32>) {
+        if self.current_clip_mask == clip_mask {
             return;
         }
         self.flush();
-        self.current_scissor_rect = next;
+        self.current_clip_mask = clip_mask;
+    }
+
+    /// returns index at which triangles of the mask start.
+    fn begin_clip_mask(&mut self) -> u32 {
+        self.flush();
+        self.indices.len() as u32
+    }
+
+    fn end_clip_mask(&mut self, start_index: u32) {
+        // NOTE: mask triangles are not part of any draw command.
+        self.pending_indices = 0;
+        self.clip_masks.push(ClipMask {
+            index_range: start_index..self.indices.len() as u32,
+            parent: self.current_clip_mask,
+        });
+        self.current_clip_mask = Some(self.clip_masks.len() as u32 - 1);
     }
 
     fn set_texture(&mut self, texture: Option<TextureHandle>) {
@@ -228,9 +864,38 @@ impl DrawData {
         self.current_sdf_params = sdf_params;
     }
 
+    fn set_gradient(&mut self, gradient: Option<Gradient>) {
+        if self.current_gradient == gradient {
+            return;
+        }
+        self.flush();
+        self.current_gradient = gradient;
+    }
+
+    fn set_shader(&mut self, shader: Option<(ShaderHandle, ShaderUniforms)>) {
+        let (shader, uniforms) = match shader {
+            Some((shader, uniforms)) => (Some(shader), uniforms),
+            None => (None, ShaderUniforms::default()),
+        };
+        if self.current_shader == shader && self.current_shader_uniforms == uniforms {
+            return;
+        }
+        self.flush();
+        self.current_shader = shader;
+        self.current_shader_uniforms = uniforms;
+    }
+
+    fn set_transform(&mut self, transform: Affine2) {
+        if self.current_transform == transform {
+            return;
+        }
+        self.flush();
+        self.current_transform = transform;
+    }
+
     fn push_vertex(&mut self, pos: Vec2, color: Rgba8, tex_coord: Vec2) {
         self.vertices.push(Vertex {
-            pos,
+            pos: self.current_transform.transform_point(pos),
             color,
             tex_coord,
         });
@@ -258,23 +923,235 @@ impl DrawData {
     }
 }
 
+/// feeds tessellator's output into draw data.
+struct DrawDataTessellationOutput<'a> {
+    draw_data: &'a mut DrawData,
+    color: Rgba8,
+    /// maps positions within `bounds` to `tex_coords`.
+    bounds: Rect,
+    tex_coords: Rect,
+}
+
+impl TessellationOutput for DrawDataTessellationOutput<'_> {
+    fn push_vertex(&mut self, pos: Vec2, coverage: f32) -> u32 {
+        let index = self.draw_data.vertices.len() as u32;
+        let color = self
+            .color
+            .with_a((self.color.a as f32 * coverage.clamp(0.0, 1.0)).round() as u8);
+        let t = ((pos - self.bounds.min) / self.bounds.size().max(Vec2::splat(f32::EPSILON)))
+            .clamp(Vec2::splat(0.0), Vec2::splat(1.0));
+        let tex_coord = self.tex_coords.min + self.tex_coords.size() * t;
+        self.draw_data.push_vertex(pos, color, tex_coord);
+        index
+    }
+
+    fn push_triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.draw_data.push_indices(a, b, c);
+    }
+}
+
+fn rounded_rect_path(rect: Rect, corner_radius: CornerRadii) -> Path {
+    // https://spencermortensen.com/articles/bezier-circle/
+    const KAPPA: f32 = 0.552_284_8;
+
+    let max_radius = rect.width().min(rect.height()) * 0.5;
+    let [tl, tr, br, bl] = corner_radius.to_array().map(|r| r.clamp(0.0, max_radius));
+    let Rect { min, max } = rect;
+    let k = 1.0 - KAPPA;
+    Path::new()
+        .move_to(Vec2::new(min.x + tl, min.y))
+        .line_to(Vec2::new(max.x - tr, min.y))
+        .cubic_to(
+            Vec2::new(max.x - tr * k, min.y),
+            Vec2::new(max.x, min.y + tr * k),
+            Vec2::new(max.x, min.y + tr),
+        )
+        .line_to(Vec2::new(max.x, max.y - br))
+        .cubic_to(
+            Vec2::new(max.x, max.y - br * k),
+            Vec2::new(max.x - br * k, max.y),
+            Vec2::new(max.x - br, max.y),
+        )
+        .line_to(Vec2::new(min.x + bl, max.y))
+        .cubic_to(
+            Vec2::new(min.x + bl * k, max.y),
+            Vec2::new(min.x, max.y - bl * k),
+            Vec2::new(min.x, max.y - bl),
+        )
+        .line_to(Vec2::new(min.x, min.y + tl))
+        .cubic_to(
+            Vec2::new(min.x, min.y + tl * k),
+            Vec2::new(min.x + tl * k, min.y),
+            Vec2::new(min.x + tl, min.y),
+        )
+        .close()
+}
+
+/// layers are drawn in ascending order. within a layer things are drawn in order they were pushed.
+///
+/// you can define your own layers in between of the predefined ones, e.g. `Layer(150)` for drag
+/// previews that must be above overlays, but below tooltips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Layer(pub i32);
+
+impl Layer {
+    pub const BACKGROUND: Self = Self(-100);
+    pub const CONTENT: Self = Self(0);
+    pub const OVERLAY: Self = Self(100);
+    pub const TOOLTIP: Self = Self(200);
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Self::CONTENT
+    }
+}
+
+/// everything that is recorded separately for each layer.
+#[derive(Debug, Default)]
+struct LayerRecording {
+    draw_data: DrawData,
+    transform_stack: Vec<Affine2>,
+    clip_stack: Vec<(Option<Rect>, Option<u32>)>,
+}
+
+fn clear_layer(
+    draw_data: &mut DrawData,
+    transform_stack: &[Affine2],
+    clip_stack: &[(Option<Rect>, Option<u32>)],
+) {
+    assert!(
+        transform_stack.is_empty(),
+        "push_transform and pop_transform are unbalanced"
+    );
+    assert!(
+        clip_stack.is_empty(),
+        "push_clip_* and pop_clip are unbalanced"
+    );
+    draw_data.clear();
+}
+
 // NOTE: this will go away (i think).
 #[derive(Debug, Default)]
 pub struct DrawBuffer {
+    // NOTE: layer_draw_data, transform_stack and clip_stack belong to the current layer.
+    //   recordings of other layers are stashed in `layers` (slot of the current one is empty).
+    layer: Layer,
+    layer_draw_data: DrawData,
+    transform_stack: Vec<Affine2>,
+    /// scissor and clip mask to restore on pop.
+    clip_stack: Vec<(Option<Rect>, Option<u32>)>,
+    /// sorted by layer.
+    layers: Vec<(Layer, LayerRecording)>,
+    /// all layers concatenated by flush.
     draw_data: DrawData,
+    damage_tracker: DamageTracker,
+
+    tessellator: Tessellator,
+    flattened_path: FlattenedPath,
 }
 
 impl DrawBuffer {
+    /// concatenates all layers into [`DrawBuffer::draw_data`].
     pub fn flush(&mut self) {
-        self.draw_data.flush();
+        self.layer_draw_data.flush();
+        for (_, recording) in self.layers.iter_mut() {
+            recording.draw_data.flush();
+        }
+
+        let current = self.layer_slot(self.layer);
+        self.draw_data.clear();
+        for (i, (_, recording)) in self.layers.iter().enumerate() {
+            let draw_data = if i == current {
+                &self.layer_draw_data
+            } else {
+                &recording.draw_data
+            };
+            self.draw_data.append(draw_data);
+        }
+
+        self.damage_tracker.update(&self.draw_data);
     }
 
     pub fn draw_data(&self) -> &DrawData {
         &self.draw_data
     }
 
+    /// whether what's drawn differs from the previous frame; if it does not - you may skip the
+    /// frame. only valid after [`DrawBuffer::flush`].
+    pub fn changed(&self) -> bool {
+        self.damage_tracker.changed()
+    }
+
+    /// stable hash of what's drawn. only valid after [`DrawBuffer::flush`].
+    pub fn frame_hash(&self) -> u64 {
+        self.damage_tracker.frame_hash()
+    }
+
+    /// regions (in logical pixels) that differ from the previous frame. only valid after
+    /// [`DrawBuffer::flush`]. see [`DamageTracker::damage`].
+    pub fn damage(&self) -> &[Rect] {
+        self.damage_tracker.damage()
+    }
+
     pub fn clear(&mut self) {
+        self.set_layer(Layer::default());
+        clear_layer(
+            &mut self.layer_draw_data,
+            &self.transform_stack,
+            &self.clip_stack,
+        );
+        for (_, recording) in self.layers.iter_mut() {
+            clear_layer(
+                &mut recording.draw_data,
+                &recording.transform_stack,
+                &recording.clip_stack,
+            );
+        }
         self.draw_data.clear();
+        self.damage_tracker.finish_frame();
+    }
+
+    pub fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    /// everything pushed after this goes into `layer`, until the layer is changed again.
+    ///
+    /// NOTE: transforms and clips are per-layer; e.g. a tooltip is not clipped by the panel that
+    ///   spawned it.
+    pub fn set_layer(&mut self, layer: Layer) {
+        if self.layer == layer {
+            return;
+        }
+        // NOTE: pending indices stay with the layer, so drawing can continue batching when we
+        //   come back to it.
+
+        let prev = self.layer_slot(self.layer);
+        self.swap_layer_recording(prev);
+        let next = self.layer_slot(layer);
+        self.swap_layer_recording(next);
+        self.layer = layer;
+    }
+
+    fn layer_slot(&mut self, layer: Layer) -> usize {
+        match self
+            .layers
+            .binary_search_by_key(&layer, |(layer, _)| *layer)
+        {
+            Ok(i) => i,
+            Err(i) => {
+                self.layers.insert(i, (layer, LayerRecording::default()));
+                i
+            }
+        }
+    }
+
+    fn swap_layer_recording(&mut self, slot: usize) {
+        let (_, recording) = &mut self.layers[slot];
+        std::mem::swap(&mut self.layer_draw_data, &mut recording.draw_data);
+        std::mem::swap(&mut self.transform_stack, &mut recording.transform_stack);
+        std::mem::swap(&mut self.clip_stack, &mut recording.clip_stack);
     }
 
     // NOTE: you don't this to have methods that return ScopeGuard.
@@ -282,26 +1159,171 @@ impl DrawBuffer {
     // rules).
     // if you want scope guard thing - you should implement it at application-level.
 
-    pub fn scissor_rect(&self) -> Option<Rect> {
-        self.draw_data.current_scissor_rect
+    /// axis-aligned bounds of the current clip in logical pixels.
+    pub fn clip_bounds(&self) -> Option<Rect> {
+        self.layer_draw_data.current_scissor_rect
+    }
+
+    /// everything pushed until the matching [`DrawBuffer::pop_clip`] is clipped by the
+    /// intersection of `rect` (in the space of the current transform) and the current clip.
+    ///
+    /// NOTE: axis-aligned rects are handled by scissor; rotated or skewed ones need a stencil.
+    pub fn push_clip_rect(&mut self, rect: Rect) {
+        let transform = self.layer_draw_data.current_transform;
+        if !transform.is_axis_aligned() {
+            self.flattened_path.clear();
+            self.flattened_path.points.extend([
+                rect.top_left(),
+                rect.top_right(),
+                rect.bottom_right(),
+                rect.bottom_left(),
+            ]);
+            self.flattened_path.sub_paths.push(FlattenedSubPath {
+                range: 0..4,
+                closed: true,
+            });
+            self.push_clip_flattened_path(rect, FillRule::NonZero);
+            return;
+        }
+
+        self.clip_stack.push((
+            self.layer_draw_data.current_scissor_rect,
+            self.layer_draw_data.current_clip_mask,
+        ));
+        self.intersect_scissor_rect(transform.transform_rect(rect));
+    }
+
+    pub fn push_clip_rounded_rect(&mut self, rect: Rect, corner_radius: CornerRadii) {
+        if corner_radius == CornerRadii::default() {
+            self.push_clip_rect(rect);
+            return;
+        }
+        let path = rounded_rect_path(rect, corner_radius);
+        self.push_clip_path(&path, FillRule::NonZero);
+    }
+
+    pub fn push_clip_path(&mut self, path: &Path, fill_rule: FillRule) {
+        let Some(bounds) = path.bounds() else {
+            // NOTE: empty path clips everything.
+            self.push_clip_rect(Rect::default());
+            return;
+        };
+        self.flattened_path
+            .flatten(path, PATH_TOLERANCE * self.local_path_scale());
+        self.push_clip_flattened_path(bounds, fill_rule);
     }
 
-    pub fn set_scissor_rect(&mut self, rect: Option<Rect>) {
-        self.draw_data.set_scissor_rect(rect)
+    /// `bounds` are in local space.
+    fn push_clip_flattened_path(&mut self, bounds: Rect, fill_rule: FillRule) {
+        self.clip_stack.push((
+            self.layer_draw_data.current_scissor_rect,
+            self.layer_draw_data.current_clip_mask,
+        ));
+        // NOTE: scissor rejects most of the fragments that are outside of the mask for free.
+        let transform = self.layer_draw_data.current_transform;
+        self.intersect_scissor_rect(transform.transform_rect(bounds));
+
+        let start_index = self.layer_draw_data.begin_clip_mask();
+        let mut output = DrawDataTessellationOutput {
+            draw_data: &mut self.layer_draw_data,
+            color: Rgba8::WHITE,
+            bounds: Rect::new(Vec2::splat(0.0), Vec2::splat(1.0)),
+            tex_coords: Rect::new(Vec2::splat(0.0), Vec2::splat(1.0)),
+        };
+        // NOTE: stencil is binary, there's no use in anti-aliasing fringe.
+        self.tessellator.fill(
+            &self.flattened_path,
+            &FillOptions {
+                rule: fill_rule,
+                feather: 0.0,
+            },
+            &mut output,
+        );
+        self.layer_draw_data.end_clip_mask(start_index);
+    }
+
+    fn intersect_scissor_rect(&mut self, rect: Rect) {
+        let rect = rect.normalize();
+        let next = match self.layer_draw_data.current_scissor_rect {
+            Some(prev) => {
+                let clamped = rect.clamp(prev);
+                // NOTE: rects that don't overlap produce an empty one.
+                if clamped.is_normalized() {
+                    clamped
+                } else {
+                    Rect::new(clamped.min, clamped.min)
+                }
+            }
+            None => rect,
+        };
+        self.layer_draw_data.set_scissor_rect(Some(next));
+    }
+
+    pub fn pop_clip(&mut self) {
+        let (scissor_rect, clip_mask) =
+            self.clip_stack.pop().expect("pop_clip without push_clip_*");
+        self.layer_draw_data.set_scissor_rect(scissor_rect);
+        self.layer_draw_data.set_clip_mask(clip_mask);
+    }
+
+    pub fn transform(&self) -> Affine2 {
+        self.layer_draw_data.current_transform
+    }
+
+    /// `transform` is applied on top of the current one to everything that is pushed until the
+    /// matching [`DrawBuffer::pop_transform`].
+    pub fn push_transform(&mut self, transform: Affine2) {
+        let current = self.layer_draw_data.current_transform;
+        self.transform_stack.push(current);
+        self.layer_draw_data.set_transform(current * transform);
+    }
+
+    pub fn pop_transform(&mut self) {
+        let transform = self
+            .transform_stack
+            .pop()
+            .expect("pop_transform without push_transform");
+        self.layer_draw_data.set_transform(transform);
+    }
+
+    /// tolerance and feather are in logical pixels, they need to be brought into local space.
+    fn local_path_scale(&self) -> f32 {
+        1.0 / self
+            .layer_draw_data
+            .current_transform
+            .scale_factor()
+            .max(f32::EPSILON)
     }
 
     fn push_rect_filled(&mut self, rect: Rect, fill: Fill) {
-        let (color, tex_coords, tex_handle) = match fill {
-            Fill::Color(color) => (color, Rect::new(Vec2::splat(0.0), Vec2::splat(1.0)), None),
+        let (color, tex_coords) = self.set_fill(fill);
+        self.layer_draw_data.push_quad(rect, color, tex_coords);
+    }
+
+    /// sets texture, gradient and shader that `fill` needs. returns vertex color and texture
+    /// coords.
+    fn set_fill(&mut self, fill: Fill) -> (Rgba8, Rect) {
+        let unit_rect = Rect::new(Vec2::splat(0.0), Vec2::splat(1.0));
+        let (color, tex_coords, texture, gradient, shader) = match fill {
+            Fill::Color(color) => (color, unit_rect, None, None, None),
             Fill::Texture(TextureFill {
                 handle,
                 coords,
                 base_color,
-            }) => (base_color, coords, Some(handle)),
+            }) => (base_color, coords, Some(handle), None, None),
+            Fill::Gradient(gradient) => (Rgba8::WHITE, unit_rect, None, Some(gradient), None),
+            Fill::Shader { shader, uniforms } => (
+                Rgba8::WHITE,
+                unit_rect,
+                None,
+                None,
+                Some((shader, uniforms)),
+            ),
         };
-
-        self.draw_data.set_texture(tex_handle);
-        self.draw_data.push_quad(rect, color, tex_coords);
+        self.layer_draw_data.set_texture(texture);
+        self.layer_draw_data.set_gradient(gradient);
+        self.layer_draw_data.set_shader(shader);
+        (color, tex_coords)
     }
 
     pub fn push_line(&mut self, line_shape: LineShape) {
@@ -314,34 +1336,90 @@ impl DrawBuffer {
         ));
 
         // NOTE: there's no sdf params for line.
-        self.draw_data.set_sdf_params(None);
+        self.layer_draw_data.set_sdf_params(None);
 
-        // computes the vertex position offset away the from center caused by line width.
-        #[inline]
-        fn compute_line_width_offset(a: Vec2, b: Vec2, width: f32) -> Vec2 {
-            // direction defines how the line is oriented in space. it allows to know
-            // which way to move the vertices to create the desired width.
-            let dir = b - a;
+        let [a, b] = line_shape.points;
+        self.flattened_path.clear();
+        self.flattened_path.points.extend([a, b]);
+        self.flattened_path.sub_paths.push(FlattenedSubPath {
+            range: 0..2,
+            closed: false,
+        });
+        let local_scale = self.local_path_scale();
+        self.stroke_flattened_path(
+            &line_shape.stroke,
+            &StrokeOptions {
+                width: line_shape.stroke.width,
+                feather: StrokeOptions::default().feather * local_scale,
+                tolerance: PATH_TOLERANCE * local_scale,
+                ..StrokeOptions::default()
+            },
+        );
+    }
 
-            // normalizing the direction vector converts it into a unit vector (length
-            // of 1). normalization ensures that the offset is proportional to the line
-            // width, regardless of the line's length.
-            let norm_dir = dir.normalize_or_zero();
+    fn stroke_flattened_path(&mut self, stroke: &Stroke, options: &StrokeOptions) {
+        self.layer_draw_data.set_texture(None);
+        self.layer_draw_data.set_gradient(None);
+        self.layer_draw_data.set_shader(None);
+        let mut output = DrawDataTessellationOutput {
+            draw_data: &mut self.layer_draw_data,
+            color: stroke.color,
+            bounds: Rect::new(Vec2::splat(0.0), Vec2::splat(1.0)),
+            tex_coords: Rect::new(Vec2::splat(0.0), Vec2::splat(1.0)),
+        };
+        self.tessellator
+            .stroke(&self.flattened_path, options, &mut output);
+    }
 
-            // create a vector that points outward from the line. we want to move the
-            // vertices away from the center of the line, not along its length.
-            let perp = norm_dir.perp();
+    pub fn push_path(&mut self, path_shape: PathShape) {
+        // NOTE: same as with lines; path's stroke may only be centered.
+        if let Some(ref stroke) = path_shape.stroke {
+            assert!(matches!(stroke.alignment, StrokeAlignment::Center));
+        }
 
-            // to distribute the offset evenly on both sides of the line
-            let offset = perp * (width * 0.5);
+        // NOTE: there's no sdf params for paths.
+        self.layer_draw_data.set_sdf_params(None);
 
-            offset
+        let local_scale = self.local_path_scale();
+        self.flattened_path
+            .flatten(path_shape.path, PATH_TOLERANCE * local_scale);
+
+        if let Some(fill) = path_shape.fill {
+            let (color, tex_coords) = self.set_fill(fill);
+            let mut output = DrawDataTessellationOutput {
+                draw_data: &mut self.layer_draw_data,
+                color,
+                bounds: path_shape
+                    .path
+                    .bounds()
+                    .unwrap_or(Rect::new(Vec2::splat(0.0), Vec2::splat(1.0))),
+                tex_coords,
+            };
+            self.tessellator.fill(
+                &self.flattened_path,
+                &FillOptions {
+                    rule: path_shape.fill_rule,
+                    feather: path_shape.feather * local_scale,
+                },
+                &mut output,
+            );
         }
 
-        let [a, b] = line_shape.points;
-        let Stroke { width, color, .. } = line_shape.stroke;
-        let offset = compute_line_width_offset(a, b, width);
-        self.push_rect_filled(Rect::new(a + offset, b - offset), Fill::Color(color));
+        if let Some(ref stroke) = path_shape.stroke {
+            self.stroke_flattened_path(
+                stroke,
+                &StrokeOptions {
+                    width: stroke.width,
+                    join: path_shape.join,
+                    cap: path_shape.cap,
+                    miter_limit: path_shape.miter_limit,
+                    dashes: path_shape.dashes,
+                    dash_offset: path_shape.dash_offset,
+                    feather: path_shape.feather * local_scale,
+                    tolerance: PATH_TOLERANCE * local_scale,
+                },
+            );
+        }
     }
 
     pub fn push_rect(&mut self, rect_shape: RectShape) {
@@ -353,39 +1431,468 @@ impl DrawBuffer {
         } = rect_shape;
         match (stroke, corner_radius) {
             (None, None) => {
-                self.draw_data.set_sdf_params(None);
+                self.layer_draw_data.set_sdf_params(None);
             }
             (stroke, corner_radius) => {
                 let mut rect_sdf = RectSdfParams {
                     center: rect.center().to_array(),
                     size: rect.size().to_array(),
-                    corner_radius: corner_radius.unwrap_or(0.0),
-                    stroke_width: 0.0,
-                    stroke_color: Rgba8::TRANSPARENT.to_f32_array(),
+                    corner_radii: corner_radius.unwrap_or_default().to_array(),
+                    stroke_widths: [0.0; 4],
+                    stroke_colors: [Rgba8::TRANSPARENT.to_f32_array(); 4],
                     stroke_alignment: StrokeAlignment::default() as i32,
                 };
                 if let Some(ref stroke) = stroke {
-                    rect_sdf.stroke_width = stroke.width;
-                    rect_sdf.stroke_color = stroke.color.to_f32_array();
+                    let sides = stroke.sides();
+                    rect_sdf.stroke_widths = sides.map(|side| side.width);
+                    rect_sdf.stroke_colors = sides.map(|side| side.color.to_f32_array());
                     rect_sdf.stroke_alignment = stroke.alignment as i32;
-                    match stroke.alignment {
-                        StrokeAlignment::Inside => {}
-                        // NOTE: in cases of outside/center outline rect needs to be scaled up.
-                        // this does not change size of the rect, no; but "reserves" space for the
-                        // outline.
-                        StrokeAlignment::Outside => {
-                            rect = rect.inflate(Vec2::splat(stroke.width));
-                        }
-                        StrokeAlignment::Center => {
-                            rect = rect.inflate(Vec2::splat(stroke.width * 0.5));
-                        }
-                    }
+                    // NOTE: in cases of outside/center outline rect needs to be scaled up.
+                    // this does not change size of the rect, no; but "reserves" space for the
+                    // outline.
+                    let outer = stroke.alignment.outer_fraction();
+                    let [top, right, bottom, left] = rect_sdf.stroke_widths.map(|w| w * outer);
+                    rect = Rect::new(
+                        rect.min - Vec2::new(left, top),
+                        rect.max + Vec2::new(right, bottom),
+                    );
                 }
-                self.draw_data
+                self.layer_draw_data
                     .set_sdf_params(Some(SdfParams::Rect(rect_sdf)));
             }
         };
 
         self.push_rect_filled(rect, fill.unwrap_or(Fill::Color(Rgba8::TRANSPARENT)));
     }
+
+    pub fn push_circle(&mut self, circle_shape: CircleShape) {
+        self.push_ellipse(circle_shape.into());
+    }
+
+    pub fn push_ellipse(&mut self, ellipse_shape: EllipseShape) {
+        let EllipseShape {
+            center,
+            radii,
+            fill,
+            stroke,
+        } = ellipse_shape;
+        let stroke = stroke.unwrap_or(Stroke::new(0.0, Rgba8::TRANSPARENT));
+        self.layer_draw_data
+            .set_sdf_params(Some(SdfParams::Ellipse(EllipseSdfParams {
+                center: center.to_array(),
+                radii: radii.to_array(),
+                stroke_width: stroke.width,
+                stroke_color: stroke.color.to_f32_array(),
+                stroke_alignment: stroke.alignment as i32,
+            })));
+
+        // NOTE: same as with rects quad "reserves" space for the outer part of the stroke.
+        let extent = radii + Vec2::splat(stroke.width * stroke.alignment.outer_fraction());
+        let rect = Rect::new(center - extent, center + extent);
+        self.push_rect_filled(rect, fill.unwrap_or(Fill::Color(Rgba8::TRANSPARENT)));
+    }
+
+    pub fn push_arc(&mut self, arc_shape: ArcShape) {
+        let ArcShape {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            thickness,
+            cap,
+            fill,
+            stroke,
+        } = arc_shape;
+        let stroke = stroke.unwrap_or(Stroke::new(0.0, Rgba8::TRANSPARENT));
+        self.layer_draw_data
+            .set_sdf_params(Some(SdfParams::Arc(ArcSdfParams {
+                center: center.to_array(),
+                radius,
+                angles: [start_angle, end_angle],
+                thickness,
+                cap: cap as i32,
+                stroke_width: stroke.width,
+                stroke_color: stroke.color.to_f32_array(),
+                stroke_alignment: stroke.alignment as i32,
+            })));
+
+        // NOTE: this covers the whole circle, not only the arc; wasted fragments are discarded by
+        //   the sdf anyway.
+        let extent = radius + thickness * 0.5 + stroke.width * stroke.alignment.outer_fraction();
+        let rect = Rect::new(center - Vec2::splat(extent), center + Vec2::splat(extent));
+        self.push_rect_filled(rect, fill.unwrap_or(Fill::Color(Rgba8::TRANSPARENT)));
+    }
+
+    pub fn push_shadow(&mut self, shadow_shape: ShadowShape) {
+        let ShadowShape {
+            rect,
+            corner_radius,
+            offset,
+            blur_radius,
+            spread,
+            color,
+        } = shadow_shape;
+
+        // NOTE: same as in css, spread grows corner radii too, but square corners stay square.
+        let rect = rect.translate(offset).inflate(Vec2::splat(spread));
+        let corner_radii = corner_radius
+            .unwrap_or_default()
+            .to_array()
+            .map(|r| if r > 0.0 { (r + spread).max(0.0) } else { 0.0 });
+        // NOTE: sigma can't be 0; half a pixel is about what sdf anti-aliasing does anyway.
+        let sigma = (blur_radius * 0.5).max(0.5);
+        self.layer_draw_data
+            .set_sdf_params(Some(SdfParams::Shadow(ShadowSdfParams {
+                center: rect.center().to_array(),
+                size: rect.size().max(Vec2::ZERO).to_array(),
+                corner_radii,
+                sigma,
+            })));
+
+        // NOTE: gaussian is negligible past 3 sigma.
+        let rect = rect.inflate(Vec2::splat(sigma * 3.0));
+        self.push_rect_filled(rect, Fill::Color(color));
+    }
+}
+
+#[test]
+fn test_push_rect_stroke_per_side() {
+    let mut draw_buffer = DrawBuffer::default();
+    let rect = Rect::new(Vec2::new(10.0, 10.0), Vec2::new(20.0, 20.0));
+    draw_buffer.push_rect(
+        RectShape::new(rect)
+            .with_corner_radius(Some(CornerRadii::top(4.0)))
+            .with_rect_stroke(Some(
+                RectStroke::default()
+                    .with_bottom(2.0, Rgba8::RED)
+                    .with_alignment(StrokeAlignment::Outside),
+            )),
+    );
+    draw_buffer.flush();
+
+    let draw_data = draw_buffer.draw_data();
+    // NOTE: quad reserves space for the bottom stroke only.
+    assert_eq!(draw_data.vertices[0].pos, Vec2::new(10.0, 10.0));
+    assert_eq!(draw_data.vertices[2].pos, Vec2::new(20.0, 22.0));
+    let Some(SdfParams::Rect(ref params)) = draw_data.commands[0].sdf_params else {
+        panic!("expected rect sdf params");
+    };
+    assert_eq!(params.center, [15.0, 15.0]);
+    assert_eq!(params.size, [10.0, 10.0]);
+    assert_eq!(params.corner_radii, [4.0, 4.0, 0.0, 0.0]);
+    assert_eq!(params.stroke_widths, [0.0, 0.0, 2.0, 0.0]);
+    assert_eq!(params.stroke_colors[2], Rgba8::RED.to_f32_array());
+}
+
+#[test]
+fn test_push_circle_and_arc() {
+    let mut draw_buffer = DrawBuffer::default();
+    let center = Vec2::new(50.0, 50.0);
+    draw_buffer.push_circle(CircleShape::new(center, 10.0).with_stroke(Some(
+        Stroke::new(4.0, Rgba8::WHITE).with_alignment(StrokeAlignment::Outside),
+    )));
+    draw_buffer.push_arc(
+        ArcShape::new(center, 20.0, 0.0, std::f32::consts::PI, 4.0).with_cap(ArcCap::Round),
+    );
+    draw_buffer.flush();
+
+    let draw_data = draw_buffer.draw_data();
+    assert_eq!(draw_data.commands.len(), 2);
+
+    assert_eq!(draw_data.vertices[0].pos, Vec2::new(36.0, 36.0));
+    assert_eq!(draw_data.vertices[2].pos, Vec2::new(64.0, 64.0));
+    let Some(SdfParams::Ellipse(ref params)) = draw_data.commands[0].sdf_params else {
+        panic!("expected ellipse sdf params");
+    };
+    assert_eq!(params.radii, [10.0, 10.0]);
+    assert_eq!(params.stroke_width, 4.0);
+
+    assert_eq!(draw_data.vertices[4].pos, Vec2::new(28.0, 28.0));
+    assert_eq!(draw_data.vertices[6].pos, Vec2::new(72.0, 72.0));
+    let Some(SdfParams::Arc(ref params)) = draw_data.commands[1].sdf_params else {
+        panic!("expected arc sdf params");
+    };
+    assert_eq!(params.angles, [0.0, std::f32::consts::PI]);
+    assert_eq!(params.cap, ArcCap::Round as i32);
+    assert_eq!(params.stroke_width, 0.0);
+}
+
+#[test]
+fn test_gradient_fill() {
+    let gradient = Gradient::linear(Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0))
+        .with_stop(0.0, Rgba8::RED)
+        .with_stop(0.5, Rgba8::GREEN)
+        .with_stop(0.25, Rgba8::BLUE);
+    // NOTE: out of order stop is bumped up.
+    assert_eq!(gradient.stops()[2].offset, 0.5);
+
+    let mut draw_buffer = DrawBuffer::default();
+    let rect = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(Some(Fill::Gradient(gradient.clone()))));
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(Some(Fill::Color(Rgba8::RED))));
+    draw_buffer.flush();
+
+    let draw_data = draw_buffer.draw_data();
+    assert_eq!(draw_data.commands.len(), 2);
+    assert_eq!(draw_data.commands[0].gradient, Some(gradient));
+    assert_eq!(draw_data.vertices[0].color, Rgba8::WHITE);
+    assert_eq!(draw_data.commands[1].gradient, None);
+}
+
+#[test]
+fn test_shader_fill() {
+    use crate::{
+        GlslProfile, ShaderDesc, ShaderService, ShaderSource, ShaderSourceDesc, ShaderSourceKind,
+        ShaderStageDesc, ShaderUniformName, ShaderUniformValue,
+    };
+
+    let stage = ShaderStageDesc {
+        source: ShaderSource {
+            kind: ShaderSourceKind::Static(""),
+            desc: ShaderSourceDesc::Glsl {
+                version: 300,
+                profile: GlslProfile::Es,
+            },
+        },
+        snippet: None,
+        defines: Default::default(),
+    };
+    let mut shader_service = ShaderService::default();
+    let shader = shader_service.create(ShaderDesc {
+        vertex_stage: stage.clone(),
+        fragment_stage: stage,
+        uniforms: Default::default(),
+    });
+    let uniforms = |value| {
+        let mut uniforms = ShaderUniforms::default();
+        uniforms.insert(
+            ShaderUniformName::from_str("u_value"),
+            ShaderUniformValue::Float(value),
+        );
+        uniforms
+    };
+
+    let mut draw_buffer = DrawBuffer::default();
+    let rect = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+    let fill = |value| {
+        Some(Fill::Shader {
+            shader,
+            uniforms: uniforms(value),
+        })
+    };
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(fill(1.0)));
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(fill(1.0)));
+    // NOTE: different uniform values can't be batched together.
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(fill(2.0)));
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(Some(Fill::Color(Rgba8::RED))));
+    draw_buffer.flush();
+
+    let draw_data = draw_buffer.draw_data();
+    assert_eq!(draw_data.commands.len(), 3);
+    assert_eq!(draw_data.commands[0].shader, Some(shader));
+    assert_eq!(draw_data.commands[0].shader_uniforms, uniforms(1.0));
+    assert_eq!(draw_data.commands[0].index_range.len(), 12);
+    assert_eq!(draw_data.commands[1].shader_uniforms, uniforms(2.0));
+    assert_eq!(draw_data.vertices[0].color, Rgba8::WHITE);
+    assert_eq!(draw_data.commands[2].shader, None);
+    assert_eq!(
+        draw_data.commands[2].shader_uniforms,
+        ShaderUniforms::default()
+    );
+
+    shader_service.delete(shader);
+    assert_eq!(shader_service.drain_deleted().collect::<Vec<_>>(), [shader]);
+    assert_eq!(shader_service.drain_deleted().count(), 0);
+}
+
+#[test]
+fn test_push_shadow() {
+    let mut draw_buffer = DrawBuffer::default();
+    let rect = Rect::new(Vec2::new(10.0, 10.0), Vec2::new(30.0, 30.0));
+    draw_buffer.push_shadow(
+        ShadowShape::new(rect)
+            .with_corner_radius(Some(CornerRadii::top(4.0)))
+            .with_offset(Vec2::new(0.0, 2.0))
+            .with_blur_radius(4.0)
+            .with_spread(1.0),
+    );
+    draw_buffer.flush();
+
+    let draw_data = draw_buffer.draw_data();
+    // NOTE: quad covers 3 sigma (blur radius / 2) around the spread rect.
+    assert_eq!(draw_data.vertices[0].pos, Vec2::new(3.0, 5.0));
+    assert_eq!(draw_data.vertices[2].pos, Vec2::new(37.0, 39.0));
+    assert_eq!(draw_data.vertices[0].color, Rgba8::BLACK);
+    let Some(SdfParams::Shadow(ref params)) = draw_data.commands[0].sdf_params else {
+        panic!("expected shadow sdf params");
+    };
+    assert_eq!(params.center, [20.0, 22.0]);
+    assert_eq!(params.size, [22.0, 22.0]);
+    assert_eq!(params.corner_radii, [5.0, 5.0, 0.0, 0.0]);
+    assert_eq!(params.sigma, 2.0);
+}
+
+#[test]
+fn test_transform_stack() {
+    let mut draw_buffer = DrawBuffer::default();
+    let rect = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+    draw_buffer.push_transform(Affine2::from_translation(Vec2::new(100.0, 0.0)));
+    draw_buffer.push_transform(Affine2::from_scale(Vec2::splat(2.0)));
+    draw_buffer.push_clip_rect(rect);
+    draw_buffer.push_rect(
+        RectShape::new(rect)
+            .with_fill(Some(Fill::Color(Rgba8::RED)))
+            .with_corner_radius(Some(CornerRadii::all(2.0))),
+    );
+    draw_buffer.pop_transform();
+    draw_buffer.pop_transform();
+    draw_buffer.pop_clip();
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(Some(Fill::Color(Rgba8::RED))));
+    draw_buffer.flush();
+
+    let draw_data = draw_buffer.draw_data();
+    assert_eq!(draw_data.commands.len(), 2);
+
+    // NOTE: vertices are transformed, sdf params stay in local space.
+    assert_eq!(draw_data.vertices[0].pos, Vec2::new(100.0, 0.0));
+    assert_eq!(draw_data.vertices[2].pos, Vec2::new(120.0, 20.0));
+    let command = &draw_data.commands[0];
+    let Some(SdfParams::Rect(ref params)) = command.sdf_params else {
+        panic!("expected rect sdf params");
+    };
+    assert_eq!(params.center, [5.0, 5.0]);
+    assert_eq!(
+        command.transform.transform_point(Vec2::new(5.0, 5.0)),
+        Vec2::new(110.0, 10.0)
+    );
+    assert_eq!(
+        command.scissor,
+        Some(Rect::new(Vec2::new(100.0, 0.0), Vec2::new(120.0, 20.0)))
+    );
+
+    assert_eq!(draw_data.vertices[4].pos, Vec2::new(0.0, 0.0));
+    assert_eq!(draw_data.commands[1].transform, Affine2::IDENTITY);
+    assert_eq!(draw_data.commands[1].scissor, None);
+}
+
+#[test]
+fn test_stroke_under_transform() {
+    let path = Path::new()
+        .move_to(Vec2::new(0.0, 0.0))
+        .line_to(Vec2::new(10.0, 0.0));
+    // NOTE: returns width of the anti-aliasing fringe in local space.
+    let fringe_width = |scale: f32| {
+        let mut draw_buffer = DrawBuffer::default();
+        draw_buffer.push_transform(Affine2::from_scale(Vec2::splat(scale)));
+        draw_buffer
+            .push_path(PathShape::new(&path).with_stroke(Some(Stroke::new(2.0, Rgba8::WHITE))));
+        draw_buffer.pop_transform();
+        draw_buffer.flush();
+
+        let vertices = &draw_buffer.draw_data().vertices;
+        let extent = |alpha: u8| {
+            vertices
+                .iter()
+                .filter(|vertex| vertex.color.a == alpha)
+                .map(|vertex| vertex.pos.y.abs() / scale)
+                .fold(0.0, f32::max)
+        };
+        extent(0) - extent(255)
+    };
+
+    // NOTE: feather is in logical pixels; zooming in must not make the edge blurrier.
+    assert!((fringe_width(1.0) - 1.0).abs() < 1e-4);
+    assert!((fringe_width(4.0) - 0.25).abs() < 1e-4);
+}
+
+#[test]
+fn test_clip_stack() {
+    let mut draw_buffer = DrawBuffer::default();
+    let panel = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 100.0));
+    let content = Rect::new(Vec2::new(-50.0, 50.0), Vec2::new(50.0, 150.0));
+    let fill = Some(Fill::Color(Rgba8::RED));
+
+    draw_buffer.push_clip_rect(panel);
+    draw_buffer.push_clip_rect(content);
+    draw_buffer.push_rect(RectShape::new(content).with_fill(fill.clone()));
+    draw_buffer.pop_clip();
+    draw_buffer.push_clip_rounded_rect(panel, CornerRadii::all(8.0));
+    draw_buffer.push_rect(RectShape::new(content).with_fill(fill.clone()));
+    draw_buffer.pop_clip();
+    draw_buffer.pop_clip();
+    draw_buffer.push_rect(RectShape::new(content).with_fill(fill));
+    draw_buffer.flush();
+
+    let draw_data = draw_buffer.draw_data();
+    assert_eq!(draw_data.commands.len(), 3);
+
+    // NOTE: nested rects are intersected.
+    assert_eq!(
+        draw_data.commands[0].scissor,
+        Some(Rect::new(Vec2::new(0.0, 50.0), Vec2::new(50.0, 100.0)))
+    );
+    assert_eq!(draw_data.commands[0].clip_mask, None);
+
+    // NOTE: rounded rect needs a mask, its triangles are not drawn as a command.
+    assert_eq!(draw_data.commands[1].scissor, Some(panel));
+    assert_eq!(draw_data.commands[1].clip_mask, Some(0));
+    assert_eq!(draw_data.clip_masks.len(), 1);
+    let mask = &draw_data.clip_masks[0];
+    assert_eq!(mask.parent, None);
+    assert!(!mask.index_range.is_empty());
+    assert!(
+        draw_data
+            .commands
+            .iter()
+            .all(|command| command.index_range.start >= mask.index_range.end
+                || command.index_range.end <= mask.index_range.start)
+    );
+
+    assert_eq!(draw_data.commands[2].scissor, None);
+    assert_eq!(draw_data.commands[2].clip_mask, None);
+}
+
+#[test]
+fn test_layers() {
+    let mut draw_buffer = DrawBuffer::default();
+    let rect = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+    let fill = |color| Some(Fill::Color(color));
+
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(fill(Rgba8::RED)));
+    draw_buffer.set_layer(Layer::TOOLTIP);
+    draw_buffer.push_clip_rounded_rect(rect, CornerRadii::all(2.0));
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(fill(Rgba8::GREEN)));
+    draw_buffer.pop_clip();
+    draw_buffer.set_layer(Layer::CONTENT);
+    // NOTE: this one is batched together with the first rect.
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(fill(Rgba8::RED)));
+    draw_buffer.set_layer(Layer::BACKGROUND);
+    draw_buffer.push_clip_rounded_rect(rect, CornerRadii::all(2.0));
+    draw_buffer.push_rect(RectShape::new(rect).with_fill(fill(Rgba8::BLUE)));
+    draw_buffer.pop_clip();
+    draw_buffer.flush();
+
+    let draw_data = draw_buffer.draw_data();
+    let colors: Vec<Rgba8> = draw_data
+        .commands
+        .iter()
+        .map(|command| {
+            let first_index = draw_data.indices[command.index_range.start as usize];
+            draw_data.vertices[first_index as usize].color
+        })
+        .collect();
+    assert_eq!(colors, [Rgba8::BLUE, Rgba8::RED, Rgba8::GREEN]);
+    assert_eq!(draw_data.commands[1].index_range.len(), 12);
+
+    // NOTE: clip masks of each layer are shifted too.
+    assert_eq!(draw_data.clip_masks.len(), 2);
+    assert_eq!(draw_data.commands[0].clip_mask, Some(0));
+    assert_eq!(draw_data.commands[1].clip_mask, None);
+    assert_eq!(draw_data.commands[2].clip_mask, Some(1));
+    assert!(draw_data.clip_masks[1].index_range.start >= draw_data.commands[1].index_range.end);
+
+    draw_buffer.clear();
+    assert_eq!(draw_buffer.layer(), Layer::CONTENT);
+    draw_buffer.flush();
+    assert!(draw_buffer.draw_data().commands.is_empty());
 }
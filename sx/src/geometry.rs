@@ -440,6 +440,12 @@ impl Rect {
         Self::new(self.min.max(other.min), self.max.min(other.max))
     }
 
+    /// smallest rect that contains both.
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
     // ----
     // sugary stuff
 
@@ -490,6 +496,134 @@ impl Rect {
     }
 }
 
+// ----
+
+/// 2d affine transform (translate, scale, rotate, skew).
+/// maps `p` to `x_axis * p.x + y_axis * p.y + translation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine2 {
+    pub x_axis: Vec2,
+    pub y_axis: Vec2,
+    pub translation: Vec2,
+}
+
+impl Default for Affine2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl ops::Mul<Affine2> for Affine2 {
+    type Output = Self;
+
+    /// `rhs` is applied first, then `self`.
+    fn mul(self, rhs: Affine2) -> Self::Output {
+        Self::new(
+            self.transform_vector(rhs.x_axis),
+            self.transform_vector(rhs.y_axis),
+            self.transform_point(rhs.translation),
+        )
+    }
+}
+
+impl Affine2 {
+    pub const IDENTITY: Self = Self::new(Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0), Vec2::ZERO);
+
+    #[inline]
+    #[must_use]
+    pub const fn new(x_axis: Vec2, y_axis: Vec2, translation: Vec2) -> Self {
+        Self {
+            x_axis,
+            y_axis,
+            translation,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn from_translation(translation: Vec2) -> Self {
+        Self::new(Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0), translation)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn from_scale(scale: Vec2) -> Self {
+        Self::new(Vec2::new(scale.x, 0.0), Vec2::new(0.0, scale.y), Vec2::ZERO)
+    }
+
+    /// `angle` is in radians; y points down, thus positive angles rotate clockwise.
+    #[must_use]
+    pub fn from_angle(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(Vec2::new(cos, sin), Vec2::new(-sin, cos), Vec2::ZERO)
+    }
+
+    /// angles are in radians, same as css skew(x, y).
+    #[must_use]
+    pub fn from_skew(skew: Vec2) -> Self {
+        Self::new(
+            Vec2::new(1.0, skew.y.tan()),
+            Vec2::new(skew.x.tan(), 1.0),
+            Vec2::ZERO,
+        )
+    }
+
+    // ----
+
+    #[inline]
+    #[must_use]
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.transform_vector(point) + self.translation
+    }
+
+    /// same as [`Affine2::transform_point`], but ignores translation.
+    #[inline]
+    #[must_use]
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2 {
+        self.x_axis * vector.x + self.y_axis * vector.y
+    }
+
+    /// bounds of the transformed rect.
+    #[must_use]
+    pub fn transform_rect(&self, rect: Rect) -> Rect {
+        let a = self.transform_point(rect.top_left());
+        let b = self.transform_point(rect.top_right());
+        let c = self.transform_point(rect.bottom_right());
+        let d = self.transform_point(rect.bottom_left());
+        Rect::new(a.min(b).min(c).min(d), a.max(b).max(c).max(d))
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn determinant(&self) -> f32 {
+        self.x_axis.x * self.y_axis.y - self.y_axis.x * self.x_axis.y
+    }
+
+    /// NOTE: result is not finite if the transform is not invertible (determinant is 0).
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        let inv_det = 1.0 / self.determinant();
+        let x_axis = Vec2::new(self.y_axis.y, -self.x_axis.y) * inv_det;
+        let y_axis = Vec2::new(-self.y_axis.x, self.x_axis.x) * inv_det;
+        let translation = -(x_axis * self.translation.x + y_axis * self.translation.y);
+        Self::new(x_axis, y_axis, translation)
+    }
+
+    /// approximate uniform scale; exact for transforms without non-uniform scale or skew.
+    #[inline]
+    #[must_use]
+    pub fn scale_factor(&self) -> f32 {
+        self.determinant().abs().sqrt()
+    }
+
+    /// true if transformed rects remain axis-aligned rects (no rotation or skew).
+    #[inline]
+    #[must_use]
+    pub fn is_axis_aligned(&self) -> bool {
+        self.x_axis.y == 0.0 && self.y_axis.x == 0.0
+    }
+}
+
 #[test]
 fn test_rect_contains_point() {
     let rect = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(2.0, 2.0));
@@ -538,3 +672,34 @@ fn test_rect_intersects() {
     let non_overlapping = Rect::new(Vec2::new(3.0, 3.0), Vec2::new(4.0, 4.0));
     assert!(!rect.intersects(&non_overlapping));
 }
+
+#[test]
+fn test_affine2() {
+    fn approx_eq(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 1e-5
+    }
+
+    let transform = Affine2::from_translation(Vec2::new(10.0, 0.0))
+        * Affine2::from_angle(std::f32::consts::FRAC_PI_2)
+        * Affine2::from_scale(Vec2::splat(2.0));
+    // NOTE: scale, then rotate clockwise (+x goes to +y), then translate.
+    let p = transform.transform_point(Vec2::new(1.0, 0.0));
+    assert!(approx_eq(p, Vec2::new(10.0, 2.0)));
+    assert!(approx_eq(
+        transform.inverse().transform_point(p),
+        Vec2::new(1.0, 0.0)
+    ));
+    assert!((transform.scale_factor() - 2.0).abs() < 1e-5);
+    assert!(!transform.is_axis_aligned());
+
+    let skew = Affine2::from_skew(Vec2::new(std::f32::consts::FRAC_PI_4, 0.0));
+    assert!(approx_eq(
+        skew.transform_point(Vec2::new(0.0, 1.0)),
+        Vec2::new(1.0, 1.0)
+    ));
+
+    let rect = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(2.0, 1.0));
+    let bounds = Affine2::from_angle(std::f32::consts::FRAC_PI_2).transform_rect(rect);
+    assert!(approx_eq(bounds.min, Vec2::new(-1.0, 0.0)));
+    assert!(approx_eq(bounds.max, Vec2::new(0.0, 2.0)));
+}
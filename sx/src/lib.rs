@@ -1,15 +1,21 @@
 mod color;
+mod damage;
 mod drawbuffer;
 mod fontservice;
 mod geometry;
+mod path;
 mod shaderservice;
+mod tessellator;
 mod texturepacker;
 mod textureservice;
 
 pub use color::*;
+pub use damage::*;
 pub use drawbuffer::*;
 pub use fontservice::*;
 pub use geometry::*;
+pub use path::*;
 pub use shaderservice::*;
+pub use tessellator::*;
 pub use texturepacker::*;
 pub use textureservice::*;
@@ -0,0 +1,241 @@
+use std::ops::Range;
+
+use crate::{Rect, Vec2};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    MoveTo(Vec2),
+    LineTo(Vec2),
+    /// control, to
+    QuadTo(Vec2, Vec2),
+    /// control 1, control 2, to
+    CubicTo(Vec2, Vec2, Vec2),
+    Close,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Path {
+    pub segments: Vec<PathSegment>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn move_to(mut self, to: Vec2) -> Self {
+        self.segments.push(PathSegment::MoveTo(to));
+        self
+    }
+
+    pub fn line_to(mut self, to: Vec2) -> Self {
+        self.segments.push(PathSegment::LineTo(to));
+        self
+    }
+
+    pub fn quad_to(mut self, ctrl: Vec2, to: Vec2) -> Self {
+        self.segments.push(PathSegment::QuadTo(ctrl, to));
+        self
+    }
+
+    pub fn cubic_to(mut self, ctrl1: Vec2, ctrl2: Vec2, to: Vec2) -> Self {
+        self.segments.push(PathSegment::CubicTo(ctrl1, ctrl2, to));
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.segments.push(PathSegment::Close);
+        self
+    }
+
+    pub fn clear(&mut self) {
+        self.segments.clear();
+    }
+
+    /// bounds of all points including control points.
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut points = self.segments.iter().flat_map(|segment| match *segment {
+            PathSegment::MoveTo(p) | PathSegment::LineTo(p) => [Some(p), None, None],
+            PathSegment::QuadTo(c, p) => [Some(c), Some(p), None],
+            PathSegment::CubicTo(c1, c2, p) => [Some(c1), Some(c2), Some(p)],
+            PathSegment::Close => [None, None, None],
+        });
+        let first = points.find_map(|p| p)?;
+        let (min, max) = points
+            .flatten()
+            .fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+        Some(Rect::new(min, max))
+    }
+}
+
+/// range of points of a single sub-path within [`FlattenedPath::points`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlattenedSubPath {
+    pub range: Range<usize>,
+    pub closed: bool,
+}
+
+/// path with curves replaced with line segments.
+#[derive(Debug, Default)]
+pub struct FlattenedPath {
+    pub points: Vec<Vec2>,
+    pub sub_paths: Vec<FlattenedSubPath>,
+}
+
+impl FlattenedPath {
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.sub_paths.clear();
+    }
+
+    pub fn sub_path_points(&self, sub_path: &FlattenedSubPath) -> &[Vec2] {
+        &self.points[sub_path.range.clone()]
+    }
+
+    fn push_point(&mut self, point: Vec2) {
+        // NOTE: consecutive duplicates are useless for both stroking (direction is undefined) and
+        // filling (zero-length edges).
+        if let Some(last) = self.points.last()
+            && self.points.len() > self.sub_path_start()
+            && (*last - point).length() < 1e-4
+        {
+            return;
+        }
+        self.points.push(point);
+    }
+
+    fn sub_path_start(&self) -> usize {
+        self.sub_paths
+            .last()
+            .map_or(0, |sub_path| sub_path.range.end)
+    }
+
+    fn end_sub_path(&mut self, closed: bool) {
+        let start = self.sub_path_start();
+        let end = self.points.len();
+        if end - start < 2 {
+            self.points.truncate(start);
+            return;
+        }
+        // NOTE: if closed sub-path ends where it started there's no need for the last point.
+        let end = if closed && end - start > 2 && self.points[start] == self.points[end - 1] {
+            self.points.truncate(end - 1);
+            end - 1
+        } else {
+            end
+        };
+        self.sub_paths.push(FlattenedSubPath {
+            range: start..end,
+            closed,
+        });
+    }
+
+    /// `tolerance` is max distance between the curve and the resulting line segments.
+    pub fn flatten(&mut self, path: &Path, tolerance: f32) {
+        self.clear();
+
+        let mut current = Vec2::ZERO;
+        let mut sub_path_start = Vec2::ZERO;
+        for segment in path.segments.iter() {
+            match *segment {
+                PathSegment::MoveTo(to) => {
+                    self.end_sub_path(false);
+                    self.push_point(to);
+                    current = to;
+                    sub_path_start = to;
+                }
+                PathSegment::LineTo(to) => {
+                    if self.points.len() == self.sub_path_start() {
+                        self.push_point(current);
+                    }
+                    self.push_point(to);
+                    current = to;
+                }
+                PathSegment::QuadTo(ctrl, to) => {
+                    if self.points.len() == self.sub_path_start() {
+                        self.push_point(current);
+                    }
+                    // https://raphlinus.github.io/graphics/curves/2019/12/23/flatten-quadbez.html
+                    // (wang's formula)
+                    let dd = (current - ctrl * 2.0 + to).length();
+                    let n = segment_count(dd * 0.25, tolerance);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let mt = 1.0 - t;
+                        self.push_point(current * (mt * mt) + ctrl * (2.0 * mt * t) + to * (t * t));
+                    }
+                    current = to;
+                }
+                PathSegment::CubicTo(ctrl1, ctrl2, to) => {
+                    if self.points.len() == self.sub_path_start() {
+                        self.push_point(current);
+                    }
+                    let dd = (current - ctrl1 * 2.0 + ctrl2)
+                        .length()
+                        .max((ctrl1 - ctrl2 * 2.0 + to).length());
+                    let n = segment_count(dd * 0.75, tolerance);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let mt = 1.0 - t;
+                        self.push_point(
+                            current * (mt * mt * mt)
+                                + ctrl1 * (3.0 * mt * mt * t)
+                                + ctrl2 * (3.0 * mt * t * t)
+                                + to * (t * t * t),
+                        );
+                    }
+                    current = to;
+                }
+                PathSegment::Close => {
+                    self.end_sub_path(true);
+                    current = sub_path_start;
+                }
+            }
+        }
+        self.end_sub_path(false);
+    }
+}
+
+fn segment_count(error: f32, tolerance: f32) -> usize {
+    const MAX_SEGMENTS: usize = 256;
+    let n = (error / tolerance).sqrt().ceil();
+    if n.is_finite() {
+        (n as usize).clamp(1, MAX_SEGMENTS)
+    } else {
+        1
+    }
+}
+
+#[test]
+fn test_flatten() {
+    let path = Path::new()
+        .move_to(Vec2::new(0.0, 0.0))
+        .line_to(Vec2::new(10.0, 0.0))
+        .line_to(Vec2::new(10.0, 0.0))
+        .quad_to(Vec2::new(10.0, 10.0), Vec2::new(0.0, 10.0))
+        .close()
+        .move_to(Vec2::new(20.0, 0.0))
+        .line_to(Vec2::new(30.0, 0.0));
+
+    let mut flattened = FlattenedPath::default();
+    flattened.flatten(&path, 0.1);
+
+    assert_eq!(flattened.sub_paths.len(), 2);
+
+    let first = flattened.sub_path_points(&flattened.sub_paths[0]);
+    assert!(flattened.sub_paths[0].closed);
+    assert_eq!(first[0], Vec2::new(0.0, 0.0));
+    assert_eq!(first[1], Vec2::new(10.0, 0.0));
+    assert_eq!(*first.last().unwrap(), Vec2::new(0.0, 10.0));
+    // NOTE: all points of the quad must be within its control polygon.
+    assert!(first.len() > 4);
+    assert!(
+        first
+            .iter()
+            .all(|p| p.x >= 0.0 && p.x <= 10.0 && p.y >= 0.0 && p.y <= 10.0)
+    );
+
+    let second = flattened.sub_path_points(&flattened.sub_paths[1]);
+    assert!(!flattened.sub_paths[1].closed);
+    assert_eq!(second, &[Vec2::new(20.0, 0.0), Vec2::new(30.0, 0.0)]);
+}
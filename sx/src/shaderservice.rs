@@ -2,8 +2,10 @@ use std::fmt;
 
 use mars::{
     alloc,
+    array::ResizableArray,
     sortedarray::{SpillableSortedArrayMap, SpillableSortedArraySet},
     string::FixedString,
+    unmanagedhandlearray::{Handle, UnmanagedHandleArray},
 };
 
 use crate::TextureHandle;
@@ -107,9 +109,12 @@ pub enum ShaderStageKind {
     Fragment,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ShaderStageDesc {
     pub source: ShaderSource,
+    /// appended after `source`; allows to plug custom code into a stage defined by the renderer
+    /// (e.g. a function that renderer's `main` calls). must use the same glsl version/profile.
+    pub snippet: Option<ShaderSource>,
     pub defines: ShaderDefines,
 }
 
@@ -123,7 +128,7 @@ pub struct ShaderStage {
 // handles / already created stages?
 //
 // TODO: ShaderDesc might need to change to be able to accommodate compute?
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ShaderDesc {
     pub vertex_stage: ShaderStageDesc,
     pub fragment_stage: ShaderStageDesc,
@@ -132,6 +137,52 @@ pub struct ShaderDesc {
     pub uniforms: ShaderUniformDescs,
 }
 
-// TODO: shader service
-//   must make it possible to attach shaders (fragment-only, or full pipeline thing) to draw buffer
-//   shapes?
+// ----
+// shader service
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShaderHandle(Handle<ShaderDesc>);
+
+/// owns descs of custom shaders that can be attached to draw buffer shapes with
+/// [`crate::Fill::Shader`].
+///
+/// NOTE: descs are meant to be produced by the renderer (e.g. by splicing a fragment snippet into
+///   renderer's own shader); renderer compiles them lazily when they are first drawn with.
+///
+/// NOTE: handles get reused. renderer must drain deleted handles (see
+///   [`ShaderService::drain_deleted`]) before drawing, otherwise a new shader may end up with a
+///   program of a deleted one.
+#[derive(Default)]
+pub struct ShaderService {
+    descs: UnmanagedHandleArray<ShaderDesc>,
+    deleted: ResizableArray<ShaderHandle, alloc::Global>,
+}
+
+impl ShaderService {
+    pub fn create(&mut self, desc: ShaderDesc) -> ShaderHandle {
+        log::debug!("ShaderService::create: {desc:?}");
+
+        ShaderHandle(self.descs.push(alloc::Global, desc))
+    }
+
+    pub fn get(&self, handle: ShaderHandle) -> &ShaderDesc {
+        &self.descs[handle.0]
+    }
+
+    pub fn delete(&mut self, handle: ShaderHandle) {
+        log::debug!("ShaderService::delete: ({handle:?})");
+
+        self.descs.remove(handle.0);
+        self.deleted.push(handle);
+    }
+
+    pub fn drain_deleted(&mut self) -> impl Iterator<Item = ShaderHandle> {
+        self.deleted.drain(..)
+    }
+}
+
+impl Drop for ShaderService {
+    fn drop(&mut self) {
+        self.descs.deinit(alloc::Global);
+    }
+}
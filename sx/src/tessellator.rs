@@ -0,0 +1,962 @@
+use std::f32::consts::PI;
+
+use crate::{FlattenedPath, Vec2};
+
+// NOTE: tessellator produces triangles with per-vertex coverage.
+//   anti-aliasing is done with feathering: shapes get a thin fringe of triangles whose outer
+//   vertices have coverage of 0; gpu's interpolation makes for a smooth edge.
+//   this approach is borrowed from dear imgui and nanovg.
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    fn is_inside(self, winding: i32) -> bool {
+        match self {
+            Self::NonZero => winding != 0,
+            Self::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+pub trait TessellationOutput {
+    /// coverage is in 0..=1 range.
+    fn push_vertex(&mut self, pos: Vec2, coverage: f32) -> u32;
+    fn push_triangle(&mut self, a: u32, b: u32, c: u32);
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StrokeOptions<'a> {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// max ratio of miter length to stroke width; beyond it miter joins fall back to bevel.
+    pub miter_limit: f32,
+    /// alternating lengths of dashes and gaps. empty means solid line.
+    pub dashes: &'a [f32],
+    pub dash_offset: f32,
+    /// width of the anti-aliasing fringe.
+    pub feather: f32,
+    /// max distance between round joins/caps and their approximation.
+    pub tolerance: f32,
+}
+
+impl Default for StrokeOptions<'_> {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+            dashes: &[],
+            dash_offset: 0.0,
+            feather: 1.0,
+            tolerance: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FillOptions {
+    pub rule: FillRule,
+    /// width of the anti-aliasing fringe.
+    pub feather: f32,
+}
+
+impl Default for FillOptions {
+    fn default() -> Self {
+        Self {
+            rule: FillRule::default(),
+            feather: 1.0,
+        }
+    }
+}
+
+/// holds scratch memory. reuse it.
+#[derive(Debug, Default)]
+pub struct Tessellator {
+    dash_points: Vec<Vec2>,
+    edges: Vec<Edge>,
+    ys: Vec<f32>,
+    active: Vec<usize>,
+    spans: Vec<SpanEdge>,
+}
+
+impl Tessellator {
+    pub fn stroke(
+        &mut self,
+        path: &FlattenedPath,
+        options: &StrokeOptions,
+        output: &mut impl TessellationOutput,
+    ) {
+        if options.width <= 0.0 {
+            return;
+        }
+        let stroker = Stroker::new(options);
+        let dashed = !options.dashes.is_empty()
+            && options
+                .dashes
+                .iter()
+                .all(|dash| *dash >= 0.0 && dash.is_finite())
+            && options.dashes.iter().sum::<f32>() > 0.0;
+        for sub_path in path.sub_paths.iter() {
+            let points = path.sub_path_points(sub_path);
+            if dashed {
+                dash_polyline(
+                    points,
+                    sub_path.closed,
+                    options.dashes,
+                    options.dash_offset,
+                    &mut self.dash_points,
+                    |dash| stroker.stroke_polyline(dash, false, output),
+                );
+            } else {
+                stroker.stroke_polyline(points, sub_path.closed, output);
+            }
+        }
+    }
+
+    /// all sub-paths are treated as closed.
+    pub fn fill(
+        &mut self,
+        path: &FlattenedPath,
+        options: &FillOptions,
+        output: &mut impl TessellationOutput,
+    ) {
+        self.edges.clear();
+        for sub_path in path.sub_paths.iter() {
+            let points = path.sub_path_points(sub_path);
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                if let Some(edge) = Edge::new(*a, b) {
+                    self.edges.push(edge);
+                }
+            }
+        }
+        if self.edges.is_empty() {
+            return;
+        }
+
+        self.fill_trapezoids(options.rule, output);
+
+        if options.feather > 0.0 {
+            for sub_path in path.sub_paths.iter() {
+                let points = path.sub_path_points(sub_path);
+                fill_fringe(points, &self.edges, options, output);
+            }
+        }
+    }
+
+    // NOTE: the plane is cut into horizontal slabs at every vertex (and at every intersection of
+    //   edges). within a slab edges do not cross, so inside spans are trapezoids.
+    fn fill_trapezoids(&mut self, rule: FillRule, output: &mut impl TessellationOutput) {
+        self.edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+
+        self.ys.clear();
+        self.ys.extend(
+            self.edges
+                .iter()
+                .flat_map(|edge| [edge.top.y, edge.bottom.y]),
+        );
+        self.ys.sort_by(f32::total_cmp);
+        self.ys.dedup();
+
+        self.active.clear();
+        let mut next_edge = 0;
+        for i in 1..self.ys.len() {
+            let (mut y0, y1) = (self.ys[i - 1], self.ys[i]);
+
+            self.active.retain(|index| self.edges[*index].bottom.y > y0);
+            while next_edge < self.edges.len() && self.edges[next_edge].top.y < y1 {
+                self.active.push(next_edge);
+                next_edge += 1;
+            }
+
+            while y0 < y1 {
+                let ym = find_first_crossing(&self.edges, &self.active, y0, y1);
+                self.spans.clear();
+                self.spans.extend(self.active.iter().map(|index| {
+                    let edge = &self.edges[*index];
+                    SpanEdge {
+                        x0: edge.x_at(y0),
+                        x1: edge.x_at(ym),
+                        winding: edge.winding,
+                    }
+                }));
+                self.spans
+                    .sort_by(|a, b| (a.x0 + a.x1).total_cmp(&(b.x0 + b.x1)));
+
+                let mut winding = 0;
+                let mut span_start: Option<&SpanEdge> = None;
+                for span in self.spans.iter() {
+                    let was_inside = rule.is_inside(winding);
+                    winding += span.winding;
+                    let is_inside = rule.is_inside(winding);
+                    match (was_inside, is_inside) {
+                        (false, true) => span_start = Some(span),
+                        (true, false) => {
+                            let start = span_start.take().unwrap();
+                            let a = output.push_vertex(Vec2::new(start.x0, y0), 1.0);
+                            let b = output.push_vertex(Vec2::new(span.x0, y0), 1.0);
+                            let c = output.push_vertex(Vec2::new(span.x1, ym), 1.0);
+                            let d = output.push_vertex(Vec2::new(start.x1, ym), 1.0);
+                            push_quad(output, a, b, c, d);
+                        }
+                        _ => {}
+                    }
+                }
+
+                y0 = ym;
+            }
+        }
+    }
+}
+
+// fill
+// ----
+
+#[derive(Debug)]
+struct Edge {
+    top: Vec2,
+    bottom: Vec2,
+    /// +1 if goes down, -1 if goes up.
+    winding: i32,
+}
+
+impl Edge {
+    fn new(a: Vec2, b: Vec2) -> Option<Self> {
+        if a.y == b.y {
+            return None;
+        }
+        Some(if a.y < b.y {
+            Self {
+                top: a,
+                bottom: b,
+                winding: 1,
+            }
+        } else {
+            Self {
+                top: b,
+                bottom: a,
+                winding: -1,
+            }
+        })
+    }
+
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        self.top.x + (self.bottom.x - self.top.x) * t
+    }
+}
+
+#[derive(Debug)]
+struct SpanEdge {
+    x0: f32,
+    x1: f32,
+    winding: i32,
+}
+
+/// returns y of the first crossing of active edges within y0..y1, or y1 if there is none.
+fn find_first_crossing(edges: &[Edge], active: &[usize], y0: f32, y1: f32) -> f32 {
+    // NOTE: tiny slabs are not worth splitting, imprecision there is invisible.
+    const MIN_SLAB_HEIGHT: f32 = 1e-3;
+
+    let mut ym = y1;
+    for (i, a) in active.iter().enumerate() {
+        let a = &edges[*a];
+        for b in active[i + 1..].iter() {
+            let b = &edges[*b];
+            let d0 = a.x_at(y0) - b.x_at(y0);
+            let d1 = a.x_at(y1) - b.x_at(y1);
+            if d0 * d1 >= 0.0 {
+                continue;
+            }
+            let y = y0 + (y1 - y0) * (d0 / (d0 - d1));
+            if y - y0 > MIN_SLAB_HEIGHT && y < ym {
+                ym = y;
+            }
+        }
+    }
+    ym
+}
+
+fn winding_at(edges: &[Edge], point: Vec2) -> i32 {
+    edges
+        .iter()
+        .filter(|edge| edge.top.y <= point.y && point.y < edge.bottom.y)
+        .filter(|edge| edge.x_at(point.y) > point.x)
+        .map(|edge| edge.winding)
+        .sum()
+}
+
+fn fill_fringe(
+    points: &[Vec2],
+    edges: &[Edge],
+    options: &FillOptions,
+    output: &mut impl TessellationOutput,
+) {
+    if points.len() < 3 {
+        return;
+    }
+
+    // NOTE: figure out which side of the contour is outside by probing both sides of its longest
+    //   edge. contours that are entirely inside (or outside) of the fill get no fringe.
+    let edge_dir = |i: usize| (points[(i + 1) % points.len()] - points[i]).normalize_or_zero();
+    let longest = (0..points.len())
+        .max_by(|a, b| {
+            let len = |i: usize| (points[(i + 1) % points.len()] - points[i]).length();
+            len(*a).total_cmp(&len(*b))
+        })
+        .unwrap();
+    let mid = (points[longest] + points[(longest + 1) % points.len()]) * 0.5;
+    let probe = edge_dir(longest).perp() * 1e-2;
+    let inside_pos = options.rule.is_inside(winding_at(edges, mid + probe));
+    let inside_neg = options.rule.is_inside(winding_at(edges, mid - probe));
+    let outward = match (inside_pos, inside_neg) {
+        (false, true) => 1.0,
+        (true, false) => -1.0,
+        _ => return,
+    };
+
+    // NOTE: fringe goes outwards only so that it does not overlap the fill (which would be visible
+    //   with translucent colors). shapes get a bit bolder, by half of the feather width.
+    let first = output.push_vertex(points[0], 1.0);
+    let mut prev_inner = first;
+    let mut prev_outer = None;
+    let mut first_outer = 0;
+    for i in 0..points.len() {
+        let n0 = edge_dir((i + points.len() - 1) % points.len()).perp() * outward;
+        let n1 = edge_dir(i).perp() * outward;
+        let m = miter(n0, n1, 2.0);
+        let inner = if i == 0 {
+            first
+        } else {
+            output.push_vertex(points[i], 1.0)
+        };
+        let outer = output.push_vertex(points[i] + m * options.feather, 0.0);
+        if let Some(prev_outer) = prev_outer {
+            push_quad(output, prev_inner, prev_outer, outer, inner);
+        } else {
+            first_outer = outer;
+        }
+        prev_inner = inner;
+        prev_outer = Some(outer);
+    }
+    push_quad(output, prev_inner, prev_outer.unwrap(), first_outer, first);
+}
+
+// stroke
+// ----
+
+/// indices of vertices of a cross-section of the stroke.
+#[derive(Debug, Clone, Copy)]
+struct Section {
+    pos_outer: u32,
+    pos_inner: u32,
+    neg_inner: u32,
+    neg_outer: u32,
+}
+
+struct Stroker {
+    join: LineJoin,
+    cap: LineCap,
+    miter_limit: f32,
+    feather: f32,
+    tolerance: f32,
+    half_width: f32,
+    /// distance from the center to the edge of full coverage.
+    inner: f32,
+    /// distance from the center to the edge of zero coverage.
+    outer: f32,
+    coverage: f32,
+}
+
+impl Stroker {
+    fn new(options: &StrokeOptions) -> Self {
+        let feather = options.feather.max(0.0);
+        let half_width = options.width * 0.5;
+        // NOTE: fringe is centered on the edge of the stroke.
+        //   strokes that are thinner than the feather are made wider but fainter.
+        let inner = (half_width - feather * 0.5).max(0.0);
+        let coverage = if feather > 0.0 {
+            (options.width / feather).min(1.0)
+        } else {
+            1.0
+        };
+        Self {
+            join: options.join,
+            cap: options.cap,
+            miter_limit: options.miter_limit,
+            feather,
+            tolerance: options.tolerance,
+            half_width,
+            inner,
+            outer: inner + feather,
+            coverage,
+        }
+    }
+
+    fn push_section(
+        &self,
+        output: &mut impl TessellationOutput,
+        center: Vec2,
+        normal: Vec2,
+        coverage: f32,
+    ) -> Section {
+        Section {
+            pos_outer: output.push_vertex(center + normal * self.outer, 0.0),
+            pos_inner: output.push_vertex(center + normal * self.inner, coverage),
+            neg_inner: output.push_vertex(center - normal * self.inner, coverage),
+            neg_outer: output.push_vertex(center - normal * self.outer, 0.0),
+        }
+    }
+
+    fn connect(&self, output: &mut impl TessellationOutput, a: Section, b: Section) {
+        push_quad(output, a.pos_outer, a.pos_inner, b.pos_inner, b.pos_outer);
+        push_quad(output, a.pos_inner, a.neg_inner, b.neg_inner, b.pos_inner);
+        push_quad(output, a.neg_inner, a.neg_outer, b.neg_outer, b.neg_inner);
+    }
+
+    fn arc_steps(&self, angle: f32) -> usize {
+        let radius = self.outer.max(1e-3);
+        let step = 2.0 * (1.0 - (self.tolerance / radius).min(1.0)).acos();
+        ((angle.abs() / step.max(1e-2)).ceil() as usize).clamp(1, 64)
+    }
+
+    /// emits a fan from `pivot` to an arc around `center` that starts at `from` direction and
+    /// spans `angle`.
+    fn push_arc(
+        &self,
+        output: &mut impl TessellationOutput,
+        pivot: u32,
+        center: Vec2,
+        from: Vec2,
+        angle: f32,
+    ) {
+        let steps = self.arc_steps(angle);
+        let start = from.y.atan2(from.x);
+        let mut prev: Option<(u32, u32)> = None;
+        for i in 0..=steps {
+            let a = start + angle * (i as f32 / steps as f32);
+            let dir = Vec2::new(a.cos(), a.sin());
+            let inner = output.push_vertex(center + dir * self.inner, self.coverage);
+            let outer = output.push_vertex(center + dir * self.outer, 0.0);
+            if let Some((prev_inner, prev_outer)) = prev {
+                output.push_triangle(pivot, prev_inner, inner);
+                push_quad(output, prev_inner, prev_outer, outer, inner);
+            }
+            prev = Some((inner, outer));
+        }
+    }
+
+    /// returns section where the body of the stroke starts (`end` false) or ends (`end` true).
+    fn push_cap(
+        &self,
+        output: &mut impl TessellationOutput,
+        point: Vec2,
+        dir: Vec2,
+        end: bool,
+    ) -> Section {
+        // NOTE: `away` points out of the stroke.
+        let away = if end { dir } else { -dir };
+        let normal = dir.perp();
+        match self.cap {
+            LineCap::Butt | LineCap::Square => {
+                let extension = match self.cap {
+                    LineCap::Square => self.half_width,
+                    _ => 0.0,
+                };
+                let body = self.push_section(
+                    output,
+                    point + away * (extension - self.feather * 0.5),
+                    normal,
+                    self.coverage,
+                );
+                let fringe = self.push_section(
+                    output,
+                    point + away * (extension + self.feather * 0.5),
+                    normal,
+                    0.0,
+                );
+                if end {
+                    self.connect(output, body, fringe);
+                } else {
+                    self.connect(output, fringe, body);
+                }
+                body
+            }
+            LineCap::Round => {
+                let body = self.push_section(output, point, normal, self.coverage);
+                let pivot = output.push_vertex(point, self.coverage);
+                // NOTE: perp rotates by 90 degrees in the direction from normal to `away`
+                //   (perp of normal is -dir).
+                let from = if end { -normal } else { normal };
+                self.push_arc(output, pivot, point, from, PI);
+                body
+            }
+        }
+    }
+
+    /// returns (end section of incoming segment, start section of outgoing segment).
+    fn push_join(
+        &self,
+        output: &mut impl TessellationOutput,
+        point: Vec2,
+        d0: Vec2,
+        d1: Vec2,
+        max_inner_offset: f32,
+    ) -> (Section, Section) {
+        let n0 = d0.perp();
+        let n1 = d1.perp();
+        let cross = d0.x * d1.y - d0.y * d1.x;
+        let dot = d0.dot(d1);
+
+        // straight enough.
+        if cross.abs() < 1e-4 && dot > 0.0 {
+            let section = self.push_section(output, point, n0, self.coverage);
+            return (section, section);
+        }
+
+        let m = miter(n0, n1, f32::INFINITY);
+        // NOTE: when line turns back miter is undefined.
+        let turns_back = 1.0 + dot < 1e-4;
+        if self.join == LineJoin::Miter && !turns_back && m.length() <= self.miter_limit {
+            let section = Section {
+                pos_outer: output.push_vertex(point + m * self.outer, 0.0),
+                pos_inner: output.push_vertex(point + m * self.inner, self.coverage),
+                neg_inner: output.push_vertex(point - m * self.inner, self.coverage),
+                neg_outer: output.push_vertex(point - m * self.outer, 0.0),
+            };
+            return (section, section);
+        }
+
+        // NOTE: turning towards the positive normal makes negative side the outer one.
+        let outward = if cross > 0.0 { -1.0 } else { 1.0 };
+        let o0 = n0 * outward;
+        let o1 = n1 * outward;
+
+        // NOTE: inner corner is where offset segments intersect. it must not go past the
+        //   neighbouring points though, in case of very sharp turns on short segments.
+        let mut mi = -m * outward;
+        let along = mi.dot(d0).abs() * self.outer;
+        if along > max_inner_offset {
+            mi = mi * (max_inner_offset / along);
+        }
+        let corner_inner = output.push_vertex(point + mi * self.inner, self.coverage);
+        let corner_outer = output.push_vertex(point + mi * self.outer, 0.0);
+
+        let a_inner = output.push_vertex(point + o0 * self.inner, self.coverage);
+        let a_outer = output.push_vertex(point + o0 * self.outer, 0.0);
+        let b_inner = output.push_vertex(point + o1 * self.inner, self.coverage);
+        let b_outer = output.push_vertex(point + o1 * self.outer, 0.0);
+
+        match self.join {
+            LineJoin::Round => {
+                self.push_arc(output, corner_inner, point, o0, cross.atan2(dot));
+            }
+            LineJoin::Miter | LineJoin::Bevel => {
+                output.push_triangle(corner_inner, a_inner, b_inner);
+                push_quad(output, a_inner, a_outer, b_outer, b_inner);
+            }
+        }
+
+        if outward > 0.0 {
+            (
+                Section {
+                    pos_outer: a_outer,
+                    pos_inner: a_inner,
+                    neg_inner: corner_inner,
+                    neg_outer: corner_outer,
+                },
+                Section {
+                    pos_outer: b_outer,
+                    pos_inner: b_inner,
+                    neg_inner: corner_inner,
+                    neg_outer: corner_outer,
+                },
+            )
+        } else {
+            (
+                Section {
+                    pos_outer: corner_outer,
+                    pos_inner: corner_inner,
+                    neg_inner: a_inner,
+                    neg_outer: a_outer,
+                },
+                Section {
+                    pos_outer: corner_outer,
+                    pos_inner: corner_inner,
+                    neg_inner: b_inner,
+                    neg_outer: b_outer,
+                },
+            )
+        }
+    }
+
+    fn stroke_polyline(&self, points: &[Vec2], closed: bool, output: &mut impl TessellationOutput) {
+        let n = points.len();
+        if n < 2 {
+            return;
+        }
+        let closed = closed && n > 2;
+
+        let segment = |i: usize| points[(i + 1) % n] - points[i];
+        let dir = |i: usize| segment(i).normalize_or_zero();
+        let len = |i: usize| segment(i).length();
+
+        let join_at = |output: &mut _, i: usize| {
+            let prev = (i + n - 1) % n;
+            self.push_join(output, points[i], dir(prev), dir(i), len(prev).min(len(i)))
+        };
+
+        if closed {
+            let (first_end, mut prev) = join_at(output, 0);
+            for i in 1..n {
+                let (end, start) = join_at(output, i);
+                self.connect(output, prev, end);
+                prev = start;
+            }
+            self.connect(output, prev, first_end);
+        } else {
+            let mut prev = self.push_cap(output, points[0], dir(0), false);
+            for i in 1..n - 1 {
+                let (end, start) = join_at(output, i);
+                self.connect(output, prev, end);
+                prev = start;
+            }
+            let end = self.push_cap(output, points[n - 1], dir(n - 2), true);
+            self.connect(output, prev, end);
+        }
+    }
+}
+
+/// splits polyline into dashes and calls `f` for each of them.
+fn dash_polyline(
+    points: &[Vec2],
+    closed: bool,
+    dashes: &[f32],
+    dash_offset: f32,
+    scratch: &mut Vec<Vec2>,
+    mut f: impl FnMut(&[Vec2]),
+) {
+    if points.len() < 2 {
+        return;
+    }
+
+    // NOTE: odd number of dashes is repeated twice so that dashes and gaps alternate, same as in
+    //   svg.
+    let pattern_len = if dashes.len().is_multiple_of(2) {
+        dashes.len()
+    } else {
+        dashes.len() * 2
+    };
+    let total: f32 = dashes.iter().sum::<f32>() * (pattern_len / dashes.len()) as f32;
+
+    let mut index = 0;
+    let mut remaining = dashes[0];
+    let mut offset = dash_offset.rem_euclid(total);
+    while offset > 0.0 {
+        if offset >= remaining {
+            offset -= remaining;
+            index = (index + 1) % pattern_len;
+            remaining = dashes[index % dashes.len()];
+        } else {
+            remaining -= offset;
+            offset = 0.0;
+        }
+    }
+
+    scratch.clear();
+    if index % 2 == 0 {
+        scratch.push(points[0]);
+    }
+
+    let segment_count = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    for i in 0..segment_count {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        let dir = (b - a).normalize_or_zero();
+        let mut pos = a;
+        let mut segment_remaining = (b - a).length();
+        while segment_remaining > 0.0 {
+            let on = index % 2 == 0;
+            if remaining >= segment_remaining {
+                remaining -= segment_remaining;
+                segment_remaining = 0.0;
+                if on {
+                    scratch.push(b);
+                }
+            } else {
+                pos += dir * remaining;
+                segment_remaining -= remaining;
+                if on {
+                    scratch.push(pos);
+                    f(scratch);
+                    scratch.clear();
+                } else {
+                    scratch.push(pos);
+                }
+                index = (index + 1) % pattern_len;
+                remaining = dashes[index % dashes.len()];
+            }
+        }
+    }
+    if index % 2 == 0 {
+        f(scratch);
+    }
+}
+
+// utils
+// ----
+
+fn push_quad(output: &mut impl TessellationOutput, a: u32, b: u32, c: u32, d: u32) {
+    output.push_triangle(a, b, c);
+    output.push_triangle(a, c, d);
+}
+
+/// returns offset direction for a corner between edges with normals `n0` and `n1` such that
+/// offsetting by it moves both edges by 1. its length is clamped to `limit`.
+fn miter(n0: Vec2, n1: Vec2, limit: f32) -> Vec2 {
+    let m = (n0 + n1) / (1.0 + n0.dot(n1)).max(1e-6);
+    let len = m.length();
+    if len > limit { m * (limit / len) } else { m }
+}
+
+#[cfg(test)]
+#[derive(Debug, Default)]
+struct TestOutput {
+    vertices: Vec<(Vec2, f32)>,
+    triangles: Vec<[u32; 3]>,
+}
+
+#[cfg(test)]
+impl TessellationOutput for TestOutput {
+    fn push_vertex(&mut self, pos: Vec2, coverage: f32) -> u32 {
+        self.vertices.push((pos, coverage));
+        self.vertices.len() as u32 - 1
+    }
+
+    fn push_triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.triangles.push([a, b, c]);
+    }
+}
+
+#[cfg(test)]
+impl TestOutput {
+    /// sums area of triangles weighted by average coverage of their vertices.
+    fn covered_area(&self) -> f32 {
+        self.triangles
+            .iter()
+            .map(|[a, b, c]| {
+                let (a, ca) = self.vertices[*a as usize];
+                let (b, cb) = self.vertices[*b as usize];
+                let (c, cc) = self.vertices[*c as usize];
+                let ab = b - a;
+                let ac = c - a;
+                let area = (ab.x * ac.y - ab.y * ac.x).abs() * 0.5;
+                area * (ca + cb + cc) / 3.0
+            })
+            .sum()
+    }
+
+    fn is_covered(&self, point: Vec2) -> bool {
+        self.triangles.iter().any(|[a, b, c]| {
+            let (a, _) = self.vertices[*a as usize];
+            let (b, _) = self.vertices[*b as usize];
+            let (c, _) = self.vertices[*c as usize];
+            let side =
+                |p: Vec2, q: Vec2| (q.x - p.x) * (point.y - p.y) - (q.y - p.y) * (point.x - p.x);
+            let (s0, s1, s2) = (side(a, b), side(b, c), side(c, a));
+            // NOTE: degenerate triangles (e.g. fringes of zero width) cover nothing.
+            let degenerate = ((b - a).x * (c - a).y - (b - a).y * (c - a).x).abs() < 1e-6;
+            !degenerate
+                && ((s0 >= 0.0 && s1 >= 0.0 && s2 >= 0.0) || (s0 <= 0.0 && s1 <= 0.0 && s2 <= 0.0))
+        })
+    }
+}
+
+#[test]
+fn test_fill_rules() {
+    use crate::Path;
+
+    // NOTE: two nested squares wound the same way.
+    let square = |path: Path, min: f32, max: f32| {
+        path.move_to(Vec2::new(min, min))
+            .line_to(Vec2::new(max, min))
+            .line_to(Vec2::new(max, max))
+            .line_to(Vec2::new(min, max))
+            .close()
+    };
+    let path = square(square(Path::new(), 0.0, 10.0), 3.0, 7.0);
+    let mut flattened = FlattenedPath::default();
+    flattened.flatten(&path, 0.1);
+
+    let mut tessellator = Tessellator::default();
+    let options = |rule| FillOptions { rule, feather: 0.0 };
+
+    let mut output = TestOutput::default();
+    tessellator.fill(&flattened, &options(FillRule::NonZero), &mut output);
+    assert!((output.covered_area() - 100.0).abs() < 1e-3);
+    assert!(output.is_covered(Vec2::new(5.0, 5.0)));
+
+    let mut output = TestOutput::default();
+    tessellator.fill(&flattened, &options(FillRule::EvenOdd), &mut output);
+    assert!((output.covered_area() - 84.0).abs() < 1e-3);
+    assert!(!output.is_covered(Vec2::new(5.0, 5.0)));
+    assert!(output.is_covered(Vec2::new(1.0, 5.0)));
+}
+
+#[test]
+fn test_fill_self_intersecting() {
+    use crate::Path;
+
+    // NOTE: bow tie; the two triangles meet at (5, 5).
+    let path = Path::new()
+        .move_to(Vec2::new(0.0, 0.0))
+        .line_to(Vec2::new(10.0, 10.0))
+        .line_to(Vec2::new(10.0, 0.0))
+        .line_to(Vec2::new(0.0, 10.0))
+        .close();
+    let mut flattened = FlattenedPath::default();
+    flattened.flatten(&path, 0.1);
+
+    let mut output = TestOutput::default();
+    Tessellator::default().fill(
+        &flattened,
+        &FillOptions {
+            rule: FillRule::NonZero,
+            feather: 0.0,
+        },
+        &mut output,
+    );
+    assert!((output.covered_area() - 50.0).abs() < 1e-3);
+    assert!(output.is_covered(Vec2::new(2.0, 5.0)));
+    assert!(!output.is_covered(Vec2::new(5.0, 2.0)));
+}
+
+#[test]
+fn test_stroke_diagonal_line() {
+    use crate::Path;
+
+    let path = Path::new()
+        .move_to(Vec2::new(0.0, 0.0))
+        .line_to(Vec2::new(10.0, 10.0));
+    let mut flattened = FlattenedPath::default();
+    flattened.flatten(&path, 0.1);
+
+    let mut output = TestOutput::default();
+    let options = StrokeOptions {
+        width: 2.0,
+        feather: 0.0,
+        ..Default::default()
+    };
+    Tessellator::default().stroke(&flattened, &options, &mut output);
+
+    let length = 200.0f32.sqrt();
+    assert!((output.covered_area() - length * 2.0).abs() < 1e-3);
+    // NOTE: stroke must follow the line, not its bounding box.
+    assert!(output.is_covered(Vec2::new(5.0, 5.0)));
+    assert!(!output.is_covered(Vec2::new(8.0, 2.0)));
+}
+
+#[test]
+fn test_stroke_joins_and_caps() {
+    use crate::Path;
+
+    let path = Path::new()
+        .move_to(Vec2::new(0.0, 0.0))
+        .line_to(Vec2::new(10.0, 0.0))
+        .line_to(Vec2::new(10.0, 10.0));
+    let mut flattened = FlattenedPath::default();
+    flattened.flatten(&path, 0.1);
+
+    let mut tessellator = Tessellator::default();
+    let stroke = |tessellator: &mut Tessellator, join, cap| {
+        let mut output = TestOutput::default();
+        let options = StrokeOptions {
+            width: 2.0,
+            join,
+            cap,
+            feather: 0.0,
+            tolerance: 0.001,
+            ..Default::default()
+        };
+        tessellator.stroke(&flattened, &options, &mut output);
+        output
+    };
+
+    // NOTE: miter join fills the corner square, bevel cuts half of it.
+    let miter = stroke(&mut tessellator, LineJoin::Miter, LineCap::Butt);
+    assert!((miter.covered_area() - 40.0).abs() < 1e-3);
+    assert!(miter.is_covered(Vec2::new(10.5, -0.9)));
+    let bevel = stroke(&mut tessellator, LineJoin::Bevel, LineCap::Butt);
+    assert!((bevel.covered_area() - 39.5).abs() < 1e-3);
+    assert!(!bevel.is_covered(Vec2::new(10.5, -0.9)));
+    let round = stroke(&mut tessellator, LineJoin::Round, LineCap::Butt);
+    assert!((round.covered_area() - (39.0 + PI * 0.25)).abs() < 1e-2);
+
+    let square = stroke(&mut tessellator, LineJoin::Miter, LineCap::Square);
+    assert!((square.covered_area() - 44.0).abs() < 1e-3);
+    let round = stroke(&mut tessellator, LineJoin::Miter, LineCap::Round);
+    assert!((round.covered_area() - (40.0 + PI)).abs() < 1e-2);
+}
+
+#[test]
+fn test_stroke_dashes() {
+    use crate::Path;
+
+    let path = Path::new()
+        .move_to(Vec2::new(0.0, 0.0))
+        .line_to(Vec2::new(10.0, 0.0));
+    let mut flattened = FlattenedPath::default();
+    flattened.flatten(&path, 0.1);
+
+    let mut output = TestOutput::default();
+    let options = StrokeOptions {
+        width: 2.0,
+        dashes: &[3.0, 1.0],
+        dash_offset: 1.0,
+        feather: 0.0,
+        ..Default::default()
+    };
+    Tessellator::default().stroke(&flattened, &options, &mut output);
+
+    // NOTE: dashes are 0..2, 3..6, 7..10.
+    assert!((output.covered_area() - 16.0).abs() < 1e-3);
+    assert!(output.is_covered(Vec2::new(1.0, 0.0)));
+    assert!(!output.is_covered(Vec2::new(2.5, 0.0)));
+    assert!(output.is_covered(Vec2::new(4.0, 0.0)));
+    assert!(!output.is_covered(Vec2::new(6.5, 0.0)));
+
+    // NOTE: flattening drops degenerate sub-paths, but dashing must not rely on that.
+    let mut scratch = Vec::new();
+    for points in [&[][..], &[Vec2::new(1.0, 1.0)]] {
+        dash_polyline(points, false, &[3.0, 1.0], 0.0, &mut scratch, |_| {
+            panic!("unexpected dash")
+        });
+    }
+}
@@ -10,10 +10,18 @@ const PROTOCOLS: &[&str] = &[
     "cursor-shape-v1.xml",
     #[cfg(feature = "fractional-scale-v1")]
     "fractional-scale-v1.xml",
+    #[cfg(feature = "idle-inhibit-unstable-v1")]
+    "idle-inhibit-unstable-v1.xml",
     #[cfg(feature = "linux-dmabuf-v1")]
     "linux-dmabuf-v1.xml",
+    #[cfg(feature = "pointer-constraints-unstable-v1")]
+    "pointer-constraints-unstable-v1.xml",
     #[cfg(feature = "pointer-gestures-unstable-v1")]
     "pointer-gestures-unstable-v1.xml",
+    #[cfg(feature = "presentation-time")]
+    "presentation-time.xml",
+    #[cfg(feature = "relative-pointer-unstable-v1")]
+    "relative-pointer-unstable-v1.xml",
     #[cfg(feature = "tablet-v2")]
     "tablet-v2.xml",
     #[cfg(feature = "viewporter")]
@@ -22,6 +30,10 @@ const PROTOCOLS: &[&str] = &[
     "wlr-layer-shell-unstable-v1.xml",
     #[cfg(feature = "wlr-screencopy-unstable-v1")]
     "wlr-screencopy-unstable-v1.xml",
+    #[cfg(feature = "xdg-activation-v1")]
+    "xdg-activation-v1.xml",
+    #[cfg(feature = "xdg-output-unstable-v1")]
+    "xdg-output-unstable-v1.xml",
     #[cfg(feature = "xdg-shell")]
     "xdg-shell.xml",
 ];
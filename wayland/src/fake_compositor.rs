@@ -0,0 +1,197 @@
+// wire protocol core of stand-in compositors for tests. speaks just enough of the wayland wire
+// protocol to receive requests (with fds) and to send events; requests themselves are handled by
+// protocol-specific handlers that live next to the tests that use them.
+//
+// NOTE: requests that are not expected are supposed to make handlers panic; that drops the
+// connection and the client sees a dispatch error.
+
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::io::Write as _;
+use std::mem;
+use std::os::fd::{AsRawFd as _, FromRawFd as _, OwnedFd};
+use std::os::unix::net::UnixStream;
+use std::thread;
+
+pub trait FakeCompositorHandler {
+    fn handle_request(&mut self, conn: &mut FakeConnection, id: u32, opcode: u16, args: Args);
+}
+
+/// returns client's end of the connection.
+pub fn spawn_fake_compositor<H>(mut handler: H) -> (OwnedFd, thread::JoinHandle<()>)
+where
+    H: FakeCompositorHandler + Send + 'static,
+{
+    let (client, server) = UnixStream::pair().expect("could not create socket pair");
+    let handle = thread::spawn(move || {
+        let mut conn = FakeConnection {
+            stream: server,
+            buf: Vec::new(),
+            out: Vec::new(),
+            fds: VecDeque::new(),
+        };
+        while conn.recv() {
+            while let Some((id, opcode, args)) = conn.next_message() {
+                handler.handle_request(
+                    &mut conn,
+                    id,
+                    opcode,
+                    Args {
+                        data: &args,
+                        pos: 0,
+                    },
+                );
+            }
+            conn.flush();
+        }
+    });
+    (OwnedFd::from(client), handle)
+}
+
+pub enum Arg<'a> {
+    Uint(u32),
+    Int(i32),
+    Str(&'a str),
+    Array(&'a [u8]),
+}
+
+pub struct Args<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Args<'_> {
+    pub fn uint(&mut self) -> u32 {
+        let value = u32::from_ne_bytes(self.data[self.pos..self.pos + 4].try_into().unwrap());
+        self.pos += 4;
+        value
+    }
+
+    pub fn int(&mut self) -> i32 {
+        self.uint() as i32
+    }
+
+    pub fn string(&mut self) -> String {
+        // NOTE: length includes nul terminator; data is padded to 4 bytes.
+        let len = self.uint() as usize;
+        let string = str::from_utf8(&self.data[self.pos..self.pos + len - 1])
+            .expect("invalid string")
+            .to_string();
+        self.pos += len.next_multiple_of(4);
+        string
+    }
+}
+
+pub struct FakeConnection {
+    stream: UnixStream,
+    buf: Vec<u8>,
+    /// events are flushed after all received requests are handled (like real compositors do).
+    /// otherwise client may disconnect after seeing wl_callback.done while delete_id is still
+    /// being sent.
+    out: Vec<u8>,
+    fds: VecDeque<OwnedFd>,
+}
+
+impl FakeConnection {
+    /// returns false when client disconnects.
+    fn recv(&mut self) -> bool {
+        let mut data = [0u8; 4096];
+        let mut cmsg = [0u8; 256];
+        let mut iov = libc::iovec {
+            iov_base: data.as_mut_ptr() as *mut c_void,
+            iov_len: data.len(),
+        };
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = cmsg.as_mut_ptr() as *mut c_void;
+        msg.msg_controllen = cmsg.len();
+
+        let n = unsafe { libc::recvmsg(self.stream.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC) };
+        if n <= 0 {
+            return false;
+        }
+        self.buf.extend_from_slice(&data[..n as usize]);
+
+        let mut hdr = unsafe { libc::CMSG_FIRSTHDR(&msg) };
+        while !hdr.is_null() {
+            let (level, ty, len) =
+                unsafe { ((*hdr).cmsg_level, (*hdr).cmsg_type, (*hdr).cmsg_len) };
+            if level == libc::SOL_SOCKET && ty == libc::SCM_RIGHTS {
+                let count = (len - unsafe { libc::CMSG_LEN(0) } as usize) / mem::size_of::<i32>();
+                let fds = unsafe { libc::CMSG_DATA(hdr) } as *const i32;
+                for i in 0..count {
+                    let fd = unsafe { fds.add(i).read_unaligned() };
+                    self.fds.push_back(unsafe { OwnedFd::from_raw_fd(fd) });
+                }
+            }
+            hdr = unsafe { libc::CMSG_NXTHDR(&msg, hdr) };
+        }
+        true
+    }
+
+    fn next_message(&mut self) -> Option<(u32, u16, Vec<u8>)> {
+        if self.buf.len() < 8 {
+            return None;
+        }
+        let id = u32::from_ne_bytes(self.buf[0..4].try_into().unwrap());
+        let size_opcode = u32::from_ne_bytes(self.buf[4..8].try_into().unwrap());
+        let size = (size_opcode >> 16) as usize;
+        if self.buf.len() < size {
+            return None;
+        }
+        let args = self.buf[8..size].to_vec();
+        self.buf.drain(..size);
+        Some((id, size_opcode as u16, args))
+    }
+
+    fn flush(&mut self) {
+        self.stream
+            .write_all(&self.out)
+            .expect("could not send events");
+        self.out.clear();
+    }
+
+    /// fds arrive in the order of requests that carry them.
+    pub fn take_fd(&mut self) -> OwnedFd {
+        self.fds.pop_front().expect("missing fd")
+    }
+
+    pub fn send(&mut self, id: u32, opcode: u16, args: &[Arg]) {
+        let mut body = Vec::new();
+        for arg in args {
+            match *arg {
+                Arg::Uint(value) => body.extend_from_slice(&value.to_ne_bytes()),
+                Arg::Int(value) => body.extend_from_slice(&value.to_ne_bytes()),
+                Arg::Str(value) => {
+                    body.extend_from_slice(&(value.len() as u32 + 1).to_ne_bytes());
+                    body.extend_from_slice(value.as_bytes());
+                    body.push(0);
+                    body.resize(body.len().next_multiple_of(4), 0);
+                }
+                Arg::Array(value) => {
+                    body.extend_from_slice(&(value.len() as u32).to_ne_bytes());
+                    body.extend_from_slice(value);
+                    body.resize(body.len().next_multiple_of(4), 0);
+                }
+            }
+        }
+        let size_opcode = ((8 + body.len() as u32) << 16) | opcode as u32;
+        self.out.extend_from_slice(&id.to_ne_bytes());
+        self.out.extend_from_slice(&size_opcode.to_ne_bytes());
+        self.out.extend_from_slice(&body);
+    }
+
+    /// handler is responsible for forgetting the object.
+    pub fn delete_id(&mut self, id: u32) {
+        // wl_display.delete_id
+        self.send(1, 1, &[Arg::Uint(id)]);
+    }
+
+    /// wl_display.sync; common to all handlers.
+    pub fn sync(&mut self, callback: u32) {
+        // wl_callback.done
+        self.send(callback, 0, &[Arg::Uint(0)]);
+        self.delete_id(callback);
+    }
+}
@@ -2,6 +2,9 @@ mod libwayland_client;
 mod libwayland_cursor;
 mod libwayland_egl;
 
+#[cfg(feature = "test-util")]
+pub mod fake_compositor;
+
 pub use libwayland_client::*;
 pub use libwayland_cursor::*;
 pub use libwayland_egl::*;
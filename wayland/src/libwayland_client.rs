@@ -62,6 +62,7 @@ unsafe impl<T> Sync for SyncWrapper<T> {}
 pub struct ClientApi {
     pub wl_display_cancel_read: unsafe extern "C" fn(display: *mut wl_display),
     pub wl_display_connect: unsafe extern "C" fn(name: *const c_char) -> *mut wl_display,
+    pub wl_display_connect_to_fd: unsafe extern "C" fn(fd: c_int) -> *mut wl_display,
     pub wl_display_disconnect: unsafe extern "C" fn(display: *mut wl_display) -> *mut c_void,
     pub wl_display_dispatch: unsafe extern "C" fn(display: *mut wl_display) -> c_int,
     pub wl_display_dispatch_pending: unsafe extern "C" fn(display: *mut wl_display) -> c_int,
@@ -98,6 +99,7 @@ impl ClientApi {
         Ok(Self {
             wl_display_cancel_read: dynlib.lookup(c"wl_display_cancel_read")?,
             wl_display_connect: dynlib.lookup(c"wl_display_connect")?,
+            wl_display_connect_to_fd: dynlib.lookup(c"wl_display_connect_to_fd")?,
             wl_display_disconnect: dynlib.lookup(c"wl_display_disconnect")?,
             wl_display_dispatch: dynlib.lookup(c"wl_display_dispatch")?,
             wl_display_dispatch_pending: dynlib.lookup(c"wl_display_dispatch_pending")?,
@@ -0,0 +1,41 @@
+//! web backend for `tests/web_stub_host.mjs` to drive under node (with stubbed out dom).
+//!
+//! NOTE: this is a cdylib; it is only meaningful when built for wasm.
+
+#![cfg(target_family = "wasm")]
+
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use window::{Window, WindowAttrs};
+
+thread_local! {
+    static WINDOW: RefCell<Option<Box<dyn Window>>> = const { RefCell::new(None) };
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn init() {
+    let window = window::create_window(WindowAttrs::default())
+        .unwrap_or_else(|err| js::throw_str(&format!("could not create window: {err:?}")));
+    WINDOW.set(Some(window));
+}
+
+/// pumps events and hands them over to the host as `globalThis.pumpedEvents` (debug
+/// representation, one per line).
+#[unsafe(no_mangle)]
+extern "C" fn pump_events() {
+    WINDOW.with_borrow_mut(|window| {
+        let Some(window) = window.as_mut() else {
+            js::throw_str("window is not initialized");
+        };
+        if let Err(err) = window.pump_events(Some(Duration::ZERO)) {
+            js::throw_str(&format!("could not pump events: {err:?}"));
+        }
+        let mut events = String::new();
+        while let Some(event) = window.pop_event() {
+            writeln!(events, "{event:?}").unwrap();
+        }
+        js::GLOBAL.set("pumpedEvents", &js::Value::from_str(&events));
+    });
+}
@@ -4,19 +4,27 @@ use std::hash::{Hash, Hasher};
 use std::io::{PipeReader, PipeWriter, Read as _};
 use std::mem::{self, MaybeUninit};
 use std::os::fd::FromRawFd as _;
+#[cfg(test)]
+use std::os::fd::{IntoRawFd as _, OwnedFd};
 use std::ptr::{NonNull, null, null_mut};
 use std::slice;
 use std::time::Duration;
 
 use anyhow::{Context as _, anyhow};
 use input::{
-    Button, ButtonState, CursorShape, GesturePhase, KeyState, KeyboardEvent, KeyboardEventKind,
-    Keycode, PointerEvent, PointerEventKind, RawKey, Scancode, SurfaceId,
+    Button, ButtonState, CursorShape, GesturePhase, KeyRepeatInfo, KeyState, KeyboardEvent,
+    KeyboardEventKind, Keycode, LogicalPosition, PointerEvent, PointerEventKind, RawKey, Scancode,
+    SurfaceId,
 };
+use mars::dropguard::DropGuard;
 use mars::nohash::{NoBuildHasher, NoHash};
 use raw_window_handle as rwh;
 
-use crate::{ClipboardDataProvider, DEFAULT_LOGICAL_SIZE, Event, Window, WindowAttrs, WindowEvent};
+use crate::{
+    ClipboardDataProvider, DEFAULT_LOGICAL_SIZE, Event, OutputId, OutputInfo, OutputMode,
+    OutputTransform, PointerGrab, PopupAnchor, PopupAttrs, PopupPositioner, PresentationFeedback,
+    PresentationFlags, Window, WindowAttrs, WindowEvent,
+};
 
 // TODO: (xd) consider checking return of wl_proxy_add_listener (xd).
 
@@ -152,6 +160,9 @@ struct Cursor {
     wl_cursor_theme: *mut wayland::wl_cursor_theme,
     wl_surface: *mut wayland::wl_surface,
     scale: f64,
+    // NOTE: wl_cursor and frame are needed for animated cursors.
+    wl_cursor: *mut wayland::wl_cursor,
+    frame: usize,
 }
 
 impl Cursor {
@@ -179,6 +190,8 @@ impl Cursor {
             wl_cursor_theme,
             wl_surface,
             scale,
+            wl_cursor: null_mut(),
+            frame: 0,
         })
     }
 
@@ -201,31 +214,64 @@ impl Cursor {
 
         self.wl_cursor_theme = load_cursor_theme(&self.libwayland_cursor, wl_shm, scale)?;
         self.scale = scale;
+        // NOTE: wl_cursor belonged to the destroyed theme.
+        self.wl_cursor = null_mut();
 
         Ok(())
     }
 
+    /// if returned value is Some - cursor is animated and [`Cursor::advance_frame`] must be called
+    /// after returned delay.
     fn set_shape(
-        &self,
+        &mut self,
         libwayland_client: &wayland::ClientApi,
         wl_pointer: *mut wayland::wl_pointer,
         name: &'static CStr,
         serial: u32,
-    ) -> anyhow::Result<()> {
-        assert!(!wl_pointer.is_null());
-        assert!(serial != 0); // NOTE: pretty certain that 0 is not a valid serial.
-
-        let cursor = unsafe {
+    ) -> anyhow::Result<Option<Duration>> {
+        let wl_cursor = unsafe {
             (self.libwayland_cursor.wl_cursor_theme_get_cursor)(self.wl_cursor_theme, name.as_ptr())
         };
-        if cursor.is_null() {
+        if wl_cursor.is_null() {
             return Err(anyhow!("could not get cursor {name:?}"));
         };
 
-        let cursor = unsafe { &*cursor };
+        self.wl_cursor = wl_cursor;
+        self.frame = 0;
+        self.attach_frame(libwayland_client, wl_pointer, serial)
+    }
+
+    /// see [`Cursor::set_shape`].
+    fn advance_frame(
+        &mut self,
+        libwayland_client: &wayland::ClientApi,
+        wl_pointer: *mut wayland::wl_pointer,
+        serial: u32,
+    ) -> anyhow::Result<Option<Duration>> {
+        if self.wl_cursor.is_null() {
+            return Ok(None);
+        }
+        let image_count = unsafe { (*self.wl_cursor).image_count } as usize;
+        self.frame = (self.frame + 1) % image_count.max(1);
+        self.attach_frame(libwayland_client, wl_pointer, serial)
+    }
+
+    fn attach_frame(
+        &self,
+        libwayland_client: &wayland::ClientApi,
+        wl_pointer: *mut wayland::wl_pointer,
+        serial: u32,
+    ) -> anyhow::Result<Option<Duration>> {
+        assert!(!wl_pointer.is_null());
+        assert!(serial != 0); // NOTE: pretty certain that 0 is not a valid serial.
+        assert!(!self.wl_cursor.is_null());
+
+        let cursor = unsafe { &*self.wl_cursor };
         let images = unsafe { slice::from_raw_parts(cursor.images, cursor.image_count as usize) };
 
-        let image_ptr = images[0];
+        let Some(&image_ptr) = images.get(self.frame) else {
+            return Err(anyhow!("cursor has no image {}", self.frame));
+        };
         let image = unsafe { &*image_ptr };
         let image_buffer =
             unsafe { (self.libwayland_cursor.wl_cursor_image_get_buffer)(image_ptr) };
@@ -233,7 +279,8 @@ impl Cursor {
             return Err(anyhow!("could not get image buffer"));
         }
 
-        // TODO: is this correct (correct enough? seems fine on scale_factor = 1.5)?
+        // NOTE: cursor image is rendered at integer (rounded) scale and hotspot is in buffer
+        // pixels. wl_pointer::set_cursor wants hotspot in surface-local (logical) coords.
         let rounded_scale = get_cursor_rounded_scale(self.scale) as f64;
         let hotspot_x = (image.hotspot_x as f64 / rounded_scale).round() as i32;
         let hotspot_y = (image.hotspot_y as f64 / rounded_scale).round() as i32;
@@ -273,10 +320,133 @@ impl Cursor {
             wayland::wl_surface_commit(libwayland_client, self.wl_surface);
         }
 
-        Ok(())
+        // QUOTE: delay: animation delay to next frame (ms)
+        let delay = (images.len() > 1 && image.delay > 0)
+            .then(|| Duration::from_millis(image.delay as u64));
+        Ok(delay)
+    }
+}
+
+/// wl_buffer backed by shared memory (memfd).
+struct ShmBuffer {
+    wl_buffer: *mut wayland::wl_buffer,
+    data: NonNull<u8>,
+    len: usize,
+    size: (u32, u32),
+}
+
+impl ShmBuffer {
+    fn new(
+        libwayland_client: &wayland::ClientApi,
+        wl_shm: *mut wayland::wl_shm,
+        size: (u32, u32),
+        format: u32,
+    ) -> anyhow::Result<Self> {
+        assert!(!wl_shm.is_null());
+        if size.0 == 0 || size.1 == 0 {
+            return Err(anyhow!("shm buffer size must be non-zero"));
+        }
+
+        // NOTE: all formats that are used here are 4 bytes per pixel.
+        let stride = size.0 * 4;
+        let len = stride as usize * size.1 as usize;
+
+        let fd = unsafe { libc::memfd_create(c"shin-shm".as_ptr(), libc::MFD_CLOEXEC) };
+        if fd == -1 {
+            let errno = unsafe { *libc::__errno_location() };
+            return Err(anyhow!("could not create memfd: 0x:{errno:x}"));
+        }
+        // NOTE: pool (and thus buffer) holds its own reference to the fd; it is fine to close it
+        // right after creating the pool.
+        let _fd_guard = DropGuard::new(|| {
+            unsafe { libc::close(fd) };
+        });
+
+        if unsafe { libc::ftruncate(fd, len as libc::off_t) } == -1 {
+            let errno = unsafe { *libc::__errno_location() };
+            return Err(anyhow!("could not truncate memfd: 0x:{errno:x}"));
+        }
+
+        let data = unsafe {
+            libc::mmap(
+                null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        };
+        if data == libc::MAP_FAILED {
+            let errno = unsafe { *libc::__errno_location() };
+            return Err(anyhow!("could not mmap memfd: 0x:{errno:x}"));
+        }
+        let data = NonNull::new(data as *mut u8).expect("mmap succeeded");
+
+        let wl_shm_pool =
+            unsafe { wayland::wl_shm_create_pool(libwayland_client, wl_shm, fd, len as i32) };
+        if wl_shm_pool.is_null() {
+            unsafe { libc::munmap(data.as_ptr() as *mut c_void, len) };
+            return Err(anyhow!("could not create shm pool"));
+        }
+        let wl_buffer = unsafe {
+            wayland::wl_shm_pool_create_buffer(
+                libwayland_client,
+                wl_shm_pool,
+                0,
+                size.0 as i32,
+                size.1 as i32,
+                stride as i32,
+                format,
+            )
+        };
+        // NOTE: buffer keeps the pool alive.
+        unsafe { wayland::wl_shm_pool_destroy(libwayland_client, wl_shm_pool) };
+        if wl_buffer.is_null() {
+            unsafe { libc::munmap(data.as_ptr() as *mut c_void, len) };
+            return Err(anyhow!("could not create shm buffer"));
+        }
+
+        Ok(Self {
+            wl_buffer,
+            data,
+            len,
+            size,
+        })
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.data.as_ptr(), self.len) }
+    }
+
+    fn destroy(self, libwayland_client: &wayland::ClientApi) {
+        unsafe {
+            wayland::wl_buffer_destroy(libwayland_client, self.wl_buffer);
+            libc::munmap(self.data.as_ptr() as *mut c_void, self.len);
+        }
+    }
+}
+
+/// converts non-premultiplied rgba into premultiplied argb8888 (which is little-endian, thus
+/// bytes are stored in bgra order).
+fn copy_rgba_to_argb8888(src: &[u8], dst: &mut [u8]) {
+    assert_eq!(src.len(), dst.len());
+    for (src, dst) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+        let a = src[3] as u32;
+        let premultiply = |c: u8| ((c as u32 * a + 127) / 255) as u8;
+        dst[0] = premultiply(src[2]);
+        dst[1] = premultiply(src[1]);
+        dst[2] = premultiply(src[0]);
+        dst[3] = src[3];
     }
 }
 
+struct CursorImage {
+    wl_surface: *mut wayland::wl_surface,
+    shm_buffer: ShmBuffer,
+    hotspot: (u32, u32),
+}
+
 // https://github.com/torvalds/linux/blob/231825b2e1ff6ba799c5eaf396d3ab2354e37c6b/include/uapi/linux/input-event-codes.h#L76
 #[inline]
 fn map_keyboard_key(key: u32) -> Scancode {
@@ -416,7 +586,9 @@ const EVDEV_OFFSET: u32 = 8;
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum SerialType {
     PointerEnter,
+    PointerButton,
     KeyboardEnter,
+    KeyboardKey,
 }
 
 impl Hash for SerialType {
@@ -427,28 +599,38 @@ impl Hash for SerialType {
 
 impl NoHash for SerialType {}
 
+/// serials of the seat along with surfaces that received the events.
 #[derive(Default)]
 struct SerialTracker {
-    map: HashMap<SerialType, u32, NoBuildHasher<SerialType>>,
+    map: HashMap<SerialType, (u32, SurfaceId), NoBuildHasher<SerialType>>,
+    /// which of user action serials (pointer button, key press) came last.
+    latest_input: Option<SerialType>,
 }
 
 impl SerialTracker {
-    fn update_serial(&mut self, ty: SerialType, serial: u32) {
-        self.map.insert(ty, serial);
+    fn update_serial(&mut self, ty: SerialType, serial: u32, surface_id: SurfaceId) {
+        self.map.insert(ty, (serial, surface_id));
+        if matches!(ty, SerialType::PointerButton | SerialType::KeyboardKey) {
+            self.latest_input = Some(ty);
+        }
     }
 
     fn reset_serial(&mut self, ty: SerialType) {
         self.map.remove(&ty);
+        if self.latest_input == Some(ty) {
+            self.latest_input = None;
+        }
     }
 
     fn get_serial(&self, ty: SerialType) -> Option<u32> {
-        self.map.get(&ty).cloned()
+        self.map.get(&ty).map(|&(serial, _)| serial)
     }
-}
 
-struct KeyRepeatInfo {
-    rate: Duration,
-    delay: Duration,
+    /// serial of the latest pointer button or key press, if it was delivered to the surface.
+    fn get_latest_input_serial(&self, surface_id: SurfaceId) -> Option<u32> {
+        let (serial, input_surface_id) = *self.map.get(&self.latest_input?)?;
+        (input_surface_id == surface_id).then_some(serial)
+    }
 }
 
 struct TimerFD(c_int);
@@ -500,6 +682,79 @@ impl TimerFD {
     }
 }
 
+fn map_popup_anchor(anchor: PopupAnchor) -> u32 {
+    match anchor {
+        PopupAnchor::None => wayland::XDG_POSITIONER_ANCHOR_NONE,
+        PopupAnchor::Top => wayland::XDG_POSITIONER_ANCHOR_TOP,
+        PopupAnchor::Bottom => wayland::XDG_POSITIONER_ANCHOR_BOTTOM,
+        PopupAnchor::Left => wayland::XDG_POSITIONER_ANCHOR_LEFT,
+        PopupAnchor::Right => wayland::XDG_POSITIONER_ANCHOR_RIGHT,
+        PopupAnchor::TopLeft => wayland::XDG_POSITIONER_ANCHOR_TOP_LEFT,
+        PopupAnchor::BottomLeft => wayland::XDG_POSITIONER_ANCHOR_BOTTOM_LEFT,
+        PopupAnchor::TopRight => wayland::XDG_POSITIONER_ANCHOR_TOP_RIGHT,
+        PopupAnchor::BottomRight => wayland::XDG_POSITIONER_ANCHOR_BOTTOM_RIGHT,
+    }
+}
+
+// NOTE: xdg_positioner's gravity enum has exactly the same values as anchor enum, but i don't want
+// to rely on that.
+fn map_popup_gravity(gravity: PopupAnchor) -> u32 {
+    match gravity {
+        PopupAnchor::None => wayland::XDG_POSITIONER_GRAVITY_NONE,
+        PopupAnchor::Top => wayland::XDG_POSITIONER_GRAVITY_TOP,
+        PopupAnchor::Bottom => wayland::XDG_POSITIONER_GRAVITY_BOTTOM,
+        PopupAnchor::Left => wayland::XDG_POSITIONER_GRAVITY_LEFT,
+        PopupAnchor::Right => wayland::XDG_POSITIONER_GRAVITY_RIGHT,
+        PopupAnchor::TopLeft => wayland::XDG_POSITIONER_GRAVITY_TOP_LEFT,
+        PopupAnchor::BottomLeft => wayland::XDG_POSITIONER_GRAVITY_BOTTOM_LEFT,
+        PopupAnchor::TopRight => wayland::XDG_POSITIONER_GRAVITY_TOP_RIGHT,
+        PopupAnchor::BottomRight => wayland::XDG_POSITIONER_GRAVITY_BOTTOM_RIGHT,
+    }
+}
+
+struct Output {
+    wl_output: *mut wayland::wl_output,
+    wl_output_version: u32,
+    zxdg_output_v1: *mut wayland::zxdg_output_v1,
+    info: OutputInfo,
+}
+
+fn output_transform_from_wl(transform: u32) -> OutputTransform {
+    match transform {
+        wayland::WL_OUTPUT_TRANSFORM_90 => OutputTransform::Rotate90,
+        wayland::WL_OUTPUT_TRANSFORM_180 => OutputTransform::Rotate180,
+        wayland::WL_OUTPUT_TRANSFORM_270 => OutputTransform::Rotate270,
+        wayland::WL_OUTPUT_TRANSFORM_FLIPPED => OutputTransform::Flipped,
+        wayland::WL_OUTPUT_TRANSFORM_FLIPPED_90 => OutputTransform::Flipped90,
+        wayland::WL_OUTPUT_TRANSFORM_FLIPPED_180 => OutputTransform::Flipped180,
+        wayland::WL_OUTPUT_TRANSFORM_FLIPPED_270 => OutputTransform::Flipped270,
+        _ => OutputTransform::Normal,
+    }
+}
+
+fn string_from_c_char(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+struct Popup {
+    wl_surface: *mut wayland::wl_surface,
+    xdg_surface: *mut wayland::xdg_surface,
+    xdg_popup: *mut wayland::xdg_popup,
+    wp_viewport: *mut wayland::wp_viewport,
+    // NOTE: parent_xdg_surface is needed to ensure that children are destroyed before parents.
+    parent_xdg_surface: *mut wayland::xdg_surface,
+    // NOTE: xdg_popup::configure is followed by xdg_surface::configure that "commits" it.
+    pending_configure: Option<((i32, i32), (u32, u32))>,
+    logical_size: (u32, u32),
+}
+
 // NOTE: i use re-use this for converting &str into *const c_char when calling ffi funcs.
 // better solution prob would be to have a proper temp allocator for this and any other kinds of
 // stuff.
@@ -538,9 +793,21 @@ pub struct WaylandBackend {
     wl_shm: *mut wayland::wl_shm,
     wp_cursor_shape_manager_v1: *mut wayland::wp_cursor_shape_manager_v1,
     wp_fractional_scale_manager_v1: *mut wayland::wp_fractional_scale_manager_v1,
+    wp_presentation: *mut wayland::wp_presentation,
     wp_viewporter: *mut wayland::wp_viewporter,
+    xdg_activation_v1: *mut wayland::xdg_activation_v1,
     xdg_wm_base: *mut wayland::xdg_wm_base,
+    zwp_idle_inhibit_manager_v1: *mut wayland::zwp_idle_inhibit_manager_v1,
+    zwp_pointer_constraints_v1: *mut wayland::zwp_pointer_constraints_v1,
     zwp_pointer_gestures_v1: *mut wayland::zwp_pointer_gestures_v1,
+    zwp_relative_pointer_manager_v1: *mut wayland::zwp_relative_pointer_manager_v1,
+    zxdg_output_manager_v1: *mut wayland::zxdg_output_manager_v1,
+
+    // outputs
+    // NOTE: there's never going to be many outputs, linear lookups are fine.
+    outputs: Vec<Output>,
+    // NOTE: outputs that window's surface is on.
+    entered_outputs: Vec<OutputId>,
 
     // window
     attrs: WindowAttrs,
@@ -548,6 +815,18 @@ pub struct WaylandBackend {
     xdg_surface: *mut wayland::xdg_surface,
     xdg_toplevel: *mut wayland::xdg_toplevel,
     acked_first_xdg_surface_configure: bool,
+    zwp_idle_inhibitor_v1: *mut wayland::zwp_idle_inhibitor_v1,
+
+    // popups
+    // NOTE: there's never going to be many popups, linear lookups are fine.
+    popups: Vec<Popup>,
+
+    // frame pacing
+    // NOTE: frame_callback is non-null while compositor did not yet tell us that it's a good time
+    // to draw a new frame.
+    frame_callback: *mut wayland::wl_callback,
+    redraw_requested: bool,
+    presentation_clock_id: u32,
 
     // dpi
     wp_fractional_scale_v1: *mut wayland::wp_fractional_scale_v1,
@@ -568,6 +847,11 @@ pub struct WaylandBackend {
     // NOTE: cursor_shape is stored here so that it can be set back to what was requested when
     // pointer re-enders the surface.
     cursor_shape: Option<CursorShape>,
+    // NOTE: cursor_image takes precedence over cursor_shape.
+    cursor_image: Option<CursorImage>,
+    cursor_visible: bool,
+    // NOTE: is used to animate themed cursors (libwayland-cursor).
+    cursor_animation_timerfd: TimerFD,
     // NOTE: only one of `axis_discrete`, `axis_value120` and `axis` values will be used if any are
     // present.
     // index 0 is vertical scroll (wayland::WL_POINTER_AXIS_VERTICAL_SCROLL),
@@ -583,6 +867,10 @@ pub struct WaylandBackend {
     // delta.
     pinch_scale: Option<f64>,
     pinch_fingers: Option<u8>,
+    zwp_relative_pointer_v1: *mut wayland::zwp_relative_pointer_v1,
+    // NOTE: only one of locked or confined pointer may exist at a time.
+    zwp_locked_pointer_v1: *mut wayland::zwp_locked_pointer_v1,
+    zwp_confined_pointer_v1: *mut wayland::zwp_confined_pointer_v1,
 
     // keyboard
     wl_keyboard: *mut wayland::wl_keyboard,
@@ -641,6 +929,32 @@ unsafe extern "C" fn handle_wl_registry_global(
                     3.min(version),
                 ) as _;
             }
+            "wl_output" => {
+                let wl_output_version = 4.min(version);
+                let wl_output: *mut wayland::wl_output = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::wl_output_interface,
+                    wl_output_version,
+                ) as _;
+                if wl_output.is_null() {
+                    log::warn!("could not bind wl output");
+                    return;
+                }
+                (this.libwayland_client.wl_proxy_add_listener)(
+                    wl_output as *mut wayland::wl_proxy,
+                    &WL_OUTPUT_LISTENER as *const wayland::wl_output_listener as _,
+                    data,
+                );
+                this.outputs.push(Output {
+                    wl_output,
+                    wl_output_version,
+                    zxdg_output_v1: null_mut(),
+                    info: OutputInfo::new(OutputId(name)),
+                });
+                this.maybe_get_xdg_outputs();
+            }
             "wl_seat" => {
                 this.wl_seat = wayland::wl_registry_bind(
                     &this.libwayland_client,
@@ -677,6 +991,15 @@ unsafe extern "C" fn handle_wl_registry_global(
                     1.min(version),
                 ) as _;
             }
+            "wp_presentation" => {
+                this.wp_presentation = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::wp_presentation_interface,
+                    1.min(version),
+                ) as _;
+            }
             "wp_viewporter" => {
                 this.wp_viewporter = wayland::wl_registry_bind(
                     &this.libwayland_client,
@@ -686,6 +1009,15 @@ unsafe extern "C" fn handle_wl_registry_global(
                     1.min(version),
                 ) as _;
             }
+            "xdg_activation_v1" => {
+                this.xdg_activation_v1 = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::xdg_activation_v1_interface,
+                    1.min(version),
+                ) as _;
+            }
             "xdg_wm_base" => {
                 this.xdg_wm_base = wayland::wl_registry_bind(
                     &this.libwayland_client,
@@ -695,6 +1027,24 @@ unsafe extern "C" fn handle_wl_registry_global(
                     6.min(version),
                 ) as _;
             }
+            "zwp_idle_inhibit_manager_v1" => {
+                this.zwp_idle_inhibit_manager_v1 = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::zwp_idle_inhibit_manager_v1_interface,
+                    1.min(version),
+                ) as _;
+            }
+            "zwp_pointer_constraints_v1" => {
+                this.zwp_pointer_constraints_v1 = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::zwp_pointer_constraints_v1_interface,
+                    1.min(version),
+                ) as _;
+            }
             "zwp_pointer_gestures_v1" => {
                 this.zwp_pointer_gestures_v1 = wayland::wl_registry_bind(
                     &this.libwayland_client,
@@ -704,6 +1054,25 @@ unsafe extern "C" fn handle_wl_registry_global(
                     3.min(version),
                 ) as _;
             }
+            "zwp_relative_pointer_manager_v1" => {
+                this.zwp_relative_pointer_manager_v1 = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::zwp_relative_pointer_manager_v1_interface,
+                    1.min(version),
+                ) as _;
+            }
+            "zxdg_output_manager_v1" => {
+                this.zxdg_output_manager_v1 = wayland::wl_registry_bind(
+                    &this.libwayland_client,
+                    wl_registry,
+                    name,
+                    &wayland::zxdg_output_manager_v1_interface,
+                    3.min(version),
+                ) as _;
+                this.maybe_get_xdg_outputs();
+            }
             _ => {
                 log::debug!("unused interface: {interface}");
             }
@@ -711,11 +1080,203 @@ unsafe extern "C" fn handle_wl_registry_global(
     }
 }
 
+unsafe extern "C" fn handle_wl_registry_global_remove(
+    data: *mut c_void,
+    _wl_registry: *mut wayland::wl_registry,
+    name: u32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+
+    // NOTE: outputs are the only globals that are expected to come and go.
+    let Some(index) = this
+        .outputs
+        .iter()
+        .position(|output| output.info.id == OutputId(name))
+    else {
+        return;
+    };
+    let output = this.outputs.remove(index);
+
+    if let Some(index) = this
+        .entered_outputs
+        .iter()
+        .position(|output_id| *output_id == output.info.id)
+    {
+        this.entered_outputs.remove(index);
+        this.events
+            .push_back(Event::Window(WindowEvent::OutputLeft {
+                output_id: output.info.id,
+            }));
+    }
+
+    unsafe {
+        if !output.zxdg_output_v1.is_null() {
+            wayland::zxdg_output_v1_destroy(&this.libwayland_client, output.zxdg_output_v1);
+        }
+        if output.wl_output_version >= 3 {
+            wayland::wl_output_release(&this.libwayland_client, output.wl_output);
+        } else {
+            (this.libwayland_client.wl_proxy_destroy)(output.wl_output as *mut wayland::wl_proxy);
+        }
+    }
+}
+
 const WL_REGISTRY_LISTENER: wayland::wl_registry_listener = wayland::wl_registry_listener {
     global: handle_wl_registry_global,
-    global_remove: noop_listener!(),
+    global_remove: handle_wl_registry_global_remove,
+};
+
+// NOTE: output info is updated in place. wl_output.done (that makes updates atomic) is not
+// waited for; outputs() returns a snapshot anyway.
+
+unsafe extern "C" fn handle_wl_output_geometry(
+    data: *mut c_void,
+    wl_output: *mut wayland::wl_output,
+    _x: i32,
+    _y: i32,
+    physical_width: i32,
+    physical_height: i32,
+    _subpixel: i32,
+    make: *const c_char,
+    model: *const c_char,
+    transform: i32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(output) = this.find_output_mut(wl_output) else {
+        return;
+    };
+    // NOTE: x and y are not meaningful for clients; xdg-output's logical position is what
+    // describes layout of the outputs.
+    output.info.physical_size_mm = (physical_width.max(0) as u32, physical_height.max(0) as u32);
+    output.info.make = string_from_c_char(make);
+    output.info.model = string_from_c_char(model);
+    output.info.transform = output_transform_from_wl(transform as u32);
+}
+
+unsafe extern "C" fn handle_wl_output_mode(
+    data: *mut c_void,
+    wl_output: *mut wayland::wl_output,
+    flags: u32,
+    width: i32,
+    height: i32,
+    refresh: i32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(output) = this.find_output_mut(wl_output) else {
+        return;
+    };
+
+    let mode = OutputMode {
+        physical_size: (width.max(0) as u32, height.max(0) as u32),
+        refresh_mhz: refresh.max(0) as u32,
+        current: flags & wayland::WL_OUTPUT_MODE_CURRENT != 0,
+        preferred: flags & wayland::WL_OUTPUT_MODE_PREFERRED != 0,
+    };
+    if mode.current {
+        for mode in output.info.modes.iter_mut() {
+            mode.current = false;
+        }
+    }
+    match output
+        .info
+        .modes
+        .iter_mut()
+        .find(|it| it.physical_size == mode.physical_size && it.refresh_mhz == mode.refresh_mhz)
+    {
+        Some(it) => *it = mode,
+        None => output.info.modes.push(mode),
+    }
+}
+
+unsafe extern "C" fn handle_wl_output_scale(
+    data: *mut c_void,
+    wl_output: *mut wayland::wl_output,
+    factor: i32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(output) = this.find_output_mut(wl_output) else {
+        return;
+    };
+    output.info.scale_factor = factor.max(1) as f64;
+}
+
+unsafe extern "C" fn handle_wl_output_name(
+    data: *mut c_void,
+    wl_output: *mut wayland::wl_output,
+    name: *const c_char,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(output) = this.find_output_mut(wl_output) else {
+        return;
+    };
+    output.info.name = string_from_c_char(name);
+}
+
+unsafe extern "C" fn handle_wl_output_description(
+    data: *mut c_void,
+    wl_output: *mut wayland::wl_output,
+    description: *const c_char,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(output) = this.find_output_mut(wl_output) else {
+        return;
+    };
+    output.info.description = string_from_c_char(description);
+}
+
+const WL_OUTPUT_LISTENER: wayland::wl_output_listener = wayland::wl_output_listener {
+    geometry: handle_wl_output_geometry,
+    mode: handle_wl_output_mode,
+    done: noop_listener!(),
+    scale: handle_wl_output_scale,
+    name: handle_wl_output_name,
+    description: handle_wl_output_description,
 };
 
+unsafe extern "C" fn handle_zxdg_output_v1_logical_position(
+    data: *mut c_void,
+    zxdg_output_v1: *mut wayland::zxdg_output_v1,
+    x: i32,
+    y: i32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(output) = this
+        .outputs
+        .iter_mut()
+        .find(|output| output.zxdg_output_v1 == zxdg_output_v1)
+    else {
+        return;
+    };
+    output.info.logical_position = Some((x, y));
+}
+
+unsafe extern "C" fn handle_zxdg_output_v1_logical_size(
+    data: *mut c_void,
+    zxdg_output_v1: *mut wayland::zxdg_output_v1,
+    width: i32,
+    height: i32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(output) = this
+        .outputs
+        .iter_mut()
+        .find(|output| output.zxdg_output_v1 == zxdg_output_v1)
+    else {
+        return;
+    };
+    output.info.logical_size = Some((width.max(0) as u32, height.max(0) as u32));
+}
+
+// NOTE: name and description are also sent by wl_output v4; xdg-output's ones are ignored.
+const ZXDG_OUTPUT_V1_LISTENER: wayland::zxdg_output_v1_listener =
+    wayland::zxdg_output_v1_listener {
+        logical_position: handle_zxdg_output_v1_logical_position,
+        logical_size: handle_zxdg_output_v1_logical_size,
+        done: noop_listener!(),
+        name: noop_listener!(),
+        description: noop_listener!(),
+    };
+
 unsafe extern "C" fn handle_wl_seat_capabilities(
     data: *mut c_void,
     _wl_seat: *mut wayland::wl_seat,
@@ -801,46 +1362,327 @@ const XDG_TOPLEVEL_LISTENER: wayland::xdg_toplevel_listener = wayland::xdg_tople
     configure_bounds: noop_listener!(),
 };
 
-unsafe extern "C" fn handle_wp_fractional_scale_v1_preferred_scale(
+unsafe extern "C" fn handle_wl_callback_frame_done(
     data: *mut c_void,
-    _wp_fractional_scale_v1: *mut wayland::wp_fractional_scale_v1,
-    scale: u32,
+    wl_callback: *mut wayland::wl_callback,
+    _callback_data: u32,
 ) {
     let this = unsafe { &mut *(data as *mut WaylandBackend) };
-    // > The sent scale is the numerator of a fraction with a denominator of 120.
-    let scale_factor = scale as f64 / 120.0;
-    this.maybe_resize(None, Some(scale_factor));
+    unsafe { (this.libwayland_client.wl_proxy_destroy)(wl_callback as *mut wayland::wl_proxy) };
+    assert!(this.frame_callback == wl_callback);
+    this.frame_callback = null_mut();
+
+    // NOTE: the event may already be queued if redraw was requested before this frame was
+    // presented and it was not popped yet.
+    if this.redraw_requested
+        && !this
+            .events
+            .iter()
+            .any(|event| matches!(event, Event::Window(WindowEvent::RedrawRequested)))
+    {
+        this.events
+            .push_back(Event::Window(WindowEvent::RedrawRequested));
+    }
 }
 
-const WP_FRACTIONAL_SCALE_MANAGER_V1_LISTENER: wayland::wp_fractional_scale_v1_listener =
-    wayland::wp_fractional_scale_v1_listener {
-        preferred_scale: handle_wp_fractional_scale_v1_preferred_scale,
-    };
+const WL_CALLBACK_FRAME_LISTENER: wayland::wl_callback_listener = wayland::wl_callback_listener {
+    done: handle_wl_callback_frame_done,
+};
 
-unsafe extern "C" fn handle_wl_pointer_enter(
+unsafe extern "C" fn handle_wp_presentation_clock_id(
     data: *mut c_void,
-    _wl_pointer: *mut wayland::wl_pointer,
-    serial: u32,
-    wl_surface: *mut wayland::wl_surface,
-    surface_x: wayland::wl_fixed,
-    surface_y: wayland::wl_fixed,
+    _wp_presentation: *mut wayland::wp_presentation,
+    clk_id: u32,
 ) {
     let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    this.presentation_clock_id = clk_id;
+}
 
-    this.pointer_enter_surface = Some(wl_surface);
-    // NOTE: it is important to update serial tracker before handling cursor shape!
-    this.serial_tracker
-        .update_serial(SerialType::PointerEnter, serial);
+const WP_PRESENTATION_LISTENER: wayland::wp_presentation_listener =
+    wayland::wp_presentation_listener {
+        clock_id: handle_wp_presentation_clock_id,
+    };
 
-    let cursor_shape = this.cursor_shape.unwrap_or(CursorShape::Default);
-    if let Err(err) = this.set_cursor_shape(cursor_shape) {
-        log::error!("could not set cursor shape (pointer enter): {err:?}");
-    }
+unsafe extern "C" fn handle_wp_presentation_feedback_presented(
+    data: *mut c_void,
+    wp_presentation_feedback: *mut wayland::wp_presentation_feedback,
+    tv_sec_hi: u32,
+    tv_sec_lo: u32,
+    tv_nsec: u32,
+    refresh: u32,
+    seq_hi: u32,
+    seq_lo: u32,
+    flags: u32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    unsafe {
+        (this.libwayland_client.wl_proxy_destroy)(
+            wp_presentation_feedback as *mut wayland::wl_proxy,
+        )
+    };
 
-    let surface_id = this.get_pointer_enter_surface_id();
-    let position = (
-        wayland::wl_fixed_to_f64(surface_x),
-        wayland::wl_fixed_to_f64(surface_y),
+    let tv_sec = ((tv_sec_hi as u64) << 32) | tv_sec_lo as u64;
+    let seq = ((seq_hi as u64) << 32) | seq_lo as u64;
+    // QUOTE: If the output does not have a constant refresh rate, explicit video mode switches
+    // excluded, then the refresh argument must be zero.
+    let refresh_interval = (refresh > 0).then(|| Duration::from_nanos(refresh as u64));
+    // QUOTE: When the system does not support such a counter (MSC), seq must be zero
+    // NOTE: zero is technically a valid counter value, but it is indistinguishable.
+    let sequence = (seq > 0).then_some(seq);
+
+    this.events.push_back(Event::Window(WindowEvent::Presented(
+        PresentationFeedback {
+            presented_at: Duration::new(tv_sec, tv_nsec),
+            clock_id: this.presentation_clock_id,
+            refresh_interval,
+            sequence,
+            flags: PresentationFlags(flags),
+        },
+    )));
+}
+
+unsafe extern "C" fn handle_wp_presentation_feedback_discarded(
+    data: *mut c_void,
+    wp_presentation_feedback: *mut wayland::wp_presentation_feedback,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    unsafe {
+        (this.libwayland_client.wl_proxy_destroy)(
+            wp_presentation_feedback as *mut wayland::wl_proxy,
+        )
+    };
+    this.events
+        .push_back(Event::Window(WindowEvent::PresentationDiscarded));
+}
+
+const WP_PRESENTATION_FEEDBACK_LISTENER: wayland::wp_presentation_feedback_listener =
+    wayland::wp_presentation_feedback_listener {
+        sync_output: noop_listener!(),
+        presented: handle_wp_presentation_feedback_presented,
+        discarded: handle_wp_presentation_feedback_discarded,
+    };
+
+unsafe extern "C" fn handle_xdg_activation_token_v1_done(
+    data: *mut c_void,
+    xdg_activation_token_v1: *mut wayland::xdg_activation_token_v1,
+    token: *const c_char,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    // NOTE: activating self with a token that is not tied to any user action (no serial) is the
+    // way to request attention; compositors usually mark the window as urgent.
+    unsafe {
+        wayland::xdg_activation_v1_activate(
+            &this.libwayland_client,
+            this.xdg_activation_v1,
+            token,
+            this.wl_surface,
+        );
+        wayland::xdg_activation_token_v1_destroy(&this.libwayland_client, xdg_activation_token_v1);
+    }
+}
+
+const XDG_ACTIVATION_TOKEN_V1_LISTENER: wayland::xdg_activation_token_v1_listener =
+    wayland::xdg_activation_token_v1_listener {
+        done: handle_xdg_activation_token_v1_done,
+    };
+
+unsafe extern "C" fn handle_xdg_popup_surface_configure(
+    data: *mut c_void,
+    xdg_surface: *mut wayland::xdg_surface,
+    serial: u32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    unsafe { wayland::xdg_surface_ack_configure(&this.libwayland_client, xdg_surface, serial) };
+
+    let Some(popup) = this
+        .popups
+        .iter_mut()
+        .find(|popup| popup.xdg_surface == xdg_surface)
+    else {
+        log::warn!("received configure for unknown popup surface");
+        return;
+    };
+    let Some((position, logical_size)) = popup.pending_configure.take() else {
+        return;
+    };
+
+    if popup.logical_size != logical_size {
+        popup.logical_size = logical_size;
+        if !popup.wp_viewport.is_null() {
+            unsafe {
+                wayland::wp_viewport_set_destination(
+                    &this.libwayland_client,
+                    popup.wp_viewport,
+                    logical_size.0 as i32,
+                    logical_size.1 as i32,
+                )
+            };
+        }
+    }
+
+    this.events
+        .push_back(Event::Window(WindowEvent::PopupConfigured {
+            surface_id: make_surface_id(popup.wl_surface),
+            position,
+            logical_size,
+        }));
+}
+
+const XDG_POPUP_SURFACE_LISTENER: wayland::xdg_surface_listener = wayland::xdg_surface_listener {
+    configure: handle_xdg_popup_surface_configure,
+};
+
+unsafe extern "C" fn handle_xdg_popup_configure(
+    data: *mut c_void,
+    xdg_popup: *mut wayland::xdg_popup,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+) {
+    assert!(width >= 0 && height >= 0);
+
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(popup) = this.find_popup_mut(xdg_popup) else {
+        log::warn!("received configure for unknown popup");
+        return;
+    };
+    popup.pending_configure = Some(((x, y), (width as u32, height as u32)));
+}
+
+unsafe extern "C" fn handle_xdg_popup_popup_done(
+    data: *mut c_void,
+    xdg_popup: *mut wayland::xdg_popup,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(popup) = this.find_popup_mut(xdg_popup) else {
+        log::warn!("received popup_done for unknown popup");
+        return;
+    };
+    let surface_id = make_surface_id(popup.wl_surface);
+    this.events
+        .push_back(Event::Window(WindowEvent::PopupDone { surface_id }));
+}
+
+unsafe extern "C" fn handle_xdg_popup_repositioned(
+    data: *mut c_void,
+    xdg_popup: *mut wayland::xdg_popup,
+    _token: u32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(popup) = this.find_popup_mut(xdg_popup) else {
+        log::warn!("received repositioned for unknown popup");
+        return;
+    };
+    let surface_id = make_surface_id(popup.wl_surface);
+    this.events
+        .push_back(Event::Window(WindowEvent::PopupRepositioned { surface_id }));
+}
+
+const XDG_POPUP_LISTENER: wayland::xdg_popup_listener = wayland::xdg_popup_listener {
+    configure: handle_xdg_popup_configure,
+    popup_done: handle_xdg_popup_popup_done,
+    repositioned: handle_xdg_popup_repositioned,
+};
+
+unsafe extern "C" fn handle_wl_surface_preferred_buffer_scale(
+    data: *mut c_void,
+    _wl_surface: *mut wayland::wl_surface,
+    factor: i32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    // NOTE: integer scale is a fallback for when fractional scale is unavailable. fractional
+    // scale is more precise.
+    if !this.wp_fractional_scale_v1.is_null() {
+        return;
+    }
+    assert!(factor > 0);
+    this.maybe_resize(None, Some(factor as f64));
+}
+
+unsafe extern "C" fn handle_wl_surface_enter(
+    data: *mut c_void,
+    _wl_surface: *mut wayland::wl_surface,
+    wl_output: *mut wayland::wl_output,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(output_id) = this.find_output_mut(wl_output).map(|output| output.info.id) else {
+        return;
+    };
+    if this.entered_outputs.contains(&output_id) {
+        return;
+    }
+    this.entered_outputs.push(output_id);
+    this.events
+        .push_back(Event::Window(WindowEvent::OutputEntered { output_id }));
+}
+
+unsafe extern "C" fn handle_wl_surface_leave(
+    data: *mut c_void,
+    _wl_surface: *mut wayland::wl_surface,
+    wl_output: *mut wayland::wl_output,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    let Some(output_id) = this.find_output_mut(wl_output).map(|output| output.info.id) else {
+        return;
+    };
+    let Some(index) = this.entered_outputs.iter().position(|it| *it == output_id) else {
+        return;
+    };
+    this.entered_outputs.remove(index);
+    this.events
+        .push_back(Event::Window(WindowEvent::OutputLeft { output_id }));
+}
+
+const WL_SURFACE_LISTENER: wayland::wl_surface_listener = wayland::wl_surface_listener {
+    enter: handle_wl_surface_enter,
+    leave: handle_wl_surface_leave,
+    preferred_buffer_scale: handle_wl_surface_preferred_buffer_scale,
+    preferred_buffer_transform: noop_listener!(),
+};
+
+unsafe extern "C" fn handle_wp_fractional_scale_v1_preferred_scale(
+    data: *mut c_void,
+    _wp_fractional_scale_v1: *mut wayland::wp_fractional_scale_v1,
+    scale: u32,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    // > The sent scale is the numerator of a fraction with a denominator of 120.
+    let scale_factor = scale as f64 / 120.0;
+    this.maybe_resize(None, Some(scale_factor));
+}
+
+const WP_FRACTIONAL_SCALE_MANAGER_V1_LISTENER: wayland::wp_fractional_scale_v1_listener =
+    wayland::wp_fractional_scale_v1_listener {
+        preferred_scale: handle_wp_fractional_scale_v1_preferred_scale,
+    };
+
+unsafe extern "C" fn handle_wl_pointer_enter(
+    data: *mut c_void,
+    _wl_pointer: *mut wayland::wl_pointer,
+    serial: u32,
+    wl_surface: *mut wayland::wl_surface,
+    surface_x: wayland::wl_fixed,
+    surface_y: wayland::wl_fixed,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+
+    this.pointer_enter_surface = Some(wl_surface);
+    // NOTE: it is important to update serial tracker before handling cursor shape!
+    this.serial_tracker.update_serial(
+        SerialType::PointerEnter,
+        serial,
+        make_surface_id(wl_surface),
+    );
+
+    if let Err(err) = this.apply_cursor() {
+        log::error!("could not apply cursor (pointer enter): {err:?}");
+    }
+
+    let surface_id = this.get_pointer_enter_surface_id();
+    let position = LogicalPosition::new(
+        wayland::wl_fixed_to_f64(surface_x),
+        wayland::wl_fixed_to_f64(surface_y),
     );
     this.events.push_back(Event::Pointer(PointerEvent {
         surface_id,
@@ -859,6 +1701,7 @@ unsafe extern "C" fn handle_wl_pointer_leave(
     let this = unsafe { &mut *(data as *mut WaylandBackend) };
 
     this.serial_tracker.reset_serial(SerialType::PointerEnter);
+    this.serial_tracker.reset_serial(SerialType::PointerButton);
 
     let surface_id = this.get_pointer_enter_surface_id();
     this.events.push_back(Event::Pointer(PointerEvent {
@@ -877,7 +1720,7 @@ unsafe extern "C" fn handle_wl_pointer_motion(
     let this = unsafe { &mut *(data as *mut WaylandBackend) };
 
     let surface_id = this.get_pointer_enter_surface_id();
-    let position = (
+    let position = LogicalPosition::new(
         wayland::wl_fixed_to_f64(surface_x),
         wayland::wl_fixed_to_f64(surface_y),
     );
@@ -892,7 +1735,7 @@ unsafe extern "C" fn handle_wl_pointer_motion(
 unsafe extern "C" fn handle_wl_pointer_button(
     data: *mut c_void,
     _wl_pointer: *mut wayland::wl_pointer,
-    _serial: u32,
+    serial: u32,
     _time: u32,
     button: u32,
     state: u32,
@@ -905,7 +1748,12 @@ unsafe extern "C" fn handle_wl_pointer_button(
         return;
     };
     let state = match state {
-        wayland::WL_POINTER_BUTTON_STATE_PRESSED => ButtonState::Pressed,
+        wayland::WL_POINTER_BUTTON_STATE_PRESSED => {
+            // NOTE: popup grabs require a serial of a user action.
+            this.serial_tracker
+                .update_serial(SerialType::PointerButton, serial, surface_id);
+            ButtonState::Pressed
+        }
         wayland::WL_POINTER_BUTTON_STATE_RELEASED => ButtonState::Released,
         other => {
             log::warn!("unknown pointer button state: {other}");
@@ -959,8 +1807,8 @@ unsafe extern "C" fn handle_wl_pointer_frame(
         // chrome uses 10 (kAxisValueScale in wayland_pointer.cc)
         // sdl uses 10 (WAYLAND_WHEEL_AXIS_UNIT in SDL_waylandevents.c).
         //
-        // TODO: since this is logical coords - do i need to apply fractional scaling here? would
-        // it make sense to do that? noobody seems to be doing that though.
+        // NOTE: the value is in logical coords (as everything else pointer-related) and thus must
+        // not be scaled by scale factor.
         const SCALE: f64 = 10.0;
         (
             wayland::wl_fixed_to_f64(x) / SCALE,
@@ -1064,7 +1912,8 @@ unsafe extern "C" fn handle_zwp_pointer_gesture_swipe_v1_update(
     let this = unsafe { &mut *(data as *mut WaylandBackend) };
 
     let surface_id = this.get_pointer_enter_surface_id();
-    // TODO: do i need to scale dx and dy by fractional scale?
+    // NOTE: dx and dy are in surface-local (logical) coordinates, same as pointer motion. they
+    // must not be scaled by scale factor.
     let translation_delta = (wayland::wl_fixed_to_f64(dx), wayland::wl_fixed_to_f64(dy));
     let fingers = this.swipe_fingers.expect("set fingers on start");
     this.events.push_back(Event::Pointer(PointerEvent {
@@ -1183,7 +2032,7 @@ unsafe extern "C" fn handle_zwp_pointer_gesture_pinch_v1_update(
         surface_id,
         kind: PointerEventKind::Pan {
             phase,
-            // TODO: do i need to scale dx and dy by fractional scale?
+            // NOTE: dx and dy are logical, see swipe update.
             translation_delta: (wayland::wl_fixed_to_f64(dx), wayland::wl_fixed_to_f64(dy)),
             touches: fingers,
         },
@@ -1257,6 +2106,40 @@ const ZWP_POINTER_GESTURE_PINCH_V1_LISTENER: wayland::zwp_pointer_gesture_pinch_
         end: handle_zwp_pointer_gesture_pinch_v1_end,
     };
 
+unsafe extern "C" fn handle_zwp_relative_pointer_v1_relative_motion(
+    data: *mut c_void,
+    _zwp_relative_pointer_v1: *mut wayland::zwp_relative_pointer_v1,
+    _utime_hi: u32,
+    _utime_lo: u32,
+    dx: wayland::wl_fixed,
+    dy: wayland::wl_fixed,
+    dx_unaccel: wayland::wl_fixed,
+    dy_unaccel: wayland::wl_fixed,
+) {
+    let this = unsafe { &mut *(data as *mut WaylandBackend) };
+
+    // NOTE: relative motion events are only sent while pointer has focus on one of our
+    // surfaces.
+    let Some(pointer_enter_surface) = this.pointer_enter_surface else {
+        return;
+    };
+    this.events.push_back(Event::Pointer(PointerEvent {
+        surface_id: make_surface_id(pointer_enter_surface),
+        kind: PointerEventKind::RelativeMotion {
+            delta: (wayland::wl_fixed_to_f64(dx), wayland::wl_fixed_to_f64(dy)),
+            unaccelerated_delta: (
+                wayland::wl_fixed_to_f64(dx_unaccel),
+                wayland::wl_fixed_to_f64(dy_unaccel),
+            ),
+        },
+    }));
+}
+
+const ZWP_RELATIVE_POINTER_V1_LISTENER: wayland::zwp_relative_pointer_v1_listener =
+    wayland::zwp_relative_pointer_v1_listener {
+        relative_motion: handle_zwp_relative_pointer_v1_relative_motion,
+    };
+
 unsafe extern "C" fn handle_wl_keyboard_keymap(
     data: *mut c_void,
     _wl_keyboard: *mut wayland::wl_keyboard,
@@ -1311,18 +2194,38 @@ unsafe extern "C" fn handle_wl_keyboard_enter(
 ) {
     let this = unsafe { &mut *(data as *mut WaylandBackend) };
     this.keyboard_enter_surface = Some(wl_surface);
-    this.serial_tracker
-        .update_serial(SerialType::KeyboardEnter, serial);
+    this.serial_tracker.update_serial(
+        SerialType::KeyboardEnter,
+        serial,
+        make_surface_id(wl_surface),
+    );
+    this.events.push_back(Event::Keyboard(KeyboardEvent {
+        surface_id: make_surface_id(wl_surface),
+        kind: KeyboardEventKind::Enter,
+    }));
+    if wl_surface == this.wl_surface {
+        this.events
+            .push_back(Event::Window(WindowEvent::Focused { focused: true }));
+    }
 }
 
 unsafe extern "C" fn handle_wl_keyboard_leave(
     data: *mut c_void,
     _wl_keyboard: *mut wayland::wl_keyboard,
     _serial: u32,
-    _wl_surface: *mut wayland::wl_surface,
+    wl_surface: *mut wayland::wl_surface,
 ) {
     let this = unsafe { &mut *(data as *mut WaylandBackend) };
+    this.events.push_back(Event::Keyboard(KeyboardEvent {
+        surface_id: make_surface_id(wl_surface),
+        kind: KeyboardEventKind::Leave,
+    }));
+    if wl_surface == this.wl_surface {
+        this.events
+            .push_back(Event::Window(WindowEvent::Focused { focused: false }));
+    }
     this.serial_tracker.reset_serial(SerialType::KeyboardEnter);
+    this.serial_tracker.reset_serial(SerialType::KeyboardKey);
     // QUOTE: The data_offer is valid until a new data_offer or NULL is received or until the
     // client loses keyboard focus.
     this.wl_data_offer = null_mut();
@@ -1331,7 +2234,7 @@ unsafe extern "C" fn handle_wl_keyboard_leave(
 unsafe extern "C" fn handle_wl_keyboard_key(
     data: *mut c_void,
     _wl_keyboard: *mut wayland::wl_keyboard,
-    _serial: u32,
+    serial: u32,
     _time: u32,
     key: u32,
     state: u32,
@@ -1357,6 +2260,9 @@ unsafe extern "C" fn handle_wl_keyboard_key(
 
     match state {
         wayland::WL_KEYBOARD_KEY_STATE_PRESSED => {
+            // NOTE: popup grabs require a serial of a user action.
+            this.serial_tracker
+                .update_serial(SerialType::KeyboardKey, serial, surface_id);
             this.events.push_back(Event::Keyboard(KeyboardEvent {
                 surface_id,
                 kind: KeyboardEventKind::Key {
@@ -1366,11 +2272,11 @@ unsafe extern "C" fn handle_wl_keyboard_key(
                     repeat: false,
                 },
             }));
-            if let Some(KeyRepeatInfo { rate, delay }) = this.key_repeat_info {
+            if let Some(KeyRepeatInfo { delay, interval }) = this.key_repeat_info {
                 assert!(!ks.keymap.is_null());
                 if unsafe { (ac.api.xkb_keymap_key_repeats)(ks.keymap, xkb_key) } == 1 {
                     this.key_repeat = Some((scancode, keycode));
-                    if let Err(err) = unsafe { this.key_repeat_timerfd.arm(rate, delay) } {
+                    if let Err(err) = unsafe { this.key_repeat_timerfd.arm(interval, delay) } {
                         log::error!("could not arm key repeat: {err}");
                     }
                 }
@@ -1444,16 +2350,10 @@ unsafe extern "C" fn handle_wl_keyboard_repeat_info(
 
     let this = unsafe { &mut *(data as *mut WaylandBackend) };
     // NOTE: a rate of zero disables any repeating, regardless of the delay's value.
-    this.key_repeat_info = if rate == 0 {
-        None
-    } else {
-        Some(KeyRepeatInfo {
-            // QUOTE: rate of repeating keys in characters per second
-            rate: Duration::from_nanos(1_000_000_000 / rate as u64),
-            // QUOTE: delay in milliseconds since key down until repeating starts
-            delay: Duration::from_millis(delay as u64),
-        })
-    };
+    // QUOTE: rate of repeating keys in characters per second
+    // QUOTE: delay in milliseconds since key down until repeating starts
+    this.key_repeat_info =
+        KeyRepeatInfo::from_rate(rate as u32, Duration::from_millis(delay as u64));
 }
 
 const WL_KEYBOARD_LISTENER: wayland::wl_keyboard_listener = wayland::wl_keyboard_listener {
@@ -1554,6 +2454,28 @@ impl WaylandBackend {
             NonNull::new(unsafe { (libwayland_client.wl_display_connect)(null_mut()) })
                 .context("could not connect to wayland display")?;
 
+        Self::new_boxed_with_display(libwayland_client, wl_display, attrs)
+    }
+
+    /// connects over an already established connection (fd of a socket).
+    #[cfg(test)]
+    fn new_boxed_with_fd(attrs: WindowAttrs, fd: OwnedFd) -> anyhow::Result<Box<Self>> {
+        let libwayland_client = wayland::ClientApi::load()?;
+        let fd = fd.into_raw_fd();
+        let Some(wl_display) =
+            NonNull::new(unsafe { (libwayland_client.wl_display_connect_to_fd)(fd) })
+        else {
+            unsafe { libc::close(fd) };
+            return Err(anyhow!("could not connect to wayland display"));
+        };
+        Self::new_boxed_with_display(libwayland_client, wl_display, attrs)
+    }
+
+    fn new_boxed_with_display(
+        libwayland_client: wayland::ClientApi,
+        wl_display: NonNull<wayland::wl_display>,
+        attrs: WindowAttrs,
+    ) -> anyhow::Result<Box<Self>> {
         let Ok(key_repeat_timerfd) = (unsafe {
             TimerFD::new(
                 libc::CLOCK_MONOTONIC,
@@ -1565,6 +2487,17 @@ impl WaylandBackend {
             return Err(anyhow!("could not create key repeat timer fd"));
         };
 
+        let Ok(cursor_animation_timerfd) = (unsafe {
+            TimerFD::new(
+                libc::CLOCK_MONOTONIC,
+                libc::TFD_CLOEXEC | libc::TFD_NONBLOCK,
+            )
+        }) else {
+            unsafe { (libwayland_client.wl_display_disconnect)(wl_display.as_ptr()) };
+
+            return Err(anyhow!("could not create cursor animation timer fd"));
+        };
+
         let mut this = Box::new(WaylandBackend {
             libwayland_client,
             wl_display,
@@ -1575,15 +2508,31 @@ impl WaylandBackend {
             wl_shm: null_mut(),
             wp_cursor_shape_manager_v1: null_mut(),
             wp_fractional_scale_manager_v1: null_mut(),
+            wp_presentation: null_mut(),
             wp_viewporter: null_mut(),
+            xdg_activation_v1: null_mut(),
             xdg_wm_base: null_mut(),
+            zwp_idle_inhibit_manager_v1: null_mut(),
+            zwp_pointer_constraints_v1: null_mut(),
             zwp_pointer_gestures_v1: null_mut(),
+            zwp_relative_pointer_manager_v1: null_mut(),
+            zxdg_output_manager_v1: null_mut(),
+
+            outputs: Vec::new(),
+            entered_outputs: Vec::new(),
 
             attrs,
             wl_surface: null_mut(),
             xdg_surface: null_mut(),
             xdg_toplevel: null_mut(),
             acked_first_xdg_surface_configure: false,
+            zwp_idle_inhibitor_v1: null_mut(),
+
+            popups: Vec::new(),
+
+            frame_callback: null_mut(),
+            redraw_requested: false,
+            presentation_clock_id: libc::CLOCK_MONOTONIC as u32,
 
             wp_fractional_scale_v1: null_mut(),
             wp_viewport: null_mut(),
@@ -1595,6 +2544,9 @@ impl WaylandBackend {
             wp_cursor_shape_device_v1: null_mut(),
             cursor: None,
             cursor_shape: None,
+            cursor_image: None,
+            cursor_visible: true,
+            cursor_animation_timerfd,
             axis: None,
             axis_discrete: None,
             axis_value120: None,
@@ -1603,6 +2555,9 @@ impl WaylandBackend {
             swipe_fingers: None,
             pinch_scale: None,
             pinch_fingers: None,
+            zwp_relative_pointer_v1: null_mut(),
+            zwp_locked_pointer_v1: null_mut(),
+            zwp_confined_pointer_v1: null_mut(),
 
             wl_keyboard: null_mut(),
             keyboard_enter_surface: None,
@@ -1663,6 +2618,16 @@ impl WaylandBackend {
             )
         };
 
+        if !this.wp_presentation.is_null() {
+            unsafe {
+                (this.libwayland_client.wl_proxy_add_listener)(
+                    this.wp_presentation as *mut wayland::wl_proxy,
+                    &WP_PRESENTATION_LISTENER as *const wayland::wp_presentation_listener as _,
+                    this.as_mut() as *mut WaylandBackend as *mut c_void,
+                )
+            };
+        }
+
         // init window
 
         this.wl_surface = unsafe {
@@ -1671,6 +2636,13 @@ impl WaylandBackend {
         if this.wl_surface.is_null() {
             return Err(anyhow!("could not create wl surface"));
         }
+        unsafe {
+            (this.libwayland_client.wl_proxy_add_listener)(
+                this.wl_surface as *mut wayland::wl_proxy,
+                &WL_SURFACE_LISTENER as *const wayland::wl_surface_listener as _,
+                this.as_mut() as *mut WaylandBackend as *mut c_void,
+            )
+        };
 
         this.xdg_surface = unsafe {
             wayland::xdg_wm_base_get_xdg_surface(
@@ -1841,6 +2813,28 @@ impl WaylandBackend {
             };
         }
 
+        if !this.zwp_relative_pointer_manager_v1.is_null() {
+            this.zwp_relative_pointer_v1 = unsafe {
+                wayland::zwp_relative_pointer_manager_v1_get_relative_pointer(
+                    &this.libwayland_client,
+                    this.zwp_relative_pointer_manager_v1,
+                    this.wl_pointer,
+                )
+            };
+            if this.zwp_relative_pointer_v1.is_null() {
+                return Err(anyhow!("could not get relative pointer"));
+            }
+            unsafe {
+                (this.libwayland_client.wl_proxy_add_listener)(
+                    this.zwp_relative_pointer_v1 as *mut wayland::wl_proxy,
+                    &ZWP_RELATIVE_POINTER_V1_LISTENER
+                        as *const wayland::zwp_relative_pointer_v1_listener
+                        as _,
+                    this.as_mut() as *mut WaylandBackend as *mut c_void,
+                )
+            };
+        }
+
         // keyboard
 
         this.wl_keyboard =
@@ -1883,9 +2877,27 @@ impl WaylandBackend {
         unsafe { wayland::wl_surface_commit(&this.libwayland_client, this.wl_surface) };
         unsafe { (this.libwayland_client.wl_display_roundtrip)(this.wl_display.as_ptr()) };
 
-        // TODO: consider waiting for fractional scale event (if fractional scale interface exists)
         assert!(this.acked_first_xdg_surface_configure);
 
+        // NOTE: compositors may send preferred scale after the initial configure. give them one
+        // more roundtrip; we want to know the scale before the first frame is rendered.
+        if this.scale_factor.is_none() {
+            unsafe { (this.libwayland_client.wl_display_roundtrip)(this.wl_display.as_ptr()) };
+        }
+        // NOTE: ScaleFactorChanged must always be delivered before the first frame, even if no
+        // scale source told us anything (then it's 1).
+        if this.scale_factor.is_none() {
+            this.maybe_resize(None, Some(1.0));
+        }
+
+        // NOTE: if we were launched by something that gave us an activation token - use it to get
+        // focus.
+        if let Some(token) = crate::activation_token_from_env()
+            && let Err(err) = this.activate(&token)
+        {
+            log::warn!("could not activate window with startup token: {err:?}");
+        }
+
         log::info!("initialized window");
 
         Ok(this)
@@ -1907,37 +2919,719 @@ impl WaylandBackend {
         make_surface_id(keyboard_enter_surface)
     }
 
-    fn set_cursor_shape(&mut self, shape: CursorShape) -> anyhow::Result<()> {
-        let Some(serial) = self.serial_tracker.get_serial(SerialType::PointerEnter) else {
-            return Ok(());
-        };
+    fn request_redraw(&mut self) {
+        // NOTE: the flag is cleared when RedrawRequested event is popped; until then any number of
+        // requests results in a single event.
+        if self.redraw_requested {
+            return;
+        }
+        self.redraw_requested = true;
 
-        if !self.wp_cursor_shape_device_v1.is_null() {
-            unsafe {
-                wayland::wp_cursor_shape_device_v1_set_shape(
+        if self.frame_callback.is_null() {
+            // NOTE: nothing was presented since the last frame callback (or ever), there's nothing
+            // to wait for.
+            self.events
+                .push_back(Event::Window(WindowEvent::RedrawRequested));
+        }
+    }
+
+    fn pre_present_notify(&mut self) {
+        // NOTE: both frame callback and presentation feedback are double-buffered and will be
+        // committed with the frame (egl swap commits the surface).
+
+        if self.frame_callback.is_null() {
+            self.frame_callback =
+                unsafe { wayland::wl_surface_frame(&self.libwayland_client, self.wl_surface) };
+            if self.frame_callback.is_null() {
+                log::warn!("could not request frame callback");
+            } else {
+                unsafe {
+                    (self.libwayland_client.wl_proxy_add_listener)(
+                        self.frame_callback as *mut wayland::wl_proxy,
+                        &WL_CALLBACK_FRAME_LISTENER as *const wayland::wl_callback_listener as _,
+                        self as *mut WaylandBackend as *mut c_void,
+                    )
+                };
+            }
+        }
+
+        if !self.wp_presentation.is_null() {
+            let wp_presentation_feedback = unsafe {
+                wayland::wp_presentation_feedback(
                     &self.libwayland_client,
-                    self.wp_cursor_shape_device_v1,
-                    serial,
-                    map_cursor_shape_to_enum(shape),
+                    self.wp_presentation,
+                    self.wl_surface,
                 )
             };
-        } else if let Some(ref cursor) = self.cursor {
-            cursor.set_shape(
-                &self.libwayland_client,
-                self.wl_pointer,
-                map_cursor_shape_to_name(shape),
-                serial,
-            )?;
-        } else {
-            return Err(anyhow!(
-                "cursor shape protocol is unavailable and libwayland_cursor thing is uninitialized (why?)"
-            ));
+            if wp_presentation_feedback.is_null() {
+                log::warn!("could not request presentation feedback");
+            } else {
+                unsafe {
+                    (self.libwayland_client.wl_proxy_add_listener)(
+                        wp_presentation_feedback as *mut wayland::wl_proxy,
+                        &WP_PRESENTATION_FEEDBACK_LISTENER
+                            as *const wayland::wp_presentation_feedback_listener
+                            as _,
+                        self as *mut WaylandBackend as *mut c_void,
+                    )
+                };
+            }
         }
+    }
 
+    fn find_output_mut(&mut self, wl_output: *mut wayland::wl_output) -> Option<&mut Output> {
+        self.outputs
+            .iter_mut()
+            .find(|output| output.wl_output == wl_output)
+    }
+
+    /// gets xdg outputs for outputs that don't have one yet. outputs and xdg output manager may
+    /// be announced by the registry in any order.
+    fn maybe_get_xdg_outputs(&mut self) {
+        if self.zxdg_output_manager_v1.is_null() {
+            return;
+        }
+        let data = self as *mut WaylandBackend as *mut c_void;
+        for output in self.outputs.iter_mut() {
+            if !output.zxdg_output_v1.is_null() {
+                continue;
+            }
+            output.zxdg_output_v1 = unsafe {
+                wayland::zxdg_output_manager_v1_get_xdg_output(
+                    &self.libwayland_client,
+                    self.zxdg_output_manager_v1,
+                    output.wl_output,
+                )
+            };
+            if output.zxdg_output_v1.is_null() {
+                log::warn!("could not get xdg output");
+                continue;
+            }
+            unsafe {
+                (self.libwayland_client.wl_proxy_add_listener)(
+                    output.zxdg_output_v1 as *mut wayland::wl_proxy,
+                    &ZXDG_OUTPUT_V1_LISTENER as *const wayland::zxdg_output_v1_listener as _,
+                    data,
+                )
+            };
+        }
+    }
+
+    fn find_popup_mut(&mut self, xdg_popup: *mut wayland::xdg_popup) -> Option<&mut Popup> {
+        self.popups
+            .iter_mut()
+            .find(|popup| popup.xdg_popup == xdg_popup)
+    }
+
+    fn find_popup_idx(&self, surface_id: SurfaceId) -> Option<usize> {
+        self.popups
+            .iter()
+            .position(|popup| make_surface_id(popup.wl_surface) == surface_id)
+    }
+
+    /// NOTE: caller is responsible for destroying the positioner. it is fine to destroy it right
+    /// after it was used.
+    fn create_xdg_positioner(
+        &self,
+        positioner: &PopupPositioner,
+    ) -> anyhow::Result<*mut wayland::xdg_positioner> {
+        let PopupPositioner {
+            logical_size,
+            anchor_rect,
+            anchor,
+            gravity,
+            constraint_adjustment,
+            offset,
+        } = positioner;
+
+        // QUOTE: Passing a zero or negative size will raise an invalid_input error.
+        if logical_size.0 == 0 || logical_size.1 == 0 {
+            return Err(anyhow!("popup size must be non-zero"));
+        }
+        if anchor_rect.2 == 0 || anchor_rect.3 == 0 {
+            return Err(anyhow!("popup anchor rect size must be non-zero"));
+        }
+
+        let xdg_positioner = unsafe {
+            wayland::xdg_wm_base_create_positioner(&self.libwayland_client, self.xdg_wm_base)
+        };
+        if xdg_positioner.is_null() {
+            return Err(anyhow!("could not create xdg positioner"));
+        }
+
+        unsafe {
+            wayland::xdg_positioner_set_size(
+                &self.libwayland_client,
+                xdg_positioner,
+                logical_size.0 as i32,
+                logical_size.1 as i32,
+            );
+            wayland::xdg_positioner_set_anchor_rect(
+                &self.libwayland_client,
+                xdg_positioner,
+                anchor_rect.0,
+                anchor_rect.1,
+                anchor_rect.2 as i32,
+                anchor_rect.3 as i32,
+            );
+            wayland::xdg_positioner_set_anchor(
+                &self.libwayland_client,
+                xdg_positioner,
+                map_popup_anchor(*anchor),
+            );
+            wayland::xdg_positioner_set_gravity(
+                &self.libwayland_client,
+                xdg_positioner,
+                map_popup_gravity(*gravity),
+            );
+            wayland::xdg_positioner_set_constraint_adjustment(
+                &self.libwayland_client,
+                xdg_positioner,
+                constraint_adjustment.0,
+            );
+            wayland::xdg_positioner_set_offset(
+                &self.libwayland_client,
+                xdg_positioner,
+                offset.0,
+                offset.1,
+            );
+        }
+
+        Ok(xdg_positioner)
+    }
+
+    fn create_popup(&mut self, attrs: PopupAttrs) -> anyhow::Result<SurfaceId> {
+        let (parent_wl_surface, parent_xdg_surface) = match attrs.parent {
+            None => (self.wl_surface, self.xdg_surface),
+            Some(parent) if parent == make_surface_id(self.wl_surface) => {
+                (self.wl_surface, self.xdg_surface)
+            }
+            Some(parent) => {
+                let idx = self
+                    .find_popup_idx(parent)
+                    .with_context(|| format!("unknown parent surface {parent:?}"))?;
+                (self.popups[idx].wl_surface, self.popups[idx].xdg_surface)
+            }
+        };
+
+        // NOTE: compositors dismiss popups right away if grab serial is not the latest user
+        // action on the parent (or pointer has left it since).
+        let grab_serial = if attrs.grab {
+            let serial = self
+                .serial_tracker
+                .get_latest_input_serial(make_surface_id(parent_wl_surface));
+            if serial.is_none() {
+                log::warn!(
+                    "latest pointer button or key press was not on the parent, creating popup without grab"
+                );
+            }
+            serial
+        } else {
+            None
+        };
+
+        let xdg_positioner = self.create_xdg_positioner(&attrs.positioner)?;
+
+        let wl_surface = unsafe {
+            wayland::wl_compositor_create_surface(&self.libwayland_client, self.wl_compositor)
+        };
+        if wl_surface.is_null() {
+            unsafe { wayland::xdg_positioner_destroy(&self.libwayland_client, xdg_positioner) };
+            return Err(anyhow!("could not create popup wl surface"));
+        }
+
+        let xdg_surface = unsafe {
+            wayland::xdg_wm_base_get_xdg_surface(
+                &self.libwayland_client,
+                self.xdg_wm_base,
+                wl_surface,
+            )
+        };
+        if xdg_surface.is_null() {
+            unsafe {
+                wayland::xdg_positioner_destroy(&self.libwayland_client, xdg_positioner);
+                wayland::wl_surface_destroy(&self.libwayland_client, wl_surface);
+            }
+            return Err(anyhow!("could not create popup xdg surface"));
+        }
+        unsafe {
+            (self.libwayland_client.wl_proxy_add_listener)(
+                xdg_surface as *mut wayland::wl_proxy,
+                &XDG_POPUP_SURFACE_LISTENER as *const wayland::xdg_surface_listener as _,
+                self as *mut WaylandBackend as *mut c_void,
+            )
+        };
+
+        let xdg_popup = unsafe {
+            wayland::xdg_surface_get_popup(
+                &self.libwayland_client,
+                xdg_surface,
+                parent_xdg_surface,
+                xdg_positioner,
+            )
+        };
+        unsafe { wayland::xdg_positioner_destroy(&self.libwayland_client, xdg_positioner) };
+        if xdg_popup.is_null() {
+            unsafe {
+                wayland::xdg_surface_destroy(&self.libwayland_client, xdg_surface);
+                wayland::wl_surface_destroy(&self.libwayland_client, wl_surface);
+            }
+            return Err(anyhow!("could not get xdg popup"));
+        }
+        unsafe {
+            (self.libwayland_client.wl_proxy_add_listener)(
+                xdg_popup as *mut wayland::wl_proxy,
+                &XDG_POPUP_LISTENER as *const wayland::xdg_popup_listener as _,
+                self as *mut WaylandBackend as *mut c_void,
+            )
+        };
+
+        if let Some(serial) = grab_serial {
+            // QUOTE: This request must be used in response to some sort of user action like a
+            // button press, key press, or touch down event.
+            unsafe {
+                wayland::xdg_popup_grab(&self.libwayland_client, xdg_popup, self.wl_seat, serial)
+            };
+        }
+
+        let logical_size = attrs.positioner.logical_size;
+
+        // NOTE: popup is rendered at the same scale as the window; viewport maps physical buffer
+        // onto logical size.
+        let mut wp_viewport = null_mut();
+        if !self.wp_viewporter.is_null() {
+            wp_viewport = unsafe {
+                wayland::wp_viewporter_get_viewport(
+                    &self.libwayland_client,
+                    self.wp_viewporter,
+                    wl_surface,
+                )
+            };
+            unsafe {
+                wayland::wp_viewport_set_destination(
+                    &self.libwayland_client,
+                    wp_viewport,
+                    logical_size.0 as i32,
+                    logical_size.1 as i32,
+                )
+            };
+        }
+
+        // NOTE: initial commit without a buffer makes the compositor send configure.
+        unsafe { wayland::wl_surface_commit(&self.libwayland_client, wl_surface) };
+
+        self.popups.push(Popup {
+            wl_surface,
+            xdg_surface,
+            xdg_popup,
+            wp_viewport,
+            parent_xdg_surface,
+            pending_configure: None,
+            logical_size,
+        });
+
+        Ok(make_surface_id(wl_surface))
+    }
+
+    fn reposition_popup(
+        &mut self,
+        surface_id: SurfaceId,
+        positioner: PopupPositioner,
+    ) -> anyhow::Result<()> {
+        // QUOTE: reposition request was introduced in xdg_wm_base version 3.
+        let xdg_wm_base_version = unsafe {
+            (self.libwayland_client.wl_proxy_get_version)(
+                self.xdg_wm_base as *mut wayland::wl_proxy,
+            )
+        };
+        if xdg_wm_base_version < 3 {
+            return Err(anyhow!(
+                "xdg_wm_base v{xdg_wm_base_version} does not support repositioning popups"
+            ));
+        }
+
+        let idx = self
+            .find_popup_idx(surface_id)
+            .with_context(|| format!("unknown popup {surface_id:?}"))?;
+
+        let xdg_positioner = self.create_xdg_positioner(&positioner)?;
+        unsafe {
+            // NOTE: token is not used because repositioned event is mapped back to the popup by
+            // xdg_popup pointer.
+            wayland::xdg_popup_reposition(
+                &self.libwayland_client,
+                self.popups[idx].xdg_popup,
+                xdg_positioner,
+                0,
+            );
+            wayland::xdg_positioner_destroy(&self.libwayland_client, xdg_positioner);
+        }
+
+        Ok(())
+    }
+
+    fn destroy_popup(&mut self, surface_id: SurfaceId) -> anyhow::Result<()> {
+        let idx = self
+            .find_popup_idx(surface_id)
+            .with_context(|| format!("unknown popup {surface_id:?}"))?;
+
+        // QUOTE: If the client destroys a popup that is not the topmost popup, the compositor
+        // must send a protocol error.
+        let xdg_surface = self.popups[idx].xdg_surface;
+        if self
+            .popups
+            .iter()
+            .any(|popup| popup.parent_xdg_surface == xdg_surface)
+        {
+            return Err(anyhow!("child popups must be destroyed first"));
+        }
+
+        let popup = self.popups.swap_remove(idx);
+        unsafe {
+            if !popup.wp_viewport.is_null() {
+                wayland::wp_viewport_destroy(&self.libwayland_client, popup.wp_viewport);
+            }
+            wayland::xdg_popup_destroy(&self.libwayland_client, popup.xdg_popup);
+            wayland::xdg_surface_destroy(&self.libwayland_client, popup.xdg_surface);
+            wayland::wl_surface_destroy(&self.libwayland_client, popup.wl_surface);
+        }
+
+        Ok(())
+    }
+
+    /// applies current cursor state (visibility, image or shape) to the pointer.
+    fn apply_cursor(&mut self) -> anyhow::Result<()> {
+        let Some(serial) = self.serial_tracker.get_serial(SerialType::PointerEnter) else {
+            return Ok(());
+        };
+
+        // NOTE: whatever was animating is not relevant anymore.
+        unsafe { self.cursor_animation_timerfd.disarm() }?;
+
+        if !self.cursor_visible {
+            // QUOTE: If surface is NULL, the pointer image is hidden.
+            unsafe {
+                wayland::wl_pointer_set_cursor(
+                    &self.libwayland_client,
+                    self.wl_pointer,
+                    serial,
+                    null_mut(),
+                    0,
+                    0,
+                )
+            };
+            return Ok(());
+        }
+
+        if let Some(ref cursor_image) = self.cursor_image {
+            unsafe {
+                wayland::wl_pointer_set_cursor(
+                    &self.libwayland_client,
+                    self.wl_pointer,
+                    serial,
+                    cursor_image.wl_surface,
+                    cursor_image.hotspot.0 as i32,
+                    cursor_image.hotspot.1 as i32,
+                )
+            };
+            return Ok(());
+        }
+
+        let shape = self.cursor_shape.unwrap_or(CursorShape::Default);
+        if !self.wp_cursor_shape_device_v1.is_null() {
+            unsafe {
+                wayland::wp_cursor_shape_device_v1_set_shape(
+                    &self.libwayland_client,
+                    self.wp_cursor_shape_device_v1,
+                    serial,
+                    map_cursor_shape_to_enum(shape),
+                )
+            };
+        } else if let Some(ref mut cursor) = self.cursor {
+            let delay = cursor.set_shape(
+                &self.libwayland_client,
+                self.wl_pointer,
+                map_cursor_shape_to_name(shape),
+                serial,
+            )?;
+            if let Some(delay) = delay {
+                unsafe { self.cursor_animation_timerfd.arm(Duration::ZERO, delay) }?;
+            }
+        } else {
+            return Err(anyhow!(
+                "cursor shape protocol is unavailable and libwayland_cursor thing is uninitialized (why?)"
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn advance_cursor_animation(&mut self) -> anyhow::Result<()> {
+        let _exp: u64 = unsafe { self.cursor_animation_timerfd.read() }?;
+
+        let Some(serial) = self.serial_tracker.get_serial(SerialType::PointerEnter) else {
+            return Ok(());
+        };
+        if !self.cursor_visible || self.cursor_image.is_some() {
+            return Ok(());
+        }
+        let Some(ref mut cursor) = self.cursor else {
+            return Ok(());
+        };
+
+        let delay = cursor.advance_frame(&self.libwayland_client, self.wl_pointer, serial)?;
+        if let Some(delay) = delay {
+            unsafe { self.cursor_animation_timerfd.arm(Duration::ZERO, delay) }?;
+        }
+
+        Ok(())
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape) -> anyhow::Result<()> {
         self.cursor_shape = Some(shape);
+        if let Some(cursor_image) = self.cursor_image.take() {
+            unsafe {
+                wayland::wl_surface_destroy(&self.libwayland_client, cursor_image.wl_surface)
+            };
+            cursor_image.shm_buffer.destroy(&self.libwayland_client);
+        }
+        self.apply_cursor()
+    }
+
+    fn set_cursor_image(
+        &mut self,
+        rgba: &[u8],
+        size: (u32, u32),
+        hotspot: (u32, u32),
+    ) -> anyhow::Result<()> {
+        if rgba.len() != size.0 as usize * size.1 as usize * 4 {
+            return Err(anyhow!(
+                "invalid cursor image data length {} (expected {}x{}x4)",
+                rgba.len(),
+                size.0,
+                size.1
+            ));
+        }
+        if hotspot.0 >= size.0 || hotspot.1 >= size.1 {
+            return Err(anyhow!("cursor hotspot is out of image bounds"));
+        }
+
+        let mut shm_buffer = ShmBuffer::new(
+            &self.libwayland_client,
+            self.wl_shm,
+            size,
+            wayland::WL_SHM_FORMAT_ARGB8888,
+        )?;
+        copy_rgba_to_argb8888(rgba, shm_buffer.as_mut_slice());
+
+        // NOTE: cursor surface is re-used between images.
+        let prev = self.cursor_image.take();
+        let wl_surface = match prev {
+            Some(ref prev) => prev.wl_surface,
+            None => unsafe {
+                wayland::wl_compositor_create_surface(&self.libwayland_client, self.wl_compositor)
+            },
+        };
+        if wl_surface.is_null() {
+            shm_buffer.destroy(&self.libwayland_client);
+            return Err(anyhow!("could not create wl_surface for cursor image"));
+        }
+
+        unsafe {
+            wayland::wl_surface_attach(
+                &self.libwayland_client,
+                wl_surface,
+                shm_buffer.wl_buffer,
+                0,
+                0,
+            );
+            wayland::wl_surface_damage_buffer(
+                &self.libwayland_client,
+                wl_surface,
+                0,
+                0,
+                shm_buffer.size.0 as i32,
+                shm_buffer.size.1 as i32,
+            );
+            wayland::wl_surface_commit(&self.libwayland_client, wl_surface);
+        }
+        // NOTE: previous buffer must stay alive while it's attached to the surface; now that the
+        // new one replaced it, it can go.
+        if let Some(prev) = prev {
+            prev.shm_buffer.destroy(&self.libwayland_client);
+        }
+
+        self.cursor_image = Some(CursorImage {
+            wl_surface,
+            shm_buffer,
+            hotspot,
+        });
+        self.apply_cursor()
+    }
+
+    fn set_idle_inhibit(&mut self, inhibit: bool) -> anyhow::Result<()> {
+        if !inhibit {
+            if !self.zwp_idle_inhibitor_v1.is_null() {
+                unsafe {
+                    wayland::zwp_idle_inhibitor_v1_destroy(
+                        &self.libwayland_client,
+                        self.zwp_idle_inhibitor_v1,
+                    )
+                };
+                self.zwp_idle_inhibitor_v1 = null_mut();
+            }
+            return Ok(());
+        }
+
+        if !self.zwp_idle_inhibitor_v1.is_null() {
+            return Ok(());
+        }
+        if self.zwp_idle_inhibit_manager_v1.is_null() {
+            return Err(anyhow!("idle inhibit protocol is unavailable"));
+        }
+
+        // QUOTE: If the surface is destroyed, unmapped, becomes occluded, loses visibility, or
+        // otherwise becomes not visually relevant for the user, the idle inhibitor will not be
+        // honored by the compositor
+        self.zwp_idle_inhibitor_v1 = unsafe {
+            wayland::zwp_idle_inhibit_manager_v1_create_inhibitor(
+                &self.libwayland_client,
+                self.zwp_idle_inhibit_manager_v1,
+                self.wl_surface,
+            )
+        };
+        if self.zwp_idle_inhibitor_v1.is_null() {
+            return Err(anyhow!("could not create idle inhibitor"));
+        }
+
+        Ok(())
+    }
+
+    fn request_attention(&mut self) -> anyhow::Result<()> {
+        if self.xdg_activation_v1.is_null() {
+            return Err(anyhow!("xdg activation protocol is unavailable"));
+        }
+
+        let xdg_activation_token_v1 = unsafe {
+            wayland::xdg_activation_v1_get_activation_token(
+                &self.libwayland_client,
+                self.xdg_activation_v1,
+            )
+        };
+        if xdg_activation_token_v1.is_null() {
+            return Err(anyhow!("could not get activation token"));
+        }
+        unsafe {
+            (self.libwayland_client.wl_proxy_add_listener)(
+                xdg_activation_token_v1 as *mut wayland::wl_proxy,
+                &XDG_ACTIVATION_TOKEN_V1_LISTENER
+                    as *const wayland::xdg_activation_token_v1_listener as _,
+                self as *mut WaylandBackend as *mut c_void,
+            );
+            wayland::xdg_activation_token_v1_set_surface(
+                &self.libwayland_client,
+                xdg_activation_token_v1,
+                self.wl_surface,
+            );
+            wayland::xdg_activation_token_v1_commit(
+                &self.libwayland_client,
+                xdg_activation_token_v1,
+            );
+        }
+
+        Ok(())
+    }
+
+    fn activate(&mut self, token: &str) -> anyhow::Result<()> {
+        if self.xdg_activation_v1.is_null() {
+            return Err(anyhow!("xdg activation protocol is unavailable"));
+        }
+
+        let c_token = self.temp_cstr.from_str(token);
+        unsafe {
+            wayland::xdg_activation_v1_activate(
+                &self.libwayland_client,
+                self.xdg_activation_v1,
+                c_token.as_ptr(),
+                self.wl_surface,
+            )
+        };
+        self.temp_cstr.clear();
+
+        Ok(())
+    }
+
+    fn set_pointer_grab(&mut self, grab: PointerGrab) -> anyhow::Result<()> {
+        if grab != PointerGrab::None && self.zwp_pointer_constraints_v1.is_null() {
+            return Err(anyhow!("pointer constraints protocol is unavailable"));
+        }
+
+        unsafe {
+            if !self.zwp_locked_pointer_v1.is_null() {
+                wayland::zwp_locked_pointer_v1_destroy(
+                    &self.libwayland_client,
+                    self.zwp_locked_pointer_v1,
+                );
+                self.zwp_locked_pointer_v1 = null_mut();
+            }
+            if !self.zwp_confined_pointer_v1.is_null() {
+                wayland::zwp_confined_pointer_v1_destroy(
+                    &self.libwayland_client,
+                    self.zwp_confined_pointer_v1,
+                );
+                self.zwp_confined_pointer_v1 = null_mut();
+            }
+        }
+
+        // NOTE: persistent constraints are re-activated by the compositor each time pointer
+        // re-enters the surface; oneshot would need to be re-created manually.
+        //
+        // NOTE: null region means the entire surface.
+        match grab {
+            PointerGrab::None => {}
+            PointerGrab::Confined => {
+                self.zwp_confined_pointer_v1 = unsafe {
+                    wayland::zwp_pointer_constraints_v1_confine_pointer(
+                        &self.libwayland_client,
+                        self.zwp_pointer_constraints_v1,
+                        self.wl_surface,
+                        self.wl_pointer,
+                        null_mut(),
+                        wayland::ZWP_POINTER_CONSTRAINTS_V1_LIFETIME_PERSISTENT,
+                    )
+                };
+                if self.zwp_confined_pointer_v1.is_null() {
+                    return Err(anyhow!("could not confine pointer"));
+                }
+            }
+            PointerGrab::Locked => {
+                self.zwp_locked_pointer_v1 = unsafe {
+                    wayland::zwp_pointer_constraints_v1_lock_pointer(
+                        &self.libwayland_client,
+                        self.zwp_pointer_constraints_v1,
+                        self.wl_surface,
+                        self.wl_pointer,
+                        null_mut(),
+                        wayland::ZWP_POINTER_CONSTRAINTS_V1_LIFETIME_PERSISTENT,
+                    )
+                };
+                if self.zwp_locked_pointer_v1.is_null() {
+                    return Err(anyhow!("could not lock pointer"));
+                }
+            }
+        }
+
         Ok(())
     }
 
+    fn set_cursor_visible(&mut self, visible: bool) -> anyhow::Result<()> {
+        if self.cursor_visible == visible {
+            return Ok(());
+        }
+        self.cursor_visible = visible;
+        self.apply_cursor()
+    }
+
     fn maybe_resize(&mut self, logical_size: Option<(u32, u32)>, scale_factor: Option<f64>) {
         assert!(logical_size.is_some() || scale_factor.is_some());
 
@@ -1962,19 +3656,31 @@ impl WaylandBackend {
 
         let mut scale_factor_changed = false;
         if let Some(scale_factor) = scale_factor {
-            scale_factor_changed = self.scale_factor() != scale_factor;
+            // NOTE: comparing options to ensure that the very first scale is always dispatched.
+            scale_factor_changed = self.scale_factor != Some(scale_factor);
             if scale_factor_changed {
                 self.scale_factor = Some(scale_factor);
 
+                // NOTE: without viewporter compositor has no idea what is the relationship
+                // between buffer size and surface size; integer buffer scale is the only option.
+                if self.wp_viewport.is_null() {
+                    unsafe {
+                        wayland::wl_surface_set_buffer_scale(
+                            &self.libwayland_client,
+                            self.wl_surface,
+                            scale_factor.round() as i32,
+                        )
+                    };
+                }
+
                 // NOTE: if we're using old cursor stuff (not wp_cursor_shape_manager_v1) - cursor
                 // needs to be re-scaled.
                 if let Some(ref mut cursor) = self.cursor {
                     match cursor.set_scale(self.wl_shm, scale_factor) {
                         Ok(_) => {
                             // NOTE: cursor needs to be updated after re-scaling.
-                            let shape = self.cursor_shape.unwrap_or(CursorShape::Default);
-                            if let Err(err) = self.set_cursor_shape(shape) {
-                                log::error!("could not set cursor shape (during rescale): {err:?}");
+                            if let Err(err) = self.apply_cursor() {
+                                log::error!("could not apply cursor (during rescale): {err:?}");
                             }
                         }
                         Err(err) => {
@@ -2152,7 +3858,7 @@ impl rwh::HasWindowHandle for WaylandBackend {
 }
 
 impl Window for WaylandBackend {
-    fn pump_events(&mut self) -> anyhow::Result<()> {
+    fn pump_events(&mut self, timeout: Option<Duration>) -> anyhow::Result<()> {
         // https://wayland.freedesktop.org/docs/html/apb.html#Client-classwl__display_1a40039c1169b153269a3dc0796a54ddb0
         // https://gitlab.freedesktop.org/wayland/weston/-/blob/5a48cedc7b8421d8342dd6a943705955217b0fd1/clients/window.c#L7180
 
@@ -2173,6 +3879,16 @@ impl Window for WaylandBackend {
             return Err(anyhow!("wl_display_flush failed"));
         }
 
+        // NOTE: dispatching above may have queued events; there's no reason to wait for more.
+        let timeout: c_int = match timeout {
+            _ if !self.events.is_empty() => 0,
+            Some(timeout) => timeout
+                .as_nanos()
+                .div_ceil(1_000_000)
+                .min(c_int::MAX as u128) as c_int,
+            None => -1,
+        };
+
         let mut fds = [
             libc::pollfd {
                 fd: unsafe { (client.wl_display_get_fd)(display) },
@@ -2184,15 +3900,24 @@ impl Window for WaylandBackend {
                 events: libc::POLLIN,
                 revents: 0,
             },
+            libc::pollfd {
+                fd: self.cursor_animation_timerfd.0,
+                events: libc::POLLIN,
+                revents: 0,
+            },
         ];
         // QUOTE: If the value of timeout is 0, poll() shall return immediately. If the value of
         // timeout is -1, poll() shall block until a requested event occurs or until the call is
         // interrupted.
-        let ret = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, 0) };
+        let ret = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) };
         match ret {
             -1 => {
                 unsafe { (client.wl_display_cancel_read)(display) };
                 let errno = unsafe { *libc::__errno_location() };
+                // NOTE: a signal arrived while blocking; that is the same as a timeout.
+                if errno == libc::EINTR {
+                    return Ok(());
+                }
                 return Err(anyhow!("could not poll fds: 0x:{errno:x}"));
             }
             0 => {
@@ -2231,6 +3956,13 @@ impl Window for WaylandBackend {
                         }
                     }
                 }
+
+                // NOTE: broken cursor animation is not a reason to stop the event loop.
+                if fds[2].revents & libc::POLLIN == libc::POLLIN
+                    && let Err(err) = self.advance_cursor_animation()
+                {
+                    log::error!("could not advance cursor animation: {err:?}");
+                }
             }
             _ => unreachable!(),
         }
@@ -2239,7 +3971,11 @@ impl Window for WaylandBackend {
     }
 
     fn pop_event(&mut self) -> Option<Event> {
-        self.events.pop_front()
+        let event = self.events.pop_front()?;
+        if matches!(event, Event::Window(WindowEvent::RedrawRequested)) {
+            self.redraw_requested = false;
+        }
+        Some(event)
     }
 
     fn set_cursor_shape(&mut self, shape: CursorShape) -> anyhow::Result<()> {
@@ -2247,13 +3983,42 @@ impl Window for WaylandBackend {
         // time it would be the same (which would not constitute a change).
         //
         // TODO: i am not 100% sure i really need this check here, but it wouldn't hurt i guess?
-        if self.cursor_shape == Some(shape) {
+        if self.cursor_shape == Some(shape) && self.cursor_image.is_none() {
             return Ok(());
         }
 
         self.set_cursor_shape(shape)
     }
 
+    fn set_cursor_image(
+        &mut self,
+        rgba: &[u8],
+        size: (u32, u32),
+        hotspot: (u32, u32),
+    ) -> anyhow::Result<()> {
+        self.set_cursor_image(rgba, size, hotspot)
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> anyhow::Result<()> {
+        self.set_cursor_visible(visible)
+    }
+
+    fn set_pointer_grab(&mut self, grab: PointerGrab) -> anyhow::Result<()> {
+        self.set_pointer_grab(grab)
+    }
+
+    fn set_idle_inhibit(&mut self, inhibit: bool) -> anyhow::Result<()> {
+        self.set_idle_inhibit(inhibit)
+    }
+
+    fn request_attention(&mut self) -> anyhow::Result<()> {
+        self.request_attention()
+    }
+
+    fn activate(&mut self, token: &str) -> anyhow::Result<()> {
+        self.activate(token)
+    }
+
     fn read_clipboard(&mut self, mime_type: &str, buf: &mut Vec<u8>) -> anyhow::Result<usize> {
         self.get_clipboard_data(mime_type, buf)
     }
@@ -2274,4 +4039,204 @@ impl Window for WaylandBackend {
     fn scale_factor(&self) -> f64 {
         self.scale_factor.unwrap_or(1.0)
     }
+
+    fn request_redraw(&mut self) {
+        self.request_redraw()
+    }
+
+    fn pre_present_notify(&mut self) {
+        self.pre_present_notify()
+    }
+
+    fn key_repeat_info(&self) -> Option<KeyRepeatInfo> {
+        self.key_repeat_info
+    }
+
+    fn outputs(&self) -> Vec<OutputInfo> {
+        self.outputs
+            .iter()
+            .map(|output| output.info.clone())
+            .collect()
+    }
+
+    fn surface_id(&self) -> SurfaceId {
+        make_surface_id(self.wl_surface)
+    }
+
+    fn surface_window_handle(
+        &self,
+        surface_id: SurfaceId,
+    ) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+        if surface_id == make_surface_id(self.wl_surface) {
+            return rwh::HasWindowHandle::window_handle(self);
+        }
+        let idx = self
+            .find_popup_idx(surface_id)
+            .ok_or(rwh::HandleError::Unavailable)?;
+        let wl_surface =
+            NonNull::new(self.popups[idx].wl_surface).ok_or(rwh::HandleError::Unavailable)?;
+        let wayland = rwh::WaylandWindowHandle::new(wl_surface.cast());
+        let raw = rwh::RawWindowHandle::Wayland(wayland);
+        Ok(unsafe { rwh::WindowHandle::borrow_raw(raw) })
+    }
+
+    fn create_popup(&mut self, attrs: PopupAttrs) -> anyhow::Result<SurfaceId> {
+        self.create_popup(attrs)
+    }
+
+    fn reposition_popup(
+        &mut self,
+        surface_id: SurfaceId,
+        positioner: PopupPositioner,
+    ) -> anyhow::Result<()> {
+        self.reposition_popup(surface_id, positioner)
+    }
+
+    fn destroy_popup(&mut self, surface_id: SurfaceId) -> anyhow::Result<()> {
+        self.destroy_popup(surface_id)
+    }
+}
+
+#[test]
+fn test_copy_rgba_to_argb8888() {
+    let src = [
+        0x10, 0x20, 0x30, 0xff, // opaque
+        0xff, 0x80, 0x00, 0x80, // half-transparent
+        0xff, 0xff, 0xff, 0x00, // transparent
+    ];
+    let mut dst = [0u8; 12];
+    copy_rgba_to_argb8888(&src, &mut dst);
+    // NOTE: argb8888 is little-endian, bytes are in bgra order.
+    assert_eq!(
+        dst,
+        [
+            0x30, 0x20, 0x10, 0xff, //
+            0x00, 0x40, 0x80, 0x80, //
+            0x00, 0x00, 0x00, 0x00,
+        ]
+    );
+}
+
+#[cfg(test)]
+fn pop_redraw_requests(backend: &mut WaylandBackend) -> usize {
+    std::iter::from_fn(|| backend.pop_event())
+        .filter(|event| matches!(event, Event::Window(WindowEvent::RedrawRequested)))
+        .count()
+}
+
+#[test]
+fn test_request_redraw() {
+    // NOTE: libwayland-client is loaded at runtime; skip if it's not installed.
+    if wayland::ClientApi::load().is_err() {
+        return;
+    }
+
+    let (fd, state, compositor) = crate::fake_compositor::spawn(None);
+    let mut backend = WaylandBackend::new_boxed_with_fd(WindowAttrs::default(), fd).unwrap();
+    assert_eq!(pop_redraw_requests(&mut backend), 0);
+
+    backend.request_redraw();
+    backend.request_redraw();
+    assert_eq!(pop_redraw_requests(&mut backend), 1);
+
+    // NOTE: after something was presented redraw waits for the frame callback.
+    backend.pre_present_notify();
+    unsafe { wayland::wl_surface_commit(&backend.libwayland_client, backend.wl_surface) };
+    backend.request_redraw();
+    backend.request_redraw();
+    assert_eq!(pop_redraw_requests(&mut backend), 0);
+    unsafe { (backend.libwayland_client.wl_display_roundtrip)(backend.wl_display.as_ptr()) };
+    assert_eq!(pop_redraw_requests(&mut backend), 1);
+    assert_eq!(state.lock().unwrap().frame_callbacks, 1);
+
+    // NOTE: pump must not block while there are events that were not popped.
+    backend.request_redraw();
+    backend.pump_events(None).unwrap();
+    assert_eq!(pop_redraw_requests(&mut backend), 1);
+    let timeout = Duration::from_millis(20);
+    let start = std::time::Instant::now();
+    backend.pump_events(Some(timeout)).unwrap();
+    assert!(start.elapsed() >= timeout);
+    assert!(backend.pop_event().is_none());
+
+    unsafe { (backend.libwayland_client.wl_display_disconnect)(backend.wl_display.as_ptr()) };
+    drop(backend);
+    compositor.join().expect("fake compositor panicked");
+}
+
+#[test]
+fn test_preferred_scale() {
+    use crate::fake_compositor::FakeScale;
+
+    // NOTE: libwayland-client is loaded at runtime; skip if it's not installed.
+    if wayland::ClientApi::load().is_err() {
+        return;
+    }
+
+    let logical_size = (101, 51);
+    for (scale, scale_factor) in [
+        (FakeScale::Fractional(180), 1.5),
+        (FakeScale::Fractional(150), 1.25),
+        (FakeScale::Integer(2), 2.0),
+    ] {
+        let (fd, state, compositor) = crate::fake_compositor::spawn(Some(scale));
+        let attrs = WindowAttrs {
+            logical_size: Some(logical_size),
+            ..WindowAttrs::default()
+        };
+        let mut backend = WaylandBackend::new_boxed_with_fd(attrs, fd).unwrap();
+
+        // NOTE: scale must be known before the first frame.
+        let scale_factor_changes: Vec<f64> = std::iter::from_fn(|| backend.pop_event())
+            .filter_map(|event| match event {
+                Event::Window(WindowEvent::ScaleFactorChanged { scale_factor }) => {
+                    Some(scale_factor)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(scale_factor_changes, [scale_factor]);
+        assert_eq!(backend.logical_size(), logical_size);
+        assert_eq!(
+            backend.physical_size(),
+            (
+                (logical_size.0 as f64 * scale_factor).ceil() as u32,
+                (logical_size.1 as f64 * scale_factor).ceil() as u32,
+            )
+        );
+        // NOTE: compositor scales the buffer down into the logical size.
+        assert_eq!(
+            state.lock().unwrap().viewport_destination,
+            Some((logical_size.0 as i32, logical_size.1 as i32))
+        );
+
+        unsafe { (backend.libwayland_client.wl_display_disconnect)(backend.wl_display.as_ptr()) };
+        drop(backend);
+        compositor.join().expect("fake compositor panicked");
+    }
+}
+
+#[test]
+fn test_serial_tracker_latest_input() {
+    let window = SurfaceId(1);
+    let popup = SurfaceId(2);
+    let mut serial_tracker = SerialTracker::default();
+    assert_eq!(serial_tracker.get_latest_input_serial(window), None);
+
+    serial_tracker.update_serial(SerialType::PointerButton, 10, window);
+    assert_eq!(serial_tracker.get_latest_input_serial(window), Some(10));
+    assert_eq!(serial_tracker.get_latest_input_serial(popup), None);
+
+    // NOTE: key press on another surface supersedes the button press.
+    serial_tracker.update_serial(SerialType::KeyboardKey, 11, popup);
+    assert_eq!(serial_tracker.get_latest_input_serial(window), None);
+    assert_eq!(serial_tracker.get_latest_input_serial(popup), Some(11));
+
+    // NOTE: enter serials are not user actions.
+    serial_tracker.update_serial(SerialType::PointerEnter, 12, window);
+    assert_eq!(serial_tracker.get_latest_input_serial(popup), Some(11));
+
+    serial_tracker.reset_serial(SerialType::KeyboardKey);
+    assert_eq!(serial_tracker.get_latest_input_serial(window), None);
+    assert_eq!(serial_tracker.get_latest_input_serial(popup), None);
 }
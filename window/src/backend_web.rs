@@ -1,10 +1,484 @@
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
+use std::mem;
+use std::rc::Rc;
+use std::slice;
+use std::time::Duration;
 
-use anyhow::Context as _;
-use input::CursorShape;
+use anyhow::{Context as _, anyhow};
+use input::{
+    Button, ButtonState, CursorShape, KeyState, KeyboardEvent, KeyboardEventKind, Keycode,
+    LogicalPosition, PointerEvent, PointerEventKind, RawKey, Scancode, SurfaceId,
+};
 use raw_window_handle as rwh;
 
-use crate::{ClipboardDataProvider, DEFAULT_LOGICAL_SIZE, Event, Window, WindowAttrs};
+use crate::{
+    ClipboardDataProvider, DEFAULT_LOGICAL_SIZE, Event, OutputId, OutputInfo, OutputMode,
+    PointerGrab, PopupAttrs, PopupPositioner, Window, WindowAttrs, WindowEvent,
+    logical_to_physical_size,
+};
+
+// https://developer.mozilla.org/en-US/docs/Web/API/UI_Events/Keyboard_event_code_values
+fn map_dom_code(code: &str) -> Scancode {
+    match code {
+        "Escape" => Scancode::Esc,
+        "Digit1" => Scancode::Num1,
+        "Digit2" => Scancode::Num2,
+        "Digit3" => Scancode::Num3,
+        "Digit4" => Scancode::Num4,
+        "Digit5" => Scancode::Num5,
+        "Digit6" => Scancode::Num6,
+        "Digit7" => Scancode::Num7,
+        "Digit8" => Scancode::Num8,
+        "Digit9" => Scancode::Num9,
+        "Digit0" => Scancode::Num0,
+        "Minus" => Scancode::Minus,
+        "Equal" => Scancode::Equal,
+        "Backspace" => Scancode::Backspace,
+        "Tab" => Scancode::Tab,
+        "KeyQ" => Scancode::Q,
+        "KeyW" => Scancode::W,
+        "KeyE" => Scancode::E,
+        "KeyR" => Scancode::R,
+        "KeyT" => Scancode::T,
+        "KeyY" => Scancode::Y,
+        "KeyU" => Scancode::U,
+        "KeyI" => Scancode::I,
+        "KeyO" => Scancode::O,
+        "KeyP" => Scancode::P,
+        "BracketLeft" => Scancode::BraceLeft,
+        "BracketRight" => Scancode::BraceRight,
+        "Enter" => Scancode::Enter,
+        "ControlLeft" => Scancode::CtrlLeft,
+        "KeyA" => Scancode::A,
+        "KeyS" => Scancode::S,
+        "KeyD" => Scancode::D,
+        "KeyF" => Scancode::F,
+        "KeyG" => Scancode::G,
+        "KeyH" => Scancode::H,
+        "KeyJ" => Scancode::J,
+        "KeyK" => Scancode::K,
+        "KeyL" => Scancode::L,
+        "Semicolon" => Scancode::Semicolon,
+        "Quote" => Scancode::Apostrophe,
+        "Backquote" => Scancode::Grave,
+        "ShiftLeft" => Scancode::ShiftLeft,
+        "Backslash" => Scancode::Backslash,
+        "KeyZ" => Scancode::Z,
+        "KeyX" => Scancode::X,
+        "KeyC" => Scancode::C,
+        "KeyV" => Scancode::V,
+        "KeyB" => Scancode::B,
+        "KeyN" => Scancode::N,
+        "KeyM" => Scancode::M,
+        "Comma" => Scancode::Comma,
+        "Period" => Scancode::Dot,
+        "Slash" => Scancode::Slash,
+        "ShiftRight" => Scancode::ShiftRight,
+        "AltLeft" => Scancode::AltLeft,
+        "Space" => Scancode::Space,
+        "CapsLock" => Scancode::CapsLock,
+        "NumLock" => Scancode::NumLock,
+        "ScrollLock" => Scancode::ScrollLock,
+        "ControlRight" => Scancode::CtrlRight,
+        "AltRight" => Scancode::AltRight,
+        "Home" => Scancode::Home,
+        "ArrowUp" => Scancode::ArrowUp,
+        "PageUp" => Scancode::PageUp,
+        "ArrowLeft" => Scancode::ArrowLeft,
+        "ArrowRight" => Scancode::ArrowRight,
+        "End" => Scancode::End,
+        "ArrowDown" => Scancode::ArrowDown,
+        "PageDown" => Scancode::PageDown,
+        "Insert" => Scancode::Insert,
+        "Delete" => Scancode::Delete,
+        _ => Scancode::Unidentified(RawKey::Unidentified),
+    }
+}
+
+// https://developer.mozilla.org/en-US/docs/Web/API/MouseEvent/button
+fn try_map_dom_button(button: f64) -> Option<Button> {
+    match button as i32 {
+        0 => Some(Button::Primary),
+        1 => Some(Button::Tertiary),
+        2 => Some(Button::Secondary),
+        _ => None,
+    }
+}
+
+// https://developer.mozilla.org/en-US/docs/Web/CSS/cursor
+fn map_cursor_shape_to_css(shape: CursorShape) -> &'static str {
+    match shape {
+        CursorShape::Default => "default",
+        CursorShape::Pointer => "pointer",
+        CursorShape::Text => "text",
+        CursorShape::Crosshair => "crosshair",
+        CursorShape::Move => "move",
+        CursorShape::Grab => "grab",
+        CursorShape::Grabbing => "grabbing",
+        CursorShape::ColResize => "col-resize",
+        CursorShape::EResize => "e-resize",
+        CursorShape::EwResize => "ew-resize",
+        CursorShape::NResize => "n-resize",
+        CursorShape::NeResize => "ne-resize",
+        CursorShape::NeswResize => "nesw-resize",
+        CursorShape::NsResize => "ns-resize",
+        CursorShape::NwResize => "nw-resize",
+        CursorShape::NwseResize => "nwse-resize",
+        CursorShape::RowResize => "row-resize",
+        CursorShape::SResize => "s-resize",
+        CursorShape::SeResize => "se-resize",
+        CursorShape::SwResize => "sw-resize",
+        CursorShape::WResize => "w-resize",
+    }
+}
+
+// clipboard
+// ----
+
+// NOTE: browsers are only required to support these in the async clipboard api.
+// https://w3c.github.io/clipboard-apis/#mandatory-data-types-x
+const CLIPBOARD_MIME_TYPE_TEXT: &str = "text/plain";
+const CLIPBOARD_MIME_TYPE_PNG: &str = "image/png";
+
+fn try_map_mime_type_to_clipboard(mime_type: &str) -> Option<&'static str> {
+    match mime_type {
+        CLIPBOARD_MIME_TYPE_TEXT => Some(CLIPBOARD_MIME_TYPE_TEXT),
+        _ if mime_type.starts_with("text/plain;") => Some(CLIPBOARD_MIME_TYPE_TEXT),
+        CLIPBOARD_MIME_TYPE_PNG => Some(CLIPBOARD_MIME_TYPE_PNG),
+        _ => None,
+    }
+}
+
+fn wasm_memory_buffer() -> js::Value {
+    js::GLUE
+        .get("instance")
+        .get("exports")
+        .get("memory")
+        .get("buffer")
+}
+
+fn copy_from_array_buffer(array_buffer: &js::Value) -> anyhow::Result<Vec<u8>> {
+    let uint8_array = js::GLOBAL.get("Uint8Array");
+    let src = uint8_array
+        .construct(slice::from_ref(array_buffer))
+        .context("could not construct uint8 array")?;
+    let len = src.get("length").as_f64() as usize;
+    let mut data = vec![0u8; len];
+    let dst = uint8_array
+        .construct(&[
+            wasm_memory_buffer(),
+            js::Value::from_f64(data.as_mut_ptr() as usize as f64),
+            js::Value::from_f64(len as f64),
+        ])
+        .context("could not construct uint8 array")?;
+    dst.get("set")
+        .call(&[src])
+        .context("could not copy array buffer")?;
+    Ok(data)
+}
+
+/// returns uint8 array that owns a copy of the data (it does not point into wasm memory that may
+/// get detached when memory grows).
+fn copy_into_uint8_array(data: &[u8]) -> anyhow::Result<js::Value> {
+    let view = js::GLOBAL
+        .get("Uint8Array")
+        .construct(&[
+            wasm_memory_buffer(),
+            js::Value::from_f64(data.as_ptr() as usize as f64),
+            js::Value::from_f64(data.len() as f64),
+        ])
+        .context("could not construct uint8 array")?;
+    view.get("slice").call(&[]).context("could not copy data")
+}
+
+fn new_array(values: &[js::Value]) -> anyhow::Result<js::Value> {
+    let array = js::GLOBAL
+        .get("Array")
+        .construct(&[])
+        .context("could not create array")?;
+    array
+        .get("push")
+        .call(values)
+        .context("could not push into array")?;
+    Ok(array)
+}
+
+fn js_error_message(err: &js::Value) -> String {
+    if err.is_object()
+        && let Some(message) = err.get("message").try_as_string()
+    {
+        return message;
+    }
+    err.try_as_string()
+        .unwrap_or_else(|| "unknown error".to_string())
+}
+
+// NOTE: async clipboard api is promise-based: read resolves to clipboard items, item resolves to a
+// blob, blob resolves to an array buffer. closures below are chained together (each one attaches
+// the next one to the promise it got) and the final result is queued as an event.
+struct WebClipboard {
+    // NOTE: mime type that was requested by the caller.
+    pending_read: Rc<RefCell<Option<String>>>,
+    events: Rc<RefCell<VecDeque<Event>>>,
+
+    read_closure: js::Closure<dyn FnMut(js::Value)>,
+    read_error_closure: js::Closure<dyn FnMut(js::Value)>,
+    _blob_closure: js::Closure<dyn FnMut(js::Value)>,
+    _array_buffer_closure: js::Closure<dyn FnMut(js::Value)>,
+    write_error_closure: js::Closure<dyn FnMut(js::Value)>,
+}
+
+impl WebClipboard {
+    fn new() -> Self {
+        let pending_read: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+        let events: Rc<RefCell<VecDeque<Event>>> = Rc::new(RefCell::new(VecDeque::new()));
+
+        let finish_read = {
+            let pending_read = Rc::clone(&pending_read);
+            let events = Rc::clone(&events);
+            move |data: Option<Vec<u8>>| {
+                let Some(mime_type) = pending_read.borrow_mut().take() else {
+                    return;
+                };
+                let event = match data {
+                    Some(data) => WindowEvent::ClipboardDataReceived { mime_type, data },
+                    None => WindowEvent::ClipboardReadFailed { mime_type },
+                };
+                events.borrow_mut().push_back(Event::Window(event));
+            }
+        };
+
+        let read_error_closure = js::Closure::new_with_arg({
+            let finish_read = finish_read.clone();
+            Box::new(move |err: js::Value| {
+                log::warn!("could not read clipboard: {}", js_error_message(&err));
+                finish_read(None);
+            })
+        });
+
+        let array_buffer_closure = js::Closure::new_with_arg({
+            let finish_read = finish_read.clone();
+            Box::new(
+                move |array_buffer: js::Value| match copy_from_array_buffer(&array_buffer) {
+                    Ok(data) => finish_read(Some(data)),
+                    Err(err) => {
+                        log::warn!("could not read clipboard: {err:?}");
+                        finish_read(None);
+                    }
+                },
+            )
+        });
+
+        let blob_closure = js::Closure::new_with_arg({
+            let finish_read = finish_read.clone();
+            let on_array_buffer = js::Value::from_closure(&array_buffer_closure);
+            let on_error = js::Value::from_closure(&read_error_closure);
+            Box::new(move |blob: js::Value| {
+                let attached = blob.get("arrayBuffer").call(&[]).and_then(|promise| {
+                    promise
+                        .get("then")
+                        .call(&[on_array_buffer.clone(), on_error.clone()])
+                });
+                if let Err(err) = attached {
+                    log::warn!("could not read clipboard blob: {err:?}");
+                    finish_read(None);
+                }
+            })
+        });
+
+        let read_closure = js::Closure::new_with_arg({
+            let pending_read = Rc::clone(&pending_read);
+            let finish_read = finish_read.clone();
+            let on_blob = js::Value::from_closure(&blob_closure);
+            let on_error = js::Value::from_closure(&read_error_closure);
+            Box::new(move |items: js::Value| {
+                let Some(clipboard_mime_type) = pending_read
+                    .borrow()
+                    .as_deref()
+                    .and_then(try_map_mime_type_to_clipboard)
+                else {
+                    return;
+                };
+                let clipboard_mime_type = js::Value::from_str(clipboard_mime_type);
+
+                let len = items.get("length").as_f64() as usize;
+                for i in 0..len {
+                    let item = items.get(&i.to_string());
+                    let has_type = item
+                        .get("types")
+                        .get("includes")
+                        .call(slice::from_ref(&clipboard_mime_type))
+                        .is_ok_and(|has_type| has_type.as_bool());
+                    if !has_type {
+                        continue;
+                    }
+
+                    let attached = item
+                        .get("getType")
+                        .call(slice::from_ref(&clipboard_mime_type))
+                        .and_then(|promise| {
+                            promise
+                                .get("then")
+                                .call(&[on_blob.clone(), on_error.clone()])
+                        });
+                    if let Err(err) = attached {
+                        log::warn!("could not get clipboard item: {err:?}");
+                        finish_read(None);
+                    }
+                    return;
+                }
+
+                finish_read(None);
+            })
+        });
+
+        let write_error_closure = js::Closure::new_with_arg(Box::new(|err: js::Value| {
+            log::warn!("could not write clipboard: {}", js_error_message(&err));
+        }));
+
+        Self {
+            pending_read,
+            events,
+
+            read_closure,
+            read_error_closure,
+            _blob_closure: blob_closure,
+            _array_buffer_closure: array_buffer_closure,
+            write_error_closure,
+        }
+    }
+
+    fn clipboard() -> anyhow::Result<js::Value> {
+        let clipboard = js::GLOBAL.get("navigator").get("clipboard");
+        if !clipboard.is_object() {
+            // NOTE: clipboard api is only available in secure contexts (https, localhost).
+            return Err(anyhow!("clipboard api is unavailable"));
+        }
+        Ok(clipboard)
+    }
+
+    fn read(&mut self, mime_type: &str) -> anyhow::Result<()> {
+        if try_map_mime_type_to_clipboard(mime_type).is_none() {
+            return Err(anyhow!("unsupported clipboard mime type: {mime_type}"));
+        }
+        if self.pending_read.borrow().is_some() {
+            return Err(anyhow!("clipboard read is already in progress"));
+        }
+
+        let promise = Self::clipboard()?
+            .get("read")
+            .call(&[])
+            .context("could not read clipboard")?;
+        *self.pending_read.borrow_mut() = Some(mime_type.to_string());
+        promise
+            .get("then")
+            .call(&[
+                js::Value::from_closure(&self.read_closure),
+                js::Value::from_closure(&self.read_error_closure),
+            ])
+            .context("could not attach clipboard read callbacks")?;
+
+        Ok(())
+    }
+
+    fn write(&mut self, data_provider: Box<dyn ClipboardDataProvider>) -> anyhow::Result<()> {
+        let clipboard = Self::clipboard()?;
+
+        // NOTE: ClipboardItem takes an object that maps mime types to blobs.
+        let item_data = js::GLOBAL
+            .get("Object")
+            .construct(&[])
+            .context("could not create object")?;
+        let mut buf: Vec<u8> = Vec::new();
+        let mut any = false;
+        for mime_type in data_provider.supported_mime_types() {
+            let Some(clipboard_mime_type) = try_map_mime_type_to_clipboard(mime_type) else {
+                continue;
+            };
+            if item_data.get(clipboard_mime_type) != js::UNDEFINED {
+                continue;
+            }
+
+            buf.clear();
+            data_provider
+                .write_as(mime_type, &mut buf)
+                .with_context(|| format!("could not write clipboard data as {mime_type}"))?;
+
+            let blob_options = js::GLOBAL
+                .get("Object")
+                .construct(&[])
+                .context("could not create object")?;
+            blob_options.set("type", &js::Value::from_str(clipboard_mime_type));
+            let blob_parts = new_array(&[copy_into_uint8_array(&buf)?])?;
+            let blob = js::GLOBAL
+                .get("Blob")
+                .construct(&[blob_parts, blob_options])
+                .context("could not create blob")?;
+            item_data.set(clipboard_mime_type, &blob);
+            any = true;
+        }
+        if !any {
+            return Err(anyhow!(
+                "none of provided mime types are supported by clipboard: {:?}",
+                data_provider.supported_mime_types()
+            ));
+        }
+
+        let item = js::GLOBAL
+            .get("ClipboardItem")
+            .construct(&[item_data])
+            .context("could not create clipboard item")?;
+        let items = new_array(&[item])?;
+        clipboard
+            .get("write")
+            .call(&[items])
+            .context("could not write clipboard")?
+            .get("then")
+            .call(&[
+                js::UNDEFINED,
+                js::Value::from_closure(&self.write_error_closure),
+            ])
+            .context("could not attach clipboard write callbacks")?;
+
+        Ok(())
+    }
+}
+
+/// prevents browser's default actions for events that the window handles itself.
+fn maybe_prevent_default(event: &js::Value) -> anyhow::Result<()> {
+    let prevent = match event.get("type").as_string().as_str() {
+        // NOTE: wheel would scroll the page; secondary button is ours.
+        "wheel" | "contextmenu" => true,
+        // NOTE: browser shortcuts are left alone; everything else (space, arrows, tab, etc.)
+        // would otherwise scroll the page or move focus away.
+        "keydown" | "keyup" => !(event.get("ctrlKey").as_bool() || event.get("metaKey").as_bool()),
+        _ => false,
+    };
+    if prevent {
+        event
+            .get("preventDefault")
+            .call(&[])
+            .context("could not prevent default")?;
+    }
+    Ok(())
+}
+
+// NOTE: dom events that the canvas listens to. all of them are queued and handled in pump_events.
+const DOM_EVENT_TYPES: &[&str] = &[
+    "pointerenter",
+    "pointerleave",
+    "pointermove",
+    "pointerdown",
+    "pointerup",
+    "pointercancel",
+    "wheel",
+    "contextmenu",
+    "keydown",
+    "keyup",
+    "focus",
+    "blur",
+];
 
 pub struct WebBackend {
     attrs: WindowAttrs,
@@ -12,6 +486,30 @@ pub struct WebBackend {
     canvas: js::Value,
     canvas_raw_handle: u32,
 
+    request_animation_frame: js::Value,
+    animation_frame_closure: js::Closure<dyn FnMut()>,
+    // NOTE: animation_frame_requested is true while waiting for animation frame callback;
+    // animation_frame_fired is set by the callback and is consumed in pump_events.
+    animation_frame_requested: bool,
+    animation_frame_fired: Rc<Cell<bool>>,
+
+    // NOTE: dom event listener only queues events (and prevents default actions). they are
+    // translated in pump_events.
+    dom_event_closure: js::Closure<dyn FnMut(js::Value)>,
+    dom_events: Rc<RefCell<VecDeque<js::Value>>>,
+
+    resize_observer: js::Value,
+    // NOTE: closure must outlive the observer.
+    _resize_observer_closure: js::Closure<dyn FnMut()>,
+    resize_observed: Rc<Cell<bool>>,
+    logical_size: (u32, u32),
+    scale_factor: f64,
+
+    cursor_shape: CursorShape,
+    cursor_visible: bool,
+
+    clipboard: WebClipboard,
+
     events: VecDeque<Event>,
 }
 
@@ -46,38 +544,117 @@ impl WebBackend {
         let dataset = canvas.get("dataset");
         dataset.set("rawHandle", &js::Value::from_f64(canvas_raw_handle as f64));
 
+        // NOTE: on web
+        //   canvas.style.width, canvas.style.height = css pixels (logical pixels)
+        //   canvas.width, canvas.height = framebuffer resolution (physical pixels).
+        //
+        // canvas.width and canvas.height are adjusted in pump_events when devicePixelRatio
+        // changes or when size of a canvas itself changes.
+        let logical_size = attrs.logical_size.unwrap_or(DEFAULT_LOGICAL_SIZE);
+        let scale_factor = js::GLOBAL.get("devicePixelRatio").as_f64();
         {
-            // NOTE: on web
-            //   canvas.style.width, canvas.style.height = css pixels (logical pixels)
-            //   canvas.width, canvas.height = framebuffer resolution (physical pixels).
-
-            let logical = attrs.logical_size.unwrap_or(DEFAULT_LOGICAL_SIZE);
-            let scale = js::GLOBAL.get("devicePixelRatio").as_f64();
-            let physical = ((logical.0 as f64 * scale), (logical.1 as f64 * scale));
+            let physical_size = logical_to_physical_size(logical_size, scale_factor);
 
             let style = canvas.get("style");
-            style.set("width", &js::Value::from_str(&format!("{}px", logical.0)));
-            style.set("height", &js::Value::from_str(&format!("{}px", logical.1)));
+            style.set(
+                "width",
+                &js::Value::from_str(&format!("{}px", logical_size.0)),
+            );
+            style.set(
+                "height",
+                &js::Value::from_str(&format!("{}px", logical_size.1)),
+            );
 
-            canvas.set("width", &js::Value::from_f64(physical.0));
-            canvas.set("height", &js::Value::from_f64(physical.1));
+            canvas.set("width", &js::Value::from_f64(physical_size.0 as f64));
+            canvas.set("height", &js::Value::from_f64(physical_size.1 as f64));
 
             // TODO: would it be good check if width and height were set correctly?
             //   ensure that there's no conflicts with css and stuff (maybe there are !important
             //   bangs on things).
             //   and if yes - bail out or log a warning or something?
+        }
 
-            // TODO: handle canvas resizing (resize observer?)
-            //   canvas.width, canvas.height need to be adjusted when devicePixelRatio changes or
-            //   when size of a canvas itself changes.
+        // NOTE: canvas is not focusable by default; it needs to be to receive keyboard events.
+        canvas.set("tabIndex", &js::Value::from_f64(0.0));
+
+        let dom_events = Rc::new(RefCell::new(VecDeque::new()));
+        let dom_event_closure = js::Closure::new_with_arg({
+            let dom_events = Rc::clone(&dom_events);
+            Box::new(move |event: js::Value| {
+                // NOTE: default actions can only be prevented while the event is being
+                // dispatched; pump_events is too late for that.
+                if let Err(err) = maybe_prevent_default(&event) {
+                    log::warn!("{err:?}");
+                }
+                dom_events.borrow_mut().push_back(event);
+            })
+        });
+        {
+            let add_event_listener = canvas.get("addEventListener");
+            // NOTE: listeners must not be passive to be able to prevent default actions (page
+            // scrolling on wheel for example).
+            let options = js::GLOBAL
+                .get("Object")
+                .construct(&[])
+                .context("could not create event listener options")?;
+            options.set("passive", &js::FALSE);
+            for dom_event_type in DOM_EVENT_TYPES {
+                add_event_listener
+                    .call(&[
+                        js::Value::from_str(dom_event_type),
+                        js::Value::from_closure(&dom_event_closure),
+                        options.clone(),
+                    ])
+                    .with_context(|| format!("could not add {dom_event_type} event listener"))?;
+            }
         }
 
+        let resize_observed = Rc::new(Cell::new(false));
+        let resize_observer_closure = js::Closure::new({
+            let resize_observed = Rc::clone(&resize_observed);
+            Box::new(move || resize_observed.set(true))
+        });
+        let resize_observer = js::GLOBAL
+            .get("ResizeObserver")
+            .construct(&[js::Value::from_closure(&resize_observer_closure)])
+            .context("could not create resize observer")?;
+        resize_observer
+            .get("observe")
+            .call(slice::from_ref(&canvas))
+            .context("could not observe canvas")?;
+
+        let request_animation_frame = js::GLOBAL.get("requestAnimationFrame");
+        let animation_frame_fired = Rc::new(Cell::new(false));
+        let animation_frame_closure = js::Closure::new({
+            let animation_frame_fired = Rc::clone(&animation_frame_fired);
+            Box::new(move || animation_frame_fired.set(true))
+        });
+
         let boxed = Box::new(Self {
             attrs,
 
             canvas,
             canvas_raw_handle,
 
+            request_animation_frame,
+            animation_frame_closure,
+            animation_frame_requested: false,
+            animation_frame_fired,
+
+            dom_event_closure,
+            dom_events,
+
+            resize_observer,
+            _resize_observer_closure: resize_observer_closure,
+            resize_observed,
+            logical_size,
+            scale_factor,
+
+            cursor_shape: CursorShape::Default,
+            cursor_visible: true,
+
+            clipboard: WebClipboard::new(),
+
             events,
         });
 
@@ -85,6 +662,196 @@ impl WebBackend {
     }
 }
 
+impl WebBackend {
+    fn handle_dom_event(&mut self, event: js::Value) -> anyhow::Result<()> {
+        let surface_id = self.surface_id();
+        let offset_position = |event: &js::Value| {
+            // NOTE: offset is in css pixels (logical pixels) relative to the canvas.
+            LogicalPosition::new(event.get("offsetX").as_f64(), event.get("offsetY").as_f64())
+        };
+        let event_type = event.get("type").as_string();
+        match event_type.as_str() {
+            "pointerenter" => {
+                self.events.push_back(Event::Pointer(PointerEvent {
+                    surface_id,
+                    kind: PointerEventKind::Enter {
+                        position: Some(offset_position(&event)),
+                    },
+                }));
+            }
+            "pointerleave" => {
+                self.events.push_back(Event::Pointer(PointerEvent {
+                    surface_id,
+                    kind: PointerEventKind::Leave,
+                }));
+            }
+            "pointermove" => {
+                self.events.push_back(Event::Pointer(PointerEvent {
+                    surface_id,
+                    kind: PointerEventKind::Move {
+                        position: offset_position(&event),
+                    },
+                }));
+            }
+            "pointerdown" | "pointerup" | "pointercancel" => {
+                let pressed = event_type == "pointerdown";
+                // NOTE: pointer capture makes the canvas receive pointer events (and thus
+                // releases) even if the pointer leaves it while button is held.
+                let capture_method = if pressed {
+                    "setPointerCapture"
+                } else {
+                    "releasePointerCapture"
+                };
+                self.canvas
+                    .get(capture_method)
+                    .call(&[event.get("pointerId")])
+                    .with_context(|| format!("could not {capture_method}"))?;
+                if pressed {
+                    self.canvas
+                        .get("focus")
+                        .call(&[])
+                        .context("could not focus canvas")?;
+                }
+
+                if let Some(button) = try_map_dom_button(event.get("button").as_f64()) {
+                    let state = if pressed {
+                        ButtonState::Pressed
+                    } else {
+                        ButtonState::Released
+                    };
+                    self.events.push_back(Event::Pointer(PointerEvent {
+                        surface_id,
+                        kind: PointerEventKind::Button { state, button },
+                    }));
+                }
+            }
+            "wheel" => {
+                // NOTE: deltas are normalized to "notches" (same as wayland backend does).
+                // https://developer.mozilla.org/en-US/docs/Web/API/WheelEvent/deltaMode
+                let scale = match event.get("deltaMode").as_f64() as u32 {
+                    // DOM_DELTA_PIXEL. chrome reports 100 pixels per notch.
+                    0 => 1.0 / 100.0,
+                    // DOM_DELTA_LINE. firefox reports 3 lines per notch.
+                    1 => 1.0 / 3.0,
+                    // DOM_DELTA_PAGE
+                    _ => 1.0,
+                };
+                let delta = (
+                    event.get("deltaX").as_f64() * scale,
+                    event.get("deltaY").as_f64() * scale,
+                );
+                self.events.push_back(Event::Pointer(PointerEvent {
+                    surface_id,
+                    kind: PointerEventKind::Scroll { delta },
+                }));
+            }
+            "contextmenu" => {
+                // NOTE: default action is prevented in the listener, there's nothing else to do.
+            }
+            "keydown" | "keyup" => {
+                let state = if event_type == "keydown" {
+                    KeyState::Pressed
+                } else {
+                    KeyState::Released
+                };
+                let scancode = map_dom_code(&event.get("code").as_string());
+                let key = event.get("key").as_string();
+                let mut chars = key.chars();
+                let keycode = match (chars.next(), chars.next()) {
+                    (Some(ch), None) => Keycode::Char(ch),
+                    _ => Keycode::Unidentified(RawKey::Unidentified),
+                };
+                self.events.push_back(Event::Keyboard(KeyboardEvent {
+                    surface_id,
+                    kind: KeyboardEventKind::Key {
+                        state,
+                        scancode,
+                        keycode,
+                        repeat: event.get("repeat").as_bool(),
+                    },
+                }));
+            }
+            "focus" | "blur" => {
+                self.events.push_back(Event::Keyboard(KeyboardEvent {
+                    surface_id,
+                    kind: if event_type == "focus" {
+                        KeyboardEventKind::Enter
+                    } else {
+                        KeyboardEventKind::Leave
+                    },
+                }));
+                self.events.push_back(Event::Window(WindowEvent::Focused {
+                    focused: event_type == "focus",
+                }));
+            }
+            other => {
+                log::debug!("unused dom event: {other}");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_cursor(&mut self) {
+        let value = if self.cursor_visible {
+            map_cursor_shape_to_css(self.cursor_shape)
+        } else {
+            "none"
+        };
+        self.canvas
+            .get("style")
+            .set("cursor", &js::Value::from_str(value));
+    }
+
+    fn maybe_resize(&mut self) {
+        // NOTE: there's no event for devicePixelRatio changes (other than matchMedia that needs to
+        // be re-created on each change); it is cheap enough to poll.
+        let scale_factor = js::GLOBAL.get("devicePixelRatio").as_f64();
+        let resized = self.resize_observed.replace(false);
+        if !resized && scale_factor == self.scale_factor {
+            return;
+        }
+
+        let logical_size = Window::logical_size(self);
+        let physical_size = logical_to_physical_size(logical_size, scale_factor);
+        self.canvas
+            .set("width", &js::Value::from_f64(physical_size.0 as f64));
+        self.canvas
+            .set("height", &js::Value::from_f64(physical_size.1 as f64));
+
+        if scale_factor != self.scale_factor {
+            self.scale_factor = scale_factor;
+            self.events
+                .push_back(Event::Window(WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                }));
+        }
+        if logical_size != self.logical_size {
+            self.logical_size = logical_size;
+            self.events
+                .push_back(Event::Window(WindowEvent::Resized { logical_size }));
+        }
+    }
+}
+
+impl Drop for WebBackend {
+    fn drop(&mut self) {
+        let remove_event_listener = self.canvas.get("removeEventListener");
+        for dom_event_type in DOM_EVENT_TYPES {
+            if let Err(err) = remove_event_listener.call(&[
+                js::Value::from_str(dom_event_type),
+                js::Value::from_closure(&self.dom_event_closure),
+            ]) {
+                log::warn!("could not remove {dom_event_type} event listener: {err:?}");
+            }
+        }
+
+        if let Err(err) = self.resize_observer.get("disconnect").call(&[]) {
+            log::warn!("could not disconnect resize observer: {err:?}");
+        }
+    }
+}
+
 impl rwh::HasDisplayHandle for WebBackend {
     fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
         let web = rwh::WebDisplayHandle::new();
@@ -102,8 +869,27 @@ impl rwh::HasWindowHandle for WebBackend {
 }
 
 impl Window for WebBackend {
-    fn pump_events(&mut self) -> anyhow::Result<()> {
-        // TODO
+    fn pump_events(&mut self, _timeout: Option<Duration>) -> anyhow::Result<()> {
+        // NOTE: there's no way to block in the browser, timeout is ignored. events arrive between
+        // animation frames.
+        if self.animation_frame_fired.replace(false) {
+            self.animation_frame_requested = false;
+            self.events
+                .push_back(Event::Window(WindowEvent::RedrawRequested));
+        }
+
+        // NOTE: handling dom events may cause new ones to be dispatched synchronously (focus for
+        // example), dom_events must not be borrowed while handling.
+        let dom_events = mem::take(&mut *self.dom_events.borrow_mut());
+        for event in dom_events {
+            self.handle_dom_event(event)?;
+        }
+
+        self.maybe_resize();
+
+        self.events
+            .extend(self.clipboard.events.borrow_mut().drain(..));
+
         Ok(())
     }
 
@@ -111,19 +897,84 @@ impl Window for WebBackend {
         self.events.pop_front()
     }
 
-    fn set_cursor_shape(&mut self, _cursor_shape: CursorShape) -> anyhow::Result<()> {
-        unimplemented!()
+    fn set_cursor_shape(&mut self, cursor_shape: CursorShape) -> anyhow::Result<()> {
+        self.cursor_shape = cursor_shape;
+        self.apply_cursor();
+        Ok(())
+    }
+
+    fn set_cursor_image(
+        &mut self,
+        _rgba: &[u8],
+        _size: (u32, u32),
+        _hotspot: (u32, u32),
+    ) -> anyhow::Result<()> {
+        // TODO: css cursor can be an image (`url(data:image/png;base64,...) x y, auto`), but that
+        // needs png encoding.
+        Err(anyhow!("custom cursor images are not supported on web"))
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> anyhow::Result<()> {
+        self.cursor_visible = visible;
+        self.apply_cursor();
+        Ok(())
+    }
+
+    fn set_pointer_grab(&mut self, grab: PointerGrab) -> anyhow::Result<()> {
+        // NOTE: browsers only support pointer lock. and it can only be requested in response to
+        // user action.
+        match grab {
+            PointerGrab::None => {
+                js::GLOBAL
+                    .get("document")
+                    .get("exitPointerLock")
+                    .call(&[])
+                    .context("could not exit pointer lock")?;
+            }
+            PointerGrab::Confined => {
+                return Err(anyhow!(
+                    "pointer confinement is not supported by web backend"
+                ));
+            }
+            PointerGrab::Locked => {
+                self.canvas
+                    .get("requestPointerLock")
+                    .call(&[])
+                    .context("could not request pointer lock")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_idle_inhibit(&mut self, _inhibit: bool) -> anyhow::Result<()> {
+        // TODO: screen wake lock api (navigator.wakeLock) can be used once js supports promises.
+        Err(anyhow!("idle inhibit is not supported by web backend"))
+    }
+
+    fn request_attention(&mut self) -> anyhow::Result<()> {
+        Err(anyhow!(
+            "attention requests are not supported by web backend"
+        ))
+    }
+
+    fn activate(&mut self, _token: &str) -> anyhow::Result<()> {
+        self.canvas
+            .get("focus")
+            .call(&[])
+            .context("could not focus canvas")?;
+        Ok(())
     }
 
-    fn read_clipboard(&mut self, _mime_type: &str, _buf: &mut Vec<u8>) -> anyhow::Result<usize> {
-        unimplemented!()
+    fn read_clipboard(&mut self, mime_type: &str, _buf: &mut Vec<u8>) -> anyhow::Result<usize> {
+        self.clipboard.read(mime_type)?;
+        Ok(0)
     }
 
     fn provide_clipboard_data(
         &mut self,
-        _data_provider: Box<dyn ClipboardDataProvider>,
+        data_provider: Box<dyn ClipboardDataProvider>,
     ) -> anyhow::Result<()> {
-        unimplemented!()
+        self.clipboard.write(data_provider)
     }
 
     fn logical_size(&self) -> (u32, u32) {
@@ -153,4 +1004,73 @@ impl Window for WebBackend {
         //   it can't really be cached.
         js::GLOBAL.get("devicePixelRatio").as_f64()
     }
+
+    fn request_redraw(&mut self) {
+        if self.animation_frame_requested {
+            return;
+        }
+        self.request_animation_frame
+            .call(&[js::Value::from_closure(&self.animation_frame_closure)])
+            .expect("could not request animation frame");
+        self.animation_frame_requested = true;
+    }
+
+    // NOTE: browser paces requestAnimationFrame itself and does not expose presentation feedback.
+    fn pre_present_notify(&mut self) {}
+
+    fn key_repeat_info(&self) -> Option<input::KeyRepeatInfo> {
+        // NOTE: there's no way to query it; `None` would disable repeating.
+        Some(input::KeyRepeatInfo::default())
+    }
+
+    fn outputs(&self) -> Vec<OutputInfo> {
+        // NOTE: browsers expose a single screen (without multi-screen window placement api).
+        let screen = js::GLOBAL.get("screen");
+        let scale_factor = js::GLOBAL.get("devicePixelRatio").as_f64();
+        let logical_size = (
+            screen.get("width").as_f64() as u32,
+            screen.get("height").as_f64() as u32,
+        );
+        let mut info = OutputInfo::new(OutputId(0));
+        info.scale_factor = scale_factor;
+        info.modes.push(OutputMode {
+            physical_size: logical_to_physical_size(logical_size, scale_factor),
+            refresh_mhz: 0,
+            current: true,
+            preferred: true,
+        });
+        info.logical_position = Some((0, 0));
+        info.logical_size = Some(logical_size);
+        vec![info]
+    }
+
+    fn surface_id(&self) -> SurfaceId {
+        SurfaceId(self.canvas_raw_handle as u64)
+    }
+
+    fn surface_window_handle(
+        &self,
+        surface_id: SurfaceId,
+    ) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+        if surface_id != self.surface_id() {
+            return Err(rwh::HandleError::Unavailable);
+        }
+        rwh::HasWindowHandle::window_handle(self)
+    }
+
+    fn create_popup(&mut self, _attrs: PopupAttrs) -> anyhow::Result<SurfaceId> {
+        Err(anyhow!("popups are not supported by web backend"))
+    }
+
+    fn reposition_popup(
+        &mut self,
+        _surface_id: SurfaceId,
+        _positioner: PopupPositioner,
+    ) -> anyhow::Result<()> {
+        Err(anyhow!("popups are not supported by web backend"))
+    }
+
+    fn destroy_popup(&mut self, _surface_id: SurfaceId) -> anyhow::Result<()> {
+        Err(anyhow!("popups are not supported by web backend"))
+    }
 }
@@ -1,15 +1,19 @@
 use std::collections::VecDeque;
 use std::hash::{DefaultHasher, Hash as _, Hasher as _};
+use std::time::Duration;
 
 use anyhow::{Context, anyhow};
 use input::{
     Button, ButtonState, CursorShape, KeyState, KeyboardEvent, KeyboardEventKind, Keycode,
-    PointerEvent, PointerEventKind, RawKey, Scancode, SurfaceId,
+    PhysicalPosition, PointerEvent, PointerEventKind, RawKey, Scancode, SurfaceId,
 };
 use raw_window_handle as rwh;
 use winit::platform::pump_events::EventLoopExtPumpEvents;
 
-use crate::{ClipboardDataProvider, DEFAULT_LOGICAL_SIZE, Event, Window, WindowAttrs, WindowEvent};
+use crate::{
+    ClipboardDataProvider, DEFAULT_LOGICAL_SIZE, Event, OutputId, OutputInfo, OutputMode,
+    PointerGrab, PopupAttrs, PopupPositioner, Window, WindowAttrs, WindowEvent,
+};
 
 #[inline]
 fn make_surface_id(window_id: winit::window::WindowId) -> SurfaceId {
@@ -18,6 +22,48 @@ fn make_surface_id(window_id: winit::window::WindowId) -> SurfaceId {
     SurfaceId(state.finish())
 }
 
+#[inline]
+fn make_output_id(monitor_handle: &winit::monitor::MonitorHandle) -> OutputId {
+    // NOTE: MonitorHandle is not hashable. name (connector name) and position identify a
+    // monitor well enough.
+    let mut state = DefaultHasher::new();
+    monitor_handle.name().hash(&mut state);
+    let position = monitor_handle.position();
+    (position.x, position.y).hash(&mut state);
+    OutputId(state.finish() as u32)
+}
+
+fn map_monitor_handle(monitor_handle: &winit::monitor::MonitorHandle) -> OutputInfo {
+    let scale_factor = monitor_handle.scale_factor();
+    let size = monitor_handle.size();
+    let refresh_mhz = monitor_handle.refresh_rate_millihertz();
+    let logical_position = monitor_handle.position().to_logical::<i32>(scale_factor);
+    let logical_size = size.to_logical::<u32>(scale_factor);
+
+    let mut info = OutputInfo::new(make_output_id(monitor_handle));
+    info.name = monitor_handle.name();
+    // NOTE: winit does not say which mode is current; the one that matches current size and
+    // refresh rate is.
+    info.modes = monitor_handle
+        .video_modes()
+        .map(|video_mode| {
+            let physical_size = video_mode.size();
+            let mode_refresh_mhz = video_mode.refresh_rate_millihertz();
+            OutputMode {
+                physical_size: (physical_size.width, physical_size.height),
+                refresh_mhz: mode_refresh_mhz,
+                current: physical_size == size && Some(mode_refresh_mhz) == refresh_mhz,
+                preferred: false,
+            }
+        })
+        .collect();
+    info.modes.dedup();
+    info.scale_factor = scale_factor;
+    info.logical_position = Some((logical_position.x, logical_position.y));
+    info.logical_size = Some((logical_size.width, logical_size.height));
+    info
+}
+
 #[inline]
 fn map_element_state_to_button_state(element_state: winit::event::ElementState) -> ButtonState {
     use winit::event::ElementState;
@@ -213,10 +259,43 @@ struct App {
 
     window: Option<winit::window::Window>,
     create_window_error: Option<winit::error::OsError>,
+    // NOTE: winit does not tell when window enters or leaves a monitor. current monitor is
+    // re-checked when window moves or its scale changes.
+    current_monitor: Option<winit::monitor::MonitorHandle>,
+    focused: bool,
+    // NOTE: device events are not tied to any window; relative motion is only forwarded while
+    // window has focus and holds a grab (like wayland's relative pointer with pointer
+    // constraints).
+    pointer_grab: PointerGrab,
 
     events: VecDeque<Event>,
 }
 
+impl App {
+    fn update_current_monitor(&mut self) {
+        let Some(ref window) = self.window else {
+            return;
+        };
+        let current_monitor = window.current_monitor();
+        if current_monitor == self.current_monitor {
+            return;
+        }
+        if let Some(ref prev) = self.current_monitor {
+            self.events
+                .push_back(Event::Window(WindowEvent::OutputLeft {
+                    output_id: make_output_id(prev),
+                }));
+        }
+        if let Some(ref next) = current_monitor {
+            self.events
+                .push_back(Event::Window(WindowEvent::OutputEntered {
+                    output_id: make_output_id(next),
+                }));
+        }
+        self.current_monitor = current_monitor;
+    }
+}
+
 pub struct WinitBackend {
     event_loop: winit::event_loop::EventLoop<()>,
     app: App,
@@ -242,6 +321,7 @@ impl winit::application::ApplicationHandler for App {
             Ok(window) => self.window = Some(window),
             Err(err) => self.create_window_error = Some(err),
         }
+        self.update_current_monitor();
 
         log::info!("created winit window");
     }
@@ -270,6 +350,10 @@ impl winit::application::ApplicationHandler for App {
                     .push_back(Event::Window(WindowEvent::ScaleFactorChanged {
                         scale_factor,
                     }));
+                self.update_current_monitor();
+            }
+            Moved(..) => {
+                self.update_current_monitor();
             }
             CursorEntered { .. } => {
                 self.events.push_back(Event::Pointer(PointerEvent {
@@ -287,7 +371,8 @@ impl winit::application::ApplicationHandler for App {
                 // NOTE: sdl, wayland provide positions in logical pixels. i kind of want to
                 // conform to that across the board.
                 let scale_factor = window.scale_factor();
-                let position = (position.x / scale_factor, position.y / scale_factor);
+                let position =
+                    PhysicalPosition::new(position.x, position.y).to_logical(scale_factor);
                 self.events.push_back(Event::Pointer(PointerEvent {
                     surface_id,
                     kind: PointerEventKind::Move { position },
@@ -352,15 +437,58 @@ impl winit::application::ApplicationHandler for App {
                     },
                 }));
             }
+            Focused(focused) => {
+                self.focused = focused;
+                self.events.push_back(Event::Keyboard(KeyboardEvent {
+                    surface_id,
+                    kind: if focused {
+                        KeyboardEventKind::Enter
+                    } else {
+                        KeyboardEventKind::Leave
+                    },
+                }));
+                self.events
+                    .push_back(Event::Window(WindowEvent::Focused { focused }));
+            }
             CloseRequested => {
                 self.events
                     .push_back(Event::Window(WindowEvent::CloseRequested));
             }
+            RedrawRequested => {
+                self.events
+                    .push_back(Event::Window(WindowEvent::RedrawRequested));
+            }
             other => {
                 log::debug!("unused window event: {other:?}");
             }
         }
     }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &winit::event_loop::ActiveEventLoop,
+        _device_id: winit::event::DeviceId,
+        device_event: winit::event::DeviceEvent,
+    ) {
+        let Some(ref window) = self.window else {
+            return;
+        };
+        let surface_id = make_surface_id(window.id());
+        if !self.focused || self.pointer_grab == PointerGrab::None {
+            return;
+        }
+
+        // NOTE: winit only provides raw (unaccelerated) motion.
+        if let winit::event::DeviceEvent::MouseMotion { delta } = device_event {
+            self.events.push_back(Event::Pointer(PointerEvent {
+                surface_id,
+                kind: PointerEventKind::RelativeMotion {
+                    delta,
+                    unaccelerated_delta: delta,
+                },
+            }));
+        }
+    }
 }
 
 impl WinitBackend {
@@ -372,13 +500,16 @@ impl WinitBackend {
 
                 window: None,
                 create_window_error: None,
+                current_monitor: None,
+                focused: false,
+                pointer_grab: PointerGrab::None,
 
                 events: VecDeque::new(),
             },
         };
 
         // NOTE: wait for window to be created on stupid winit's "resume".
-        this.pump_events()
+        this.pump_events(Some(Duration::ZERO))
             .context("could not punp events while awaiting window creation")?;
 
         if let Some(err) = this.app.create_window_error.take() {
@@ -407,16 +538,25 @@ impl rwh::HasWindowHandle for WinitBackend {
 }
 
 impl Window for WinitBackend {
-    fn pump_events(&mut self) -> anyhow::Result<()> {
-        // NOTE: passing timeout to appear to pump_app_events appear to do absolutely nothing
-        // (tested only on wayland). thus control flow "hack"?
-        //
-        // TODO: support timeout arg that would allow to set different control flows.
+    fn pump_events(&mut self, timeout: Option<Duration>) -> anyhow::Result<()> {
+        let timeout = if self.app.events.is_empty() {
+            timeout
+        } else {
+            Some(Duration::ZERO)
+        };
+
+        // NOTE: pump_app_events's timeout alone does nothing; with ControlFlow::Poll it never
+        // blocks. ControlFlow::Wait lets it block for as long as timeout allows.
         use winit::event_loop::ControlFlow;
-        self.event_loop.set_control_flow(ControlFlow::Poll);
+        self.event_loop
+            .set_control_flow(if timeout == Some(Duration::ZERO) {
+                ControlFlow::Poll
+            } else {
+                ControlFlow::Wait
+            });
 
         use winit::platform::pump_events::PumpStatus;
-        let ret = match self.event_loop.pump_app_events(None, &mut self.app) {
+        let ret = match self.event_loop.pump_app_events(timeout, &mut self.app) {
             PumpStatus::Exit(code) => Err(anyhow!(format!("unexpected exit (code {code})"))),
             PumpStatus::Continue => Ok(()),
         };
@@ -435,6 +575,72 @@ impl Window for WinitBackend {
         Ok(())
     }
 
+    fn set_cursor_image(
+        &mut self,
+        rgba: &[u8],
+        size: (u32, u32),
+        hotspot: (u32, u32),
+    ) -> anyhow::Result<()> {
+        let Some(ref window) = self.app.window else {
+            return Ok(());
+        };
+        let custom_cursor_source = winit::window::CustomCursor::from_rgba(
+            rgba,
+            size.0.try_into().context("cursor image is too wide")?,
+            size.1.try_into().context("cursor image is too tall")?,
+            hotspot.0.try_into().context("invalid cursor hotspot")?,
+            hotspot.1.try_into().context("invalid cursor hotspot")?,
+        )
+        .context("invalid cursor image")?;
+        let custom_cursor = self.event_loop.create_custom_cursor(custom_cursor_source);
+        window.set_cursor(custom_cursor);
+        Ok(())
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> anyhow::Result<()> {
+        if let Some(ref window) = self.app.window {
+            window.set_cursor_visible(visible);
+        }
+        Ok(())
+    }
+
+    fn set_pointer_grab(&mut self, grab: PointerGrab) -> anyhow::Result<()> {
+        use winit::window::CursorGrabMode;
+        let Some(ref window) = self.app.window else {
+            return Ok(());
+        };
+        let mode = match grab {
+            PointerGrab::None => CursorGrabMode::None,
+            PointerGrab::Confined => CursorGrabMode::Confined,
+            PointerGrab::Locked => CursorGrabMode::Locked,
+        };
+        window
+            .set_cursor_grab(mode)
+            .with_context(|| format!("could not set cursor grab mode to {mode:?}"))?;
+        self.app.pointer_grab = grab;
+        Ok(())
+    }
+
+    fn set_idle_inhibit(&mut self, _inhibit: bool) -> anyhow::Result<()> {
+        Err(anyhow!("winit backend does not support idle inhibit"))
+    }
+
+    fn request_attention(&mut self) -> anyhow::Result<()> {
+        if let Some(ref window) = self.app.window {
+            window.request_user_attention(Some(winit::window::UserAttentionType::Informational));
+        }
+        Ok(())
+    }
+
+    fn activate(&mut self, _token: &str) -> anyhow::Result<()> {
+        // NOTE: winit consumes startup activation token on its own; it does not allow to activate
+        // with arbitrary token, focus_window is the closest thing.
+        if let Some(ref window) = self.app.window {
+            window.focus_window();
+        }
+        Ok(())
+    }
+
     fn read_clipboard(&mut self, _mime_type: &str, _buf: &mut Vec<u8>) -> anyhow::Result<usize> {
         log::warn!("winit backend does not support clipboard");
         // TODO: support wayland clipboard (but first separate it out from wayland backend).
@@ -461,4 +667,61 @@ impl Window for WinitBackend {
         let window = self.app.window.as_ref().expect("initialized window");
         window.scale_factor()
     }
+
+    fn request_redraw(&mut self) {
+        let window = self.app.window.as_ref().expect("initialized window");
+        window.request_redraw();
+    }
+
+    // TODO: winit does not provide presentation feedback.
+    fn pre_present_notify(&mut self) {
+        let window = self.app.window.as_ref().expect("initialized window");
+        window.pre_present_notify();
+    }
+
+    fn key_repeat_info(&self) -> Option<input::KeyRepeatInfo> {
+        // NOTE: there's no way to query it; `None` would disable repeating.
+        Some(input::KeyRepeatInfo::default())
+    }
+
+    fn outputs(&self) -> Vec<OutputInfo> {
+        let window = self.app.window.as_ref().expect("initialized window");
+        window
+            .available_monitors()
+            .map(|monitor_handle| map_monitor_handle(&monitor_handle))
+            .collect()
+    }
+
+    fn surface_id(&self) -> SurfaceId {
+        let window = self.app.window.as_ref().expect("initialized window");
+        make_surface_id(window.id())
+    }
+
+    fn surface_window_handle(
+        &self,
+        surface_id: SurfaceId,
+    ) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+        if surface_id != self.surface_id() {
+            return Err(rwh::HandleError::Unavailable);
+        }
+        rwh::HasWindowHandle::window_handle(self)
+    }
+
+    // TODO: winit does not support popups (yet?). those could be emulated with child windows.
+
+    fn create_popup(&mut self, _attrs: PopupAttrs) -> anyhow::Result<SurfaceId> {
+        Err(anyhow!("popups are not supported by winit backend"))
+    }
+
+    fn reposition_popup(
+        &mut self,
+        _surface_id: SurfaceId,
+        _positioner: PopupPositioner,
+    ) -> anyhow::Result<()> {
+        Err(anyhow!("popups are not supported by winit backend"))
+    }
+
+    fn destroy_popup(&mut self, _surface_id: SurfaceId) -> anyhow::Result<()> {
+        Err(anyhow!("popups are not supported by winit backend"))
+    }
 }
@@ -0,0 +1,339 @@
+// stand-in compositor for tests. handles just enough of wayland requests to let wayland backend
+// create a toplevel window, to tell it the preferred scale and to deliver frame callbacks. wire
+// protocol itself is spoken by wayland::fake_compositor.
+
+use std::collections::HashMap;
+use std::mem;
+use std::os::fd::OwnedFd;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use wayland::fake_compositor::{Arg, Args, FakeCompositorHandler, FakeConnection};
+
+const WL_COMPOSITOR_GLOBAL_NAME: u32 = 1;
+const WL_SEAT_GLOBAL_NAME: u32 = 2;
+const WL_SHM_GLOBAL_NAME: u32 = 3;
+const XDG_WM_BASE_GLOBAL_NAME: u32 = 4;
+const WP_VIEWPORTER_GLOBAL_NAME: u32 = 5;
+const WP_CURSOR_SHAPE_MANAGER_V1_GLOBAL_NAME: u32 = 6;
+const WP_FRACTIONAL_SCALE_MANAGER_V1_GLOBAL_NAME: u32 = 7;
+
+#[derive(Debug, Clone, Copy)]
+pub enum FakeScale {
+    /// wp_fractional_scale_v1.preferred_scale; numerator of a fraction with a denominator of 120.
+    Fractional(u32),
+    /// wl_surface.preferred_buffer_scale (wl_compositor version 6); fractional-scale-v1 is not
+    /// advertised.
+    Integer(i32),
+}
+
+/// what client did, as seen by the compositor.
+#[derive(Debug, Default, Clone)]
+pub struct FakeSurfaceState {
+    pub commits: u32,
+    pub frame_callbacks: u32,
+    pub viewport_destination: Option<(i32, i32)>,
+}
+
+/// returns client's end of the connection.
+pub fn spawn(
+    scale: Option<FakeScale>,
+) -> (
+    OwnedFd,
+    Arc<Mutex<FakeSurfaceState>>,
+    thread::JoinHandle<()>,
+) {
+    let state = Arc::new(Mutex::new(FakeSurfaceState::default()));
+    let (fd, handle) = wayland::fake_compositor::spawn_fake_compositor(FakeCompositor {
+        objects: HashMap::from([(1, Object::Display)]),
+        scale,
+        next_serial: 1,
+        pending_frame_callbacks: Vec::new(),
+        state: Arc::clone(&state),
+    });
+    (fd, state, handle)
+}
+
+enum Object {
+    Display,
+    Registry,
+    Compositor,
+    Surface {
+        xdg_surface: Option<u32>,
+        fractional_scale: Option<u32>,
+    },
+    Seat,
+    Pointer,
+    Keyboard,
+    Shm,
+    XdgWmBase,
+    XdgSurface {
+        xdg_toplevel: Option<u32>,
+    },
+    XdgToplevel,
+    Viewporter,
+    Viewport,
+    CursorShapeManager,
+    CursorShapeDevice,
+    FractionalScaleManager,
+    FractionalScale,
+}
+
+struct FakeCompositor {
+    objects: HashMap<u32, Object>,
+    scale: Option<FakeScale>,
+    next_serial: u32,
+    pending_frame_callbacks: Vec<u32>,
+    state: Arc<Mutex<FakeSurfaceState>>,
+}
+
+impl FakeCompositor {
+    fn delete_id(&mut self, conn: &mut FakeConnection, id: u32) {
+        self.objects.remove(&id);
+        conn.delete_id(id);
+    }
+
+    fn next_serial(&mut self) -> u32 {
+        let serial = self.next_serial;
+        self.next_serial += 1;
+        serial
+    }
+
+    fn handle_surface_commit(&mut self, conn: &mut FakeConnection, surface: u32) {
+        let Some(&Object::Surface {
+            xdg_surface,
+            fractional_scale,
+        }) = self.objects.get(&surface)
+        else {
+            panic!("invalid surface");
+        };
+        let first_commit = {
+            let mut state = self.state.lock().unwrap();
+            state.commits += 1;
+            state.commits == 1
+        };
+
+        // NOTE: scale is sent before the initial configure, like compositors do when the surface
+        // is mapped on a known output.
+        if first_commit {
+            match self.scale {
+                Some(FakeScale::Fractional(scale)) => {
+                    let fractional_scale = fractional_scale.expect("missing fractional scale");
+                    // wp_fractional_scale_v1.preferred_scale
+                    conn.send(fractional_scale, 0, &[Arg::Uint(scale)]);
+                }
+                Some(FakeScale::Integer(factor)) => {
+                    // wl_surface.preferred_buffer_scale
+                    conn.send(surface, 2, &[Arg::Int(factor)]);
+                }
+                None => {}
+            }
+        }
+
+        if first_commit && let Some(xdg_surface) = xdg_surface {
+            let Some(Object::XdgSurface {
+                xdg_toplevel: Some(xdg_toplevel),
+            }) = self.objects.get(&xdg_surface)
+            else {
+                panic!("xdg surface has no role");
+            };
+            let xdg_toplevel = *xdg_toplevel;
+            // xdg_toplevel.configure; zero size lets client pick its own.
+            conn.send(
+                xdg_toplevel,
+                0,
+                &[Arg::Int(0), Arg::Int(0), Arg::Array(&[])],
+            );
+            // xdg_surface.configure
+            let serial = self.next_serial();
+            conn.send(xdg_surface, 0, &[Arg::Uint(serial)]);
+        }
+
+        for callback in mem::take(&mut self.pending_frame_callbacks) {
+            // wl_callback.done
+            conn.send(callback, 0, &[Arg::Uint(0)]);
+            self.delete_id(conn, callback);
+        }
+    }
+}
+
+impl FakeCompositorHandler for FakeCompositor {
+    fn handle_request(&mut self, conn: &mut FakeConnection, id: u32, opcode: u16, mut args: Args) {
+        let object = self.objects.get(&id).expect("unknown object");
+        match (object, opcode) {
+            // wl_display.sync
+            (Object::Display, 0) => conn.sync(args.uint()),
+            // wl_display.get_registry
+            (Object::Display, 1) => {
+                let registry = args.uint();
+                self.objects.insert(registry, Object::Registry);
+                let (wl_compositor_version, fractional_scale) = match self.scale {
+                    Some(FakeScale::Fractional(..)) => (4, true),
+                    Some(FakeScale::Integer(..)) => (6, false),
+                    None => (4, false),
+                };
+                let mut globals = vec![
+                    (
+                        WL_COMPOSITOR_GLOBAL_NAME,
+                        "wl_compositor",
+                        wl_compositor_version,
+                    ),
+                    (WL_SEAT_GLOBAL_NAME, "wl_seat", 5),
+                    (WL_SHM_GLOBAL_NAME, "wl_shm", 1),
+                    (XDG_WM_BASE_GLOBAL_NAME, "xdg_wm_base", 1),
+                    (WP_VIEWPORTER_GLOBAL_NAME, "wp_viewporter", 1),
+                    (
+                        WP_CURSOR_SHAPE_MANAGER_V1_GLOBAL_NAME,
+                        "wp_cursor_shape_manager_v1",
+                        1,
+                    ),
+                ];
+                if fractional_scale {
+                    globals.push((
+                        WP_FRACTIONAL_SCALE_MANAGER_V1_GLOBAL_NAME,
+                        "wp_fractional_scale_manager_v1",
+                        1,
+                    ));
+                }
+                for (name, interface, version) in globals {
+                    // wl_registry.global
+                    conn.send(
+                        registry,
+                        0,
+                        &[Arg::Uint(name), Arg::Str(interface), Arg::Uint(version)],
+                    );
+                }
+            }
+            // wl_registry.bind
+            (Object::Registry, 0) => {
+                let name = args.uint();
+                let _interface = args.string();
+                let _version = args.uint();
+                let new_id = args.uint();
+                let object = match name {
+                    WL_COMPOSITOR_GLOBAL_NAME => Object::Compositor,
+                    WL_SEAT_GLOBAL_NAME => Object::Seat,
+                    WL_SHM_GLOBAL_NAME => Object::Shm,
+                    XDG_WM_BASE_GLOBAL_NAME => Object::XdgWmBase,
+                    WP_VIEWPORTER_GLOBAL_NAME => Object::Viewporter,
+                    WP_CURSOR_SHAPE_MANAGER_V1_GLOBAL_NAME => Object::CursorShapeManager,
+                    WP_FRACTIONAL_SCALE_MANAGER_V1_GLOBAL_NAME => Object::FractionalScaleManager,
+                    _ => panic!("unknown global: {name}"),
+                };
+                self.objects.insert(new_id, object);
+            }
+            // wl_compositor.create_surface
+            (Object::Compositor, 0) => {
+                let new_id = args.uint();
+                self.objects.insert(
+                    new_id,
+                    Object::Surface {
+                        xdg_surface: None,
+                        fractional_scale: None,
+                    },
+                );
+            }
+            // wl_surface.destroy
+            (Object::Surface { .. }, 0) => self.delete_id(conn, id),
+            // wl_surface.frame
+            (Object::Surface { .. }, 3) => {
+                let callback = args.uint();
+                self.pending_frame_callbacks.push(callback);
+                self.state.lock().unwrap().frame_callbacks += 1;
+            }
+            // wl_surface.commit
+            (Object::Surface { .. }, 6) => self.handle_surface_commit(conn, id),
+            // wl_seat.get_pointer
+            (Object::Seat, 0) => {
+                let new_id = args.uint();
+                self.objects.insert(new_id, Object::Pointer);
+            }
+            // wl_seat.get_keyboard
+            (Object::Seat, 1) => {
+                let new_id = args.uint();
+                self.objects.insert(new_id, Object::Keyboard);
+            }
+            // wl_seat.release
+            (Object::Seat, 3) => self.delete_id(conn, id),
+            // wl_pointer.release
+            (Object::Pointer, 1) => self.delete_id(conn, id),
+            // wl_keyboard.release
+            (Object::Keyboard, 0) => self.delete_id(conn, id),
+            // xdg_wm_base.destroy
+            (Object::XdgWmBase, 0) => self.delete_id(conn, id),
+            // xdg_wm_base.get_xdg_surface
+            (Object::XdgWmBase, 2) => {
+                let new_id = args.uint();
+                let surface = args.uint();
+                let Some(Object::Surface { xdg_surface, .. }) = self.objects.get_mut(&surface)
+                else {
+                    panic!("invalid surface");
+                };
+                *xdg_surface = Some(new_id);
+                self.objects
+                    .insert(new_id, Object::XdgSurface { xdg_toplevel: None });
+            }
+            // xdg_surface.destroy
+            (Object::XdgSurface { .. }, 0) => self.delete_id(conn, id),
+            // xdg_surface.get_toplevel
+            (Object::XdgSurface { .. }, 1) => {
+                let new_id = args.uint();
+                self.objects.insert(
+                    id,
+                    Object::XdgSurface {
+                        xdg_toplevel: Some(new_id),
+                    },
+                );
+                self.objects.insert(new_id, Object::XdgToplevel);
+            }
+            // xdg_surface.ack_configure
+            (Object::XdgSurface { .. }, 4) => {}
+            // xdg_toplevel.destroy
+            (Object::XdgToplevel, 0) => self.delete_id(conn, id),
+            // xdg_toplevel.set_max_size, xdg_toplevel.set_min_size
+            (Object::XdgToplevel, 7 | 8) => {}
+            // wp_viewporter.destroy
+            (Object::Viewporter, 0) => self.delete_id(conn, id),
+            // wp_viewporter.get_viewport
+            (Object::Viewporter, 1) => {
+                let new_id = args.uint();
+                self.objects.insert(new_id, Object::Viewport);
+            }
+            // wp_viewport.destroy
+            (Object::Viewport, 0) => self.delete_id(conn, id),
+            // wp_viewport.set_destination
+            (Object::Viewport, 2) => {
+                let width = args.uint() as i32;
+                let height = args.uint() as i32;
+                self.state.lock().unwrap().viewport_destination = Some((width, height));
+            }
+            // wp_cursor_shape_manager_v1.destroy
+            (Object::CursorShapeManager, 0) => self.delete_id(conn, id),
+            // wp_cursor_shape_manager_v1.get_pointer
+            (Object::CursorShapeManager, 1) => {
+                let new_id = args.uint();
+                self.objects.insert(new_id, Object::CursorShapeDevice);
+            }
+            // wp_cursor_shape_device_v1.destroy
+            (Object::CursorShapeDevice, 0) => self.delete_id(conn, id),
+            // wp_fractional_scale_manager_v1.destroy
+            (Object::FractionalScaleManager, 0) => self.delete_id(conn, id),
+            // wp_fractional_scale_manager_v1.get_fractional_scale
+            (Object::FractionalScaleManager, 1) => {
+                let new_id = args.uint();
+                let surface = args.uint();
+                let Some(Object::Surface {
+                    fractional_scale, ..
+                }) = self.objects.get_mut(&surface)
+                else {
+                    panic!("invalid surface");
+                };
+                *fractional_scale = Some(new_id);
+                self.objects.insert(new_id, Object::FractionalScale);
+            }
+            // wp_fractional_scale_v1.destroy
+            (Object::FractionalScale, 0) => self.delete_id(conn, id),
+            _ => panic!("unexpected request: object {id}, opcode {opcode}"),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::env;
+use std::time::Duration;
 
 use anyhow::{Context as _, anyhow};
 use raw_window_handle as rwh;
@@ -6,6 +7,9 @@ use raw_window_handle as rwh;
 #[cfg(unix)]
 mod backend_wayland;
 
+#[cfg(all(test, unix))]
+mod fake_compositor;
+
 #[cfg(feature = "winit")]
 mod backend_winit;
 
@@ -18,6 +22,34 @@ mod backend_web;
 
 pub const DEFAULT_LOGICAL_SIZE: (u32, u32) = (640, 480);
 
+pub const ACTIVATION_TOKEN_ENV_VAR: &str = "XDG_ACTIVATION_TOKEN";
+
+/// reads activation token that was given to this process by whatever launched it (if any).
+///
+/// window backends use it on startup to get focus. but it may be useful to read it manually if
+/// an instance of the app is already running: the token can be passed to it (in whatever way)
+/// and it can use it with [`Window::activate`].
+///
+/// NOTE: the token is not removed from the environment, modifying it is unsound while other
+/// threads may be reading it. if child processes must not inherit the token, remove it with
+/// `Command::env_remove` when spawning them (or from the environment early in `main`).
+pub fn activation_token_from_env() -> Option<String> {
+    env::var(ACTIVATION_TOKEN_ENV_VAR).ok()
+}
+
+/// computes size of the buffer that needs to be rendered to cover the surface of given logical
+/// size.
+///
+/// NOTE: fractional-scale-v1 wants toplevel sizes to be rounded halfway away from zero, but
+/// rounding up ensures that buffer is never smaller than the surface (it gets scaled down into
+/// viewport destination anyway).
+pub fn logical_to_physical_size(logical_size: (u32, u32), scale_factor: f64) -> (u32, u32) {
+    (
+        (logical_size.0 as f64 * scale_factor).ceil() as u32,
+        (logical_size.1 as f64 * scale_factor).ceil() as u32,
+    )
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct WindowAttrs {
     /// defaults to `canvas`.
@@ -33,14 +65,279 @@ pub struct WindowAttrs {
     pub resizable: bool,
 }
 
+// output
+// ----
+
+/// is unique for the lifetime of the window (on wayland it's the registry name of wl_output
+/// global).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutputId(pub u32);
+
+/// mirrors wl_output's transform enum. rotations are counter-clockwise.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputTransform {
+    #[default]
+    Normal,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Flipped,
+    Flipped90,
+    Flipped180,
+    Flipped270,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputMode {
+    pub physical_size: (u32, u32),
+    /// in mHz. may be 0 if it does not make sense (virtual outputs for example).
+    pub refresh_mhz: u32,
+    pub current: bool,
+    pub preferred: bool,
+}
+
+impl OutputMode {
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        if self.refresh_mhz == 0 {
+            return None;
+        }
+        Some(Duration::from_nanos(
+            1_000_000_000_000 / self.refresh_mhz as u64,
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputInfo {
+    pub id: OutputId,
+    /// connector name (e.g. `DP-1`). is stable across sessions, unlike id.
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    /// may be 0 if it does not make sense (projectors, virtual outputs).
+    pub physical_size_mm: (u32, u32),
+    pub modes: Vec<OutputMode>,
+    /// integer scale advertised by the output. windows get their own (possibly fractional)
+    /// scale, see [`WindowEvent::ScaleFactorChanged`].
+    pub scale_factor: f64,
+    pub transform: OutputTransform,
+    /// position and size in the global compositor space. may be unavailable.
+    pub logical_position: Option<(i32, i32)>,
+    pub logical_size: Option<(u32, u32)>,
+}
+
+impl OutputInfo {
+    pub fn new(id: OutputId) -> Self {
+        Self {
+            id,
+            name: None,
+            description: None,
+            make: None,
+            model: None,
+            physical_size_mm: (0, 0),
+            modes: Vec::new(),
+            scale_factor: 1.0,
+            transform: OutputTransform::default(),
+            logical_position: None,
+            logical_size: None,
+        }
+    }
+
+    pub fn current_mode(&self) -> Option<&OutputMode> {
+        self.modes.iter().find(|mode| mode.current)
+    }
+}
+
+// popup
+// ----
+
+/// mirrors xdg_positioner's anchor enum. the same set of values is used for gravity.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PopupAnchor {
+    #[default]
+    None,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    BottomLeft,
+    TopRight,
+    BottomRight,
+}
+
+pub type PopupGravity = PopupAnchor;
+
+/// tells the compositor how to move the popup if it would end up being constrained (partially
+/// off-screen for example). multiple adjustments can be combined.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PopupConstraintAdjustment(pub u32);
+
+impl PopupConstraintAdjustment {
+    pub const NONE: u32 = 0;
+    pub const SLIDE_X: u32 = 1 << 0;
+    pub const SLIDE_Y: u32 = 1 << 1;
+    pub const FLIP_X: u32 = 1 << 2;
+    pub const FLIP_Y: u32 = 1 << 3;
+    pub const RESIZE_X: u32 = 1 << 4;
+    pub const RESIZE_Y: u32 = 1 << 5;
+}
+
+// https://wayland.app/protocols/xdg-shell#xdg_positioner
+#[derive(Debug, Clone)]
+pub struct PopupPositioner {
+    pub logical_size: (u32, u32),
+    /// position and size of the rect relative to the parent surface that the popup will be
+    /// anchored to (x, y, width, height). in logical pixels.
+    pub anchor_rect: (i32, i32, u32, u32),
+    /// edge or corner of the anchor rect that the popup will be attached to.
+    pub anchor: PopupAnchor,
+    /// direction in which the popup will be placed relative to the anchor point.
+    pub gravity: PopupGravity,
+    pub constraint_adjustment: PopupConstraintAdjustment,
+    pub offset: (i32, i32),
+}
+
+impl PopupPositioner {
+    pub fn new(logical_size: (u32, u32), anchor_rect: (i32, i32, u32, u32)) -> Self {
+        Self {
+            logical_size,
+            anchor_rect,
+            anchor: PopupAnchor::BottomLeft,
+            gravity: PopupGravity::BottomRight,
+            constraint_adjustment: PopupConstraintAdjustment(
+                PopupConstraintAdjustment::SLIDE_X | PopupConstraintAdjustment::FLIP_Y,
+            ),
+            offset: (0, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PopupAttrs {
+    /// if not specified - popup will be a child of the window.
+    pub parent: Option<input::SurfaceId>,
+    pub positioner: PopupPositioner,
+    /// grab makes popup receive all pointer and keyboard events (context menus want this,
+    /// tooltips don't). grab is only taken in response to a pointer button or a key press on the
+    /// parent; otherwise popup is created without it.
+    pub grab: bool,
+}
+
+// pointer grab
+// ----
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PointerGrab {
+    #[default]
+    None,
+    /// pointer can't leave the window.
+    Confined,
+    /// pointer can't move at all; only [`input::PointerEventKind::RelativeMotion`] events are
+    /// dispatched. cursor visibility is not affected (see [`Window::set_cursor_visible`]).
+    Locked,
+}
+
+// presentation
+// ----
+
+/// mirrors wp_presentation_feedback's kind enum.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PresentationFlags(pub u32);
+
+impl PresentationFlags {
+    /// presentation was synchronized to the vertical retrace.
+    pub const VSYNC: u32 = 1 << 0;
+    /// timestamp was provided by the display hardware.
+    pub const HW_CLOCK: u32 = 1 << 1;
+    /// display hardware signalled that it started using the new content.
+    pub const HW_COMPLETION: u32 = 1 << 2;
+    /// buffer was scanned out directly, without compositing.
+    pub const ZERO_COPY: u32 = 1 << 3;
+}
+
+// https://wayland.app/protocols/presentation-time#wp_presentation_feedback:event:presented
+#[derive(Debug, Clone)]
+pub struct PresentationFeedback {
+    /// time when the frame became visible. it is in the domain of
+    /// [`PresentationFeedback::clock_id`] clock (usually CLOCK_MONOTONIC).
+    pub presented_at: Duration,
+    pub clock_id: u32,
+    /// None if the output does not have a constant refresh rate.
+    pub refresh_interval: Option<Duration>,
+    /// vblank counter. None if the output does not have one.
+    pub sequence: Option<u64>,
+    pub flags: PresentationFlags,
+}
+
+impl PresentationFeedback {
+    /// predicts when the next vblank is going to happen (in the domain of the same clock as
+    /// [`PresentationFeedback::presented_at`]).
+    pub fn predict_next_vblank(&self) -> Option<Duration> {
+        self.refresh_interval
+            .map(|refresh_interval| self.presented_at + refresh_interval)
+    }
+}
+
 // TODO: i don't want this ti be called window event. maybe it does not even need to be separated
 // from Event? cosider either renaming WindowEvent to SurfaceEvent or moving all variants into
 // Event? or maybe you'll get better ideas?
 #[derive(Debug)]
 pub enum WindowEvent {
-    Resized { logical_size: (u32, u32) },
-    ScaleFactorChanged { scale_factor: f64 },
+    Resized {
+        logical_size: (u32, u32),
+    },
+    ScaleFactorChanged {
+        scale_factor: f64,
+    },
     CloseRequested,
+    /// window gained or lost keyboard focus.
+    Focused {
+        focused: bool,
+    },
+    /// it is a good time to draw a new frame (the compositor is ready to show it). it only arrives
+    /// after [`Window::request_redraw`] was called.
+    RedrawRequested,
+    /// frame that was submitted after [`Window::pre_present_notify`] call became visible.
+    Presented(PresentationFeedback),
+    /// frame that was submitted after [`Window::pre_present_notify`] call was never shown.
+    PresentationDiscarded,
+    /// position is relative to the parent surface.
+    PopupConfigured {
+        surface_id: input::SurfaceId,
+        position: (i32, i32),
+        logical_size: (u32, u32),
+    },
+    /// is dispatched in response to [`Window::reposition_popup`]; new geometry follows in
+    /// [`WindowEvent::PopupConfigured`].
+    PopupRepositioned {
+        surface_id: input::SurfaceId,
+    },
+    /// popup was dismissed by the compositor (for example user clicked outside of it). it needs to
+    /// be destroyed with [`Window::destroy_popup`].
+    PopupDone {
+        surface_id: input::SurfaceId,
+    },
+    /// window (or some part of it) became visible on the output.
+    OutputEntered {
+        output_id: OutputId,
+    },
+    /// window is no longer visible on the output (or the output was disconnected).
+    OutputLeft {
+        output_id: OutputId,
+    },
+    /// is dispatched in response to [`Window::read_clipboard`] by backends where clipboard can
+    /// only be accessed asynchronously (web).
+    ClipboardDataReceived {
+        mime_type: String,
+        data: Vec<u8>,
+    },
+    /// asynchronous clipboard read failed (clipboard is empty, there's no data of requested type
+    /// or permission was denied).
+    ClipboardReadFailed {
+        mime_type: String,
+    },
 }
 
 // TODO: event probably needs to be split into Event and EventKind where Event will contain
@@ -110,21 +407,48 @@ impl ClipboardDataProvider for ClipboardTextProvider {
 // need to be moved into a separate thing that will probably retain the name Window (or maybe it
 // would make more sense to call it surface?).
 pub trait Window: rwh::HasDisplayHandle + rwh::HasWindowHandle {
-    // TODO: add timeout: Option<Duration>.
-    // timeout limits how long it may block waiting for new events. a timeout of
-    // Some(Duration::ZERO) = don't block; None means that it may wait indefinitely.
+    /// `timeout` limits how long it may block waiting for new events. a timeout of
+    /// `Some(Duration::ZERO)` = don't block; `None` means that it may wait indefinitely.
+    ///
+    /// NOTE: it does not block if there are events that were not popped yet.
     //
     // TODO: as a replacement for pump_events + pop_event consider adding non-blocking poll_event
     // and wait_event(timeout).
-    fn pump_events(&mut self) -> anyhow::Result<()>;
+    fn pump_events(&mut self, timeout: Option<Duration>) -> anyhow::Result<()>;
     fn pop_event(&mut self) -> Option<Event>;
 
     fn set_cursor_shape(&mut self, cursor_shape: input::CursorShape) -> anyhow::Result<()>;
+    /// custom cursor image takes precedence over cursor shape until the next
+    /// [`Window::set_cursor_shape`] call.
+    ///
+    /// rgba is tightly packed, 8 bits per channel, not premultiplied. size and hotspot are in
+    /// logical pixels.
+    fn set_cursor_image(
+        &mut self,
+        rgba: &[u8],
+        size: (u32, u32),
+        hotspot: (u32, u32),
+    ) -> anyhow::Result<()>;
+    fn set_cursor_visible(&mut self, visible: bool) -> anyhow::Result<()>;
+    /// grab is only active while window has pointer focus; it is re-activated when pointer
+    /// re-enters the window.
+    fn set_pointer_grab(&mut self, grab: PointerGrab) -> anyhow::Result<()>;
+
+    /// prevents screen from blanking / locking while the window is visible.
+    fn set_idle_inhibit(&mut self, inhibit: bool) -> anyhow::Result<()>;
+    /// marks the window as urgent (most compositors highlight it in some way).
+    fn request_attention(&mut self) -> anyhow::Result<()>;
+    /// requests focus using activation token that was received from another client (or another
+    /// instance of this app). see [`activation_token_from_env`].
+    fn activate(&mut self, token: &str) -> anyhow::Result<()>;
 
     // NOTE: it is okay for read_clipboard and provide_clipboard_data methods to fail silently in
     // if clipboard is not-available.
 
     // if successful, this function will return the total number of bytes read (might be 0).
+    //
+    // NOTE: on web clipboard is asynchronous; this will always return 0 and the data will arrive
+    // with [`WindowEvent::ClipboardDataReceived`] (or [`WindowEvent::ClipboardReadFailed`]).
     fn read_clipboard(&mut self, mime_type: &str, buf: &mut Vec<u8>) -> anyhow::Result<usize>;
     // TODO: consider changing provider from being all boxed and ugly to an enum that would support
     // most common mime types as well as allow for providing manual/custom boxed providers.
@@ -137,6 +461,47 @@ pub trait Window: rwh::HasDisplayHandle + rwh::HasWindowHandle {
 
     fn logical_size(&self) -> (u32, u32);
     fn scale_factor(&self) -> f64;
+    /// size of the buffer that must be rendered into. see [`logical_to_physical_size`].
+    fn physical_size(&self) -> (u32, u32) {
+        logical_to_physical_size(self.logical_size(), self.scale_factor())
+    }
+
+    /// schedules [`WindowEvent::RedrawRequested`]. multiple requests made before the event arrives
+    /// result in a single event. to render continuously request redraw each frame.
+    fn request_redraw(&mut self);
+    /// must be called right before submitting a frame (before swapping buffers). it allows the
+    /// window to throttle [`WindowEvent::RedrawRequested`] to the compositor's pace and to
+    /// request [`WindowEvent::Presented`] feedback for that frame.
+    fn pre_present_notify(&mut self);
+
+    /// returns key repeat info that is configured in the system (if it's known).
+    ///
+    /// NOTE: backends differ in whether and how they repeat keys. to get consistent behaviour
+    /// feed events through [`input::KeyRepeater`] (it can be initialized with this info).
+    fn key_repeat_info(&self) -> Option<input::KeyRepeatInfo>;
+
+    /// returns all outputs that are currently known. info is complete (all initial events were
+    /// received) by the time window is created.
+    fn outputs(&self) -> Vec<OutputInfo>;
+
+    /// returns id of the window's own surface.
+    fn surface_id(&self) -> input::SurfaceId;
+    /// allows to get a handle of popup surfaces (for rendering into them). window's own surface id
+    /// is also accepted.
+    fn surface_window_handle(
+        &self,
+        surface_id: input::SurfaceId,
+    ) -> Result<rwh::WindowHandle<'_>, rwh::HandleError>;
+
+    /// popup is not usable until [`WindowEvent::PopupConfigured`] is received.
+    fn create_popup(&mut self, attrs: PopupAttrs) -> anyhow::Result<input::SurfaceId>;
+    fn reposition_popup(
+        &mut self,
+        surface_id: input::SurfaceId,
+        positioner: PopupPositioner,
+    ) -> anyhow::Result<()>;
+    /// child popups must be destroyed before their parents.
+    fn destroy_popup(&mut self, surface_id: input::SurfaceId) -> anyhow::Result<()>;
 }
 
 pub fn create_window(attrs: WindowAttrs) -> anyhow::Result<Box<dyn Window>> {
@@ -174,3 +539,46 @@ pub fn create_window(attrs: WindowAttrs) -> anyhow::Result<Box<dyn Window>> {
 
     Err(anyhow!("{errors:?}"))
 }
+
+#[test]
+fn test_output_mode_refresh_interval() {
+    let mode = OutputMode {
+        physical_size: (1920, 1080),
+        refresh_mhz: 60_000,
+        current: true,
+        preferred: true,
+    };
+    assert_eq!(
+        mode.refresh_interval(),
+        Some(Duration::from_nanos(16_666_666))
+    );
+
+    let mode = OutputMode {
+        refresh_mhz: 0,
+        ..mode
+    };
+    assert_eq!(mode.refresh_interval(), None);
+}
+
+#[test]
+fn test_logical_to_physical_size() {
+    assert_eq!(logical_to_physical_size((640, 480), 1.0), (640, 480));
+    assert_eq!(logical_to_physical_size((640, 480), 1.5), (960, 720));
+    assert_eq!(logical_to_physical_size((641, 479), 1.25), (802, 599));
+    assert_eq!(logical_to_physical_size((333, 333), 1.75), (583, 583));
+
+    // NOTE: fractional-scale-v1 scales are multiples of 1/120. buffer must always cover the whole
+    // surface and must not be more than a pixel larger.
+    for scale_120 in 120..=360 {
+        let scale_factor = scale_120 as f64 / 120.0;
+        for logical_size in [(1, 1), (641, 479), (1366, 767), DEFAULT_LOGICAL_SIZE] {
+            let physical_size = logical_to_physical_size(logical_size, scale_factor);
+            let exact = (
+                logical_size.0 as f64 * scale_factor,
+                logical_size.1 as f64 * scale_factor,
+            );
+            assert!(physical_size.0 as f64 >= exact.0 && (physical_size.0 as f64) < exact.0 + 1.0);
+            assert!(physical_size.1 as f64 >= exact.1 && (physical_size.1 as f64) < exact.1 + 1.0);
+        }
+    }
+}
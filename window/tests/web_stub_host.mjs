@@ -0,0 +1,166 @@
+// stub dom host for web backend. it provides just enough of browser's globals for the backend to
+// create a window, dispatches dom events synchronously (like browsers do) and checks how they were
+// handled.
+//
+// usage: node web_stub_host.mjs <path to web_stub_host.wasm>
+
+import { readFileSync } from "node:fs";
+import { argv, exit } from "node:process";
+
+import { Glue } from "../../js/glue.mjs";
+
+function assert(truth, msg) {
+    if (!truth) {
+        throw new Error(msg ? `assertion failed: ${msg}` : "assertion failed");
+    }
+}
+
+function assertEq(left, right, msg) {
+    if (left !== right) {
+        const baseMsg = `assertion left === right failed\n  left: ${left}\n right: ${right}`;
+        throw new Error(msg ? `${baseMsg}: ${msg}` : baseMsg);
+    }
+}
+
+class StubCanvas {
+    constructor() {
+        this.style = {};
+        this.dataset = {};
+        this.listeners = new Map();
+        this.capturedPointers = new Set();
+        this.focused = false;
+    }
+
+    addEventListener(type, listener, options) {
+        assertEq(options.passive, false, `${type} listener must not be passive`);
+        this.listeners.set(type, listener);
+    }
+
+    removeEventListener(type, listener) {
+        assertEq(this.listeners.get(type), listener);
+        this.listeners.delete(type);
+    }
+
+    setPointerCapture(pointerId) {
+        this.capturedPointers.add(pointerId);
+    }
+
+    releasePointerCapture(pointerId) {
+        this.capturedPointers.delete(pointerId);
+    }
+
+    focus() {
+        this.focused = true;
+    }
+
+    // returns whether default action was prevented by the time listener returned.
+    dispatch(type, props) {
+        const listener = this.listeners.get(type);
+        assert(listener, `no ${type} listener`);
+        const event = {
+            type,
+            defaultPrevented: false,
+            preventDefault() {
+                this.defaultPrevented = true;
+            },
+            ...props,
+        };
+        listener(event);
+        return event.defaultPrevented;
+    }
+}
+
+let canvas = null;
+let resizeObserverCallback = null;
+
+globalThis.devicePixelRatio = 1;
+globalThis.document = {
+    createElement(tagName) {
+        assertEq(tagName, "canvas");
+        assertEq(canvas, null);
+        canvas = new StubCanvas();
+        return canvas;
+    },
+    body: {
+        append(element) {
+            assertEq(element, canvas);
+        },
+    },
+};
+globalThis.ResizeObserver = class {
+    constructor(callback) {
+        resizeObserverCallback = callback;
+    }
+    observe(target) {
+        assertEq(target, canvas);
+    }
+    disconnect() {}
+};
+globalThis.requestAnimationFrame = () => 0;
+// NOTE: nothing but inline style affects canvas size here.
+globalThis.getComputedStyle = (element) => ({
+    width: element.style.width,
+    height: element.style.height,
+});
+
+const glue = new Glue();
+const { instance } = await WebAssembly.instantiate(readFileSync(argv[2]), glue.importObject);
+glue.init(instance);
+const { init, pump_events: pumpEvents } = instance.exports;
+
+const pump = () => {
+    pumpEvents();
+    return globalThis.pumpedEvents.split("\n").filter((line) => line.length > 0);
+};
+
+init();
+assert(canvas, "canvas was not created");
+assertEq(canvas.width, 640);
+assertEq(canvas.style.width, "640px");
+pump();
+
+const keyEvent = (code, key, props) => ({
+    code,
+    key,
+    repeat: false,
+    ctrlKey: false,
+    metaKey: false,
+    ...props,
+});
+
+// NOTE: default actions must be prevented synchronously, before pump_events.
+assert(
+    canvas.dispatch("wheel", { deltaMode: 0, deltaX: 0, deltaY: 100 }),
+    "wheel would scroll the page",
+);
+assert(canvas.dispatch("contextmenu", {}), "context menu would open");
+assert(canvas.dispatch("keydown", keyEvent("Tab", "Tab")), "tab would move focus");
+assert(canvas.dispatch("keyup", keyEvent("Tab", "Tab")), "tab would move focus");
+assert(
+    !canvas.dispatch("keydown", keyEvent("KeyC", "c", { ctrlKey: true })),
+    "browser shortcuts must be left alone",
+);
+assert(
+    !canvas.dispatch("pointermove", { offsetX: 10, offsetY: 20 }),
+    "pointer move has no default action",
+);
+
+const events = pump();
+assertEq(events.length, 5, events.join("\n"));
+assert(events[0].includes("Scroll { delta: (0.0, 1.0) }"), events[0]);
+assert(events[1].includes("state: Pressed, scancode: Tab"), events[1]);
+assert(events[2].includes("state: Released, scancode: Tab"), events[2]);
+assert(events[3].includes("keycode: Char('c')"), events[3]);
+assert(events[4].includes("Move"), events[4]);
+
+globalThis.devicePixelRatio = 2;
+canvas.style.width = "800px";
+resizeObserverCallback();
+const resizeEvents = pump();
+assertEq(resizeEvents.length, 2, resizeEvents.join("\n"));
+assert(resizeEvents[0].includes("ScaleFactorChanged { scale_factor: 2.0 }"), resizeEvents[0]);
+assert(resizeEvents[1].includes("Resized { logical_size: (800, 480) }"), resizeEvents[1]);
+assertEq(canvas.width, 1600);
+assertEq(canvas.height, 960);
+
+exit(0);
@@ -0,0 +1,41 @@
+//! runs web backend (`examples/web_stub_host.rs` built for wasm) against a stub dom host under
+//! node. skipped if node is not installed.
+
+#![cfg(not(target_family = "wasm"))]
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_web_backend_in_stub_host() {
+    if Command::new("node").arg("--version").output().is_err() {
+        eprintln!("node is not available, skipping");
+        return;
+    }
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // NOTE: separate target dir because the outer cargo may be holding the lock of the default
+    // one.
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("web_stub_host");
+    let status = Command::new(env!("CARGO"))
+        .args([
+            "build",
+            "--example",
+            "web_stub_host",
+            "--target",
+            "wasm32-unknown-unknown",
+        ])
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .current_dir(manifest_dir)
+        .status()
+        .expect("could not run cargo");
+    assert!(status.success(), "could not build web_stub_host for wasm");
+
+    let wasm = target_dir.join("wasm32-unknown-unknown/debug/examples/web_stub_host.wasm");
+    let status = Command::new("node")
+        .arg(manifest_dir.join("tests/web_stub_host.mjs"))
+        .arg(&wasm)
+        .status()
+        .expect("could not run node");
+    assert!(status.success(), "stub host checks failed");
+}